@@ -3,7 +3,10 @@
 // See http://www.gnu.org/licenses/
 mod miner;
 
+pub use crate::miner::getwork::SubmitError as GetWorkSubmitError;
+
 use crate::miner::{
+    getwork::GetWork,
     stratum::{Options as StratumOption, Stratum},
     work_notify::NotifyWork,
 };
@@ -50,6 +53,15 @@ enum MiningState {
     Stop,
 }
 
+/// The block timestamp controls of the dev mode RPCs.
+#[derive(Default)]
+struct DevTimestamp {
+    /// The seconds added to the current time.
+    offset: u64,
+    /// The timestamp of the next generated block.
+    next: Option<u64>,
+}
+
 /// The interface for a conflux block generator
 pub struct BlockGenerator {
     pub pow_config: ProofOfWorkConfig,
@@ -62,7 +74,12 @@ pub struct BlockGenerator {
     state: RwLock<MiningState>,
     workers: Mutex<Vec<(Worker, mpsc::Sender<ProofOfWorkProblem>)>>,
     pub stratum: RwLock<Option<Stratum>>,
+    getwork: RwLock<Option<GetWork>>,
     pos_verifier: Arc<PosVerifier>,
+    dev_timestamp: Mutex<DevTimestamp>,
+    /// The blocks that should not be referenced by the generated blocks,
+    /// e.g. the abandoned tips after a `dev_revert`.
+    excluded_referees: RwLock<HashSet<H256>>,
 }
 
 pub struct Worker {
@@ -161,7 +178,10 @@ impl BlockGenerator {
             state: RwLock::new(MiningState::Start),
             workers: Mutex::new(Vec::new()),
             stratum: RwLock::new(None),
+            getwork: RwLock::new(None),
             pos_verifier,
+            dev_timestamp: Default::default(),
+            excluded_referees: Default::default(),
         }
     }
 
@@ -189,6 +209,9 @@ impl BlockGenerator {
         if bg.pow_config.use_stratum() {
             let stratum = bg.stratum.read();
             stratum.as_ref().unwrap().notify(problem);
+        } else if bg.pow_config.use_getwork() {
+            let getwork = bg.getwork.read();
+            getwork.as_ref().unwrap().notify(problem);
         } else {
             for item in bg.workers.lock().iter() {
                 item.1
@@ -223,6 +246,12 @@ impl BlockGenerator {
         // checkpoint making that happens before we acquire the inner lock
         referees
             .retain(|h| consensus_inner.hash_to_arena_indices.contains_key(h));
+        {
+            let excluded_referees = self.excluded_referees.read();
+            if !excluded_referees.is_empty() {
+                referees.retain(|h| !excluded_referees.contains(h));
+            }
+        }
         let mut expected_difficulty =
             consensus_inner.expected_difficulty(&parent_hash);
         let adaptive = if let Some(x) = adaptive_opt {
@@ -257,7 +286,7 @@ impl BlockGenerator {
         // Adjust the timestamp of the currently mined block to be later
         // than or equal to its parent's.
         // See comments in verify_header_graph_ready_block()
-        let my_timestamp = max(parent_timestamp, now);
+        let my_timestamp = max(parent_timestamp, self.dev_timestamp(now));
 
         let custom = self
             .txpool
@@ -440,6 +469,23 @@ impl BlockGenerator {
         )
     }
 
+    /// The PoW problem of the block being mined for the getwork mining RPCs,
+    /// or `None` if getwork mining is not enabled or not started yet.
+    pub fn get_work(&self) -> Option<ProofOfWorkProblem> {
+        self.getwork.read().as_ref()?.work()
+    }
+
+    /// Submit the `nonce` solving the PoW problem with `pow_hash` from the
+    /// getwork mining RPCs. The mined block is sent out by the mining loop.
+    pub fn submit_work(
+        &self, pow_hash: H256, nonce: U256,
+    ) -> Result<(), GetWorkSubmitError> {
+        match self.getwork.read().as_ref() {
+            Some(getwork) => getwork.submit(pow_hash, nonce),
+            None => Err(GetWorkSubmitError::Disabled),
+        }
+    }
+
     /// Update and sync a new block
     pub fn on_mined_block(&self, block: Block) {
         // FIXME: error handling.
@@ -666,6 +712,82 @@ impl BlockGenerator {
         hash
     }
 
+    /// Generate an empty block on `parent_hash` without referees. This is
+    /// used by `dev_revert` to fork the chain from a previous pivot block.
+    pub fn generate_fork_block(
+        &self, parent_hash: H256,
+    ) -> Result<H256, String> {
+        let consensus_graph = self.consensus_graph();
+        let state_blame_info = consensus_graph
+            .force_compute_blame_and_deferred_state_for_generation(
+                &parent_hash,
+            )?;
+        let block_gas_limit = *self
+            .graph
+            .data_man
+            .block_header_by_hash(&parent_hash)
+            .ok_or_else(|| format!("parent {:?} not found", parent_hash))?
+            .gas_limit();
+        let pos_reference = self.get_pos_reference(&parent_hash);
+        // The fork block is not adjusted by `choose_correct_parent`, so the
+        // adaptive flag is computed here.
+        let adaptive = {
+            let mut consensus_inner = consensus_graph.inner.write();
+            let difficulty = consensus_inner.expected_difficulty(&parent_hash);
+            consensus_graph.check_mining_adaptive_block(
+                &mut *consensus_inner,
+                &parent_hash,
+                &vec![],
+                &difficulty,
+                pos_reference,
+            )
+        };
+
+        let block = self.assemble_new_block_impl(
+            parent_hash,
+            vec![],
+            state_blame_info,
+            block_gas_limit,
+            vec![],
+            0,
+            Some(adaptive),
+            pos_reference,
+        );
+        Ok(self.generate_block_impl(block))
+    }
+
+    /// Do not reference `hashes` in the blocks generated later.
+    pub fn exclude_referees(&self, hashes: Vec<H256>) {
+        self.excluded_referees.write().extend(hashes);
+    }
+
+    /// Increase the timestamps of the blocks generated later by `seconds`,
+    /// and return the total increased seconds, or `None` if it overflows.
+    pub fn increase_time(&self, seconds: u64) -> Option<u64> {
+        let mut dev_timestamp = self.dev_timestamp.lock();
+        dev_timestamp.offset = dev_timestamp.offset.checked_add(seconds)?;
+        Some(dev_timestamp.offset)
+    }
+
+    /// Set the timestamp of the next generated block. The timestamps of the
+    /// blocks generated later continue from it.
+    pub fn set_next_block_timestamp(&self, timestamp: u64) {
+        self.dev_timestamp.lock().next = Some(timestamp);
+    }
+
+    /// Return the timestamp of a new block given the current time `now`,
+    /// adjusted by the dev mode RPCs.
+    fn dev_timestamp(&self, now: u64) -> u64 {
+        let mut dev_timestamp = self.dev_timestamp.lock();
+        match dev_timestamp.next.take() {
+            Some(next) => {
+                dev_timestamp.offset = next.saturating_sub(now);
+                next
+            }
+            None => now.saturating_add(dev_timestamp.offset),
+        }
+    }
+
     pub fn pow_config(&self) -> ProofOfWorkConfig { self.pow_config.clone() }
 
     /// Start num_worker new workers
@@ -709,6 +831,19 @@ impl BlockGenerator {
         solution_receiver
     }
 
+    pub fn start_new_getwork_worker(
+        bg: Arc<BlockGenerator>,
+    ) -> mpsc::Receiver<ProofOfWorkSolution> {
+        let (solution_sender, solution_receiver) = mpsc::channel();
+        let getwork = GetWork::new(
+            solution_sender,
+            bg.pow.clone(),
+            bg.pow_config.pow_problem_window_size,
+        );
+        *bg.getwork.write() = Some(getwork);
+        solution_receiver
+    }
+
     pub fn start_mining(bg: Arc<BlockGenerator>, _payload_len: u32) {
         let mut current_mining_block = None;
         let mut recent_mining_blocks = vec![];
@@ -720,6 +855,8 @@ impl BlockGenerator {
         let receiver: mpsc::Receiver<ProofOfWorkSolution> =
             if bg.pow_config.use_stratum() {
                 BlockGenerator::start_new_stratum_worker(bg.clone())
+            } else if bg.pow_config.use_getwork() {
+                BlockGenerator::start_new_getwork_worker(bg.clone())
             } else {
                 BlockGenerator::start_new_worker(1, bg.clone())
             };
@@ -0,0 +1,225 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Job dispatcher for the getwork mining RPCs
+
+use crate::miner::work_notify::NotifyWork;
+use cfx_types::{H256, U256};
+use cfxcore::pow::{
+    validate, PowComputer, ProofOfWorkProblem, ProofOfWorkSolution,
+};
+use log::{info, trace, warn};
+use parking_lot::Mutex;
+use std::{
+    collections::HashSet,
+    fmt,
+    sync::{mpsc, Arc},
+};
+
+#[derive(Debug, PartialEq)]
+pub enum SubmitError {
+    /// The node is not mining with `mining_type = "getwork"`.
+    Disabled,
+    /// The work is not one of the recent problems, e.g. it is replaced because
+    /// the mining block is outdated.
+    StaleWork,
+    /// The work has been solved with the same nonce.
+    AlreadySolved,
+    /// The nonce does not solve the work.
+    InvalidNonce,
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            SubmitError::Disabled => "Getwork mining is not enabled",
+            SubmitError::StaleWork => "Solution for a stale work",
+            SubmitError::AlreadySolved => "Work already solved with the nonce",
+            SubmitError::InvalidNonce => "Incorrect nonce",
+        };
+        f.write_str(msg)
+    }
+}
+
+/// Job dispatcher for external miners polling the work through RPC
+pub struct GetWork {
+    recent_problems: Mutex<Vec<(ProofOfWorkProblem, HashSet<U256>)>>,
+    solution_sender: Mutex<mpsc::Sender<ProofOfWorkSolution>>,
+    pow: Arc<PowComputer>,
+    window_size: usize,
+}
+
+impl GetWork {
+    pub fn new(
+        solution_sender: mpsc::Sender<ProofOfWorkSolution>,
+        pow: Arc<PowComputer>, pow_window_size: usize,
+    ) -> GetWork
+    {
+        GetWork {
+            recent_problems: Mutex::new(vec![]),
+            solution_sender: Mutex::new(solution_sender),
+            pow,
+            window_size: pow_window_size,
+        }
+    }
+
+    /// The latest problem, or `None` if no block is being mined yet.
+    pub fn work(&self) -> Option<ProofOfWorkProblem> {
+        self.recent_problems.lock().last().map(|(prob, _)| *prob)
+    }
+
+    /// Submit the solution `nonce` of the problem with `pow_hash`.
+    pub fn submit(
+        &self, pow_hash: H256, nonce: U256,
+    ) -> Result<(), SubmitError> {
+        trace!("submit_work: nonce={}, pow_hash={}", nonce, pow_hash);
+
+        let sol = ProofOfWorkSolution { nonce };
+        let pow_prob = {
+            let probs = self.recent_problems.lock();
+            let (pow_prob, solved_nonce) = probs
+                .iter()
+                .find(|(prob, _)| prob.block_hash == pow_hash)
+                .ok_or(SubmitError::StaleWork)?;
+            if solved_nonce.contains(&nonce) {
+                return Err(SubmitError::AlreadySolved);
+            }
+            *pow_prob
+        };
+
+        // The PoW is computed without holding the lock, so that the invalid
+        // submissions do not block the new problems and other submissions.
+        if !validate(self.pow.clone(), &pow_prob, &sol) {
+            return Err(SubmitError::InvalidNonce);
+        }
+
+        // The problem may be replaced or solved with the same nonce while
+        // validating.
+        {
+            let mut probs = self.recent_problems.lock();
+            let (_, solved_nonce) = probs
+                .iter_mut()
+                .find(|(prob, _)| prob.block_hash == pow_hash)
+                .ok_or(SubmitError::StaleWork)?;
+            if !solved_nonce.insert(nonce) {
+                return Err(SubmitError::AlreadySolved);
+            }
+        }
+        info!("Getwork miner mined a block!");
+
+        if let Err(e) = self.solution_sender.lock().send(sol) {
+            warn!("{}", e);
+        }
+        Ok(())
+    }
+}
+
+impl NotifyWork for GetWork {
+    fn notify(&self, prob: ProofOfWorkProblem) {
+        trace!("Notify work");
+
+        let mut probs = self.recent_problems.lock();
+        if probs.len() == self.window_size {
+            probs.remove(0);
+        }
+        probs.push((prob, HashSet::new()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(block_hash: u64, difficulty: U256) -> ProofOfWorkProblem {
+        ProofOfWorkProblem::new(
+            1,
+            H256::from_low_u64_be(block_hash),
+            difficulty,
+        )
+    }
+
+    fn getwork(
+        window_size: usize,
+    ) -> (GetWork, mpsc::Receiver<ProofOfWorkSolution>) {
+        let (sender, receiver) = mpsc::channel();
+        let pow = Arc::new(PowComputer::new(false));
+        (GetWork::new(sender, pow, window_size), receiver)
+    }
+
+    #[test]
+    fn test_accept() {
+        let (getwork, receiver) = getwork(4);
+        assert!(getwork.work().is_none());
+
+        // Any nonce solves a problem with difficulty 1.
+        let prob = problem(1, 1.into());
+        getwork.notify(prob);
+        assert_eq!(getwork.work().unwrap().block_hash, prob.block_hash);
+
+        assert_eq!(getwork.submit(prob.block_hash, 7.into()), Ok(()));
+        assert_eq!(receiver.try_recv().unwrap().nonce, 7.into());
+    }
+
+    #[test]
+    fn test_duplicate() {
+        let (getwork, receiver) = getwork(4);
+        let prob = problem(1, 1.into());
+        getwork.notify(prob);
+
+        assert_eq!(getwork.submit(prob.block_hash, 7.into()), Ok(()));
+        assert_eq!(
+            getwork.submit(prob.block_hash, 7.into()),
+            Err(SubmitError::AlreadySolved)
+        );
+        assert_eq!(getwork.submit(prob.block_hash, 8.into()), Ok(()));
+
+        let nonces: Vec<U256> = receiver.try_iter().map(|s| s.nonce).collect();
+        assert_eq!(nonces, vec![7.into(), 8.into()]);
+    }
+
+    #[test]
+    fn test_invalid_nonce() {
+        let (getwork, receiver) = getwork(4);
+        let prob = problem(1, U256::MAX);
+        getwork.notify(prob);
+
+        assert_eq!(
+            getwork.submit(prob.block_hash, 7.into()),
+            Err(SubmitError::InvalidNonce)
+        );
+        // An invalid nonce is not recorded as solved.
+        assert_eq!(
+            getwork.submit(prob.block_hash, 7.into()),
+            Err(SubmitError::InvalidNonce)
+        );
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_stale() {
+        let (getwork, receiver) = getwork(2);
+        assert_eq!(
+            getwork.submit(H256::from_low_u64_be(1), 7.into()),
+            Err(SubmitError::StaleWork)
+        );
+
+        // Only the latest `window_size` problems are kept.
+        for block_hash in 1..=3 {
+            getwork.notify(problem(block_hash, 1.into()));
+        }
+        assert_eq!(
+            getwork.work().unwrap().block_hash,
+            H256::from_low_u64_be(3)
+        );
+        assert_eq!(
+            getwork.submit(H256::from_low_u64_be(1), 7.into()),
+            Err(SubmitError::StaleWork)
+        );
+        assert!(receiver.try_recv().is_err());
+
+        assert_eq!(getwork.submit(H256::from_low_u64_be(2), 7.into()), Ok(()));
+        assert_eq!(getwork.submit(H256::from_low_u64_be(3), 7.into()), Ok(()));
+        assert_eq!(receiver.try_iter().count(), 2);
+    }
+}
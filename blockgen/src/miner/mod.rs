@@ -2,5 +2,6 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
+pub mod getwork;
 pub mod stratum;
 pub mod work_notify;
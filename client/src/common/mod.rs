@@ -25,12 +25,12 @@ use cfx_types::{address_util::AddressUtil, Address, Space, U256};
 pub use cfxcore::pos::pos::PosDropHandle;
 use cfxcore::{
     block_data_manager::BlockDataManager,
-    consensus::pos_handler::{PosConfiguration, PosVerifier},
+    consensus::pos_handler::{PosConfiguration, PosKeys, PosVerifier},
     machine::{new_machine_with_builtin, Machine},
     pow::PowComputer,
     spec::genesis::{self, genesis_block, DEV_GENESIS_KEY_PAIR_2},
     statistics::Statistics,
-    sync::SyncPhaseType,
+    sync::{checkpoint::TrustedCheckpoint, SyncPhaseType},
     vm_factory::VmFactory,
     ConsensusGraph, LightProvider, NodeType, Notifications, Stopable,
     SynchronizationGraph, SynchronizationService, TransactionPool,
@@ -38,7 +38,7 @@ use cfxcore::{
 };
 use cfxcore_accounts::AccountProvider;
 use cfxkey::public_to_address;
-use diem_config::keys::ConfigKey;
+use diem_config::{config::NodeConfig, keys::ConfigKey};
 use diem_crypto::{
     key_file::{load_pri_key, save_pri_key},
     PrivateKey, Uniform,
@@ -238,7 +238,13 @@ pub fn initialize_common_modules(
     info!("Working directory: {:?}", std::env::current_dir());
 
     // TODO(lpl): Keep it properly and allow not running pos.
-    let (self_pos_private_key, self_vrf_private_key) = {
+    // If the PoS safety rules run as a remote signer, the private keys are
+    // held by the signer and only the public keys are queried from it.
+    let pos_keys = if let Some(pos_keys) =
+        PosKeys::from_remote_signer(&conf.raw_conf.pos_config_path)?
+    {
+        pos_keys
+    } else {
         let key_path = Path::new(&conf.raw_conf.pos_private_key_path);
         let default_passwd = if conf.is_test_or_dev_mode() {
             Some(vec![])
@@ -258,7 +264,10 @@ pub fn initialize_common_modules(
             };
             let (sk, vrf_sk): (ConsensusPrivateKey, ConsensusVRFPrivateKey) =
                 load_pri_key(key_path, &passwd).unwrap();
-            (ConfigKey::new(sk), ConfigKey::new(vrf_sk))
+            PosKeys::Local {
+                bls_key: ConfigKey::new(sk),
+                vrf_key: ConfigKey::new(vrf_sk),
+            }
         } else {
             create_dir_all(key_path.parent().unwrap()).unwrap();
             let passwd = match default_passwd {
@@ -281,7 +290,10 @@ pub fn initialize_common_modules(
             let vrf_private_key = ConsensusVRFPrivateKey::generate(&mut rng);
             save_pri_key(key_path, &passwd, &(&private_key, &vrf_private_key))
                 .expect("error saving private key");
-            (ConfigKey::new(private_key), ConfigKey::new(vrf_private_key))
+            PosKeys::Local {
+                bls_key: ConfigKey::new(private_key),
+                vrf_key: ConfigKey::new(vrf_private_key),
+            }
         }
     };
 
@@ -395,6 +407,25 @@ pub fn initialize_common_modules(
         pow.clone(),
     ));
 
+    if let Some(path) = &conf.raw_conf.trusted_checkpoint_file {
+        if node_type != NodeType::Full {
+            bail!("trusted_checkpoint_file is only supported by full nodes");
+        }
+        let checkpoint = TrustedCheckpoint::load(Path::new(path))?;
+        let pos_config_path = match &conf.raw_conf.pos_config_path {
+            Some(path) => path,
+            None => bail!("pos_config_path is required to verify checkpoint"),
+        };
+        let waypoint = NodeConfig::load(pos_config_path)
+            .map_err(|e| format!("Failed to load node config: e={:?}", e))?
+            .base
+            .waypoint
+            .waypoint();
+        checkpoint
+            .verify(&waypoint)?
+            .install(&data_man, consensus_conf.inner_conf.era_epoch_count)?;
+    }
+
     let network = {
         let mut rng = StdRng::from_rng(OsRng).unwrap();
         let private_key = ConsensusPrivateKey::generate(&mut rng);
@@ -412,8 +443,7 @@ pub fn initialize_common_modules(
     let pos_verifier = Arc::new(PosVerifier::new(
         Some(network.clone()),
         PosConfiguration {
-            bls_key: self_pos_private_key,
-            vrf_key: self_vrf_private_key,
+            pos_keys,
             diem_conf_path: conf.raw_conf.pos_config_path.clone(),
             protocol_conf: conf.protocol_config(),
             pos_initial_nodes_path: conf
@@ -422,6 +452,15 @@ pub fn initialize_common_modules(
                 .clone(),
             vrf_proposal_threshold: conf.raw_conf.vrf_proposal_threshold,
             pos_state_config: conf.pos_state_config(),
+            backup_service_address: conf
+                .raw_conf
+                .pos_backup_service_address
+                .as_ref()
+                .map(|address| address.parse())
+                .transpose()
+                .map_err(|e| {
+                    format!("Invalid pos_backup_service_address: {:?}", e)
+                })?,
         },
         conf.raw_conf.pos_reference_enable_height,
     ));
@@ -568,7 +607,7 @@ pub fn initialize_not_light_node_modules(
         network,
         common_impl,
         accounts,
-        _notifications,
+        notifications,
         pubsub,
         runtime,
         eth_pubsub,
@@ -593,6 +632,7 @@ pub fn initialize_not_light_node_modules(
         SyncPhaseType::CatchUpRecoverBlockHeaderFromDB,
         light_provider,
         consensus.clone(),
+        notifications,
     ));
     sync.register().unwrap();
 
@@ -796,7 +836,7 @@ pub fn initialize_not_light_node_modules(
         ),
     )?;
 
-    network.start();
+    start_network(&network, &conf)?;
 
     Ok((
         data_man,
@@ -818,6 +858,32 @@ pub fn initialize_not_light_node_modules(
     ))
 }
 
+/// Start the network. If `p2p_replay_dir` is set, the captured p2p messages
+/// are replayed into the protocol handlers instead, without connecting to
+/// any peer.
+pub fn start_network(
+    network: &Arc<NetworkService>, conf: &Configuration,
+) -> Result<(), String> {
+    let dir = match &conf.raw_conf.p2p_replay_dir {
+        Some(dir) => dir.clone(),
+        None => {
+            network.start();
+            return Ok(());
+        }
+    };
+    let network = network.clone();
+    thread::Builder::new()
+        .name("p2p replay".into())
+        .spawn(move || match network.replay_capture(&dir) {
+            Ok(count) => {
+                info!("Replayed {} captured p2p events from {}", count, dir)
+            }
+            Err(e) => error!("Failed to replay captured p2p events: {:?}", e),
+        })
+        .map_err(|e| format!("failed to start p2p replay: {}", e))?;
+    Ok(())
+}
+
 pub fn initialize_txgens(
     consensus: Arc<ConsensusGraph>, txpool: Arc<TransactionPool>,
     sync: Arc<SynchronizationService>, secret_store: SharedSecretStore,
@@ -2,7 +2,12 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
-use std::{collections::BTreeMap, convert::TryInto, path::PathBuf, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashSet},
+    convert::TryInto,
+    path::PathBuf,
+    sync::Arc,
+};
 
 use lazy_static::*;
 use parking_lot::RwLock;
@@ -12,7 +17,13 @@ use cfx_addr::{cfx_addr_decode, Network};
 use cfx_internal_common::{
     ChainIdParams, ChainIdParamsInner, ChainIdParamsOneChainInner,
 };
-use cfx_parameters::block::DEFAULT_TARGET_BLOCK_GAS_LIMIT;
+use cfx_parameters::{
+    block::DEFAULT_TARGET_BLOCK_GAS_LIMIT,
+    rpc::{
+        EVM_GAS_PRICE_BLOCK_SAMPLE_SIZE, EVM_GAS_PRICE_TRANSACTION_SAMPLE_SIZE,
+        GAS_PRICE_BLOCK_SAMPLE_SIZE, GAS_PRICE_TRANSACTION_SAMPLE_SIZE,
+    },
+};
 use cfx_storage::{
     defaults::DEFAULT_DEBUG_SNAPSHOT_CHECKER_THREADS, storage_dir,
     ConsensusParam, ProvideExtraSnapshotSyncConfig, StorageConfiguration,
@@ -28,7 +39,8 @@ use cfxcore::{
     },
     consensus::{
         consensus_inner::consensus_executor::ConsensusExecutionConfiguration,
-        pos_handler::PosVerifier, ConsensusConfig, ConsensusInnerConfig,
+        gas_price_oracle::GasPriceOracleConfig, pos_handler::PosVerifier,
+        ConsensusConfig, ConsensusInnerConfig,
     },
     consensus_internal_parameters::*,
     consensus_parameters::*,
@@ -37,7 +49,7 @@ use cfxcore::{
     spec::CommonParams,
     sync::{ProtocolConfiguration, StateSyncConfiguration, SyncGraphConfig},
     sync_parameters::*,
-    transaction_pool::TxPoolConfig,
+    transaction_pool::{new_packing_policy, TxPoolConfig},
     NodeType,
 };
 use diem_types::term_state::{
@@ -45,7 +57,7 @@ use diem_types::term_state::{
     OUT_QUEUE_LOCKED_VIEWS, ROUND_PER_TERM, TERM_ELECTED_SIZE, TERM_MAX_SIZE,
 };
 use metrics::MetricsConfiguration;
-use network::DiscoveryConfiguration;
+use network::{capture::CaptureConfig, DiscoveryConfiguration};
 use txgen::TransactionGeneratorConfig;
 
 use crate::rpc::{
@@ -219,6 +231,8 @@ build_config! {
         (max_unprocessed_block_size_mb, (usize), (128))
         (min_peers_tx_propagation, (usize), 8)
         (min_phase_change_normal_peer_count, (usize), 3)
+        (propagation_metrics, (bool), false)
+        (propagation_record_capacity, (usize), 10_000)
         (received_tx_index_maintain_timeout_ms, (u64), 300_000)
         (request_block_with_public, (bool), false)
         (send_tx_period_ms, (u64), 1300)
@@ -249,6 +263,16 @@ build_config! {
         (net_key, (Option<String>), None)
         (node_table_timeout_s, (u64), 300)
         (node_table_promotion_timeout_s, (u64), 3 * 24 * 3600)
+        (p2p_compression_threshold, (usize), 1024)
+        (peer_ban_score_threshold, (f64), -500.0)
+        (peers_file, (Option<String>), None)
+        (peers_file_check_interval_ms, (u64), 10_000)
+        (reserved_nodes, (Option<String>), None)
+        (reserved_peers_only, (bool), false)
+        (p2p_capture_dir, (Option<String>), None)
+        (p2p_capture_file_size_mb, (u64), 64)
+        (p2p_capture_max_files, (usize), 16)
+        (p2p_replay_dir, (Option<String>), None)
         (session_ip_limits, (String), "1,8,4,2".into())
         (subnet_quota, (usize), 128)
 
@@ -258,6 +282,10 @@ build_config! {
         (tx_pool_min_tx_gas_price, (Option<u64>), None)
         (tx_weight_scaling, (u64), 1)
         (tx_weight_exp, (u8), 1)
+        (tx_packing_policy, (String), "gas_price".to_string())
+        (tx_packing_max_per_sender, (usize), 16)
+        (tx_packing_max_per_contract, (usize), 64)
+        (tx_packing_whitelist, (Option<String>), None)
 
         // Storage Section.
         (additional_maintained_snapshot_count, (u32), 1)
@@ -289,6 +317,7 @@ build_config! {
         (sync_state_starting_epoch, (Option<u64>), None)
         (sync_state_epoch_gap, (Option<u64>), None)
         (target_difficulties_cache_size_in_count, (usize), DEFAULT_TARGET_DIFFICULTIES_CACHE_SIZE_IN_COUNT)
+        (trusted_checkpoint_file, (Option<String>), None)
 
         // General/Unclassified section.
         (account_provider_refresh_time_ms, (u64), 1000)
@@ -299,12 +328,23 @@ build_config! {
         (get_logs_filter_max_epoch_range, (Option<u64>), None)
         (get_logs_filter_max_block_number_range, (Option<u64>), None)
         (get_logs_epoch_batch_size, (usize), 32)
+        (gas_price_oracle_block_sample_size, (usize), GAS_PRICE_BLOCK_SAMPLE_SIZE)
+        (gas_price_oracle_transaction_sample_size, (usize), GAS_PRICE_TRANSACTION_SAMPLE_SIZE)
+        (gas_price_oracle_evm_block_sample_size, (usize), EVM_GAS_PRICE_BLOCK_SAMPLE_SIZE)
+        (gas_price_oracle_evm_transaction_sample_size, (usize), EVM_GAS_PRICE_TRANSACTION_SAMPLE_SIZE)
+        (gas_price_oracle_slow_percentile, (f64), 12.5)
+        (gas_price_oracle_standard_percentile, (f64), 50.0)
+        (gas_price_oracle_fast_percentile, (f64), 90.0)
         (max_trans_count_received_in_catch_up, (u64), 60_000)
+        (safe_epoch_confirmation_risk, (f64), 0.000001)
         (persist_tx_index, (bool), false)
         (persist_block_number_index, (bool), true)
         (print_memory_usage_period_s, (Option<u64>), None)
         (target_block_gas_limit, (u64), DEFAULT_TARGET_BLOCK_GAS_LIMIT)
         (executive_trace, (bool), false)
+        (record_storage_key_preimages, (bool), false)
+        (persist_epoch_state_diff, (bool), false)
+        (parallel_execution_threads, (usize), 0)
         (check_status_genesis, (bool), true)
         (packing_gas_limit_block_count, (u64), 10)
 
@@ -328,6 +368,7 @@ build_config! {
         (pos_cip99_out_queue_locked_views, (u64), OUT_QUEUE_LOCKED_VIEWS)
         (dev_pos_private_key_encryption_password, (Option<String>), None)
         (pos_started_as_voter, (bool), true)
+        (pos_backup_service_address, (Option<String>), None)
 
         // Light node section
         (ln_epoch_request_batch_size, (Option<usize>), None)
@@ -469,6 +510,13 @@ impl Configuration {
             Duration::from_secs(self.raw_conf.node_table_promotion_timeout_s);
         network_config.test_mode = self.is_test_mode();
         network_config.subnet_quota = self.raw_conf.subnet_quota;
+        network_config.ban_score_threshold =
+            Some(self.raw_conf.peer_ban_score_threshold);
+        network_config.compression_threshold =
+            match self.raw_conf.p2p_compression_threshold {
+                0 => None,
+                threshold => Some(threshold),
+            };
         network_config.session_ip_limit_config =
             self.raw_conf.session_ip_limits.clone().try_into().map_err(
                 |e| format!("failed to parse session ip limit config: {}", e),
@@ -486,6 +534,21 @@ impl Configuration {
         network_config.max_outgoing_peers = self.raw_conf.max_outgoing_peers;
         network_config.max_outgoing_peers_archive =
             self.raw_conf.max_outgoing_peers_archive.unwrap_or(0);
+        network_config.reserved_nodes =
+            to_bootnodes(&self.raw_conf.reserved_nodes).map_err(|e| {
+                format!("failed to parse reserved nodes: {}", e)
+            })?;
+        network_config.reserved_peers_only = self.raw_conf.reserved_peers_only;
+        network_config.peers_file = self.raw_conf.peers_file.clone();
+        network_config.peers_file_check_interval =
+            Duration::from_millis(self.raw_conf.peers_file_check_interval_ms);
+        if let Some(dir) = &self.raw_conf.p2p_capture_dir {
+            let mut capture_config = CaptureConfig::new(dir.clone());
+            capture_config.max_file_size =
+                self.raw_conf.p2p_capture_file_size_mb * 1024 * 1024;
+            capture_config.max_files = self.raw_conf.p2p_capture_max_files;
+            network_config.capture = Some(capture_config);
+        }
         Ok(network_config)
     }
 
@@ -600,6 +663,28 @@ impl Configuration {
             get_logs_filter_max_limit: self.raw_conf.get_logs_filter_max_limit,
             sync_state_starting_epoch: self.raw_conf.sync_state_starting_epoch,
             sync_state_epoch_gap: self.raw_conf.sync_state_epoch_gap,
+            gas_price_oracle: GasPriceOracleConfig {
+                block_sample_size: self
+                    .raw_conf
+                    .gas_price_oracle_block_sample_size,
+                transaction_sample_size: self
+                    .raw_conf
+                    .gas_price_oracle_transaction_sample_size,
+                evm_block_sample_size: self
+                    .raw_conf
+                    .gas_price_oracle_evm_block_sample_size,
+                evm_transaction_sample_size: self
+                    .raw_conf
+                    .gas_price_oracle_evm_transaction_sample_size,
+                slow_percentile: self.raw_conf.gas_price_oracle_slow_percentile,
+                standard_percentile: self
+                    .raw_conf
+                    .gas_price_oracle_standard_percentile,
+                fast_percentile: self.raw_conf.gas_price_oracle_fast_percentile,
+            },
+            safe_epoch_confirmation_risk: self
+                .raw_conf
+                .safe_epoch_confirmation_risk,
         };
         match self.raw_conf.node_type {
             Some(NodeType::Archive) => {
@@ -829,6 +914,10 @@ impl Configuration {
                 .pos_genesis_pivot_decision
                 .expect("set to genesis if none"),
             check_status_genesis: self.raw_conf.check_status_genesis,
+            propagation_record_capacity: self
+                .raw_conf
+                .propagation_record_capacity,
+            export_propagation_metrics: self.raw_conf.propagation_metrics,
             pos_started_as_voter: self.raw_conf.pos_started_as_voter,
         }
     }
@@ -970,6 +1059,28 @@ impl Configuration {
                 .raw_conf
                 .packing_gas_limit_block_count,
             target_block_gas_limit: self.raw_conf.target_block_gas_limit,
+            packing_policy: new_packing_policy(
+                &self.raw_conf.tx_packing_policy,
+                self.raw_conf.tx_packing_max_per_sender,
+                self.raw_conf.tx_packing_max_per_contract,
+                self.tx_packing_whitelist(),
+            )
+            .unwrap_or_else(|e| panic!("{}", e)),
+        }
+    }
+
+    fn tx_packing_whitelist(&self) -> HashSet<Address> {
+        match &self.raw_conf.tx_packing_whitelist {
+            Some(addresses) => addresses
+                .split(',')
+                .map(|addr| {
+                    parse_hex_string(addr.trim()).expect(
+                        "tx_packing_whitelist should be a list of 40-digit \
+                         hex addresses",
+                    )
+                })
+                .collect(),
+            None => HashSet::new(),
         }
     }
 
@@ -980,6 +1091,7 @@ impl Configuration {
                 && self.raw_conf.dev_block_interval_ms.is_none(),
             max_payload_bytes: self.raw_conf.jsonrpc_ws_max_payload_bytes,
             enable_metrics: self.raw_conf.rpc_enable_metrics,
+            dev_mode: self.is_dev_mode(),
         }
     }
 
@@ -1051,6 +1163,13 @@ impl Configuration {
     pub fn execution_config(&self) -> ConsensusExecutionConfiguration {
         ConsensusExecutionConfiguration {
             executive_trace: self.raw_conf.executive_trace,
+            record_storage_key_preimages: self
+                .raw_conf
+                .record_storage_key_preimages,
+            persist_epoch_state_diff: self.raw_conf.persist_epoch_state_diff,
+            parallel_execution_threads: self
+                .raw_conf
+                .parallel_execution_threads,
         }
     }
 
@@ -12,8 +12,8 @@ use crate::{
 };
 use blockgen::BlockGenerator;
 use cfxcore::{
-    pow::PowComputer, ConsensusGraph, NodeType, SynchronizationService,
-    TransactionPool,
+    client::ExecutionPluginManager, pow::PowComputer, ConsensusGraph, NodeType,
+    SynchronizationService, TransactionPool,
 };
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use parking_lot::{Condvar, Mutex};
@@ -34,6 +34,8 @@ pub struct FullClientExtraComponents {
     pub pow: Arc<PowComputer>,
     pub eth_rpc_http_server: Option<HttpServer>,
     pub eth_rpc_ws_server: Option<WsServer>,
+    /// Register the in-process plugins notified of the executed epochs.
+    pub execution_plugins: ExecutionPluginManager,
 }
 
 impl MallocSizeOf for FullClientExtraComponents {
@@ -73,6 +75,9 @@ impl FullClient {
             blockgen: Some(blockgen),
             pos_handler: Some(pos_handler),
             other_components: FullClientExtraComponents {
+                execution_plugins: ExecutionPluginManager::new(
+                    consensus.clone(),
+                ),
                 consensus,
                 debug_rpc_http_server,
                 rpc_http_server,
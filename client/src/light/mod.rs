@@ -12,7 +12,7 @@ use jsonrpc_tcp_server::Server as TcpServer;
 use jsonrpc_ws_server::Server as WsServer;
 
 use crate::{
-    common::{initialize_common_modules, ClientComponents},
+    common::{initialize_common_modules, start_network, ClientComponents},
     configuration::Configuration,
     rpc::{
         extractor::RpcExtractor, impls::light::RpcImpl,
@@ -168,7 +168,7 @@ impl LightClient {
             ),
         )?;
 
-        network.start();
+        start_network(&network, &conf)?;
 
         Ok(Box::new(ClientComponents {
             data_manager_weak_ptr: Arc::downgrade(&data_man),
@@ -42,24 +42,30 @@ pub use cfxcore::rpc_errors::{
 
 use self::{
     impls::{
+        admin::AdminHandler,
         cfx::{CfxHandler, LocalRpcImpl, RpcImpl, TestRpcImpl},
         common::RpcImpl as CommonImpl,
+        dev::DevHandler,
         eth_pubsub::PubSubClient as EthPubSubClient,
         light::{
             CfxHandler as LightCfxHandler, DebugRpcImpl as LightDebugRpcImpl,
             RpcImpl as LightImpl, TestRpcImpl as LightTestRpcImpl,
         },
+        mining::MiningHandler,
         pool::TransactionPoolHandler,
         pos::{PoSInterceptor, PosHandler},
         pubsub::PubSubClient,
         trace::TraceHandler,
     },
     traits::{
+        admin::Admin,
         cfx::Cfx,
         debug::LocalRpc,
+        dev::Dev,
         eth_space::{
             eth::Eth, eth_pubsub::EthPubSub, trace::Trace as EthTrace,
         },
+        mining::Mining,
         pool::TransactionPool,
         pos::Pos,
         pubsub::PubSub,
@@ -217,6 +223,14 @@ pub fn setup_debug_rpc_apis(
     eth_pubsub: EthPubSubClient, conf: &Configuration,
 ) -> MetaIoHandler<Metadata>
 {
+    let mut apis = ApiSet::All.list_apis();
+    apis.insert(Api::Admin);
+    if conf.is_dev_mode() {
+        apis.insert(Api::Dev);
+    }
+    if conf.pow_config().use_getwork() {
+        apis.insert(Api::Mining);
+    }
     setup_rpc_apis(
         common,
         rpc,
@@ -224,7 +238,7 @@ pub fn setup_debug_rpc_apis(
         eth_pubsub,
         &conf.raw_conf.throttling_conf,
         "rpc_local",
-        ApiSet::All.list_apis(),
+        apis,
     )
 }
 
@@ -237,6 +251,11 @@ fn setup_rpc_apis(
     let mut handler = MetaIoHandler::default();
     for api in &apis {
         match api {
+            Api::Admin => {
+                handler.extend_with(
+                    AdminHandler::new(common.clone()).to_delegate(),
+                );
+            }
             Api::Cfx => {
                 let cfx =
                     CfxHandler::new(common.clone(), rpc.clone()).to_delegate();
@@ -327,6 +346,24 @@ fn setup_rpc_apis(
                     PoSInterceptor::new(common.pos_handler.clone());
                 handler.extend_with(RpcProxy::new(pos, pos_interceptor));
             }
+            Api::Dev => {
+                if rpc.config.dev_mode {
+                    handler.extend_with(
+                        DevHandler::new(rpc.clone()).to_delegate(),
+                    );
+                } else {
+                    warn!("Dev RPC is only available in dev mode");
+                }
+            }
+            Api::Mining => {
+                if rpc.block_gen.pow_config.use_getwork() {
+                    handler.extend_with(
+                        MiningHandler::new(rpc.block_gen.clone()).to_delegate(),
+                    );
+                } else {
+                    warn!("Mining RPC is only available with getwork mining");
+                }
+            }
         }
     }
 
@@ -404,6 +441,7 @@ pub fn setup_debug_rpc_apis_light(
 ) -> MetaIoHandler<Metadata>
 {
     let mut light_debug_apis = ApiSet::All.list_apis();
+    light_debug_apis.insert(Api::Admin);
     light_debug_apis.remove(&Api::Trace);
     setup_rpc_apis_light(
         common,
@@ -425,6 +463,11 @@ fn setup_rpc_apis_light(
     let mut handler = MetaIoHandler::default();
     for api in apis {
         match api {
+            Api::Admin => {
+                handler.extend_with(
+                    AdminHandler::new(common.clone()).to_delegate(),
+                );
+            }
             Api::Cfx => {
                 let cfx = LightCfxHandler::new(common.clone(), rpc.clone())
                     .to_delegate();
@@ -462,6 +505,12 @@ fn setup_rpc_apis_light(
             Api::Pos => {
                 warn!("Light nodes do not support PoS RPC");
             }
+            Api::Dev => {
+                warn!("Light nodes do not support dev RPC");
+            }
+            Api::Mining => {
+                warn!("Light nodes do not support mining RPC");
+            }
         }
     }
     handler
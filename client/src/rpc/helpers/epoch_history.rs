@@ -0,0 +1,104 @@
+// Copyright 2020 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use std::collections::VecDeque;
+
+/// EpochHistory keeps the epochs published to a subscriber that might still
+/// be reverted by a pivot chain reorg, so that the subscriber can be told
+/// what exactly was reverted.
+pub struct EpochHistory<T> {
+    epochs: VecDeque<(u64, T)>,
+    max_depth: u64,
+}
+
+impl<T> EpochHistory<T> {
+    /// Create a history that keeps at most `max_depth` epochs before the
+    /// latest one, so that it stays bounded even if no epoch is finalized.
+    pub fn new(max_depth: u64) -> Self {
+        Self {
+            epochs: VecDeque::new(),
+            max_depth,
+        }
+    }
+
+    /// Record the published epoch `new` and return the recorded epochs
+    /// reverted by it, i.e. the ones not before `new`, latest first.
+    pub fn push(&mut self, new: (u64, T)) -> Vec<(u64, T)> {
+        let mut reverted = vec![];
+
+        while matches!(self.epochs.back(), Some((e, _)) if *e >= new.0) {
+            reverted.push(self.epochs.pop_back().unwrap());
+        }
+
+        let epoch = new.0;
+        self.epochs.push_back(new);
+        self.prune(epoch.saturating_sub(self.max_depth));
+        reverted
+    }
+
+    /// Forget the epochs before `epoch`, which can no longer be reverted.
+    pub fn prune(&mut self, epoch: u64) {
+        while matches!(self.epochs.front(), Some((e, _)) if *e < epoch) {
+            self.epochs.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_no_reorgs() {
+        let mut history = EpochHistory::new(10);
+
+        assert_eq!(history.push((0, 0)), vec![]);
+        assert_eq!(history.push((1, 1)), vec![]);
+        assert_eq!(history.push((2, 2)), vec![]);
+        assert_eq!(history.push((3, 3)), vec![]);
+    }
+
+    #[test]
+    fn test_reorgs() {
+        let mut history = EpochHistory::new(10);
+
+        assert_eq!(history.push((0, 0)), vec![]);
+        assert_eq!(history.push((1, 1)), vec![]);
+        assert_eq!(history.push((2, 2)), vec![]);
+        assert_eq!(history.push((3, 3)), vec![]);
+        assert_eq!(history.push((2, 4)), vec![(3, 3), (2, 2)]); // reorg: 3 --> 2
+        assert_eq!(history.push((3, 5)), vec![]);
+        assert_eq!(history.push((3, 6)), vec![(3, 5)]); // reorg: 3 --> 3
+        assert_eq!(history.push((4, 7)), vec![]);
+    }
+
+    #[test]
+    fn test_prune() {
+        let mut history = EpochHistory::new(10);
+
+        assert_eq!(history.push((0, 0)), vec![]);
+        assert_eq!(history.push((1, 1)), vec![]);
+        assert_eq!(history.push((2, 2)), vec![]);
+        assert_eq!(history.push((3, 3)), vec![]);
+
+        // epochs before 2 can no longer be reverted
+        history.prune(2);
+        assert_eq!(history.push((1, 4)), vec![(3, 3), (2, 2)]);
+        assert_eq!(history.push((2, 5)), vec![]);
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut history = EpochHistory::new(2);
+
+        for epoch in 0..100 {
+            assert_eq!(history.push((epoch, epoch)), vec![]);
+        }
+        assert_eq!(history.epochs.len(), 3);
+
+        assert_eq!(history.push((98, 100)), vec![(99, 99), (98, 98)]);
+        // epochs more than 2 before the latest one are forgotten
+        assert_eq!(history.push((96, 101)), vec![(98, 100), (97, 97)]);
+    }
+}
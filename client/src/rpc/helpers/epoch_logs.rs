@@ -0,0 +1,135 @@
+// Copyright 2021 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use itertools::zip;
+use primitives::{log_entry::LocalizedLogEntry, Block, BlockReceipts};
+use std::sync::Arc;
+
+/// Localize the logs in the receipts of `blocks` in epoch `epoch_number`,
+/// where the blocks are in the execution order and `log_index` counts from
+/// the first log of the epoch.
+pub fn localize_epoch_logs(
+    epoch_number: u64, blocks: &[(Arc<Block>, Arc<BlockReceipts>)],
+) -> Vec<LocalizedLogEntry> {
+    let mut logs = vec![];
+    let mut log_index = 0;
+
+    for (block, block_receipts) in blocks {
+        let block_hash = block.hash();
+        for (txid, (receipt, tx)) in
+            zip(&block_receipts.receipts, &block.transactions).enumerate()
+        {
+            for (logid, entry) in receipt.logs.iter().cloned().enumerate() {
+                logs.push(LocalizedLogEntry {
+                    entry,
+                    block_hash,
+                    epoch_number,
+                    transaction_hash: tx.hash,
+                    transaction_index: txid,
+                    log_index,
+                    transaction_log_index: logid,
+                });
+
+                log_index += 1;
+            }
+        }
+    }
+
+    logs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::localize_epoch_logs;
+    use cfx_types::{Address, AddressSpaceUtil, Bloom, Space, U256};
+    use primitives::{
+        log_entry::LogEntry, Block, BlockHeaderBuilder, BlockReceipts,
+        NativeTransaction, Receipt, TransactionOutcome,
+    };
+    use std::sync::Arc;
+
+    fn log(data: u8) -> LogEntry {
+        LogEntry {
+            address: Address::zero(),
+            topics: vec![],
+            data: vec![data],
+            space: Space::Native,
+        }
+    }
+
+    /// A block with a transaction for each log list in `logs`.
+    fn block(
+        height: u64, logs: Vec<Vec<LogEntry>>,
+    ) -> (Arc<Block>, Arc<BlockReceipts>) {
+        let transactions = (0..logs.len())
+            .map(|nonce| {
+                Arc::new(
+                    NativeTransaction {
+                        nonce: nonce.into(),
+                        ..Default::default()
+                    }
+                    .fake_sign(Address::zero().with_native_space()),
+                )
+            })
+            .collect();
+        let receipts = logs
+            .into_iter()
+            .map(|logs| {
+                Receipt::new(
+                    TransactionOutcome::Success,
+                    U256::zero(),
+                    U256::zero(),
+                    false,
+                    logs,
+                    Bloom::zero(),
+                    false,
+                    vec![],
+                    vec![],
+                )
+            })
+            .collect();
+        let header = BlockHeaderBuilder::new().with_height(height).build();
+        (
+            Arc::new(Block::new(header, transactions)),
+            Arc::new(BlockReceipts {
+                receipts,
+                block_number: height,
+                secondary_reward: U256::zero(),
+                tx_execution_error_messages: vec![],
+            }),
+        )
+    }
+
+    #[test]
+    fn test_localize_epoch_logs() {
+        let blocks = vec![
+            block(1, vec![vec![log(0), log(1)], vec![]]),
+            block(2, vec![vec![], vec![log(2)]]),
+        ];
+        let logs = localize_epoch_logs(5, &blocks);
+
+        let localized: Vec<_> = logs
+            .iter()
+            .map(|log| {
+                (
+                    log.entry.data[0],
+                    log.block_hash,
+                    log.transaction_index,
+                    log.log_index,
+                    log.transaction_log_index,
+                )
+            })
+            .collect();
+        assert_eq!(
+            localized,
+            vec![
+                (0, blocks[0].0.hash(), 0, 0, 0),
+                (1, blocks[0].0.hash(), 0, 1, 1),
+                (2, blocks[1].0.hash(), 1, 2, 0),
+            ]
+        );
+        assert!(logs.iter().all(|log| log.epoch_number == 5));
+        assert_eq!(logs[2].transaction_hash, blocks[1].0.transactions[1].hash);
+    }
+}
@@ -2,11 +2,15 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
+mod epoch_history;
+mod epoch_logs;
 mod epoch_queue;
 mod poll_manager;
 mod subscribers;
 mod variadic_value;
 
+pub use epoch_history::EpochHistory;
+pub use epoch_logs::localize_epoch_logs;
 pub use epoch_queue::EpochQueue;
 pub use subscribers::{Id as SubscriberId, Subscribers};
 pub use variadic_value::{maybe_vec_into, VariadicValue};
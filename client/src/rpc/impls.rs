@@ -16,13 +16,19 @@ pub struct RpcImplConfiguration {
     pub max_payload_bytes: usize,
 
     pub enable_metrics: bool,
+
+    /// If it's `true`, the `dev_*` RPCs are enabled.
+    pub dev_mode: bool,
 }
 
+pub mod admin;
 pub mod cfx;
 pub mod common;
+pub mod dev;
 pub mod eth;
 pub mod eth_pubsub;
 pub mod light;
+pub mod mining;
 pub mod pool;
 pub mod pos;
 pub mod pubsub;
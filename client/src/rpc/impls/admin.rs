@@ -0,0 +1,30 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use crate::rpc::{impls::common::RpcImpl as CommonImpl, traits::admin::Admin};
+use delegate::delegate;
+use jsonrpc_core::Result as JsonRpcResult;
+use network::{node_table::NodeId, AdminPeerInfo, LocalNodeInfo};
+use std::sync::Arc;
+
+pub struct AdminHandler {
+    common: Arc<CommonImpl>,
+}
+
+impl AdminHandler {
+    pub fn new(common: Arc<CommonImpl>) -> Self { AdminHandler { common } }
+}
+
+impl Admin for AdminHandler {
+    delegate! {
+        to self.common {
+            fn admin_add_trusted_peer(&self, url: String) -> JsonRpcResult<bool>;
+            fn admin_remove_trusted_peer(&self, node_id: NodeId) -> JsonRpcResult<bool>;
+            fn admin_add_reserved_peer(&self, url: String) -> JsonRpcResult<bool>;
+            fn admin_remove_reserved_peer(&self, node_id: NodeId) -> JsonRpcResult<bool>;
+            fn admin_peers(&self) -> JsonRpcResult<Vec<AdminPeerInfo>>;
+            fn admin_node_info(&self) -> JsonRpcResult<Option<LocalNodeInfo>>;
+        }
+    }
+}
@@ -29,7 +29,7 @@ use network::{
     node_table::{Node, NodeId},
     throttling, SessionDetails, UpdateNodeOperation,
 };
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
 use primitives::{
     filter::LogFilter, Account, Block, BlockReceipts, DepositInfo,
     SignedTransaction, StorageKey, StorageRoot, StorageValue, Transaction,
@@ -40,7 +40,11 @@ use random_crash::*;
 use rlp::Rlp;
 use rustc_hex::ToHex;
 use std::{
-    collections::BTreeMap, net::SocketAddr, sync::Arc, thread, time::Duration,
+    collections::{BTreeMap, HashSet},
+    net::SocketAddr,
+    sync::Arc,
+    thread,
+    time::Duration,
 };
 use txgen::{DirectTransactionGenerator, TransactionGenerator};
 // To convert from RpcResult to BoxFuture by delegate! macro automatically.
@@ -59,12 +63,13 @@ use crate::{
         types::{
             pos::Block as PosBlock, sign_call, Account as RpcAccount,
             AccountPendingInfo, AccountPendingTransactions, BlameInfo,
-            Block as RpcBlock, BlockHashOrEpochNumber, Bytes, CallRequest,
-            CfxRpcLogFilter, CheckBalanceAgainstTransactionResponse,
-            ConsensusGraphStates, EpochNumber,
-            EstimateGasAndCollateralResponse, Log as RpcLog, PackedOrExecuted,
+            Block as RpcBlock, BlockHashOrEpochNumber, BlockPropagation, Bytes,
+            CallRequest, CfxRpcLogFilter,
+            CheckBalanceAgainstTransactionResponse, ConsensusGraphStates,
+            EpochNumber, EpochStateDiff, EstimateGasAndCollateralResponse,
+            GasPriceOracle, Log as RpcLog, PackedOrExecuted, PropagationEvent,
             Receipt as RpcReceipt, RewardInfo as RpcRewardInfo, SendTxRequest,
-            Status as RpcStatus, SyncGraphStates,
+            Status as RpcStatus, SyncGraphStates, SyncProgress,
             Transaction as RpcTransaction,
         },
         RpcResult,
@@ -101,11 +106,14 @@ pub struct RpcImpl {
     pub config: RpcImplConfiguration,
     pub consensus: SharedConsensusGraph,
     pub sync: SharedSynchronizationService,
-    block_gen: Arc<BlockGenerator>,
+    pub block_gen: Arc<BlockGenerator>,
     pub tx_pool: SharedTransactionPool,
     maybe_txgen: Option<Arc<TransactionGenerator>>,
     maybe_direct_txgen: Option<Arc<Mutex<DirectTransactionGenerator>>>,
     accounts: Arc<AccountProvider>,
+    /// The accounts whose transactions are sent without signatures in dev
+    /// mode, see `dev_impersonateAccount`.
+    impersonated_accounts: RwLock<HashSet<Address>>,
 }
 
 impl RpcImpl {
@@ -126,6 +134,7 @@ impl RpcImpl {
             maybe_direct_txgen,
             config,
             accounts,
+            impersonated_accounts: Default::default(),
         }
     }
 
@@ -422,27 +431,51 @@ impl RpcImpl {
         let r = self.send_transaction_with_signature(tx);
         if r.is_ok() && self.config.dev_pack_tx_immediately {
             // Try to pack and execute this new tx.
-            for _ in 0..DEFERRED_STATE_EPOCH_COUNT {
-                let generated = self.generate_one_block(
-                    1, /* num_txs */
-                    self.sync
-                        .get_synchronization_graph()
-                        .verification_config
-                        .max_block_size_in_bytes,
-                )?;
-                loop {
-                    // Wait for the new block to be fully processed, so all
-                    // generated blocks form a chain for
-                    // `tx` to be executed.
-                    if self.consensus.best_block_hash() == generated {
-                        break;
-                    } else {
-                        thread::sleep(Duration::from_millis(10));
-                    }
+            self.generate_blocks_and_wait(
+                DEFERRED_STATE_EPOCH_COUNT as usize,
+                1, /* num_txs */
+            )?;
+        }
+        r
+    }
+
+    /// Generate `num_blocks` blocks on the pivot chain one by one, each with
+    /// at most `num_txs` transactions, and wait for them to be processed.
+    pub fn generate_blocks_and_wait(
+        &self, num_blocks: usize, num_txs: usize,
+    ) -> RpcResult<Vec<H256>> {
+        let mut hashes = Vec::with_capacity(num_blocks);
+        for _ in 0..num_blocks {
+            let generated = self.generate_one_block(
+                num_txs,
+                self.sync
+                    .get_synchronization_graph()
+                    .verification_config
+                    .max_block_size_in_bytes,
+            )?;
+            loop {
+                // Wait for the new block to be fully processed, so all
+                // generated blocks form a chain for the transactions to be
+                // executed.
+                if self.consensus.best_block_hash() == generated {
+                    break;
+                } else {
+                    thread::sleep(Duration::from_millis(10));
                 }
             }
+            hashes.push(generated);
         }
-        r
+        Ok(hashes)
+    }
+
+    /// Send transactions from `address` without signatures. This is only
+    /// used in dev mode.
+    pub fn impersonate_account(&self, address: Address) {
+        self.impersonated_accounts.write().insert(address);
+    }
+
+    pub fn stop_impersonating_account(&self, address: &Address) {
+        self.impersonated_accounts.write().remove(address);
     }
 
     fn storage_at(
@@ -512,9 +545,10 @@ impl RpcImpl {
         }
     }
 
-    fn prepare_transaction(
-        &self, mut tx: SendTxRequest, password: Option<String>,
-    ) -> RpcResult<TransactionWithSignature> {
+    /// Check the address network of `tx` and fill its nonce if it's missing.
+    fn fill_send_tx_request(
+        &self, mut tx: SendTxRequest,
+    ) -> RpcResult<SendTxRequest> {
         let consensus_graph = self.consensus_graph();
         tx.check_rpc_address_network(
             "tx",
@@ -537,7 +571,14 @@ impl RpcImpl {
             tx.nonce.replace(nonce.into());
             debug!("after loading nonce in latest state, tx = {:?}", tx);
         }
+        Ok(tx)
+    }
 
+    fn prepare_transaction(
+        &self, tx: SendTxRequest, password: Option<String>,
+    ) -> RpcResult<TransactionWithSignature> {
+        let tx = self.fill_send_tx_request(tx)?;
+        let consensus_graph = self.consensus_graph();
         let epoch_height = consensus_graph.best_epoch_number();
         let chain_id = consensus_graph.best_chain_id();
         tx.sign_with(
@@ -548,11 +589,47 @@ impl RpcImpl {
         )
     }
 
+    /// Insert `tx` sent from an impersonated account into the transaction
+    /// pool with a fake signature. The transaction is not broadcast, because
+    /// other nodes cannot verify it.
+    fn send_impersonated_transaction(
+        &self, tx: SendTxRequest,
+    ) -> RpcResult<H256> {
+        let tx = self.fill_send_tx_request(tx)?;
+        let consensus_graph = self.consensus_graph();
+        let epoch_height = consensus_graph.best_epoch_number();
+        let chain_id = consensus_graph.best_chain_id();
+        let from = tx.from.hex_address.with_native_space();
+        let signed = tx
+            .to_native_transaction(epoch_height, chain_id.in_native_space())?
+            .fake_sign_impersonated(from);
+
+        let (signed_trans, failed_trans) = self
+            .tx_pool
+            .insert_new_signed_transactions(vec![Arc::new(signed)]);
+        if let Some(tx_err) = failed_trans.values().next() {
+            bail!(invalid_params("tx", tx_err.clone()))
+        }
+        match signed_trans.first() {
+            Some(tx) => Ok(tx.hash()),
+            None => {
+                bail!(invalid_params("tx", String::from("tx already exist")))
+            }
+        }
+    }
+
     fn send_transaction(
         &self, tx: SendTxRequest, password: Option<String>,
     ) -> RpcResult<H256> {
         info!("RPC Request: cfx_sendTransaction, tx = {:?}", tx);
 
+        if self
+            .impersonated_accounts
+            .read()
+            .contains(&tx.from.hex_address)
+        {
+            return self.send_impersonated_transaction(tx);
+        }
         self.prepare_transaction(tx, password)
             .and_then(|tx| self.send_transaction_with_signature(tx))
     }
@@ -1135,6 +1212,37 @@ impl RpcImpl {
         Ok(ret)
     }
 
+    fn get_epoch_state_diff(
+        &self, epoch: EpochNumber,
+    ) -> RpcResult<Option<EpochStateDiff>> {
+        info!("RPC Request: cfx_getEpochStateDiff epoch={:?}", epoch);
+
+        // Resolve the pivot block once, so that the epoch number and the diff
+        // are consistent even if the pivot chain changes in between.
+        let epoch_hash = self
+            .consensus
+            .get_hash_from_epoch_number(epoch.into_primitive())?;
+        let data_man = self.consensus.get_data_manager();
+        // The height of a pivot block is the number of its epoch.
+        let epoch_number = match data_man
+            .block_header_by_hash(&epoch_hash)
+        {
+            Some(header) => header.height(),
+            None => return Ok(None),
+        };
+
+        match data_man.epoch_state_diff(&epoch_hash) {
+            None => Ok(None),
+            Some(diff) => Ok(Some(EpochStateDiff::try_from(
+                epoch_number,
+                epoch_hash,
+                diff,
+                &|key| data_man.storage_key_preimage(key),
+                *self.sync.network.get_network_type(),
+            )?)),
+        }
+    }
+
     fn call(
         &self, request: CallRequest, epoch: Option<EpochNumber>,
     ) -> RpcResult<Bytes> {
@@ -1363,6 +1471,11 @@ impl RpcImpl {
         Ok(self.sync.current_sync_phase().name().into())
     }
 
+    fn sync_progress(&self) -> RpcResult<SyncProgress> {
+        info!("RPC Request: cfx_syncProgress");
+        Ok(self.sync.sync_progress().into())
+    }
+
     /// Return the pivot chain block hashes in `height_range` (inclusive) and
     /// their subtree weight. If it's none, return all pivot chain from
     /// `cur_era_genesis` to chain tip.
@@ -1411,6 +1524,18 @@ impl RpcImpl {
         Ok(SyncGraphStates::new(sync_graph_states))
     }
 
+    pub fn block_propagation(
+        &self, block_hash: H256,
+    ) -> RpcResult<Option<BlockPropagation>> {
+        Ok(self.sync.block_propagation(&block_hash).map(Into::into))
+    }
+
+    pub fn transaction_first_seen(
+        &self, tx_hash: H256,
+    ) -> RpcResult<Option<PropagationEvent>> {
+        Ok(self.sync.transaction_first_seen(&tx_hash).map(Into::into))
+    }
+
     /// Return (block_info.status, state_valid)
     /// Return Error if either field is missing
     pub fn get_block_status(&self, block_hash: H256) -> RpcResult<(u8, bool)> {
@@ -1617,6 +1742,7 @@ impl Cfx for CfxHandler {
             fn skipped_blocks_by_epoch(&self, num: EpochNumber) -> JsonRpcResult<Vec<H256>>;
             fn epoch_number(&self, epoch_num: Option<EpochNumber>) -> JsonRpcResult<U256>;
             fn gas_price(&self) -> BoxFuture<U256>;
+            fn gas_price_oracle(&self) -> JsonRpcResult<GasPriceOracle>;
             fn next_nonce(&self, address: RpcAddress, num: Option<BlockHashOrEpochNumber>)
                 -> BoxFuture<U256>;
             fn get_status(&self) -> JsonRpcResult<RpcStatus>;
@@ -1661,6 +1787,8 @@ impl Cfx for CfxHandler {
             fn storage_root(&self, address: RpcAddress, epoch_num: Option<EpochNumber>) -> BoxFuture<Option<StorageRoot>>;
             fn get_supply_info(&self, epoch_num: Option<EpochNumber>) -> JsonRpcResult<TokenSupplyInfo>;
             fn get_vote_params(&self, epoch_num: Option<EpochNumber>) -> JsonRpcResult<VoteParamsInfo>;
+            fn get_epoch_state_diff(&self, epoch: EpochNumber) -> JsonRpcResult<Option<EpochStateDiff>>;
+            fn sync_progress(&self) -> JsonRpcResult<SyncProgress>;
         }
     }
 }
@@ -1760,6 +1888,7 @@ impl LocalRpc for LocalRpcImpl {
             fn net_node(&self, id: NodeId) -> JsonRpcResult<Option<(String, Node)>>;
             fn net_disconnect_node(&self, id: NodeId, op: Option<UpdateNodeOperation>)
                 -> JsonRpcResult<bool>;
+            fn net_reset_node_score(&self, id: NodeId) -> JsonRpcResult<bool>;
             fn net_sessions(&self, node_id: Option<NodeId>) -> JsonRpcResult<Vec<SessionDetails>>;
             fn net_throttling(&self) -> JsonRpcResult<throttling::Service>;
             fn accounts(&self) -> JsonRpcResult<Vec<RpcAddress>>;
@@ -1778,6 +1907,8 @@ impl LocalRpc for LocalRpcImpl {
             fn consensus_graph_state(&self) -> JsonRpcResult<ConsensusGraphStates>;
             fn epoch_receipts(&self, epoch: BlockHashOrEpochNumber) -> JsonRpcResult<Option<Vec<Vec<RpcReceipt>>>>;
             fn sync_graph_state(&self) -> JsonRpcResult<SyncGraphStates>;
+            fn block_propagation(&self, block_hash: H256) -> JsonRpcResult<Option<BlockPropagation>>;
+            fn transaction_first_seen(&self, tx_hash: H256) -> JsonRpcResult<Option<PropagationEvent>>;
             fn send_transaction(
                 &self, tx: SendTxRequest, password: Option<String>) -> BoxFuture<H256>;
             fn sign_transaction(&self, tx: SendTxRequest, password: Option<String>) -> JsonRpcResult<String>;
@@ -5,6 +5,7 @@
 use std::{
     collections::{BTreeMap, HashSet},
     net::SocketAddr,
+    str::FromStr,
     sync::Arc,
     time::Duration,
 };
@@ -15,7 +16,7 @@ use crate::rpc::{
         errors::check_rpc_address_network, pos::PoSEpochReward,
         AccountPendingInfo, AccountPendingTransactions, Block as RpcBlock,
         BlockHashOrEpochNumber, Bytes, CheckBalanceAgainstTransactionResponse,
-        EpochNumber, RpcAddress, Status as RpcStatus,
+        EpochNumber, GasPriceOracle, RpcAddress, Status as RpcStatus,
         Transaction as RpcTransaction, TxPoolPendingNonceRange, TxPoolStatus,
         TxWithPoolInfo,
     },
@@ -40,9 +41,11 @@ use cfx_types::{
     Address, AddressSpaceUtil, Space, H160, H256, H520, U128, U256, U512, U64,
 };
 use cfxcore::{
-    consensus::pos_handler::PosVerifier, rpc_errors::invalid_params_check,
-    spec::genesis::register_transaction, BlockDataManager, ConsensusGraph,
-    ConsensusGraphTrait, PeerInfo, SharedConsensusGraph, SharedTransactionPool,
+    consensus::pos_handler::{PosKeys, PosVerifier},
+    rpc_errors::invalid_params_check,
+    spec::genesis::register_transaction,
+    BlockDataManager, ConsensusGraph, ConsensusGraphTrait, PeerInfo,
+    SharedConsensusGraph, SharedTransactionPool,
 };
 use cfxcore_accounts::AccountProvider;
 use cfxkey::Password;
@@ -55,7 +58,8 @@ use diem_types::{
 use network::{
     node_table::{Node, NodeEndpoint, NodeEntry, NodeId},
     throttling::{self, THROTTLING_SERVICE},
-    NetworkService, SessionDetails, UpdateNodeOperation,
+    AdminPeerInfo, LocalNodeInfo, NetworkService, SessionDetails,
+    UpdateNodeOperation,
 };
 use primitives::{Account, Action, Block, SignedTransaction, Transaction};
 use storage_interface::DBReaderForPoW;
@@ -82,6 +86,26 @@ where F: Fn(Arc<SignedTransaction>) -> T {
     addr_grouped_txs
 }
 
+/// Scale the confirmation risk `risk` in [0, 1] to [0, 2^256 - 1], as returned
+/// by `cfx_getConfirmationRiskByHash`.
+pub fn scaled_confirmation_risk(risk: f64) -> U256 {
+    let risk: BigDecimal = risk.into();
+    let scale = BigInt::parse_bytes(
+        b"FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+        16,
+    )
+    .expect("failed to unwrap U256::max into bigInt");
+
+    //TODO: there's a precision problem here, it should be fine under a
+    // (2^256 - 1) scale
+    let scaled_risk: BigInt = (risk * scale)
+        .to_bigint()
+        .expect("failed to convert scaled risk to bigInt");
+    let (sign, big_endian_bytes) = scaled_risk.to_bytes_be();
+    assert_ne!(sign, num_bigint::Sign::Minus);
+    U256::from(big_endian_bytes.as_slice())
+}
+
 pub fn check_balance_against_transaction(
     user_account: Option<Account>, contract_account: Option<Account>,
     is_sponsored: bool, gas_limit: U256, gas_price: U256, storage_limit: U256,
@@ -212,6 +236,15 @@ impl RpcImpl {
             .into())
     }
 
+    pub fn gas_price_oracle(&self) -> JsonRpcResult<GasPriceOracle> {
+        let consensus_graph = self.consensus_graph();
+        info!("RPC Request: cfx_gasPriceOracle()");
+        Ok(GasPriceOracle::new(
+            consensus_graph.gas_price_oracle(Space::Native),
+            &self.tx_pool,
+        ))
+    }
+
     pub fn epoch_number(
         &self, epoch_num: Option<EpochNumber>,
     ) -> JsonRpcResult<U256> {
@@ -336,22 +369,7 @@ impl RpcImpl {
         let result = consensus_graph
             .confirmation_meter
             .confirmation_risk_by_hash(inner, block_hash.into());
-        if result.is_none() {
-            Ok(None)
-        } else {
-            let risk: BigDecimal = result.unwrap().into();
-            let scale = BigInt::parse_bytes(b"FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF", 16).expect("failed to unwrap U256::max into bigInt");
-
-            //TODO: there's a precision problem here, it should be fine under a
-            // (2^256 - 1) scale
-            let scaled_risk: BigInt = (risk * scale)
-                .to_bigint()
-                .expect("failed to convert scaled risk to bigInt");
-            let (sign, big_endian_bytes) = scaled_risk.to_bytes_be();
-            assert_ne!(sign, num_bigint::Sign::Minus);
-            let rpc_result = U256::from(big_endian_bytes.as_slice());
-            Ok(Some(rpc_result.into()))
-        }
+        Ok(result.map(scaled_confirmation_risk))
     }
 
     pub fn block_by_hash(
@@ -757,15 +775,22 @@ impl RpcImpl {
     pub fn pos_register(
         &self, voting_power: U64,
     ) -> JsonRpcResult<(Bytes, AccountAddress)> {
+        let (bls_key, vrf_key) = match &self.pos_handler.config().pos_keys {
+            PosKeys::Local { bls_key, vrf_key } => (bls_key, vrf_key),
+            PosKeys::Remote { .. } => {
+                warn!("pos_register: PoS keys are held by a remote signer");
+                bail!(RpcError::internal_error())
+            }
+        };
         let tx = register_transaction(
-            self.pos_handler.config().bls_key.private_key(),
-            self.pos_handler.config().vrf_key.public_key(),
+            bls_key.private_key(),
+            vrf_key.public_key(),
             voting_power.as_u64(),
             0,
         );
         let identifier = from_consensus_public_key(
-            &self.pos_handler.config().bls_key.public_key(),
-            &self.pos_handler.config().vrf_key.public_key(),
+            &bls_key.public_key(),
+            &vrf_key.public_key(),
         );
         Ok((tx.data.into(), identifier))
     }
@@ -953,6 +978,10 @@ impl RpcImpl {
         Ok(self.network.disconnect_node(&id, op))
     }
 
+    pub fn net_reset_node_score(&self, id: NodeId) -> JsonRpcResult<bool> {
+        Ok(self.network.reset_node_score(&id))
+    }
+
     pub fn net_sessions(
         &self, node_id: Option<NodeId>,
     ) -> JsonRpcResult<Vec<SessionDetails>> {
@@ -962,6 +991,64 @@ impl RpcImpl {
         }
     }
 
+    pub fn admin_add_trusted_peer(&self, url: String) -> JsonRpcResult<bool> {
+        info!("RPC Request: admin_addTrustedPeer({})", url);
+        let node = Self::parse_node_url(&url)?;
+        match self.network.add_trusted_peer(node) {
+            Ok(_) => Ok(true),
+            Err(_) => Err(RpcError::internal_error()),
+        }
+    }
+
+    pub fn admin_remove_trusted_peer(
+        &self, node_id: NodeId,
+    ) -> JsonRpcResult<bool> {
+        info!("RPC Request: admin_removeTrustedPeer({:?})", node_id);
+        self.network
+            .remove_trusted_peer(&node_id)
+            .map_err(|_| RpcError::internal_error())
+    }
+
+    pub fn admin_add_reserved_peer(&self, url: String) -> JsonRpcResult<bool> {
+        info!("RPC Request: admin_addReservedPeer({})", url);
+        let node = Self::parse_node_url(&url)?;
+        match self.network.add_reserved_peer(node) {
+            Ok(_) => Ok(true),
+            Err(_) => Err(RpcError::internal_error()),
+        }
+    }
+
+    pub fn admin_remove_reserved_peer(
+        &self, node_id: NodeId,
+    ) -> JsonRpcResult<bool> {
+        info!("RPC Request: admin_removeReservedPeer({:?})", node_id);
+        self.network
+            .remove_reserved_peer(&node_id)
+            .map_err(|_| RpcError::internal_error())
+    }
+
+    pub fn admin_peers(&self) -> JsonRpcResult<Vec<AdminPeerInfo>> {
+        Ok(self.network.get_admin_peers().unwrap_or_default())
+    }
+
+    pub fn admin_node_info(&self) -> JsonRpcResult<Option<LocalNodeInfo>> {
+        Ok(self.network.get_local_node_info())
+    }
+
+    /// Parse node with url format "cfxnode://ID@IP:PORT".
+    fn parse_node_url(url: &str) -> JsonRpcResult<NodeEntry> {
+        let node = Node::from_str(url).map_err(|e| {
+            RpcError::invalid_params(format!("invalid node url: {}", e))
+        })?;
+        if node.id == NodeId::default() {
+            return Err(RpcError::invalid_params("node id is missing"));
+        }
+        Ok(NodeEntry {
+            id: node.id,
+            endpoint: node.endpoint,
+        })
+    }
+
     pub fn net_throttling(&self) -> JsonRpcResult<throttling::Service> {
         Ok(THROTTLING_SERVICE.read().clone())
     }
@@ -0,0 +1,270 @@
+// Copyright 2021 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use crate::{
+    common::delegate_convert::into_jsonrpc_result,
+    rpc::{
+        error_codes::invalid_params,
+        impls::cfx::RpcImpl,
+        traits::dev::Dev,
+        types::{
+            errors::check_rpc_address_network, Bytes, DevAddress, RpcAddress,
+        },
+        RpcResult,
+    },
+};
+use cfx_addr::Network;
+use cfx_types::{AddressWithSpace, BigEndianHash, H256, U256, U64};
+use cfxcore::{
+    consensus_parameters::DEFERRED_STATE_EPOCH_COUNT,
+    rpc_errors::invalid_params_check, state::DevStateOverride, ConsensusGraph,
+    ConsensusGraphTrait,
+};
+use jsonrpc_core::Result as JsonRpcResult;
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// The maximum number of transactions packed in a block mined by `dev_mine`,
+/// the same as the automatic block generation in dev mode.
+const DEV_MINE_MAX_TXS: usize = 3000;
+
+pub struct DevHandler {
+    rpc_impl: Arc<RpcImpl>,
+    /// The pivot block hashes of the snapshots, indexed by the snapshot id.
+    snapshots: Mutex<Vec<H256>>,
+}
+
+impl DevHandler {
+    pub fn new(rpc_impl: Arc<RpcImpl>) -> Self {
+        DevHandler {
+            rpc_impl,
+            snapshots: Default::default(),
+        }
+    }
+
+    fn consensus_graph(&self) -> &ConsensusGraph {
+        self.rpc_impl
+            .consensus
+            .as_any()
+            .downcast_ref::<ConsensusGraph>()
+            .expect("downcast should succeed")
+    }
+
+    fn check_address_network(&self, network: Option<Network>) -> RpcResult<()> {
+        invalid_params_check(
+            "address",
+            check_rpc_address_network(
+                network,
+                self.rpc_impl.sync.network.get_network_type(),
+            ),
+        )
+    }
+
+    fn snapshot_impl(&self) -> RpcResult<U64> {
+        let mut snapshots = self.snapshots.lock();
+        snapshots.push(self.rpc_impl.consensus.best_block_hash());
+        Ok((snapshots.len() - 1).into())
+    }
+
+    /// Revert to a snapshot by mining a fork of empty blocks on its pivot
+    /// block until the fork becomes the pivot chain. The abandoned blocks are
+    /// never referenced later, so their transactions are not executed. Note
+    /// that the epoch numbers keep growing after reverting.
+    fn revert_impl(&self, id: U64) -> RpcResult<bool> {
+        let snapshot_pivot = {
+            let mut snapshots = self.snapshots.lock();
+            let id = id.as_usize();
+            if id >= snapshots.len() {
+                return Ok(false);
+            }
+            let snapshot_pivot = snapshots[id];
+            snapshots.truncate(id);
+            snapshot_pivot
+        };
+        let consensus_graph = self.consensus_graph();
+        let best_info = consensus_graph.best_info();
+        if best_info.best_block_hash == snapshot_pivot {
+            return Ok(true);
+        }
+
+        info!("RPC Request: dev_revert to pivot {:?}", snapshot_pivot);
+        let block_gen = &self.rpc_impl.block_gen;
+        block_gen
+            .exclude_referees(best_info.bounded_terminal_block_hashes.clone());
+        // The transactions in the abandoned blocks may be invalid on the fork.
+        self.rpc_impl.tx_pool.clear_tx_pool();
+
+        let snapshot_height = consensus_graph
+            .data_man
+            .block_height_by_hash(&snapshot_pivot)
+            .ok_or("snapshot pivot block not found")?;
+        // The fork becomes heavier than the abandoned chain after mining one
+        // more block than it, unless it's rejected by the PoS finality.
+        let max_fork_blocks = 2
+            * (best_info.best_epoch_number.saturating_sub(snapshot_height) + 1);
+        let mut fork_tip = snapshot_pivot;
+        for _ in 0..max_fork_blocks {
+            fork_tip = block_gen.generate_fork_block(fork_tip)?;
+            if consensus_graph.best_block_hash() == fork_tip {
+                return Ok(true);
+            }
+        }
+        bail!(format!(
+            "failed to revert to pivot {:?} after mining {} blocks",
+            snapshot_pivot, max_fork_blocks
+        ))
+    }
+
+    /// Apply `state_override` in the next executed epoch, and mine blocks
+    /// so that it's visible in the latest state.
+    fn set_state(&self, state_override: DevStateOverride) -> RpcResult<()> {
+        info!("RPC Request: dev state override {:?}", state_override);
+        self.consensus_graph()
+            .queue_dev_state_override(state_override);
+        self.rpc_impl.generate_blocks_and_wait(
+            DEFERRED_STATE_EPOCH_COUNT as usize,
+            0, /* num_txs */
+        )?;
+        Ok(())
+    }
+
+    fn dev_address(&self, address: DevAddress) -> RpcResult<AddressWithSpace> {
+        self.check_address_network(address.network())?;
+        Ok(address.into())
+    }
+
+    fn set_balance_impl(
+        &self, address: DevAddress, balance: U256,
+    ) -> RpcResult<()> {
+        let address = self.dev_address(address)?;
+        self.set_state(DevStateOverride::Balance { address, balance })
+    }
+
+    fn set_code_impl(&self, address: DevAddress, code: Bytes) -> RpcResult<()> {
+        let address = self.dev_address(address)?;
+        self.set_state(DevStateOverride::Code {
+            address,
+            code: code.into_vec(),
+        })
+    }
+
+    fn set_storage_at_impl(
+        &self, address: DevAddress, position: U256, value: H256,
+    ) -> RpcResult<()> {
+        let address = self.dev_address(address)?;
+        self.set_state(DevStateOverride::Storage {
+            address,
+            key: H256::from_uint(&position),
+            value: value.into_uint(),
+        })
+    }
+
+    fn set_nonce_impl(
+        &self, address: DevAddress, nonce: U256,
+    ) -> RpcResult<()> {
+        let address = self.dev_address(address)?;
+        self.set_state(DevStateOverride::Nonce { address, nonce })
+    }
+
+    fn mine_impl(&self, num_blocks: Option<U64>) -> RpcResult<Vec<H256>> {
+        let num_blocks = num_blocks.map_or(1, |n| n.as_usize());
+        if num_blocks == 0 {
+            bail!(invalid_params("num_blocks", "should be positive"));
+        }
+        info!("RPC Request: dev_mine({})", num_blocks);
+        self.rpc_impl
+            .generate_blocks_and_wait(num_blocks, DEV_MINE_MAX_TXS)
+    }
+
+    fn increase_time_impl(&self, seconds: U64) -> RpcResult<U64> {
+        info!("RPC Request: dev_increaseTime({})", seconds);
+        match self.rpc_impl.block_gen.increase_time(seconds.as_u64()) {
+            Some(total) => Ok(total.into()),
+            None => bail!(invalid_params(
+                "seconds",
+                "the total increased time overflows"
+            )),
+        }
+    }
+
+    fn impersonate_account_impl(&self, address: DevAddress) -> RpcResult<()> {
+        let address = match address {
+            DevAddress::Native(address) => address,
+            // There is no `eth_sendTransaction` to send the transactions of
+            // an impersonated eSpace account.
+            DevAddress::Ethereum(_) => bail!(invalid_params(
+                "address",
+                "impersonating eSpace accounts is not supported"
+            )),
+        };
+        self.check_address_network(Some(address.network))?;
+        self.rpc_impl.impersonate_account(address.hex_address);
+        Ok(())
+    }
+
+    fn stop_impersonating_account_impl(
+        &self, address: RpcAddress,
+    ) -> RpcResult<()> {
+        self.check_address_network(Some(address.network))?;
+        self.rpc_impl
+            .stop_impersonating_account(&address.hex_address);
+        Ok(())
+    }
+}
+
+impl Dev for DevHandler {
+    fn snapshot(&self) -> JsonRpcResult<U64> {
+        into_jsonrpc_result(self.snapshot_impl())
+    }
+
+    fn revert(&self, id: U64) -> JsonRpcResult<bool> {
+        into_jsonrpc_result(self.revert_impl(id))
+    }
+
+    fn set_balance(
+        &self, address: DevAddress, balance: U256,
+    ) -> JsonRpcResult<()> {
+        into_jsonrpc_result(self.set_balance_impl(address, balance))
+    }
+
+    fn set_code(&self, address: DevAddress, code: Bytes) -> JsonRpcResult<()> {
+        into_jsonrpc_result(self.set_code_impl(address, code))
+    }
+
+    fn set_storage_at(
+        &self, address: DevAddress, position: U256, value: H256,
+    ) -> JsonRpcResult<()> {
+        into_jsonrpc_result(self.set_storage_at_impl(address, position, value))
+    }
+
+    fn set_nonce(&self, address: DevAddress, nonce: U256) -> JsonRpcResult<()> {
+        into_jsonrpc_result(self.set_nonce_impl(address, nonce))
+    }
+
+    fn increase_time(&self, seconds: U64) -> JsonRpcResult<U64> {
+        into_jsonrpc_result(self.increase_time_impl(seconds))
+    }
+
+    fn set_next_block_timestamp(&self, timestamp: U64) -> JsonRpcResult<()> {
+        info!("RPC Request: dev_setNextBlockTimestamp({})", timestamp);
+        self.rpc_impl
+            .block_gen
+            .set_next_block_timestamp(timestamp.as_u64());
+        Ok(())
+    }
+
+    fn mine(&self, num_blocks: Option<U64>) -> JsonRpcResult<Vec<H256>> {
+        into_jsonrpc_result(self.mine_impl(num_blocks))
+    }
+
+    fn impersonate_account(&self, address: DevAddress) -> JsonRpcResult<()> {
+        into_jsonrpc_result(self.impersonate_account_impl(address))
+    }
+
+    fn stop_impersonating_account(
+        &self, address: RpcAddress,
+    ) -> JsonRpcResult<()> {
+        into_jsonrpc_result(self.stop_impersonating_account_impl(address))
+    }
+}
@@ -15,7 +15,7 @@ use crate::rpc::{
             CallRequest, EthRpcLogFilter, FilterChanges, Log, Receipt,
             SyncInfo, SyncStatus, Transaction,
         },
-        Bytes, Index, MAX_GAS_CALL_REQUEST,
+        Bytes, GasPriceOracle, Index, MAX_GAS_CALL_REQUEST,
     },
 };
 use cfx_parameters::rpc::GAS_PRICE_DEFAULT_VALUE;
@@ -382,6 +382,14 @@ impl Eth for EthHandler {
             .unwrap_or(GAS_PRICE_DEFAULT_VALUE.into()))
     }
 
+    fn gas_price_oracle(&self) -> jsonrpc_core::Result<GasPriceOracle> {
+        info!("RPC Request: eth_gasPriceOracle");
+        Ok(GasPriceOracle::new(
+            self.consensus_graph().gas_price_oracle(Space::Ethereum),
+            &self.tx_pool,
+        ))
+    }
+
     fn max_priority_fee_per_gas(&self) -> jsonrpc_core::Result<U256> {
         info!("RPC Request: eth_maxPriorityFeePerGas");
         // TODO: Change this
@@ -4,10 +4,16 @@
 
 use crate::rpc::{
     error_codes,
-    helpers::{EpochQueue, SubscriberId, Subscribers},
+    helpers::{
+        localize_epoch_logs, EpochHistory, EpochQueue, SubscriberId,
+        Subscribers,
+    },
     metadata::Metadata,
     traits::eth_space::eth_pubsub::EthPubSub as PubSub,
-    types::eth::{eth_pubsub as pubsub, Header as RpcHeader, Log as RpcLog},
+    types::eth::{
+        eth_pubsub::{self as pubsub, RevertedBlock},
+        Header as RpcHeader, Log as RpcLog,
+    },
 };
 use cfx_parameters::consensus::DEFERRED_STATE_EPOCH_COUNT;
 use cfx_types::{Space, H256};
@@ -27,11 +33,11 @@ use jsonrpc_pubsub::{
 };
 use parking_lot::RwLock;
 use primitives::{
-    filter::LogFilter, log_entry::LocalizedLogEntry, BlockReceipts, EpochNumber,
+    filter::LogFilter, log_entry::LocalizedLogEntry, BlockReceipts,
+    EpochNumber, TransactionOutcome,
 };
 use runtime::Executor;
 use std::{
-    collections::VecDeque,
     sync::{Arc, Weak},
     time::Duration,
 };
@@ -45,6 +51,8 @@ pub struct PubSubClient {
     handler: Arc<ChainNotificationHandler>,
     heads_subscribers: Arc<RwLock<Subscribers<Client>>>,
     logs_subscribers: Arc<RwLock<Subscribers<(Client, LogFilter)>>>,
+    reorgs_subscribers: Arc<RwLock<Subscribers<Client>>>,
+    finalized_subscribers: Arc<RwLock<Subscribers<Client>>>,
     epochs_ordered: Arc<Channel<(u64, Vec<H256>)>>,
     consensus: SharedConsensusGraph,
     heads_loop_started: Arc<RwLock<bool>>,
@@ -59,6 +67,9 @@ impl PubSubClient {
     {
         let heads_subscribers = Arc::new(RwLock::new(Subscribers::default()));
         let logs_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+        let reorgs_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+        let finalized_subscribers =
+            Arc::new(RwLock::new(Subscribers::default()));
 
         let handler = Arc::new(ChainNotificationHandler {
             executor,
@@ -71,6 +82,8 @@ impl PubSubClient {
             handler,
             heads_subscribers,
             logs_subscribers,
+            reorgs_subscribers,
+            finalized_subscribers,
             epochs_ordered: notifications.epochs_ordered.clone(),
             consensus: consensus.clone(),
             heads_loop_started: Arc::new(RwLock::new(false)),
@@ -150,8 +163,11 @@ impl PubSubClient {
 
         // loop asynchronously
         let fut = async move {
-            let mut last_epoch = 0;
-            let mut epochs: VecDeque<(u64, Vec<H256>)> = VecDeque::new();
+            // bound the history by the era length in case no epoch is
+            // finalized
+            let era_epoch_count =
+                consensus.get_config().inner_conf.era_epoch_count;
+            let mut history = EpochHistory::new(era_epoch_count);
 
             while let Some(epoch) = receiver.recv().await {
                 trace!("logs_loop({:?}): {:?}", id, epoch);
@@ -172,18 +188,10 @@ impl PubSubClient {
                 };
 
                 // publish pivot chain reorg if necessary
-                if epoch.0 <= last_epoch {
-                    debug!("pivot chain reorg: {} -> {}", last_epoch, epoch.0);
+                let reverted = history.push(epoch.clone());
+                if !reverted.is_empty() {
                     assert!(epoch.0 > 0, "Unexpected epoch number received.");
-
-                    let mut reverted = vec![];
-                    while let Some(e) = epochs.back() {
-                        if e.0 >= epoch.0 {
-                            reverted.push(epochs.pop_back().unwrap());
-                        } else {
-                            break;
-                        }
-                    }
+                    debug!("pivot chain reorg: revert to {}", epoch.0 - 1);
 
                     for e in reverted.into_iter() {
                         handler
@@ -192,21 +200,132 @@ impl PubSubClient {
                     }
                 }
 
-                last_epoch = epoch.0;
-                epochs.push_back(epoch.clone());
+                history.prune(consensus.latest_finalized_epoch_number());
+
+                // publish matching logs
+                handler.notify_logs(&sub, filter, epoch, false).await;
+            }
+        };
+
+        // run futures@0.3 future on tokio@0.1 executor
+        let fut = fut.unit_error().boxed().compat();
+        self.handler.executor.spawn(fut);
+    }
 
-                let latest_finalized_epoch_number =
-                    consensus.latest_finalized_epoch_number();
-                while let Some(e) = epochs.front() {
-                    if e.0 < latest_finalized_epoch_number {
-                        epochs.pop_front();
-                    } else {
-                        break;
+    // Start an async loop that continuously receives epoch notifications and
+    // publishes the pivot chain reorgs to subscriber `id`, with the blocks
+    // reverted by each reorg. The loop terminates when subscriber `id`
+    // unsubscribes.
+    fn start_reorgs_loop(&self, id: SubscriberId) {
+        trace!("start_reorgs_loop({:?})", id);
+
+        // clone everything we use in our async loop
+        let subscribers = self.reorgs_subscribers.clone();
+        let epochs_ordered = self.epochs_ordered.clone();
+        let handler = self.handler.clone();
+
+        // subscribe to the `epochs_ordered` channel
+        let mut receiver = epochs_ordered.subscribe();
+
+        // use a queue to make sure we only process an epoch once it has been
+        // executed for sure
+        let mut queue = EpochQueue::<Vec<H256>>::with_capacity(
+            (DEFERRED_STATE_EPOCH_COUNT - 1) as usize,
+        );
+
+        let consensus = self.consensus.clone();
+
+        // loop asynchronously
+        let fut = async move {
+            // bound the history by the era length in case no epoch is
+            // finalized
+            let era_epoch_count =
+                consensus.get_config().inner_conf.era_epoch_count;
+            let mut history = EpochHistory::new(era_epoch_count);
+
+            while let Some(epoch) = receiver.recv().await {
+                trace!("reorgs_loop({:?}): {:?}", id, epoch);
+
+                // retrieve subscriber
+                let sub = match subscribers.read().get(&id) {
+                    Some(sub) => sub.clone(),
+                    None => {
+                        // unsubscribed, terminate loop
+                        epochs_ordered.unsubscribe(receiver.id);
+                        return;
                     }
+                };
+
+                let epoch = match queue.push(epoch) {
+                    None => continue,
+                    Some(e) => e,
+                };
+
+                // publish pivot chain reorg if necessary
+                let reverted = history.push(epoch.clone());
+                if !reverted.is_empty() {
+                    assert!(epoch.0 > 0, "Unexpected epoch number received.");
+                    debug!("pivot chain reorg: revert to {}", epoch.0 - 1);
+                    handler.notify_revert(&sub, epoch.0 - 1, reverted).await;
                 }
 
-                // publish matching logs
-                handler.notify_logs(&sub, filter, epoch, false).await;
+                history.prune(consensus.latest_finalized_epoch_number());
+            }
+        };
+
+        // run futures@0.3 future on tokio@0.1 executor
+        let fut = fut.unit_error().boxed().compat();
+        self.handler.executor.spawn(fut);
+    }
+
+    // Start an async loop that checks the latest finalized block whenever an
+    // epoch notification is received and publishes the newly finalized block
+    // range to subscriber `id`. The loop terminates when subscriber `id`
+    // unsubscribes.
+    fn start_finalized_loop(&self, id: SubscriberId) {
+        trace!("start_finalized_loop({:?})", id);
+
+        // clone everything we use in our async loop
+        let subscribers = self.finalized_subscribers.clone();
+        let epochs_ordered = self.epochs_ordered.clone();
+        let consensus = self.consensus.clone();
+
+        // subscribe to the `epochs_ordered` channel
+        let mut receiver = epochs_ordered.subscribe();
+
+        // only the blocks finalized after subscribing are published
+        let mut last_finalized = consensus.latest_finalized_epoch_number();
+
+        // loop asynchronously
+        let fut = async move {
+            while let Some(epoch) = receiver.recv().await {
+                trace!("finalized_loop({:?}): {:?}", id, epoch);
+
+                // retrieve subscriber
+                let sub = match subscribers.read().get(&id) {
+                    Some(sub) => sub.clone(),
+                    None => {
+                        // unsubscribed, terminate loop
+                        epochs_ordered.unsubscribe(receiver.id);
+                        return;
+                    }
+                };
+
+                let finalized = consensus.latest_finalized_epoch_number();
+                if finalized <= last_finalized {
+                    continue;
+                }
+
+                // publish finalized block range
+                ChainNotificationHandler::notify_async(
+                    &sub,
+                    pubsub::Result::FinalizedBlocks {
+                        from_block: (last_finalized + 1).into(),
+                        to_block: finalized.into(),
+                    },
+                )
+                .await;
+                last_finalized = finalized;
             }
         };
 
@@ -314,6 +433,8 @@ impl ChainNotificationHandler {
     {
         debug!("notify_logs({:?})", epoch);
 
+        let pivot = epoch.1.last().cloned().expect("epoch should not be empty");
+
         // NOTE: calls to DbManager are supposed to be cached
         // FIXME(thegaram): what is the perf impact of calling this for each
         // subscriber? would it be better to do this once for each epoch?
@@ -323,12 +444,18 @@ impl ChainNotificationHandler {
         };
 
         // apply filter to logs
+        // removed logs are not in the pivot chain any more, so they refer to
+        // the pivot block of the reverted epoch
         let logs = logs
             .iter()
             .filter(|l| filter.matches(&l.entry))
             .cloned()
             .map(|l| {
-                RpcLog::try_from_localized(l, self.consensus.clone(), removed)
+                if removed {
+                    Ok(RpcLog::from_localized_with_pivot(l, pivot, true))
+                } else {
+                    RpcLog::try_from_localized(l, self.consensus.clone(), false)
+                }
             });
 
         // send logs in order
@@ -349,6 +476,28 @@ impl ChainNotificationHandler {
         }
     }
 
+    async fn notify_revert(
+        &self, subscriber: &Client, revert_to: u64,
+        reverted: Vec<(u64, Vec<H256>)>,
+    )
+    {
+        debug!("notify_revert({:?})", revert_to);
+
+        let reverted_blocks = reverted
+            .into_iter()
+            .map(|epoch| self.retrieve_reverted_block(epoch))
+            .collect();
+
+        Self::notify_async(
+            subscriber,
+            pubsub::Result::ChainReorg {
+                revert_to: revert_to.into(),
+                reverted_blocks,
+            },
+        )
+        .await
+    }
+
     // attempt to retrieve block receipts from BlockDataManager
     // on failure, wait and retry a few times, then fail
     // NOTE: we do this because we might get epoch notifications
@@ -411,9 +560,7 @@ impl ChainNotificationHandler {
             .into_iter()
             .collect::<Option<Vec<_>>>()?;
 
-        let mut logs = vec![];
-        let mut log_index = 0;
-
+        let mut blocks = Vec::with_capacity(hashes.len());
         for (block_hash, block_receipts) in zip(hashes, receipts) {
             // retrieve block transactions
             let block = match self
@@ -426,31 +573,82 @@ impl ChainNotificationHandler {
                     return None;
                 }
             };
+            assert_eq!(block_receipts.receipts.len(), block.transactions.len());
+            blocks.push((block, block_receipts));
+        }
+
+        Some(localize_epoch_logs(epoch_number, &blocks))
+    }
+
+    // collect the Ethereum transactions executed and the Ethereum logs in
+    // `epoch`, which is reverted by a pivot chain reorg. the epoch has been
+    // executed before it is published, so we do not wait for the execution
+    // results here.
+    fn retrieve_reverted_block(
+        &self, epoch: (u64, Vec<H256>),
+    ) -> RevertedBlock {
+        let (epoch_number, hashes) = epoch;
+        let pivot = hashes.last().cloned().expect("epoch should not be empty");
 
-            let txs = &block.transactions;
-            assert_eq!(block_receipts.receipts.len(), txs.len());
+        let mut transaction_hashes = vec![];
+        let mut blocks = vec![];
+
+        for block_hash in &hashes {
+            let block_receipts =
+                match self.data_man.block_execution_result_by_hash_with_epoch(
+                    block_hash, &pivot,
+                    false, /* update_pivot_assumption */
+                    false, /* update_cache */
+                ) {
+                    Some(res) => res.block_receipts,
+                    None => {
+                        warn!(
+                            "Cannot find receipts with {:?}/{:?}",
+                            block_hash, pivot
+                        );
+                        continue;
+                    }
+                };
+
+            let block = match self
+                .data_man
+                .block_by_hash(block_hash, false /* update_cache */)
+            {
+                Some(b) => b,
+                None => {
+                    warn!("Unable to retrieve block {:?}", block_hash);
+                    continue;
+                }
+            };
 
-            // construct logs
-            for (txid, (receipt, tx)) in
-                zip(&block_receipts.receipts, txs).enumerate()
+            for (receipt, tx) in
+                zip(&block_receipts.receipts, &block.transactions)
             {
-                for (logid, entry) in receipt.logs.iter().cloned().enumerate() {
-                    logs.push(LocalizedLogEntry {
-                        entry,
-                        block_hash,
-                        epoch_number,
-                        transaction_hash: tx.hash,
-                        transaction_index: txid,
-                        log_index,
-                        transaction_log_index: logid,
-                    });
-
-                    log_index += 1;
+                if tx.space() == Space::Ethereum
+                    && receipt.outcome_status != TransactionOutcome::Skipped
+                {
+                    transaction_hashes.push(tx.hash);
                 }
             }
+            blocks.push((block, block_receipts));
         }
 
-        Some(logs)
+        let removed_logs = localize_epoch_logs(epoch_number, &blocks)
+            .into_iter()
+            .filter(|log| log.entry.space == Space::Ethereum)
+            .map(|log| {
+                RpcLog::from_localized_with_pivot(
+                    log, pivot, true, /* removed */
+                )
+            })
+            .collect();
+
+        RevertedBlock {
+            block_number: epoch_number.into(),
+            block_hash: pivot,
+            transaction_hashes,
+            removed_logs,
+        }
     }
 
     fn consensus_graph(&self) -> &ConsensusGraph {
@@ -512,6 +710,28 @@ impl PubSub for PubSubClient {
                 "logs",
                 "Expected filter parameter.",
             ),
+            // --------- chainReorgs ---------
+            (pubsub::Kind::ChainReorgs, None) => {
+                info!("eth pubsub chainReorgs");
+                let id = self.reorgs_subscribers.write().push(subscriber);
+                self.start_reorgs_loop(id);
+                return;
+            }
+            (pubsub::Kind::ChainReorgs, _) => error_codes::invalid_params(
+                "chainReorgs",
+                "Expected no parameters.",
+            ),
+            // --------- finalizedBlocks ---------
+            (pubsub::Kind::FinalizedBlocks, None) => {
+                info!("eth pubsub finalizedBlocks");
+                let id = self.finalized_subscribers.write().push(subscriber);
+                self.start_finalized_loop(id);
+                return;
+            }
+            (pubsub::Kind::FinalizedBlocks, _) => error_codes::invalid_params(
+                "finalizedBlocks",
+                "Expected no parameters.",
+            ),
             _ => error_codes::unimplemented(None),
         };
 
@@ -523,7 +743,9 @@ impl PubSub for PubSubClient {
     ) -> RpcResult<bool> {
         let res0 = self.heads_subscribers.write().remove(&id).is_some();
         let res1 = self.logs_subscribers.write().remove(&id).is_some();
+        let res2 = self.reorgs_subscribers.write().remove(&id).is_some();
+        let res3 = self.finalized_subscribers.write().remove(&id).is_some();
 
-        Ok(res0 || res1)
+        Ok(res0 || res1 || res2 || res3)
     }
 }
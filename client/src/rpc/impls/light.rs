@@ -39,13 +39,14 @@ use crate::{
             pos::{Block as PosBlock, PoSEpochReward},
             Account as RpcAccount, AccountPendingInfo,
             AccountPendingTransactions, BlameInfo, Block as RpcBlock,
-            BlockHashOrEpochNumber, Bytes, CallRequest, CfxRpcLogFilter,
-            CheckBalanceAgainstTransactionResponse, ConsensusGraphStates,
-            EpochNumber, EstimateGasAndCollateralResponse, Log as RpcLog,
-            PoSEconomics, Receipt as RpcReceipt, RewardInfo as RpcRewardInfo,
-            RpcAddress, SendTxRequest, SponsorInfo, Status as RpcStatus,
-            SyncGraphStates, TokenSupplyInfo, Transaction as RpcTransaction,
-            VoteParamsInfo,
+            BlockHashOrEpochNumber, BlockPropagation, Bytes, CallRequest,
+            CfxRpcLogFilter, CheckBalanceAgainstTransactionResponse,
+            ConsensusGraphStates, EpochNumber, EpochStateDiff,
+            EstimateGasAndCollateralResponse, GasPriceOracle, Log as RpcLog,
+            PoSEconomics, PropagationEvent, Receipt as RpcReceipt,
+            RewardInfo as RpcRewardInfo, RpcAddress, SendTxRequest,
+            SponsorInfo, Status as RpcStatus, SyncGraphStates, SyncProgress,
+            TokenSupplyInfo, Transaction as RpcTransaction, VoteParamsInfo,
         },
         RpcBoxFuture, RpcResult,
     },
@@ -1101,6 +1102,9 @@ impl Cfx for CfxHandler {
         fn get_supply_info(&self, epoch_num: Option<EpochNumber>) -> JsonRpcResult<TokenSupplyInfo>;
         fn get_vote_params(&self, epoch_num: Option<EpochNumber>) -> JsonRpcResult<VoteParamsInfo>;
         fn get_pos_reward_by_epoch(&self, epoch: EpochNumber) -> JsonRpcResult<Option<PoSEpochReward>>;
+        fn get_epoch_state_diff(&self, epoch: EpochNumber) -> JsonRpcResult<Option<EpochStateDiff>>;
+        fn gas_price_oracle(&self) -> JsonRpcResult<GasPriceOracle>;
+        fn sync_progress(&self) -> JsonRpcResult<SyncProgress>;
     }
 }
 
@@ -1190,6 +1194,7 @@ impl LocalRpc for DebugRpcImpl {
             fn lock_account(&self, address: RpcAddress) -> JsonRpcResult<bool>;
             fn net_disconnect_node(&self, id: NodeId, op: Option<UpdateNodeOperation>) -> JsonRpcResult<bool>;
             fn net_node(&self, id: NodeId) -> JsonRpcResult<Option<(String, Node)>>;
+            fn net_reset_node_score(&self, id: NodeId) -> JsonRpcResult<bool>;
             fn net_sessions(&self, node_id: Option<NodeId>) -> JsonRpcResult<Vec<SessionDetails>>;
             fn net_throttling(&self) -> JsonRpcResult<throttling::Service>;
             fn new_account(&self, password: String) -> JsonRpcResult<RpcAddress>;
@@ -1203,10 +1208,12 @@ impl LocalRpc for DebugRpcImpl {
     }
 
     not_supported! {
+        fn block_propagation(&self, block_hash: H256) -> JsonRpcResult<Option<BlockPropagation>>;
         fn consensus_graph_state(&self) -> JsonRpcResult<ConsensusGraphStates>;
         fn current_sync_phase(&self) -> JsonRpcResult<String>;
         fn epoch_receipts(&self, epoch: BlockHashOrEpochNumber) -> JsonRpcResult<Option<Vec<Vec<RpcReceipt>>>>;
         fn sign_transaction(&self, tx: SendTxRequest, password: Option<String>) -> JsonRpcResult<String>;
         fn sync_graph_state(&self) -> JsonRpcResult<SyncGraphStates>;
+        fn transaction_first_seen(&self, tx_hash: H256) -> JsonRpcResult<Option<PropagationEvent>>;
     }
 }
@@ -0,0 +1,63 @@
+// Copyright 2021 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use crate::{
+    common::delegate_convert::into_jsonrpc_result,
+    rpc::{
+        error_codes::{build_rpc_server_error, codes, invalid_params},
+        traits::mining::Mining,
+        types::MiningWork,
+        RpcResult,
+    },
+};
+use blockgen::{BlockGenerator, GetWorkSubmitError};
+use cfx_types::{H256, U256};
+use jsonrpc_core::Result as JsonRpcResult;
+use std::sync::Arc;
+
+pub struct MiningHandler {
+    block_gen: Arc<BlockGenerator>,
+}
+
+impl MiningHandler {
+    pub fn new(block_gen: Arc<BlockGenerator>) -> Self {
+        MiningHandler { block_gen }
+    }
+
+    fn get_work_impl(&self) -> RpcResult<MiningWork> {
+        info!("RPC Request: cfx_getWork");
+        match self.block_gen.get_work() {
+            Some(problem) => Ok(problem.into()),
+            None => bail!(build_rpc_server_error(
+                codes::INCAPABLE,
+                "No mining work available yet".into(),
+            )),
+        }
+    }
+
+    fn submit_work_impl(&self, pow_hash: H256, nonce: U256) -> RpcResult<bool> {
+        info!(
+            "RPC Request: cfx_submitWork pow_hash={:?} nonce={:?}",
+            pow_hash, nonce
+        );
+        match self.block_gen.submit_work(pow_hash, nonce) {
+            Ok(()) => Ok(true),
+            Err(GetWorkSubmitError::Disabled) => bail!(build_rpc_server_error(
+                codes::INCAPABLE,
+                GetWorkSubmitError::Disabled.to_string(),
+            )),
+            Err(e) => bail!(invalid_params("nonce", e.to_string())),
+        }
+    }
+}
+
+impl Mining for MiningHandler {
+    fn get_work(&self) -> JsonRpcResult<MiningWork> {
+        into_jsonrpc_result(self.get_work_impl())
+    }
+
+    fn submit_work(&self, pow_hash: H256, nonce: U256) -> JsonRpcResult<bool> {
+        into_jsonrpc_result(self.submit_work_impl(pow_hash, nonce))
+    }
+}
@@ -4,19 +4,28 @@
 
 use crate::rpc::{
     error_codes,
-    helpers::{EpochQueue, SubscriberId, Subscribers},
+    helpers::{
+        localize_epoch_logs, EpochHistory, EpochQueue, SubscriberId,
+        Subscribers,
+    },
+    impls::common::scaled_confirmation_risk,
     metadata::Metadata,
     traits::pubsub::PubSub,
     types::{
-        pubsub::{self, SubscriptionEpoch},
-        Header as RpcHeader, Log as RpcLog,
+        pubsub::{
+            self, RevertedEpoch, SubscriptionEpoch,
+            TransactionConfirmationParams,
+        },
+        EpochStateDiff as RpcEpochStateDiff, Header as RpcHeader,
+        Log as RpcLog, SyncPhaseChange as RpcSyncPhaseChange,
     },
 };
 use cfx_addr::Network;
 use cfx_parameters::consensus::DEFERRED_STATE_EPOCH_COUNT;
-use cfx_types::H256;
+use cfx_types::{Space, H256};
 use cfxcore::{
-    channel::Channel, BlockDataManager, Notifications, SharedConsensusGraph,
+    channel::Channel, sync::SyncPhaseChange, BlockDataManager, ConsensusGraph,
+    Notifications, SharedConsensusGraph,
 };
 use futures::{
     compat::Future01CompatExt,
@@ -31,6 +40,7 @@ use jsonrpc_pubsub::{
 use parking_lot::RwLock;
 use primitives::{
     filter::LogFilter, log_entry::LocalizedLogEntry, BlockReceipts,
+    TransactionOutcome,
 };
 use runtime::Executor;
 use std::{
@@ -48,6 +58,11 @@ pub struct PubSubClient {
     heads_subscribers: Arc<RwLock<Subscribers<Client>>>,
     epochs_subscribers: Arc<RwLock<Subscribers<Client>>>,
     logs_subscribers: Arc<RwLock<Subscribers<(Client, LogFilter)>>>,
+    state_diff_subscribers: Arc<RwLock<Subscribers<Client>>>,
+    syncing_subscribers: Arc<RwLock<Subscribers<Client>>>,
+    finalized_subscribers: Arc<RwLock<Subscribers<Client>>>,
+    tx_confirmation_subscribers:
+        Arc<RwLock<Subscribers<(Client, TransactionConfirmationParams)>>>,
     epochs_ordered: Arc<Channel<(u64, Vec<H256>)>>,
 }
 
@@ -61,12 +76,20 @@ impl PubSubClient {
         let heads_subscribers = Arc::new(RwLock::new(Subscribers::default()));
         let epochs_subscribers = Arc::new(RwLock::new(Subscribers::default()));
         let logs_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+        let state_diff_subscribers =
+            Arc::new(RwLock::new(Subscribers::default()));
+        let syncing_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+        let finalized_subscribers =
+            Arc::new(RwLock::new(Subscribers::default()));
+        let tx_confirmation_subscribers =
+            Arc::new(RwLock::new(Subscribers::default()));
 
         let handler = Arc::new(ChainNotificationHandler {
             executor,
             consensus: consensus.clone(),
             data_man: consensus.get_data_manager().clone(),
             heads_subscribers: heads_subscribers.clone(),
+            syncing_subscribers: syncing_subscribers.clone(),
             network,
         });
 
@@ -84,11 +107,29 @@ impl PubSubClient {
         // run futures@0.3 future on tokio@0.1 executor
         handler.executor.spawn(fut.unit_error().boxed().compat());
 
+        // --------- syncing ---------
+        // subscribe to the `sync_phase_changes` channel
+        let receiver = notifications.sync_phase_changes.subscribe();
+
+        // loop asynchronously
+        let handler_clone = handler.clone();
+
+        let fut = receiver.for_each(move |change| {
+            handler_clone.notify_sync_phase_change(change);
+        });
+
+        // run futures@0.3 future on tokio@0.1 executor
+        handler.executor.spawn(fut.unit_error().boxed().compat());
+
         PubSubClient {
             handler,
             heads_subscribers,
             epochs_subscribers,
             logs_subscribers,
+            state_diff_subscribers,
+            syncing_subscribers,
+            finalized_subscribers,
+            tx_confirmation_subscribers,
             epochs_ordered: notifications.epochs_ordered.clone(),
         }
     }
@@ -100,7 +141,8 @@ impl PubSubClient {
 
     // Start an async loop that continuously receives epoch notifications and
     // publishes the corresponding epochs to subscriber `id`, keeping their
-    // original order. The loop terminates when subscriber `id` unsubscribes.
+    // original order. Pivot chain reorgs are published with the reverted
+    // epochs. The loop terminates when subscriber `id` unsubscribes.
     fn start_epoch_loop(&self, id: SubscriberId, sub_epoch: SubscriptionEpoch) {
         trace!("start_epoch_loop({:?})", id);
 
@@ -124,6 +166,12 @@ impl PubSubClient {
 
         // loop asynchronously
         let fut = async move {
+            // bound the history by the era length in case no epoch is
+            // finalized
+            let era_epoch_count =
+                handler.consensus.get_config().inner_conf.era_epoch_count;
+            let mut history = EpochHistory::new(era_epoch_count);
+
             while let Some((epoch, hashes)) = receiver.recv().await {
                 trace!("epoch_loop({:?}): {:?}", id, (epoch, &hashes));
 
@@ -148,6 +196,20 @@ impl PubSubClient {
                     handler.wait_for_epoch(&pivot).await;
                 }
 
+                // publish pivot chain reorg if necessary
+                let reverted = history.push((epoch, hashes.clone()));
+                if !reverted.is_empty() {
+                    assert!(epoch > 0, "Unexpected epoch number received.");
+                    debug!("pivot chain reorg: revert to {}", epoch - 1);
+                    let filter = LogFilter::default();
+                    handler
+                        .notify_revert(&sub, epoch - 1, reverted, &filter)
+                        .await;
+                }
+                let finalized =
+                    handler.consensus.latest_finalized_epoch_number();
+                history.prune(finalized);
+
                 // publish epochs
                 handler.notify_epoch(sub, (epoch, hashes)).await;
             }
@@ -180,7 +242,11 @@ impl PubSubClient {
 
         // loop asynchronously
         let fut = async move {
-            let mut last_epoch = 0;
+            // bound the history by the era length in case no epoch is
+            // finalized
+            let era_epoch_count =
+                handler.consensus.get_config().inner_conf.era_epoch_count;
+            let mut history = EpochHistory::new(era_epoch_count);
 
             while let Some(epoch) = receiver.recv().await {
                 trace!("logs_loop({:?}): {:?}", id, epoch);
@@ -201,13 +267,17 @@ impl PubSubClient {
                 };
 
                 // publish pivot chain reorg if necessary
-                if epoch.0 <= last_epoch {
-                    debug!("pivot chain reorg: {} -> {}", last_epoch, epoch.0);
+                let reverted = history.push(epoch.clone());
+                if !reverted.is_empty() {
                     assert!(epoch.0 > 0, "Unexpected epoch number received.");
-                    handler.notify_revert(&sub, epoch.0 - 1).await;
+                    debug!("pivot chain reorg: revert to {}", epoch.0 - 1);
+                    handler
+                        .notify_revert(&sub, epoch.0 - 1, reverted, &filter)
+                        .await;
                 }
-
-                last_epoch = epoch.0;
+                let finalized =
+                    handler.consensus.latest_finalized_epoch_number();
+                history.prune(finalized);
 
                 // publish matching logs
                 handler.notify_logs(&sub, filter, epoch).await;
@@ -218,6 +288,193 @@ impl PubSubClient {
         let fut = fut.unit_error().boxed().compat();
         self.handler.executor.spawn(fut);
     }
+
+    // Start an async loop that continuously receives epoch notifications and
+    // publishes the state diff of each executed epoch to subscriber `id`,
+    // keeping their original order. The loop terminates when subscriber `id`
+    // unsubscribes.
+    fn start_state_diff_loop(&self, id: SubscriberId) {
+        trace!("start_state_diff_loop({:?})", id);
+
+        // clone everything we use in our async loop
+        let subscribers = self.state_diff_subscribers.clone();
+        let epochs_ordered = self.epochs_ordered.clone();
+        let handler = self.handler.clone();
+
+        // subscribe to the `epochs_ordered` channel
+        let mut receiver = epochs_ordered.subscribe();
+
+        // use a queue to make sure we only process an epoch once it has been
+        // executed for sure
+        let mut queue = EpochQueue::<Vec<H256>>::with_capacity(
+            (DEFERRED_STATE_EPOCH_COUNT - 1) as usize,
+        );
+
+        // loop asynchronously
+        let fut = async move {
+            while let Some(epoch) = receiver.recv().await {
+                trace!("state_diff_loop({:?}): {:?}", id, epoch);
+
+                // retrieve subscriber
+                let sub = match subscribers.read().get(&id) {
+                    Some(sub) => sub.clone(),
+                    None => {
+                        // unsubscribed, terminate loop
+                        epochs_ordered.unsubscribe(receiver.id);
+                        return;
+                    }
+                };
+
+                let epoch = match queue.push(epoch) {
+                    None => continue,
+                    Some(e) => e,
+                };
+
+                // publish state diff
+                handler.notify_epoch_state_diff(&sub, epoch).await;
+            }
+        };
+
+        // run futures@0.3 future on tokio@0.1 executor
+        let fut = fut.unit_error().boxed().compat();
+        self.handler.executor.spawn(fut);
+    }
+
+    // Start an async loop that checks the latest finalized epoch whenever an
+    // epoch notification is received and publishes the newly finalized epoch
+    // range to subscriber `id`. The loop terminates when subscriber `id`
+    // unsubscribes.
+    fn start_finalized_loop(&self, id: SubscriberId) {
+        trace!("start_finalized_loop({:?})", id);
+
+        // clone everything we use in our async loop
+        let subscribers = self.finalized_subscribers.clone();
+        let epochs_ordered = self.epochs_ordered.clone();
+        let handler = self.handler.clone();
+
+        // subscribe to the `epochs_ordered` channel
+        let mut receiver = epochs_ordered.subscribe();
+
+        // only the epochs finalized after subscribing are published
+        let mut last_finalized =
+            handler.consensus.latest_finalized_epoch_number();
+
+        // loop asynchronously
+        let fut = async move {
+            while let Some(epoch) = receiver.recv().await {
+                trace!("finalized_loop({:?}): {:?}", id, epoch);
+
+                // retrieve subscriber
+                let sub = match subscribers.read().get(&id) {
+                    Some(sub) => sub.clone(),
+                    None => {
+                        // unsubscribed, terminate loop
+                        epochs_ordered.unsubscribe(receiver.id);
+                        return;
+                    }
+                };
+
+                let finalized =
+                    handler.consensus.latest_finalized_epoch_number();
+                if finalized <= last_finalized {
+                    continue;
+                }
+
+                // publish finalized epoch range
+                ChainNotificationHandler::notify_async(
+                    &sub,
+                    pubsub::Result::FinalizedEpochs {
+                        from_epoch: (last_finalized + 1).into(),
+                        to_epoch: finalized.into(),
+                    },
+                )
+                .await;
+                last_finalized = finalized;
+            }
+        };
+
+        // run futures@0.3 future on tokio@0.1 executor
+        let fut = fut.unit_error().boxed().compat();
+        self.handler.executor.spawn(fut);
+    }
+
+    // Start an async loop that checks the transaction watched by subscriber
+    // `id` whenever an epoch notification is received. The confirmation is
+    // published once the transaction reaches the required confirmation risk
+    // or finality, after which the subscription is removed. If the
+    // transaction is dropped from the pivot chain before that, the reverted
+    // block is published and the transaction is watched further. The loop
+    // terminates when subscriber `id` unsubscribes.
+    fn start_tx_confirmation_loop(&self, id: SubscriberId) {
+        trace!("start_tx_confirmation_loop({:?})", id);
+
+        // clone everything we use in our async loop
+        let subscribers = self.tx_confirmation_subscribers.clone();
+        let epochs_ordered = self.epochs_ordered.clone();
+        let handler = self.handler.clone();
+
+        // subscribe to the `epochs_ordered` channel
+        let mut receiver = epochs_ordered.subscribe();
+
+        // loop asynchronously
+        let fut = async move {
+            // the block executing the transaction on the pivot chain
+            let mut executed_in: Option<H256> = None;
+
+            while let Some(epoch) = receiver.recv().await {
+                trace!("tx_confirmation_loop({:?}): {:?}", id, epoch);
+
+                // retrieve subscriber
+                let (sub, params) = match subscribers.read().get(&id) {
+                    Some(sub) => sub.clone(),
+                    None => {
+                        // unsubscribed, terminate loop
+                        epochs_ordered.unsubscribe(receiver.id);
+                        return;
+                    }
+                };
+
+                let block_hash = handler.executed_block_of_tx(&params.hash);
+
+                // publish revert if necessary
+                if let Some(reverted) = executed_in {
+                    if block_hash != Some(reverted) {
+                        debug!("transaction reverted from {:?}", reverted);
+                        ChainNotificationHandler::notify_async(
+                            &sub,
+                            pubsub::Result::TransactionReverted {
+                                transaction_hash: params.hash,
+                                reverted_block_hash: reverted,
+                            },
+                        )
+                        .await;
+                    }
+                }
+                executed_in = block_hash;
+
+                let block_hash = match block_hash {
+                    Some(h) => h,
+                    None => continue,
+                };
+
+                // publish confirmation and terminate loop
+                if let Some(result) =
+                    handler.tx_confirmation(&params, block_hash)
+                {
+                    ChainNotificationHandler::notify_async(&sub, result).await;
+                    subscribers
+                        .write()
+                        .remove(&SubscriptionId::String(id.as_string()));
+                    epochs_ordered.unsubscribe(receiver.id);
+                    return;
+                }
+            }
+        };
+
+        // run futures@0.3 future on tokio@0.1 executor
+        let fut = fut.unit_error().boxed().compat();
+        self.handler.executor.spawn(fut);
+    }
 }
 
 /// PubSub notification handler.
@@ -226,6 +483,7 @@ pub struct ChainNotificationHandler {
     consensus: SharedConsensusGraph,
     data_man: Arc<BlockDataManager>,
     heads_subscribers: Arc<RwLock<Subscribers<Client>>>,
+    syncing_subscribers: Arc<RwLock<Subscribers<Client>>>,
     network: Network,
 }
 
@@ -289,6 +547,74 @@ impl ChainNotificationHandler {
         }
     }
 
+    // notify each subscriber about the sync phase change concurrently
+    fn notify_sync_phase_change(&self, change: SyncPhaseChange) {
+        trace!("notify_sync_phase_change({:?})", change);
+
+        let subscribers = self.syncing_subscribers.read();
+        let change = RpcSyncPhaseChange::from(change);
+
+        for subscriber in subscribers.values() {
+            Self::notify(
+                &self.executor,
+                subscriber,
+                pubsub::Result::SyncPhaseChange(change.clone()),
+            );
+        }
+    }
+
+    // the block executing transaction `hash` on the current pivot chain
+    fn executed_block_of_tx(&self, hash: &H256) -> Option<H256> {
+        let (_, info) = self.consensus.get_transaction_info_by_hash(hash)?;
+        let block_hash = info.tx_index.block_hash;
+        info.maybe_executed_extra_info.map(|_| block_hash)
+    }
+
+    // the confirmation of the transaction executed in `block_hash`, if it
+    // is confirmed as required by `params`
+    fn tx_confirmation(
+        &self, params: &TransactionConfirmationParams, block_hash: H256,
+    ) -> Option<pubsub::Result> {
+        let epoch_number =
+            self.consensus.get_block_epoch_number(&block_hash)?;
+
+        let risk = {
+            let consensus_graph = self.consensus_graph();
+            let inner = &*consensus_graph.inner.read();
+            consensus_graph
+                .confirmation_meter
+                .confirmation_risk_by_hash(inner, block_hash)?
+        };
+        let risk = scaled_confirmation_risk(risk);
+
+        let finalized =
+            epoch_number <= self.consensus.latest_finalized_epoch_number();
+
+        let confirmed = match params.risk_threshold {
+            Some(threshold) => risk <= threshold,
+            None => finalized,
+        };
+
+        if !confirmed {
+            return None;
+        }
+
+        Some(pubsub::Result::TransactionConfirmed {
+            transaction_hash: params.hash,
+            block_hash,
+            epoch_number: epoch_number.into(),
+            confirmation_risk: risk,
+            finalized,
+        })
+    }
+
+    fn consensus_graph(&self) -> &ConsensusGraph {
+        self.consensus
+            .as_any()
+            .downcast_ref::<ConsensusGraph>()
+            .expect("downcast should succeed")
+    }
+
     async fn notify_epoch(&self, subscriber: Client, epoch: (u64, Vec<H256>)) {
         trace!("notify_epoch({:?})", epoch);
 
@@ -305,13 +631,23 @@ impl ChainNotificationHandler {
         .await
     }
 
-    async fn notify_revert(&self, subscriber: &Client, epoch: u64) {
-        trace!("notify_revert({:?})", epoch);
+    async fn notify_revert(
+        &self, subscriber: &Client, revert_to: u64,
+        reverted: Vec<(u64, Vec<H256>)>, filter: &LogFilter,
+    )
+    {
+        trace!("notify_revert({:?})", revert_to);
+
+        let reverted_epochs = reverted
+            .into_iter()
+            .map(|epoch| self.retrieve_reverted_epoch(epoch, filter))
+            .collect();
 
         Self::notify_async(
             subscriber,
             pubsub::Result::ChainReorg {
-                revert_to: epoch.into(),
+                revert_to: revert_to.into(),
+                reverted_epochs,
             },
         )
         .await
@@ -355,6 +691,49 @@ impl ChainNotificationHandler {
         }
     }
 
+    async fn notify_epoch_state_diff(
+        &self, subscriber: &Client, epoch: (u64, Vec<H256>),
+    ) {
+        trace!("notify_epoch_state_diff({:?})", epoch);
+
+        let (epoch_number, hashes) = epoch;
+        let pivot = hashes.last().cloned().expect("epoch should not be empty");
+
+        // the state diff is persisted before the execution commitment
+        if !self.wait_for_execution_commitment(&pivot).await {
+            return;
+        }
+
+        let diff = match self.data_man.epoch_state_diff(&pivot) {
+            Some(diff) => diff,
+            None => {
+                return warn!("Unable to retrieve state diff for {:?}", pivot)
+            }
+        };
+
+        match RpcEpochStateDiff::try_from(
+            epoch_number,
+            pivot,
+            diff,
+            &|key| self.data_man.storage_key_preimage(key),
+            self.network,
+        ) {
+            Ok(diff) => {
+                Self::notify_async(
+                    subscriber,
+                    pubsub::Result::EpochStateDiff(diff),
+                )
+                .await
+            }
+            Err(e) => {
+                error!(
+                    "Unexpected error while constructing EpochStateDiff: {:?}",
+                    e
+                );
+            }
+        }
+    }
+
     // attempt to retrieve block receipts from BlockDataManager
     // on failure, wait and retry a few times, then fail
     // NOTE: we do this because we might get epoch notifications
@@ -399,6 +778,25 @@ impl ChainNotificationHandler {
         let _ = self.retrieve_block_receipts(&pivot, &pivot).await;
     }
 
+    // wait until the epoch with pivot block `pivot` is executed, i.e. its
+    // execution commitment becomes available. returns false on timeout.
+    async fn wait_for_execution_commitment(&self, pivot: &H256) -> bool {
+        const POLL_INTERVAL_MS: Duration = Duration::from_millis(100);
+
+        // we assume that an epoch gets executed within 100 seconds
+        for _ in 0..1000 {
+            if self.data_man.epoch_executed(pivot) {
+                return true;
+            }
+
+            trace!("Cannot find execution commitment for {:?}", pivot);
+            let _ = sleep(POLL_INTERVAL_MS).compat().await;
+        }
+
+        error!("Cannot find execution commitment for {:?}", pivot);
+        false
+    }
+
     async fn retrieve_epoch_logs(
         &self, epoch: (u64, Vec<H256>),
     ) -> Option<Vec<LocalizedLogEntry>> {
@@ -415,9 +813,7 @@ impl ChainNotificationHandler {
             .into_iter()
             .collect::<Option<Vec<_>>>()?;
 
-        let mut logs = vec![];
-        let mut log_index = 0;
-
+        let mut blocks = Vec::with_capacity(hashes.len());
         for (block_hash, block_receipts) in zip(hashes, receipts) {
             // retrieve block transactions
             let block = match self
@@ -430,31 +826,86 @@ impl ChainNotificationHandler {
                     return None;
                 }
             };
+            assert_eq!(block_receipts.receipts.len(), block.transactions.len());
+            blocks.push((block, block_receipts));
+        }
 
-            let txs = &block.transactions;
-            assert_eq!(block_receipts.receipts.len(), txs.len());
+        Some(localize_epoch_logs(epoch_number, &blocks))
+    }
 
-            // construct logs
-            for (txid, (receipt, tx)) in
-                zip(&block_receipts.receipts, txs).enumerate()
+    // collect the transactions executed and the logs matching `filter` in
+    // `epoch`, which is reverted by a pivot chain reorg. unlike
+    // `retrieve_epoch_logs`, we do not wait for the execution results here,
+    // as the epoch might have been reverted before it was executed.
+    fn retrieve_reverted_epoch(
+        &self, epoch: (u64, Vec<H256>), filter: &LogFilter,
+    ) -> RevertedEpoch {
+        let (epoch_number, hashes) = epoch;
+        let pivot = hashes.last().cloned().expect("epoch should not be empty");
+
+        let mut transaction_hashes = vec![];
+        let mut blocks = vec![];
+
+        for block_hash in &hashes {
+            let block_receipts =
+                match self.data_man.block_execution_result_by_hash_with_epoch(
+                    block_hash, &pivot,
+                    false, /* update_pivot_assumption */
+                    false, /* update_cache */
+                ) {
+                    Some(res) => res.block_receipts,
+                    None => continue,
+                };
+
+            let block = match self
+                .data_man
+                .block_by_hash(block_hash, false /* update_cache */)
+            {
+                Some(b) => b,
+                None => {
+                    warn!("Unable to retrieve block {:?}", block_hash);
+                    continue;
+                }
+            };
+
+            for (receipt, tx) in
+                zip(&block_receipts.receipts, &block.transactions)
             {
-                for (logid, entry) in receipt.logs.iter().cloned().enumerate() {
-                    logs.push(LocalizedLogEntry {
-                        entry,
-                        block_hash,
-                        epoch_number,
-                        transaction_hash: tx.hash,
-                        transaction_index: txid,
-                        log_index,
-                        transaction_log_index: logid,
-                    });
-
-                    log_index += 1;
+                if tx.space() == Space::Native
+                    && receipt.outcome_status != TransactionOutcome::Skipped
+                {
+                    transaction_hashes.push(tx.hash);
+                }
+            }
+            blocks.push((block, block_receipts));
+        }
+
+        let mut removed_logs = vec![];
+        for log in localize_epoch_logs(epoch_number, &blocks) {
+            if !filter.matches(&log.entry) {
+                continue;
+            }
+
+            match RpcLog::try_from_localized(log, self.network) {
+                Ok(mut l) => {
+                    l.removed = true;
+                    removed_logs.push(l);
+                }
+                Err(e) => {
+                    error!(
+                        "Unexpected error while constructing RpcLog: {:?}",
+                        e
+                    );
                 }
             }
         }
 
-        Some(logs)
+        RevertedEpoch {
+            epoch_number: epoch_number.into(),
+            epoch_hashes_ordered: hashes,
+            transaction_hashes,
+            removed_logs,
+        }
     }
 }
 
@@ -519,6 +970,54 @@ impl PubSub for PubSubClient {
                 "logs",
                 "Expected filter parameter.",
             ),
+            // --------- epochStateDiffs ---------
+            (pubsub::Kind::EpochStateDiffs, None) => {
+                let id = self.state_diff_subscribers.write().push(subscriber);
+                self.start_state_diff_loop(id);
+                return;
+            }
+            (pubsub::Kind::EpochStateDiffs, _) => error_codes::invalid_params(
+                "epochStateDiffs",
+                "Expected no parameters.",
+            ),
+            // --------- finalizedEpochs ---------
+            (pubsub::Kind::FinalizedEpochs, None) => {
+                let id = self.finalized_subscribers.write().push(subscriber);
+                self.start_finalized_loop(id);
+                return;
+            }
+            (pubsub::Kind::FinalizedEpochs, _) => error_codes::invalid_params(
+                "finalizedEpochs",
+                "Expected no parameters.",
+            ),
+            // --------- transactionConfirmation ---------
+            (
+                pubsub::Kind::TransactionConfirmation,
+                Some(pubsub::Params::TransactionConfirmation(params)),
+            ) if params.risk_threshold.is_some() != params.finalized => {
+                let id = self
+                    .tx_confirmation_subscribers
+                    .write()
+                    .push(subscriber, params);
+
+                self.start_tx_confirmation_loop(id);
+                return;
+            }
+            (pubsub::Kind::TransactionConfirmation, _) => {
+                error_codes::invalid_params(
+                    "transactionConfirmation",
+                    "Expected transaction hash with either riskThreshold or finalized.",
+                )
+            }
+            // --------- syncing ---------
+            (pubsub::Kind::Syncing, None) => {
+                self.syncing_subscribers.write().push(subscriber);
+                return;
+            }
+            (pubsub::Kind::Syncing, _) => error_codes::invalid_params(
+                "syncing",
+                "Expected no parameters.",
+            ),
             _ => error_codes::unimplemented(None),
         };
 
@@ -531,7 +1030,15 @@ impl PubSub for PubSubClient {
         let res0 = self.heads_subscribers.write().remove(&id).is_some();
         let res1 = self.epochs_subscribers.write().remove(&id).is_some();
         let res2 = self.logs_subscribers.write().remove(&id).is_some();
-
-        Ok(res0 || res1 || res2)
+        let res3 = self.state_diff_subscribers.write().remove(&id).is_some();
+        let res4 = self.syncing_subscribers.write().remove(&id).is_some();
+        let res5 = self.finalized_subscribers.write().remove(&id).is_some();
+        let res6 = self
+            .tx_confirmation_subscribers
+            .write()
+            .remove(&id)
+            .is_some();
+
+        Ok(res0 || res1 || res2 || res3 || res4 || res5 || res6)
     }
 }
@@ -12,8 +12,8 @@ use crate::{
                 BlockNumber, LocalizedTrace as EthLocalizedTrace,
                 Res as EthRes, TraceFilter as EthTraceFilter,
             },
-            Action as RpcAction, LocalizedTrace as RpcLocalizedTrace,
-            LocalizedTrace, TraceFilter as RpcTraceFilter,
+            Action as RpcAction, Bytes, LocalizedTrace as RpcLocalizedTrace,
+            LocalizedTrace, StorageKeyPreimage, TraceFilter as RpcTraceFilter,
         },
         RpcResult,
     },
@@ -193,6 +193,17 @@ impl TraceHandler {
                     })
             }))
     }
+
+    fn storage_key_preimage_impl(
+        &self, key: &[u8],
+    ) -> RpcResult<Option<StorageKeyPreimage>> {
+        match self.data_man.storage_key_preimage(key) {
+            None => Ok(None),
+            Some(preimage) => {
+                Ok(Some(StorageKeyPreimage::try_from(preimage, self.network)?))
+            }
+        }
+    }
 }
 
 impl Trace for TraceHandler {
@@ -214,6 +225,12 @@ impl Trace for TraceHandler {
     ) -> JsonRpcResult<Option<Vec<LocalizedTrace>>> {
         into_jsonrpc_result(self.transaction_trace_impl(&tx_hash))
     }
+
+    fn storage_key_preimage(
+        &self, key: Bytes,
+    ) -> JsonRpcResult<Option<StorageKeyPreimage>> {
+        into_jsonrpc_result(self.storage_key_preimage_impl(&key.into_vec()))
+    }
 }
 
 pub struct EthTraceHandler {
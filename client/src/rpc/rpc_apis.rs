@@ -10,6 +10,7 @@ use std::{
 
 #[derive(Debug, PartialEq, Clone, Eq, Hash)]
 pub enum Api {
+    Admin,
     Cfx,
     Eth,
     Debug,
@@ -19,6 +20,8 @@ pub enum Api {
     TxPool,
     Pos,
     EthPubsub,
+    Dev,
+    Mining,
 }
 
 impl FromStr for Api {
@@ -27,6 +30,7 @@ impl FromStr for Api {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         use self::Api::*;
         match s {
+            "admin" => Ok(Admin),
             "cfx" => Ok(Cfx),
             "eth" => Ok(Eth),
             "debug" => Ok(Debug),
@@ -36,6 +40,8 @@ impl FromStr for Api {
             "txpool" => Ok(TxPool),
             "pos" => Ok(Pos),
             "ethpubsub" => Ok(EthPubsub),
+            "dev" => Ok(Dev),
+            "mining" => Ok(Mining),
             _ => Err("Unknown api type".into()),
         }
     }
@@ -44,6 +50,7 @@ impl FromStr for Api {
 impl Display for Api {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
+            Api::Admin => write!(f, "admin"),
             Api::Cfx => write!(f, "cfx"),
             Api::Eth => write!(f, "eth"),
             Api::Debug => write!(f, "debug"),
@@ -53,6 +60,8 @@ impl Display for Api {
             Api::TxPool => write!(f, "txpool"),
             Api::Pos => write!(f, "pos"),
             Api::EthPubsub => write!(f, "ethpubsub"),
+            Api::Dev => write!(f, "dev"),
+            Api::Mining => write!(f, "mining"),
         }
     }
 }
@@ -69,6 +78,8 @@ impl ApiSet {
     pub fn list_apis(&self) -> HashSet<Api> {
         match *self {
             ApiSet::List(ref apis) => apis.clone(),
+            // `Admin` is not included, because it's only safe on the local
+            // RPC ports.
             ApiSet::All => [
                 Api::Cfx,
                 Api::Debug,
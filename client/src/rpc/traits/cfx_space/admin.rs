@@ -0,0 +1,41 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use jsonrpc_core::Result as JsonRpcResult;
+use jsonrpc_derive::rpc;
+use network::{node_table::NodeId, AdminPeerInfo, LocalNodeInfo};
+
+/// Peer management RPCs for node operators, which are only available on the
+/// local RPC ports unless `admin` is added to `public_rpc_apis`.
+///
+/// Nodes are specified with url format "cfxnode://ID@IP:PORT".
+#[rpc(server)]
+pub trait Admin {
+    /// Add a trusted node, which is persisted in the node table.
+    #[rpc(name = "admin_addTrustedPeer")]
+    fn admin_add_trusted_peer(&self, url: String) -> JsonRpcResult<bool>;
+
+    /// Remove a trusted node, and disconnect it if connected.
+    #[rpc(name = "admin_removeTrustedPeer")]
+    fn admin_remove_trusted_peer(&self, node_id: NodeId)
+        -> JsonRpcResult<bool>;
+
+    /// Add a reserved node, which is always connected and never banned. The
+    /// node is persisted as a trusted node, but it's only reserved until
+    /// restart unless it's also configured in `reserved_nodes`.
+    #[rpc(name = "admin_addReservedPeer")]
+    fn admin_add_reserved_peer(&self, url: String) -> JsonRpcResult<bool>;
+
+    /// Remove a reserved node, which is still kept as a trusted node.
+    #[rpc(name = "admin_removeReservedPeer")]
+    fn admin_remove_reserved_peer(
+        &self, node_id: NodeId,
+    ) -> JsonRpcResult<bool>;
+
+    #[rpc(name = "admin_peers")]
+    fn admin_peers(&self) -> JsonRpcResult<Vec<AdminPeerInfo>>;
+
+    #[rpc(name = "admin_nodeInfo")]
+    fn admin_node_info(&self) -> JsonRpcResult<Option<LocalNodeInfo>>;
+}
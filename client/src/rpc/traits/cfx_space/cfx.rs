@@ -6,10 +6,10 @@ use crate::rpc::types::{
     pos::PoSEpochReward, Account as RpcAccount, AccountPendingInfo,
     AccountPendingTransactions, Block, BlockHashOrEpochNumber, Bytes,
     CallRequest, CfxRpcLogFilter, CheckBalanceAgainstTransactionResponse,
-    EpochNumber, EstimateGasAndCollateralResponse, Log as RpcLog, PoSEconomics,
-    Receipt as RpcReceipt, RewardInfo as RpcRewardInfo, RpcAddress,
-    SponsorInfo, Status as RpcStatus, TokenSupplyInfo, Transaction,
-    VoteParamsInfo,
+    EpochNumber, EpochStateDiff, EstimateGasAndCollateralResponse,
+    GasPriceOracle, Log as RpcLog, PoSEconomics, Receipt as RpcReceipt,
+    RewardInfo as RpcRewardInfo, RpcAddress, SponsorInfo, Status as RpcStatus,
+    SyncProgress, TokenSupplyInfo, Transaction, VoteParamsInfo,
 };
 use cfx_types::{H256, U256, U64};
 use jsonrpc_core::{BoxFuture, Result as JsonRpcResult};
@@ -39,6 +39,12 @@ pub trait Cfx {
     #[rpc(name = "cfx_gasPrice")]
     fn gas_price(&self) -> BoxFuture<U256>;
 
+    /// Returns the slow, standard and fast gas prices suggested from the
+    /// recent blocks, together with how congested the blocks and the
+    /// transaction pool are.
+    #[rpc(name = "cfx_gasPriceOracle")]
+    fn gas_price_oracle(&self) -> JsonRpcResult<GasPriceOracle>;
+
     /// Returns highest epoch number.
     #[rpc(name = "cfx_epochNumber")]
     fn epoch_number(
@@ -273,6 +279,18 @@ pub trait Cfx {
         &self, epoch_number: Option<EpochNumber>,
     ) -> JsonRpcResult<VoteParamsInfo>;
 
+    /// Returns the accounts and storage entries changed by an executed epoch.
+    /// Only available if `persist_epoch_state_diff` is enabled.
+    #[rpc(name = "cfx_getEpochStateDiff")]
+    fn get_epoch_state_diff(
+        &self, epoch: EpochNumber,
+    ) -> JsonRpcResult<Option<EpochStateDiff>>;
+
+    /// Returns the current sync phase, the progress and throughput of the
+    /// synchronization, and the sync data received from each peer.
+    #[rpc(name = "cfx_syncProgress")]
+    fn sync_progress(&self) -> JsonRpcResult<SyncProgress>;
+
     //        /// Returns transaction at given block hash and index.
     //        #[rpc(name = "cfx_getTransactionByBlockHashAndIndex")]
     //        fn transaction_by_block_hash_and_index(&self, H256, Index) ->
@@ -3,9 +3,9 @@
 // See http://www.gnu.org/licenses/
 
 use crate::rpc::types::{
-    BlockHashOrEpochNumber, Bytes as RpcBytes, ConsensusGraphStates,
-    Receipt as RpcReceipt, RpcAddress, SendTxRequest, SyncGraphStates,
-    Transaction as RpcTransaction,
+    BlockHashOrEpochNumber, BlockPropagation, Bytes as RpcBytes,
+    ConsensusGraphStates, PropagationEvent, Receipt as RpcReceipt, RpcAddress,
+    SendTxRequest, SyncGraphStates, Transaction as RpcTransaction,
 };
 use cfx_types::{H256, H520, U128};
 use jsonrpc_core::{BoxFuture, Result as JsonRpcResult};
@@ -58,6 +58,9 @@ pub trait LocalRpc {
         &self, id: NodeId, op: Option<UpdateNodeOperation>,
     ) -> JsonRpcResult<bool>;
 
+    #[rpc(name = "net_reset_node_score")]
+    fn net_reset_node_score(&self, node_id: NodeId) -> JsonRpcResult<bool>;
+
     #[rpc(name = "net_sessions")]
     fn net_sessions(
         &self, node_id: Option<NodeId>,
@@ -72,6 +75,19 @@ pub trait LocalRpc {
     #[rpc(name = "sync_graph_state")]
     fn sync_graph_state(&self) -> JsonRpcResult<SyncGraphStates>;
 
+    /// Return when the block hash, the compact block and the full block were
+    /// first received and from which peers.
+    #[rpc(name = "block_propagation")]
+    fn block_propagation(
+        &self, block_hash: H256,
+    ) -> JsonRpcResult<Option<BlockPropagation>>;
+
+    /// Return when the transaction was first seen and from which peer.
+    #[rpc(name = "transaction_first_seen")]
+    fn transaction_first_seen(
+        &self, tx_hash: H256,
+    ) -> JsonRpcResult<Option<PropagationEvent>>;
+
     #[rpc(name = "cfx_sendTransaction")]
     fn send_transaction(
         &self, tx: SendTxRequest, password: Option<String>,
@@ -0,0 +1,62 @@
+// Copyright 2021 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use crate::rpc::types::{Bytes, DevAddress, RpcAddress};
+use cfx_types::{H256, U256, U64};
+use jsonrpc_core::Result as JsonRpcResult;
+use jsonrpc_derive::rpc;
+
+/// Dev mode RPCs to control the chain state in contract tests. They are only
+/// available in dev mode.
+#[rpc(server)]
+pub trait Dev {
+    /// Take a snapshot of the current pivot chain, and return its id.
+    #[rpc(name = "dev_snapshot")]
+    fn snapshot(&self) -> JsonRpcResult<U64>;
+
+    /// Revert the chain state to the snapshot `id`. The snapshot and all the
+    /// snapshots taken after it are removed. Return `false` if the snapshot
+    /// does not exist.
+    #[rpc(name = "dev_revert")]
+    fn revert(&self, id: U64) -> JsonRpcResult<bool>;
+
+    #[rpc(name = "dev_setBalance")]
+    fn set_balance(
+        &self, address: DevAddress, balance: U256,
+    ) -> JsonRpcResult<()>;
+
+    #[rpc(name = "dev_setCode")]
+    fn set_code(&self, address: DevAddress, code: Bytes) -> JsonRpcResult<()>;
+
+    #[rpc(name = "dev_setStorageAt")]
+    fn set_storage_at(
+        &self, address: DevAddress, position: U256, value: H256,
+    ) -> JsonRpcResult<()>;
+
+    #[rpc(name = "dev_setNonce")]
+    fn set_nonce(&self, address: DevAddress, nonce: U256) -> JsonRpcResult<()>;
+
+    /// Increase the timestamps of the blocks mined later by `seconds`, and
+    /// return the total increased seconds. It fails if the total overflows.
+    #[rpc(name = "dev_increaseTime")]
+    fn increase_time(&self, seconds: U64) -> JsonRpcResult<U64>;
+
+    #[rpc(name = "dev_setNextBlockTimestamp")]
+    fn set_next_block_timestamp(&self, timestamp: U64) -> JsonRpcResult<()>;
+
+    /// Mine `num_blocks` (1 by default) blocks with the pending transactions.
+    #[rpc(name = "dev_mine")]
+    fn mine(&self, num_blocks: Option<U64>) -> JsonRpcResult<Vec<H256>>;
+
+    /// Accept the transactions sent from `address` by `cfx_sendTransaction`
+    /// without signing them. Only Core space accounts can be impersonated,
+    /// and an eSpace address is rejected.
+    #[rpc(name = "dev_impersonateAccount")]
+    fn impersonate_account(&self, address: DevAddress) -> JsonRpcResult<()>;
+
+    #[rpc(name = "dev_stopImpersonatingAccount")]
+    fn stop_impersonating_account(
+        &self, address: RpcAddress,
+    ) -> JsonRpcResult<()>;
+}
@@ -0,0 +1,21 @@
+// Copyright 2021 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use crate::rpc::types::MiningWork;
+use cfx_types::{H256, U256};
+use jsonrpc_core::Result as JsonRpcResult;
+use jsonrpc_derive::rpc;
+
+/// Getwork mining RPCs for external miners. They are only available when
+/// `mining_type` is "getwork" and the `mining` api is enabled.
+#[rpc(server)]
+pub trait Mining {
+    /// Returns the PoW problem of the block being mined.
+    #[rpc(name = "cfx_getWork")]
+    fn get_work(&self) -> JsonRpcResult<MiningWork>;
+
+    /// Submits the nonce solving the PoW problem identified by `pow_hash`.
+    #[rpc(name = "cfx_submitWork")]
+    fn submit_work(&self, pow_hash: H256, nonce: U256) -> JsonRpcResult<bool>;
+}
@@ -1,5 +1,8 @@
+pub mod admin;
 pub mod cfx;
 pub mod debug;
+pub mod dev;
+pub mod mining;
 pub mod pool;
 pub mod pos;
 pub mod pubsub;
@@ -2,7 +2,9 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
-use crate::rpc::types::{LocalizedBlockTrace, LocalizedTrace, TraceFilter};
+use crate::rpc::types::{
+    Bytes, LocalizedBlockTrace, LocalizedTrace, StorageKeyPreimage, TraceFilter,
+};
 use cfx_types::H256;
 use jsonrpc_core::Result as JsonRpcResult;
 use jsonrpc_derive::rpc;
@@ -27,4 +29,11 @@ pub trait Trace {
     fn transaction_traces(
         &self, tx_hash: H256,
     ) -> JsonRpcResult<Option<Vec<LocalizedTrace>>>;
+
+    /// Returns the contract address and slot encoded in the given storage
+    /// key, if recorded with `record_storage_key_preimages`.
+    #[rpc(name = "trace_storageKeyPreimage")]
+    fn storage_key_preimage(
+        &self, key: Bytes,
+    ) -> JsonRpcResult<Option<StorageKeyPreimage>>;
 }
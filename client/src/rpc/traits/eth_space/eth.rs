@@ -28,7 +28,7 @@ use crate::rpc::types::{
         AccountPendingTransactions, Block, BlockNumber, CallRequest,
         EthRpcLogFilter, FilterChanges, Log, Receipt, SyncStatus, Transaction,
     },
-    Bytes, Index,
+    Bytes, GasPriceOracle, Index,
 };
 
 /// Eth rpc interface.
@@ -70,6 +70,12 @@ pub trait Eth {
     #[rpc(name = "eth_gasPrice")]
     fn gas_price(&self) -> Result<U256>;
 
+    /// Returns the slow, standard and fast gas prices suggested from the
+    /// recent blocks, together with how congested the blocks and the
+    /// transaction pool are.
+    #[rpc(name = "eth_gasPriceOracle")]
+    fn gas_price_oracle(&self) -> Result<GasPriceOracle>;
+
     /// Returns current max_priority_fee
     #[rpc(name = "eth_maxPriorityFeePerGas")]
     fn max_priority_fee_per_gas(&self) -> Result<U256>;
@@ -9,21 +9,28 @@ mod block;
 mod bytes;
 pub mod call_request;
 mod consensus_graph_states;
+mod dev_address;
 mod epoch_number;
+mod epoch_state_diff;
 pub mod errors;
 pub mod eth;
 mod filter;
+mod gas_price_oracle;
 mod index;
 mod log;
+mod mining_work;
 pub mod pos;
 mod pos_economics;
+mod propagation;
 mod provenance;
 pub mod pubsub;
 mod receipt;
 mod reward_info;
 mod sponsor_info;
 mod status;
+mod storage_key_preimage;
 mod sync_graph_states;
+mod sync_progress;
 mod token_supply_info;
 mod trace;
 mod trace_filter;
@@ -42,17 +49,24 @@ pub use self::{
         EstimateGasAndCollateralResponse, SendTxRequest, MAX_GAS_CALL_REQUEST,
     },
     consensus_graph_states::ConsensusGraphStates,
+    dev_address::DevAddress,
     epoch_number::{BlockHashOrEpochNumber, EpochNumber},
+    epoch_state_diff::EpochStateDiff,
     filter::CfxRpcLogFilter,
+    gas_price_oracle::GasPriceOracle,
     index::Index,
     log::Log,
+    mining_work::MiningWork,
     pos_economics::PoSEconomics,
+    propagation::{BlockPropagation, PropagationEvent},
     provenance::Origin,
     receipt::Receipt,
     reward_info::RewardInfo,
     sponsor_info::SponsorInfo,
     status::Status,
+    storage_key_preimage::StorageKeyPreimage,
     sync_graph_states::SyncGraphStates,
+    sync_progress::{SyncPhaseChange, SyncProgress},
     token_supply_info::TokenSupplyInfo,
     trace::{
         Action, LocalizedBlockTrace, LocalizedTrace, LocalizedTransactionTrace,
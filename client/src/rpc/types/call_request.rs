@@ -99,18 +99,18 @@ impl SendTxRequest {
         )
     }
 
-    pub fn sign_with(
-        self, best_epoch_height: u64, chain_id: u32, password: Option<String>,
-        accounts: Arc<AccountProvider>,
-    ) -> RpcResult<TransactionWithSignature>
-    {
+    /// Build the unsigned native transaction, with the missing epoch height
+    /// and chain id filled by `best_epoch_height` and `chain_id`.
+    pub fn to_native_transaction(
+        &self, best_epoch_height: u64, chain_id: u32,
+    ) -> RpcResult<PrimitiveTransaction> {
         let tx = PrimitiveTransaction {
             nonce: self.nonce.unwrap_or_default().into(),
             gas_price: self.gas_price.into(),
             gas: self.gas.into(),
-            action: match self.to {
+            action: match &self.to {
                 None => Action::Create,
-                Some(address) => Action::Call(address.into()),
+                Some(address) => Action::Call(address.clone().into()),
             },
             value: self.value.into(),
             storage_limit: self.storage_limit.unwrap_or_default().as_usize()
@@ -120,12 +120,21 @@ impl SendTxRequest {
                 .unwrap_or(best_epoch_height.into())
                 .as_usize() as u64,
             chain_id: self.chain_id.unwrap_or(chain_id.into()).as_u32(),
-            data: self.data.unwrap_or(Bytes::new(vec![])).into(),
+            data: self.data.clone().unwrap_or(Bytes::new(vec![])).into(),
         };
 
         if tx.epoch_height == u64::MAX {
             return Err("Can not sign Ethereum like transaction by RPC.".into());
         }
+        Ok(tx)
+    }
+
+    pub fn sign_with(
+        self, best_epoch_height: u64, chain_id: u32, password: Option<String>,
+        accounts: Arc<AccountProvider>,
+    ) -> RpcResult<TransactionWithSignature>
+    {
+        let tx = self.to_native_transaction(best_epoch_height, chain_id)?;
 
         let password = password.map(Password::from);
         let sig = accounts
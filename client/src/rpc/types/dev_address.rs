@@ -0,0 +1,71 @@
+// Copyright 2021 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use crate::rpc::types::RpcAddress;
+use cfx_addr::Network;
+use cfx_types::{AddressSpaceUtil, AddressWithSpace, H160};
+
+/// The address type of the dev mode RPCs. A base32 address is in the native
+/// space, and a hex address is in the Ethereum space.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum DevAddress {
+    Native(RpcAddress),
+    Ethereum(H160),
+}
+
+impl DevAddress {
+    /// The network of a native space address.
+    pub fn network(&self) -> Option<Network> {
+        match self {
+            DevAddress::Native(address) => Some(address.network),
+            DevAddress::Ethereum(_) => None,
+        }
+    }
+}
+
+impl From<DevAddress> for AddressWithSpace {
+    fn from(address: DevAddress) -> Self {
+        match address {
+            DevAddress::Native(address) => {
+                address.hex_address.with_native_space()
+            }
+            DevAddress::Ethereum(address) => address.with_evm_space(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DevAddress;
+    use cfx_addr::Network;
+    use cfx_types::{AddressWithSpace, Space, H160};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_deserialize_dev_address() {
+        let native: DevAddress = serde_json::from_str(
+            r#""cfx:acc7uawf5ubtnmezvhu9dhc6sghea0403y2dgpyfjp""#,
+        )
+        .unwrap();
+        assert_eq!(native.network(), Some(Network::Main));
+        assert_eq!(AddressWithSpace::from(native).space, Space::Native);
+
+        let ethereum: DevAddress = serde_json::from_str(
+            r#""0x1a2f80341409639ea6a35bbcab8299066109aa55""#,
+        )
+        .unwrap();
+        assert_eq!(ethereum.network(), None);
+        assert_eq!(
+            AddressWithSpace::from(ethereum),
+            AddressWithSpace {
+                address: H160::from_str(
+                    "1a2f80341409639ea6a35bbcab8299066109aa55"
+                )
+                .unwrap(),
+                space: Space::Ethereum,
+            }
+        );
+    }
+}
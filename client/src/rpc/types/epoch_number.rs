@@ -26,6 +26,9 @@ pub enum EpochNumber {
     LatestFinalized,
     /// The latest confirmed (with the estimation of the confirmation meter)
     LatestConfirmed,
+    /// The latest epoch whose confirmation risk is below the configured
+    /// threshold
+    LatestSafe,
     /// Latest block with state.
     LatestState,
     /// Latest mined block.
@@ -66,6 +69,7 @@ impl Serialize for EpochNumber {
             EpochNumber::LatestConfirmed => {
                 serializer.serialize_str("latest_confirmed")
             }
+            EpochNumber::LatestSafe => serializer.serialize_str("latest_safe"),
         }
     }
 }
@@ -86,6 +90,7 @@ impl EpochNumber {
             EpochNumber::LatestConfirmed => {
                 PrimitiveEpochNumber::LatestConfirmed
             }
+            EpochNumber::LatestSafe => PrimitiveEpochNumber::LatestSafe,
         }
     }
 }
@@ -99,6 +104,7 @@ impl FromStr for EpochNumber {
             "latest_state" => Ok(EpochNumber::LatestState),
             "latest_finalized" => Ok(EpochNumber::LatestFinalized),
             "latest_confirmed" => Ok(EpochNumber::LatestConfirmed),
+            "latest_safe" => Ok(EpochNumber::LatestSafe),
             "earliest" => Ok(EpochNumber::Earliest),
             "latest_checkpoint" => Ok(EpochNumber::LatestCheckpoint),
             _ if s.starts_with("0x") => u64::from_str_radix(&s[2..], 16)
@@ -126,7 +132,7 @@ impl<'a> Visitor<'a> for EpochNumberVisitor {
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(
             formatter,
-            "an epoch number or 'latest_mined', 'latest_state', 'latest_checkpoint', 'latest_finalized', 'latest_confirmed' or 'earliest'"
+            "an epoch number or 'latest_mined', 'latest_state', 'latest_checkpoint', 'latest_finalized', 'latest_confirmed', 'latest_safe' or 'earliest'"
         )
     }
 
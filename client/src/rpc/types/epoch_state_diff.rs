@@ -0,0 +1,161 @@
+// Copyright 2022 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use super::{Bytes, RpcAddress, SponsorInfo, StorageKeyPreimage};
+use cfx_addr::Network;
+use cfx_types::{Address, H256, U256, U64};
+use primitives::{
+    Account as PrimitiveAccount, EpochStateDiff as PrimitiveEpochStateDiff,
+    SkipInputCheck, StorageKey,
+    StorageKeyPreimage as PrimitiveStorageKeyPreimage, StorageKeyWithSpace,
+    StorageValue,
+};
+use rlp::Rlp;
+
+/// The accounts and storage entries changed by the execution of an epoch.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochStateDiff {
+    pub epoch_number: U64,
+    /// The hash of the pivot block of the epoch.
+    pub epoch_hash: H256,
+    pub accounts: Vec<AccountDiff>,
+    pub storage: Vec<StorageDiff>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountDiff {
+    /// The space of the account, "native" or "evm".
+    pub space: String,
+    pub address: RpcAddress,
+    /// The account before the epoch, `None` if it did not exist.
+    pub before: Option<AccountState>,
+    /// The account after the epoch, `None` if it has been removed.
+    pub after: Option<AccountState>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountState {
+    pub balance: U256,
+    pub nonce: U256,
+    pub code_hash: H256,
+    pub staking_balance: U256,
+    pub collateral_for_storage: U256,
+    pub accumulated_interest_return: U256,
+    pub admin: RpcAddress,
+    pub sponsor_info: SponsorInfo,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageDiff {
+    /// The space of the contract, "native" or "evm".
+    pub space: String,
+    pub address: RpcAddress,
+    pub slot: Bytes,
+    /// The value before the epoch, `None` if the slot was empty.
+    pub before: Option<U256>,
+    /// The value after the epoch, `None` if the slot has been cleared.
+    pub after: Option<U256>,
+    /// The recorded preimage of the storage key, `None` if the preimages are
+    /// not recorded with `record_storage_key_preimages`.
+    pub preimage: Option<StorageKeyPreimage>,
+}
+
+impl EpochStateDiff {
+    pub fn try_from(
+        epoch_number: u64, epoch_hash: H256, diff: PrimitiveEpochStateDiff,
+        preimage_of: &dyn Fn(&[u8]) -> Option<PrimitiveStorageKeyPreimage>,
+        network: Network,
+    ) -> Result<Self, String>
+    {
+        let mut accounts = vec![];
+        let mut storage = vec![];
+
+        for change in diff.changes {
+            let key = StorageKeyWithSpace::from_key_bytes::<SkipInputCheck>(
+                &change.key,
+            );
+
+            // Other entries (code, deposit list, vote list, storage root and
+            // storage layout) are reflected by the account fields or are
+            // internal to the storage, so they are not reported.
+            match key.key {
+                StorageKey::AccountKey(address_bytes) => {
+                    let address = Address::from_slice(address_bytes);
+                    let decode = |value: Option<Vec<u8>>| match value {
+                        None => Ok(None),
+                        Some(raw) => {
+                            let account = PrimitiveAccount::new_from_rlp(
+                                address,
+                                &Rlp::new(&raw),
+                            )
+                            .map_err(|e| format!("{:?}", e))?;
+                            AccountState::try_from(account, network).map(Some)
+                        }
+                    };
+                    accounts.push(AccountDiff {
+                        space: key.space.into(),
+                        address: RpcAddress::try_from_h160(address, network)?,
+                        before: decode(change.original_value)?,
+                        after: decode(change.current_value)?,
+                    });
+                }
+                StorageKey::StorageKey {
+                    address_bytes,
+                    storage_key,
+                } => {
+                    let decode = |value: Option<Vec<u8>>| match value {
+                        None => Ok(None),
+                        Some(raw) => rlp::decode::<StorageValue>(&raw)
+                            .map(|v| Some(v.value))
+                            .map_err(|e| format!("{:?}", e)),
+                    };
+                    storage.push(StorageDiff {
+                        space: key.space.into(),
+                        address: RpcAddress::try_from_h160(
+                            Address::from_slice(address_bytes),
+                            network,
+                        )?,
+                        slot: Bytes::new(storage_key.to_vec()),
+                        before: decode(change.original_value)?,
+                        after: decode(change.current_value)?,
+                        preimage: preimage_of(&change.key)
+                            .map(|preimage| {
+                                StorageKeyPreimage::try_from(preimage, network)
+                            })
+                            .transpose()?,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            epoch_number: epoch_number.into(),
+            epoch_hash,
+            accounts,
+            storage,
+        })
+    }
+}
+
+impl AccountState {
+    pub fn try_from(
+        account: PrimitiveAccount, network: Network,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            balance: account.balance,
+            nonce: account.nonce,
+            code_hash: account.code_hash,
+            staking_balance: account.staking_balance,
+            collateral_for_storage: account.collateral_for_storage,
+            accumulated_interest_return: account.accumulated_interest_return,
+            admin: RpcAddress::try_from_h160(account.admin, network)?,
+            sponsor_info: SponsorInfo::try_from(account.sponsor_info, network)?,
+        })
+    }
+}
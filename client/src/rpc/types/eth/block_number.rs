@@ -46,6 +46,10 @@ pub enum BlockNumber {
     Earliest,
     /// Pending block (being mined)
     Pending,
+    /// Latest block finalized by PoS
+    Finalized,
+    /// Latest block whose confirmation risk is below the configured threshold
+    Safe,
 }
 
 impl Default for BlockNumber {
@@ -86,6 +90,8 @@ impl Serialize for BlockNumber {
             BlockNumber::Latest => serializer.serialize_str("latest"),
             BlockNumber::Earliest => serializer.serialize_str("earliest"),
             BlockNumber::Pending => serializer.serialize_str("pending"),
+            BlockNumber::Finalized => serializer.serialize_str("finalized"),
+            BlockNumber::Safe => serializer.serialize_str("safe"),
         }
     }
 }
@@ -98,7 +104,7 @@ impl<'a> Visitor<'a> for BlockNumberVisitor {
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(
             formatter,
-            "a block number or 'latest', 'earliest' or 'pending'"
+            "a block number or 'latest', 'earliest', 'pending', 'finalized' or 'safe'"
         )
     }
 
@@ -169,6 +175,8 @@ impl<'a> Visitor<'a> for BlockNumberVisitor {
             "latest" => Ok(BlockNumber::Latest),
             "earliest" => Ok(BlockNumber::Earliest),
             "pending" => Ok(BlockNumber::Pending),
+            "finalized" => Ok(BlockNumber::Finalized),
+            "safe" => Ok(BlockNumber::Safe),
             _ if value.starts_with("0x") => {
                 u64::from_str_radix(&value[2..], 16)
                     .map(BlockNumber::Num)
@@ -197,6 +205,8 @@ impl TryFrom<BlockNumber> for EpochNumber {
             BlockNumber::Latest => Ok(EpochNumber::LatestState),
             BlockNumber::Earliest => Ok(EpochNumber::Earliest),
             BlockNumber::Pending => Ok(EpochNumber::LatestMined),
+            BlockNumber::Finalized => Ok(EpochNumber::LatestFinalized),
+            BlockNumber::Safe => Ok(EpochNumber::LatestSafe),
             BlockNumber::Hash { .. } => Err(invalid_params(
                 "block_num",
                 "Expected block number, found block hash",
@@ -218,6 +228,8 @@ mod tests {
 			"latest",
 			"earliest",
 			"pending",
+			"finalized",
+			"safe",
 			{"blockNumber": "0xa"},
 			{"blockHash": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"},
 			{"blockHash": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347", "requireCanonical": true}
@@ -231,6 +243,8 @@ mod tests {
                 BlockNumber::Latest,
                 BlockNumber::Earliest,
                 BlockNumber::Pending,
+                BlockNumber::Finalized,
+                BlockNumber::Safe,
                 BlockNumber::Num(10),
                 BlockNumber::Hash {
                     hash: H256::from_str(
@@ -5,7 +5,7 @@
 //! Pub-Sub types.
 
 use super::{EthRpcLogFilter, Header, Log};
-use cfx_types::H256;
+use cfx_types::{H256, U256};
 use serde::{de::Error, Deserialize, Deserializer, Serialize};
 use serde_json::{from_value, Value};
 
@@ -24,6 +24,30 @@ pub enum Result {
 
     /// Transaction hash
     TransactionHash(H256),
+
+    /// Chain reorg
+    #[serde(rename_all = "camelCase")]
+    ChainReorg {
+        revert_to: U256,
+        reverted_blocks: Vec<RevertedBlock>,
+    },
+
+    /// Newly finalized block range
+    #[serde(rename_all = "camelCase")]
+    FinalizedBlocks { from_block: U256, to_block: U256 },
+}
+
+/// A block reverted by a pivot chain reorg.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertedBlock {
+    pub block_number: U256,
+    pub block_hash: H256,
+    /// The transactions executed in the block, which are executed again in
+    /// another block or not executed at all after the reorg.
+    pub transaction_hashes: Vec<H256>,
+    /// The logs of the block, which are marked as removed.
+    pub removed_logs: Vec<Log>,
 }
 
 /// Subscription kind.
@@ -39,6 +63,10 @@ pub enum Kind {
     NewPendingTransactions,
     /// Node syncing status subscription.
     Syncing,
+    /// Pivot chain reorg subscription.
+    ChainReorgs,
+    /// Newly finalized block range subscription.
+    FinalizedBlocks,
 }
 
 /// Subscription kind.
@@ -73,12 +73,20 @@ impl Log {
         let pivot_hash =
             hashes.last().ok_or(internal_error("Inconsistent state"))?;
 
-        // construct RPC log
-        Ok(Log {
+        Ok(Self::from_localized_with_pivot(e, *pivot_hash, removed))
+    }
+
+    /// Construct the RPC log of `e` in the epoch with pivot block
+    /// `pivot_hash`, which might not be on the pivot chain any more if the
+    /// log is removed.
+    pub fn from_localized_with_pivot(
+        e: LocalizedLogEntry, pivot_hash: H256, removed: bool,
+    ) -> Log {
+        Log {
             address: e.entry.address,
             topics: e.entry.topics.into_iter().map(Into::into).collect(),
             data: e.entry.data.into(),
-            block_hash: *pivot_hash,
+            block_hash: pivot_hash,
             // note: blocks in EVM space RPCs correspond to epochs
             block_number: e.epoch_number.into(),
             transaction_hash: e.transaction_hash.into(),
@@ -86,7 +94,7 @@ impl Log {
             log_index: Some(e.log_index.into()),
             transaction_log_index: Some(e.transaction_log_index.into()),
             removed,
-        })
+        }
     }
 
     pub fn try_from(_e: LogEntry) -> Result<Log, String> {
@@ -0,0 +1,39 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use cfx_types::{U256, U64};
+use cfxcore::{
+    consensus::gas_price_oracle::GasPriceSuggestion, TransactionPool,
+};
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GasPriceOracle {
+    pub slow: U256,
+    pub standard: U256,
+    pub fast: U256,
+    /// The fraction of the block gas limit used by the sampled transactions.
+    pub gas_used_ratio: f64,
+    pub pending_transactions: U64,
+    pub deferred_transactions: U64,
+    /// The number of the transactions in the pool divided by its capacity.
+    pub pool_pressure: f64,
+}
+
+impl GasPriceOracle {
+    pub fn new(
+        suggestion: GasPriceSuggestion, txpool: &TransactionPool,
+    ) -> Self {
+        let (_ready_accounts, deferred, _received, unpacked) = txpool.stats();
+        GasPriceOracle {
+            slow: suggestion.slow,
+            standard: suggestion.standard,
+            fast: suggestion.fast,
+            gas_used_ratio: suggestion.gas_used_ratio,
+            pending_transactions: unpacked.into(),
+            deferred_transactions: deferred.into(),
+            pool_pressure: deferred as f64 / txpool.capacity().max(1) as f64,
+        }
+    }
+}
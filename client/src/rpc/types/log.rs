@@ -42,6 +42,10 @@ pub struct Log {
     /// Log Index in Transaction
     #[serde(skip_serializing_if = "Option::is_none")]
     pub transaction_log_index: Option<U256>,
+
+    /// Whether the log is removed by a pivot chain reorg
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub removed: bool,
 }
 
 impl Log {
@@ -58,6 +62,7 @@ impl Log {
             transaction_index: Some(e.transaction_index.into()),
             log_index: Some(e.log_index.into()),
             transaction_log_index: Some(e.transaction_log_index.into()),
+            removed: false,
         })
     }
 
@@ -72,6 +77,7 @@ impl Log {
             transaction_index: None,
             log_index: None,
             transaction_log_index: None,
+            removed: false,
         })
     }
 }
@@ -101,6 +107,7 @@ mod tests {
             transaction_index: Some(U256::default()),
             transaction_log_index: Some(1.into()),
             log_index: Some(U256::from(1)),
+            removed: false,
         };
 
         let serialized = serde_json::to_string(&log).unwrap();
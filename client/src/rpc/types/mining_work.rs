@@ -0,0 +1,28 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use cfx_types::{H256, U256, U64};
+use cfxcore::pow::ProofOfWorkProblem;
+
+/// The PoW problem of the block being mined, returned by `cfx_getWork`.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MiningWork {
+    /// The hash to be solved, which identifies the work in
+    /// `cfx_submitWork`.
+    pub pow_hash: H256,
+    /// A solution is valid if its PoW quality hash is below the boundary.
+    pub boundary: U256,
+    pub block_height: U64,
+}
+
+impl From<ProofOfWorkProblem> for MiningWork {
+    fn from(problem: ProofOfWorkProblem) -> Self {
+        MiningWork {
+            pow_hash: problem.block_hash,
+            boundary: problem.boundary,
+            block_height: problem.block_height.into(),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use cfx_types::{H512, U64};
+use cfxcore::sync::request_manager::propagation::{
+    BlockPropagation as PrimitiveBlockPropagation,
+    PropagationEvent as PrimitivePropagationEvent,
+};
+use serde_derive::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PropagationEvent {
+    /// Milliseconds since the UNIX epoch.
+    pub timestamp: U64,
+    /// The peer that sent the item, or null if it is from the local node.
+    pub node_id: Option<H512>,
+}
+
+impl From<PrimitivePropagationEvent> for PropagationEvent {
+    fn from(event: PrimitivePropagationEvent) -> Self {
+        PropagationEvent {
+            timestamp: event.timestamp_ms.into(),
+            node_id: event.peer,
+        }
+    }
+}
+
+/// The first arrival of each propagation stage of a block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockPropagation {
+    pub new_block_hashes: Option<PropagationEvent>,
+    pub compact_block: Option<PropagationEvent>,
+    pub full_block: Option<PropagationEvent>,
+}
+
+impl From<PrimitiveBlockPropagation> for BlockPropagation {
+    fn from(propagation: PrimitiveBlockPropagation) -> Self {
+        BlockPropagation {
+            new_block_hashes: propagation.new_block_hashes.map(Into::into),
+            compact_block: propagation.compact_block.map(Into::into),
+            full_block: propagation.full_block.map(Into::into),
+        }
+    }
+}
@@ -20,7 +20,7 @@
 
 //! Pub-Sub types.
 
-use super::{CfxRpcLogFilter, Header, Log};
+use super::{CfxRpcLogFilter, EpochStateDiff, Header, Log, SyncPhaseChange};
 use cfx_types::{H256, U256};
 use serde::{de::Error, Deserialize, Deserializer, Serialize};
 use serde_json::{from_value, Value};
@@ -50,7 +50,50 @@ pub enum Result {
 
     /// Chain reorg
     #[serde(rename_all = "camelCase")]
-    ChainReorg { revert_to: U256 },
+    ChainReorg {
+        revert_to: U256,
+        reverted_epochs: Vec<RevertedEpoch>,
+    },
+
+    /// Newly finalized epoch range
+    #[serde(rename_all = "camelCase")]
+    FinalizedEpochs { from_epoch: U256, to_epoch: U256 },
+
+    /// Transaction confirmed with the required risk or finality
+    #[serde(rename_all = "camelCase")]
+    TransactionConfirmed {
+        transaction_hash: H256,
+        block_hash: H256,
+        epoch_number: U256,
+        confirmation_risk: U256,
+        finalized: bool,
+    },
+
+    /// Transaction dropped from the pivot chain before it is confirmed
+    #[serde(rename_all = "camelCase")]
+    TransactionReverted {
+        transaction_hash: H256,
+        reverted_block_hash: H256,
+    },
+
+    /// State diff of an executed epoch
+    EpochStateDiff(EpochStateDiff),
+
+    /// Sync phase transition
+    SyncPhaseChange(SyncPhaseChange),
+}
+
+/// An epoch reverted by a pivot chain reorg.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertedEpoch {
+    pub epoch_number: U256,
+    pub epoch_hashes_ordered: Vec<H256>,
+    /// The transactions executed in the epoch, which are executed again in
+    /// another epoch or not executed at all after the reorg.
+    pub transaction_hashes: Vec<H256>,
+    /// The logs of the epoch, which are marked as removed.
+    pub removed_logs: Vec<Log>,
 }
 
 /// Subscription kind.
@@ -68,6 +111,12 @@ pub enum Kind {
     Syncing,
     /// Epoch
     Epochs,
+    /// Executed epoch state diff subscription.
+    EpochStateDiffs,
+    /// Newly finalized epoch range subscription.
+    FinalizedEpochs,
+    /// Transaction confirmation subscription.
+    TransactionConfirmation,
 }
 
 /// Subscription epoch.
@@ -81,6 +130,22 @@ pub enum SubscriptionEpoch {
     LatestState,
 }
 
+/// Transaction confirmation subscription parameters, with either
+/// `risk_threshold` or `finalized` set.
+#[derive(Debug, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionConfirmationParams {
+    /// Hash of the watched transaction.
+    pub hash: H256,
+    /// Maximal confirmation risk, scaled the same as the result of
+    /// `cfx_getConfirmationRiskByHash`.
+    pub risk_threshold: Option<U256>,
+    /// Wait for the block of the transaction to be finalized by PoS.
+    #[serde(default)]
+    pub finalized: bool,
+}
+
 /// Subscription kind.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum Params {
@@ -90,6 +155,8 @@ pub enum Params {
     Logs(CfxRpcLogFilter),
     /// Epoch parameters.
     Epochs(SubscriptionEpoch),
+    /// Transaction confirmation parameters.
+    TransactionConfirmation(TransactionConfirmationParams),
 }
 
 impl Default for Params {
@@ -112,6 +179,13 @@ impl<'a> Deserialize<'a> for Params {
             return Ok(v);
         }
 
+        // try to interpret as transaction confirmation parameters
+        if let Ok(v) =
+            from_value(v.clone()).map(Params::TransactionConfirmation)
+        {
+            return Ok(v);
+        }
+
         // otherwise, interpret as epoch
         from_value(v).map(Params::Epochs).map_err(|e| {
             D::Error::custom(format!("Invalid Pub-Sub parameters: {}", e))
@@ -119,6 +193,115 @@ impl<'a> Deserialize<'a> for Params {
     }
 }
 
+#[cfg(test)]
+mod tx_confirmation_tests {
+    use super::{Kind, Params, Result, TransactionConfirmationParams};
+    use cfx_types::{H256, U256};
+
+    const HASH: &str =
+        "0x0000000000000000000000000000000000000000000000000000000000000001";
+
+    fn params(json: &str) -> Params {
+        serde_json::from_str::<Params>(json).unwrap()
+    }
+
+    #[test]
+    fn should_deserialize_kind() {
+        assert_eq!(
+            serde_json::from_str::<Kind>(r#""transactionConfirmation""#)
+                .unwrap(),
+            Kind::TransactionConfirmation
+        );
+    }
+
+    #[test]
+    fn should_deserialize_tx_confirmation_params() {
+        let hash = H256::from_low_u64_be(1);
+
+        let risk =
+            params(&format!(r#"{{"hash":"{}","riskThreshold":"0x10"}}"#, HASH));
+        assert_eq!(
+            risk,
+            Params::TransactionConfirmation(TransactionConfirmationParams {
+                hash,
+                risk_threshold: Some(U256::from(16)),
+                finalized: false,
+            })
+        );
+
+        let finalized =
+            params(&format!(r#"{{"hash":"{}","finalized":true}}"#, HASH));
+        assert_eq!(
+            finalized,
+            Params::TransactionConfirmation(TransactionConfirmationParams {
+                hash,
+                risk_threshold: None,
+                finalized: true,
+            })
+        );
+
+        // Parsed, but rejected by the subscription without a condition.
+        let bare = params(&format!(r#"{{"hash":"{}"}}"#, HASH));
+        assert_eq!(
+            bare,
+            Params::TransactionConfirmation(TransactionConfirmationParams {
+                hash,
+                risk_threshold: None,
+                finalized: false,
+            })
+        );
+    }
+
+    #[test]
+    fn should_not_confuse_tx_confirmation_params() {
+        // Log filters and epochs are still recognized.
+        assert!(matches!(params("{}"), Params::Logs(_)));
+        assert!(matches!(params(r#""latest_state""#), Params::Epochs(_)));
+
+        // Unknown fields and malformed values are rejected.
+        let unknown = format!(r#"{{"hash":"{}","risk":"0x10"}}"#, HASH);
+        assert!(serde_json::from_str::<Params>(&unknown).is_err());
+        let malformed = r#"{"hash":"0x01","finalized":true}"#;
+        assert!(serde_json::from_str::<Params>(malformed).is_err());
+    }
+
+    #[test]
+    fn should_serialize_tx_confirmation_results() {
+        let hash = H256::from_low_u64_be(1);
+        let block_hash = H256::from_low_u64_be(2);
+
+        let confirmed = Result::TransactionConfirmed {
+            transaction_hash: hash,
+            block_hash,
+            epoch_number: 10.into(),
+            confirmation_risk: 16.into(),
+            finalized: true,
+        };
+        assert_eq!(
+            serde_json::to_value(&confirmed).unwrap(),
+            serde_json::json!({
+                "transactionHash": hash,
+                "blockHash": block_hash,
+                "epochNumber": "0xa",
+                "confirmationRisk": "0x10",
+                "finalized": true,
+            })
+        );
+
+        let reverted = Result::TransactionReverted {
+            transaction_hash: hash,
+            reverted_block_hash: block_hash,
+        };
+        assert_eq!(
+            serde_json::to_value(&reverted).unwrap(),
+            serde_json::json!({
+                "transactionHash": hash,
+                "revertedBlockHash": block_hash,
+            })
+        );
+    }
+}
+
 //#[cfg(test)]
 //mod tests {
 //    use serde_json;
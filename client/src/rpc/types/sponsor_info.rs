@@ -7,7 +7,7 @@ use cfx_addr::Network;
 use cfx_types::U256;
 use primitives::SponsorInfo as PrimitiveSponsorInfo;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SponsorInfo {
     /// This is the address of the sponsor for gas cost of the contract.
@@ -0,0 +1,30 @@
+// Copyright 2022 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use super::{Bytes, RpcAddress};
+use cfx_addr::Network;
+use primitives::StorageKeyPreimage as PrimitiveStorageKeyPreimage;
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageKeyPreimage {
+    /// The space of the contract, "native" or "evm".
+    pub space: String,
+    /// The address of the contract owning the storage entry.
+    pub address: RpcAddress,
+    /// The storage slot within the contract.
+    pub slot: Bytes,
+}
+
+impl StorageKeyPreimage {
+    pub fn try_from(
+        preimage: PrimitiveStorageKeyPreimage, network: Network,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            space: preimage.space.into(),
+            address: RpcAddress::try_from_h160(preimage.address, network)?,
+            slot: Bytes::new(preimage.slot),
+        })
+    }
+}
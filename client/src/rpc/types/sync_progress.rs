@@ -0,0 +1,136 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use cfx_types::{H512, U64};
+use cfxcore::sync::{
+    PeerContribution as PrimitivePeerContribution,
+    ProgressCounter as PrimitiveProgressCounter,
+    SyncPhaseChange as PrimitiveSyncPhaseChange,
+    SyncProgress as PrimitiveSyncProgress,
+};
+use serde_derive::Serialize;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressCounter {
+    pub current: U64,
+    pub target: U64,
+}
+
+impl From<PrimitiveProgressCounter> for ProgressCounter {
+    fn from(counter: PrimitiveProgressCounter) -> Self {
+        ProgressCounter {
+            current: counter.current.into(),
+            target: counter.target.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncPhaseDuration {
+    pub phase: String,
+    pub elapsed_ms: U64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerSyncContribution {
+    pub node_id: H512,
+    pub headers: U64,
+    pub blocks: U64,
+    pub chunks: U64,
+    pub bytes: U64,
+}
+
+impl PeerSyncContribution {
+    fn new(node_id: H512, contribution: PrimitivePeerContribution) -> Self {
+        PeerSyncContribution {
+            node_id,
+            headers: contribution.headers.into(),
+            blocks: contribution.blocks.into(),
+            chunks: contribution.chunks.into(),
+            bytes: contribution.bytes.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncProgress {
+    /// The current sync phase.
+    pub phase: String,
+    /// Time spent in the current phase.
+    pub phase_elapsed_ms: U64,
+    /// Finished phases in order.
+    pub finished_phases: Vec<SyncPhaseDuration>,
+    /// The best epoch of the local header chain against the median epoch of
+    /// the peers.
+    pub headers: Option<ProgressCounter>,
+    /// Block bodies filled against the blocks to fill after the checkpoint
+    /// is synced.
+    pub blocks: Option<ProgressCounter>,
+    /// Snapshot chunks restored against the chunks in the manifest.
+    pub chunks: Option<ProgressCounter>,
+    /// Bytes of sync data received per second in the last minute.
+    pub bytes_per_second: U64,
+    /// Estimated time to finish the current phase, based on the throughput
+    /// in the last minute.
+    pub eta_ms: Option<U64>,
+    /// Sync data received from each connected peer, sorted by bytes in
+    /// descending order.
+    pub peers: Vec<PeerSyncContribution>,
+}
+
+impl From<PrimitiveSyncProgress> for SyncProgress {
+    fn from(progress: PrimitiveSyncProgress) -> Self {
+        SyncProgress {
+            phase: progress.phase.name().into(),
+            phase_elapsed_ms: millis(progress.phase_elapsed),
+            finished_phases: progress
+                .finished_phases
+                .into_iter()
+                .map(|(phase, elapsed)| SyncPhaseDuration {
+                    phase: phase.name().into(),
+                    elapsed_ms: millis(elapsed),
+                })
+                .collect(),
+            headers: progress.headers.map(Into::into),
+            blocks: progress.blocks.map(Into::into),
+            chunks: progress.chunks.map(Into::into),
+            bytes_per_second: (progress.bytes_per_second as u64).into(),
+            eta_ms: progress.eta.map(millis),
+            peers: progress
+                .peers
+                .into_iter()
+                .map(|(node_id, contribution)| {
+                    PeerSyncContribution::new(node_id, contribution)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A sync phase transition published to `syncing` subscribers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncPhaseChange {
+    pub from: String,
+    pub to: String,
+    /// Time spent in the phase `from`.
+    pub elapsed_ms: U64,
+}
+
+impl From<PrimitiveSyncPhaseChange> for SyncPhaseChange {
+    fn from(change: PrimitiveSyncPhaseChange) -> Self {
+        SyncPhaseChange {
+            from: change.from.name().into(),
+            to: change.to.name().into(),
+            elapsed_ms: millis(change.elapsed),
+        }
+    }
+}
+
+fn millis(duration: Duration) -> U64 { (duration.as_millis() as u64).into() }
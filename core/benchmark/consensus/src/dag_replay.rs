@@ -0,0 +1,552 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Export the block header DAG of a synced node and replay it into the
+//! consensus graph without execution.
+//!
+//! The exported file starts with the consensus parameters in the same order
+//! as the synthetic input (`timer_ratio timer_beta beta h era_epoch_count`).
+//! Each following line is a block, and the blocks are in a topological order:
+//!
+//! `is_pivot adaptive parent_idx timestamp difficulty pow_quality pos_reference
+//! [referee_idx ...]`
+//!
+//! The index 0 is the pivot block of the epoch before the exported range. A
+//! parent outside the range is replaced by the index 0 and a referee outside
+//! the range is dropped. `pos_reference` is `-` if it is not set.
+//!
+//! A PoS block is read from the PoS ledger database of the node when it is
+//! first referenced, and written in a line before the referencing block:
+//!
+//! `pos hash epoch round view pivot_decision_idx`
+//!
+//! where a pivot decision outside the range is replaced by the index 0. The
+//! replay resolves the PoS references with these recorded blocks, so they
+//! force confirm the pivot chain as on the synced node.
+
+use cfx_types::{address_util::AddressUtil, Address, H256, U256};
+use cfxcore::{
+    block_data_manager::{db_manager::DBManager, DbType},
+    consensus::{
+        pos_handler::{PosBlock, PosInterface, RecordedPos},
+        ConsensusGraph, ConsensusGraphTrait,
+    },
+    consensus_parameters::{
+        ADAPTIVE_WEIGHT_DEFAULT_BETA, ERA_DEFAULT_EPOCH_COUNT,
+        GENESIS_GAS_LIMIT, HEAVY_BLOCK_DEFAULT_DIFFICULTY_RATIO,
+        TIMER_CHAIN_BLOCK_DEFAULT_DIFFICULTY_RATIO, TIMER_CHAIN_DEFAULT_BETA,
+    },
+    db::NUM_COLUMNS,
+    pow::{self, PowComputer},
+    sync::utils::initialize_synchronization_graph,
+};
+use diem_crypto::HashValue;
+use pos_ledger_db::PosLedgerDB;
+use primitives::{pos::PosBlockId, Block, BlockHeader, BlockHeaderBuilder};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{BufWriter, Write},
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+    time,
+};
+use storage_interface::DBReaderForPoW;
+
+const REPLAY_DB_DIR: &str = "./__consensus_replay_db";
+
+/// Write the blocks in the epochs `[start_epoch, end_epoch]` of the ledger
+/// database at `db_dir` into `output_file`. Without `end_epoch`, the export
+/// stops at the first epoch not in the database. The PoS blocks referenced
+/// are read from the PoS ledger database at `pos_db_dir`.
+pub fn export(
+    db_dir: &str, output_file: &str, start_epoch: u64, end_epoch: Option<u64>,
+    pos_db_dir: Option<&str>,
+)
+{
+    assert!(start_epoch >= 1, "The start epoch should be at least 1");
+    let ledger_db = db::open_database(
+        db_dir,
+        &db::db_config(
+            Path::new(db_dir),
+            Some(128),
+            db::DatabaseCompactionProfile::default(),
+            NUM_COLUMNS,
+            false,
+        ),
+    )
+    .expect("Cannot open the ledger database!");
+    let db_man = DBManager::new_from_rocksdb(
+        ledger_db,
+        Arc::new(PowComputer::new(true)),
+    );
+    let pos_db = pos_db_dir.map(|dir| {
+        PosLedgerDB::open(
+            dir,
+            true, /* readonly */
+            None, /* prune_window */
+            Default::default(),
+        )
+        .expect("Cannot open the PoS ledger database!")
+    });
+    let mut pos_blocks = HashSet::new();
+
+    let root_hash = *db_man
+        .executed_epoch_set_hashes_from_db(start_epoch - 1)
+        .expect("The epoch before the start epoch is not in the database!")
+        .last()
+        .expect("Epoch set is not empty");
+    let mut indices = HashMap::new();
+    indices.insert(root_hash, 0);
+
+    let mut writer = BufWriter::new(
+        fs::File::create(output_file).expect("Cannot create the output file!"),
+    );
+    writeln!(
+        writer,
+        "// Exported from epoch {} to {:?}",
+        start_epoch, end_epoch
+    )
+    .unwrap();
+    writeln!(
+        writer,
+        "{} {} {} {} {}",
+        TIMER_CHAIN_BLOCK_DEFAULT_DIFFICULTY_RATIO,
+        TIMER_CHAIN_DEFAULT_BETA,
+        ADAPTIVE_WEIGHT_DEFAULT_BETA,
+        HEAVY_BLOCK_DEFAULT_DIFFICULTY_RATIO,
+        ERA_DEFAULT_EPOCH_COUNT,
+    )
+    .unwrap();
+
+    let mut epoch = start_epoch;
+    while end_epoch.map_or(true, |end| epoch <= end) {
+        let executed = match db_man.executed_epoch_set_hashes_from_db(epoch) {
+            Some(hashes) => hashes,
+            None if end_epoch.is_none() => break,
+            None => panic!("Epoch {} is not in the database!", epoch),
+        };
+        let pivot_hash = *executed.last().expect("Epoch set is not empty");
+        // The skipped blocks are the earliest blocks of the epoch set.
+        let mut epoch_hashes = db_man
+            .skipped_epoch_set_hashes_from_db(epoch)
+            .unwrap_or_default();
+        epoch_hashes.extend(executed);
+
+        for hash in epoch_hashes {
+            let header = db_man
+                .block_header_from_db(&hash)
+                .unwrap_or_else(|| panic!("Block {:?} is not found!", hash));
+            if let Some(pos_reference) = header.pos_reference() {
+                if pos_blocks.insert(*pos_reference) {
+                    let pos_db = pos_db.as_ref().unwrap_or_else(|| {
+                        panic!(
+                            "Block {:?} in epoch {} has a PoS reference! Set \
+                             the PoS ledger database to export it.",
+                            hash, epoch
+                        )
+                    });
+                    write_pos_block(
+                        &mut writer,
+                        pos_db,
+                        pos_reference,
+                        &indices,
+                    );
+                }
+            }
+            write_block(&mut writer, &header, hash == pivot_hash, &indices);
+            let idx = indices.len();
+            indices.insert(hash, idx);
+        }
+        if epoch % 10000 == 0 {
+            println!("Exported epoch {}, {} blocks", epoch, indices.len() - 1);
+        }
+        epoch += 1;
+    }
+    println!(
+        "Exported {} blocks from epoch {} to {}",
+        indices.len() - 1,
+        start_epoch,
+        epoch - 1
+    );
+}
+
+fn write_block<W: Write>(
+    writer: &mut W, header: &BlockHeader, is_pivot: bool,
+    indices: &HashMap<H256, usize>,
+)
+{
+    let parent_idx = indices.get(&header.parent_hash()).cloned().unwrap_or(0);
+    let pow_quality = pow::pow_hash_to_quality(
+        &header.pow_hash.expect("Filled when loaded"),
+        &header.nonce(),
+    );
+    let pos_reference = match header.pos_reference() {
+        Some(pos_reference) => format!("{:x}", pos_reference),
+        None => "-".into(),
+    };
+    write!(
+        writer,
+        "{} {} {} {} {} {} {}",
+        is_pivot as u8,
+        header.adaptive() as u8,
+        parent_idx,
+        header.timestamp(),
+        header.difficulty(),
+        pow_quality,
+        pos_reference,
+    )
+    .unwrap();
+    for referee in header.referee_hashes() {
+        if let Some(idx) = indices.get(referee) {
+            write!(writer, " {}", idx).unwrap();
+        }
+    }
+    writeln!(writer).unwrap();
+}
+
+fn write_pos_block<W: Write>(
+    writer: &mut W, pos_db: &PosLedgerDB, pos_reference: &PosBlockId,
+    indices: &HashMap<H256, usize>,
+)
+{
+    let block = pos_db
+        .get_committed_block_by_hash(&HashValue::new(
+            pos_reference.to_fixed_bytes(),
+        ))
+        .unwrap_or_else(|e| {
+            panic!("PoS block {:?} is not found: {:?}", pos_reference, e)
+        });
+    let pivot_decision_idx = indices
+        .get(&block.pivot_decision.block_hash)
+        .cloned()
+        .unwrap_or(0);
+    writeln!(
+        writer,
+        "pos {:x} {} {} {} {}",
+        pos_reference, block.epoch, block.round, block.view, pivot_decision_idx
+    )
+    .unwrap();
+}
+
+struct ReplayBlock {
+    is_pivot: bool,
+    adaptive: bool,
+    parent_idx: usize,
+    timestamp: u64,
+    difficulty: U256,
+    pow_quality: U256,
+    pos_reference: Option<PosBlockId>,
+    referee_idxs: Vec<usize>,
+}
+
+fn parse_block(line: &str) -> ReplayBlock {
+    let err = "Cannot parse the DAG file!";
+    let mut tokens = line.split_whitespace();
+    let mut next = || tokens.next().expect(err);
+    let is_pivot = next() == "1";
+    let adaptive = next() == "1";
+    let parent_idx = usize::from_str(next()).expect(err);
+    let timestamp = u64::from_str(next()).expect(err);
+    let difficulty = U256::from_dec_str(next()).expect(err);
+    let pow_quality = U256::from_dec_str(next()).expect(err);
+    let pos_reference = match next() {
+        "-" => None,
+        hash => Some(H256::from_str(hash).expect(err)),
+    };
+    let referee_idxs =
+        tokens.map(|idx| usize::from_str(idx).expect(err)).collect();
+    ReplayBlock {
+        is_pivot,
+        adaptive,
+        parent_idx,
+        timestamp,
+        difficulty,
+        pow_quality,
+        pos_reference,
+        referee_idxs,
+    }
+}
+
+/// Parse a PoS block line without the `pos` prefix, and return the block
+/// with the index of its pivot decision.
+fn parse_pos_block(line: &str) -> (PosBlockId, u64, u64, u64, usize) {
+    let err = "Cannot parse the PoS block in the DAG file!";
+    let mut tokens = line.split_whitespace();
+    let mut next = || tokens.next().expect(err);
+    let hash = H256::from_str(next()).expect(err);
+    let epoch = u64::from_str(next()).expect(err);
+    let round = u64::from_str(next()).expect(err);
+    let view = u64::from_str(next()).expect(err);
+    let pivot_decision_idx = usize::from_str(next()).expect(err);
+    (hash, epoch, round, view, pivot_decision_idx)
+}
+
+fn create_replay_block(
+    b: &ReplayBlock, parent_hash: H256, referee_hashes: Vec<H256>, height: u64,
+    nonce: U256,
+) -> Block
+{
+    let mut author = Address::zero();
+    author.set_user_account_type_bits();
+    let mut builder = BlockHeaderBuilder::new();
+    let mut header = builder
+        .with_parent_hash(parent_hash)
+        .with_height(height)
+        .with_timestamp(b.timestamp)
+        .with_referee_hashes(referee_hashes)
+        .with_gas_limit(GENESIS_GAS_LIMIT.into())
+        .with_nonce(nonce)
+        .with_difficulty(b.difficulty)
+        .with_adaptive(b.adaptive)
+        .with_author(author)
+        .with_pos_reference(b.pos_reference)
+        .build();
+    header.compute_hash();
+    header.pow_hash = Some(pow::pow_quality_to_hash(&b.pow_quality, &nonce));
+    Block::new(header, vec![])
+}
+
+/// Resident memory of this process in KB.
+fn resident_memory_kb() -> u64 {
+    fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|status| {
+            status
+                .lines()
+                .find(|line| line.starts_with("VmRSS:"))
+                .and_then(|line| line.split_whitespace().nth(1))
+                .and_then(|kb| kb.parse().ok())
+        })
+        .unwrap_or(0)
+}
+
+fn percentile(sorted: &[u128], p: f64) -> u128 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = ((sorted.len() - 1) as f64 * p / 100.0).round() as usize;
+    sorted[rank]
+}
+
+/// Compares the replayed pivot chain with the recorded one.
+#[derive(Default)]
+struct PivotChecker {
+    next_epoch: u64,
+    compared: u64,
+    agreed: u64,
+    dropped: u64,
+    first_disagreement: Option<u64>,
+}
+
+impl PivotChecker {
+    /// Compare the epochs up to `to_epoch` which are still in the current era
+    /// of `consensus`.
+    fn check_until(
+        &mut self, to_epoch: u64, consensus: &ConsensusGraph,
+        recorded_pivots: &Vec<usize>, indices: &HashMap<H256, usize>,
+    )
+    {
+        let inner = consensus.inner.read();
+        while self.next_epoch <= to_epoch {
+            let epoch = self.next_epoch;
+            self.next_epoch += 1;
+            let recorded = match recorded_pivots.get(epoch as usize - 1) {
+                Some(idx) => *idx,
+                None => continue,
+            };
+            let replayed = match inner.get_pivot_hash_from_epoch_number(epoch)
+            {
+                Ok(hash) => indices.get(&hash).cloned(),
+                Err(_) => {
+                    self.dropped += 1;
+                    continue;
+                }
+            };
+            self.compared += 1;
+            if replayed == Some(recorded) {
+                self.agreed += 1;
+            } else if self.first_disagreement.is_none() {
+                self.first_disagreement = Some(epoch);
+            }
+        }
+    }
+}
+
+/// Replay the DAG in `input_file` into a new consensus graph and report the
+/// insertion latency, the memory growth, and whether the pivot chain agrees
+/// with the recorded one.
+pub fn replay(input_file: &str) {
+    let content = fs::read_to_string(input_file)
+        .expect("Cannot open the DAG input file!");
+    let mut lines = content
+        .split('\n')
+        .filter(|line| !line.starts_with("//") && !line.trim().is_empty());
+    let header = lines.next().expect("The DAG file has no header line!");
+    let params: Vec<u64> = header
+        .split_whitespace()
+        .map(|w| {
+            u64::from_str(w).unwrap_or_else(|_| {
+                panic!("Cannot parse {:?} in the DAG file header!", w)
+            })
+        })
+        .collect();
+    if params.len() != 5 {
+        panic!(
+            "The DAG file header should be `timer_ratio timer_beta beta h \
+             era_epoch_count`, but it is {:?}!",
+            header
+        );
+    }
+    let (timer_ratio, timer_beta, beta, h_ratio, era_epoch_count) =
+        (params[0], params[1], params[2], params[3], params[4]);
+    println!(
+        "timer_ratio = {}, timer_beta = {}, beta = {} h = {} era_epoch_count = {}",
+        timer_ratio, timer_beta, beta, h_ratio, era_epoch_count
+    );
+
+    let _ = fs::remove_dir_all(REPLAY_DB_DIR);
+    let (_sync, consensus, data_man, genesis_block) =
+        initialize_synchronization_graph(
+            REPLAY_DB_DIR,
+            beta,
+            h_ratio,
+            timer_ratio,
+            timer_beta,
+            era_epoch_count,
+            DbType::Sqlite,
+        );
+
+    let recorded_pos = RecordedPos::default();
+    consensus
+        .inner
+        .read()
+        .pos_verifier
+        .initialize_with(Box::new(recorded_pos.clone()))
+        .expect("PoS is not initialized in the replay");
+
+    let mut hashes = vec![genesis_block.hash()];
+    let mut heights = vec![0];
+    let mut indices = HashMap::new();
+    indices.insert(genesis_block.hash(), 0);
+    let mut recorded_pivots = Vec::new();
+    let mut latencies_us = Vec::new();
+    let mut pivot_checker = PivotChecker {
+        next_epoch: 1,
+        ..Default::default()
+    };
+
+    let start_memory_kb = resident_memory_kb();
+    let start_time = time::Instant::now();
+    let mut last_report_time = start_time;
+    for line in lines {
+        if let Some(line) = line.strip_prefix("pos ") {
+            let (hash, epoch, round, view, pivot_decision_idx) =
+                parse_pos_block(line);
+            let pivot_decision =
+                *hashes.get(pivot_decision_idx).unwrap_or_else(|| {
+                    panic!("PoS block {:?} refers to a later block!", hash)
+                });
+            recorded_pos.record(PosBlock::new(
+                hash,
+                epoch,
+                round,
+                pivot_decision,
+                0, /* version */
+                view,
+            ));
+            continue;
+        }
+        let b = parse_block(line);
+        let idx = hashes.len();
+        if b.is_pivot {
+            recorded_pivots.push(idx);
+        }
+        if b.parent_idx >= idx || b.referee_idxs.iter().any(|r| *r >= idx) {
+            panic!("Block {} refers to a block not before it!", idx);
+        }
+        if let Some(pos_reference) = &b.pos_reference {
+            if recorded_pos.get_committed_block(pos_reference).is_none() {
+                panic!("Block {} refers to an unknown PoS block!", idx);
+            }
+        }
+        let height = heights[b.parent_idx] + 1;
+        let referee_hashes =
+            b.referee_idxs.iter().map(|idx| hashes[*idx]).collect();
+        let block = create_replay_block(
+            &b,
+            hashes[b.parent_idx],
+            referee_hashes,
+            height,
+            U256::from(idx),
+        );
+        let hash = block.hash();
+        data_man.insert_block(Arc::new(block), true /* persistent */);
+
+        let insert_time = time::Instant::now();
+        consensus.on_new_block(&hash);
+        latencies_us.push(insert_time.elapsed().as_micros());
+
+        hashes.push(hash);
+        heights.push(height);
+        indices.insert(hash, idx);
+
+        // The pivot chain far enough from the tip is not expected to change.
+        let best_epoch = consensus.best_epoch_number();
+        if best_epoch > era_epoch_count {
+            pivot_checker.check_until(
+                best_epoch - era_epoch_count,
+                &consensus,
+                &recorded_pivots,
+                &indices,
+            );
+        }
+
+        if last_report_time.elapsed().as_secs() >= 5 {
+            last_report_time = time::Instant::now();
+            println!(
+                "Replayed {} blocks, {} blocks/s, memory {} MB",
+                idx,
+                idx as f64 / start_time.elapsed().as_secs_f64(),
+                resident_memory_kb() / 1024
+            );
+        }
+    }
+    let elapsed = start_time.elapsed().as_secs_f64();
+    let end_memory_kb = resident_memory_kb();
+    pivot_checker.check_until(
+        consensus.best_epoch_number(),
+        &consensus,
+        &recorded_pivots,
+        &indices,
+    );
+
+    latencies_us.sort();
+    let total_us: u128 = latencies_us.iter().sum();
+    println!("Total Block count: {}", latencies_us.len());
+    println!("Elapsed {} s", elapsed);
+    println!(
+        "Insertion latency (us): mean {}, p50 {}, p90 {}, p99 {}, p99.9 {}, max {}",
+        total_us / latencies_us.len().max(1) as u128,
+        percentile(&latencies_us, 50.0),
+        percentile(&latencies_us, 90.0),
+        percentile(&latencies_us, 99.0),
+        percentile(&latencies_us, 99.9),
+        latencies_us.last().cloned().unwrap_or(0),
+    );
+    println!(
+        "Memory: start {} MB, end {} MB, growth {} MB",
+        start_memory_kb / 1024,
+        end_memory_kb / 1024,
+        end_memory_kb.saturating_sub(start_memory_kb) / 1024
+    );
+    println!(
+        "Pivot chain: {}/{} epochs agree with the recorded pivot chain, {} epochs not compared",
+        pivot_checker.agreed, pivot_checker.compared, pivot_checker.dropped
+    );
+    if let Some(epoch) = pivot_checker.first_disagreement {
+        println!("First disagreement at epoch {}", epoch);
+    }
+}
@@ -2,6 +2,8 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
+mod dag_replay;
+
 use cfx_types::H256;
 use cfxcore::{
     block_data_manager::DbType,
@@ -141,6 +143,37 @@ fn main() {
     }
 
     let args: Vec<String> = env::args().collect();
+    // Usage:
+    //   consensus_bench [seq.in]
+    //   consensus_bench export <ledger_db_dir> <output_file> [start_epoch]
+    //     [end_epoch|-] [pos_db_dir]
+    //   consensus_bench replay <dag_file>
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some("export") => {
+            assert!(args.len() >= 4, "Missing the ledger db or output file!");
+            let start_epoch = args.get(4).map_or(1, |epoch| {
+                u64::from_str(epoch).expect("Cannot parse the start epoch!")
+            });
+            let end_epoch =
+                args.get(5).filter(|epoch| *epoch != "-").map(|epoch| {
+                    u64::from_str(epoch).expect("Cannot parse the end epoch!")
+                });
+            dag_replay::export(
+                &args[2],
+                &args[3],
+                start_epoch,
+                end_epoch,
+                args.get(6).map(|dir| dir.as_str()),
+            );
+            return;
+        }
+        Some("replay") => {
+            assert!(args.len() >= 3, "Missing the DAG file!");
+            dag_replay::replay(&args[2]);
+            return;
+        }
+        _ => {}
+    }
     let mut input_file = "./seq.in";
     if args.len() >= 2 {
         input_file = &*args[1];
@@ -28,6 +28,8 @@ macro_rules! impl_db_encoding_as_rlp {
 impl_db_encoding_as_rlp!(H256);
 impl_db_encoding_as_rlp!(u64);
 impl_db_encoding_as_rlp!(TransactionIndex);
+impl_db_encoding_as_rlp!(StorageKeyPreimage);
+impl_db_encoding_as_rlp!(EpochStateDiff);
 
 impl DatabaseDecodable for BlockHeader {
     fn db_decode(bytes: &[u8]) -> Result<Self, DecoderError> {
@@ -45,5 +47,7 @@ impl DatabaseEncodable for BlockHeader {
 
 use cfx_bytes::Bytes;
 use cfx_types::H256;
-use primitives::{BlockHeader, TransactionIndex};
+use primitives::{
+    BlockHeader, EpochStateDiff, StorageKeyPreimage, TransactionIndex,
+};
 use rlp::*;
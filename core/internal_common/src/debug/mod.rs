@@ -49,6 +49,8 @@ pub struct ComputeEpochDebugRecord {
     // Storage operations.
     // op name, key, maybe_value
     pub state_ops: Vec<StateOp>,
+    // The preimages of the storage slot keys written by the epoch.
+    pub storage_key_preimages: Vec<(Vec<u8>, StorageKeyPreimage)>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -92,12 +94,13 @@ impl Default for ComputeEpochDebugRecord {
                 &Default::default(),
             ),
             state_ops: Default::default(),
+            storage_key_preimages: Default::default(),
         }
     }
 }
 
 use crate::StateRootWithAuxInfo;
 use cfx_types::{Address, H256, U256};
-use primitives::SignedTransaction;
+use primitives::{SignedTransaction, StorageKeyPreimage};
 use serde_derive::{Deserialize, Serialize};
 use std::{sync::Arc, vec::Vec};
@@ -313,6 +313,14 @@ impl CheckpointHashes {
     }
 }
 
+/// The last epoch notified to an execution plugin, stored in the database so
+/// that the plugin resumes from the next epoch after restart.
+#[derive(RlpEncodable, RlpDecodable, Clone, Debug, PartialEq)]
+pub struct ExecutionPluginCheckpoint {
+    pub epoch_number: u64,
+    pub pivot_hash: H256,
+}
+
 /// Verified roots of blamed headers stored on disk on light nodes.
 #[derive(Clone, Debug, RlpEncodable, RlpDecodable)]
 pub struct BlamedHeaderVerifiedRoots {
@@ -387,3 +395,4 @@ impl_db_encoding_as_rlp!(EpochExecutionContext);
 impl_db_encoding_as_rlp!(BlockRewardResult);
 impl_db_encoding_as_rlp!(BlamedHeaderVerifiedRoots);
 impl_db_encoding_as_rlp!(PosRewardInfo);
+impl_db_encoding_as_rlp!(ExecutionPluginCheckpoint);
@@ -3,12 +3,12 @@ use crate::{
         db_decode_list, db_encode_list, BlamedHeaderVerifiedRoots,
         BlockExecutionResultWithEpoch, BlockRewardResult, BlockTracesWithEpoch,
         CheckpointHashes, DataVersionTuple, EpochExecutionContext,
-        LocalBlockInfo, PosRewardInfo,
+        ExecutionPluginCheckpoint, LocalBlockInfo, PosRewardInfo,
     },
     db::{
         COL_BLAMED_HEADER_VERIFIED_ROOTS, COL_BLOCKS, COL_BLOCK_TRACES,
         COL_EPOCH_NUMBER, COL_HASH_BY_BLOCK_NUMBER, COL_MISC,
-        COL_REWARD_BY_POS_EPOCH, COL_TX_INDEX,
+        COL_REWARD_BY_POS_EPOCH, COL_STORAGE_KEY_PREIMAGE, COL_TX_INDEX,
     },
     pow::PowComputer,
     verification::VerificationConfig,
@@ -23,7 +23,10 @@ use cfx_storage::{
 use cfx_types::H256;
 use db::SystemDB;
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
-use primitives::{Block, BlockHeader, SignedTransaction, TransactionIndex};
+use primitives::{
+    Block, BlockHeader, EpochStateDiff, SignedTransaction, StorageKeyPreimage,
+    TransactionIndex,
+};
 use rlp::Rlp;
 use std::{collections::HashMap, fs, path::Path, sync::Arc};
 use strum::IntoEnumIterator;
@@ -37,8 +40,11 @@ const EPOCH_CONSENSUS_EXECUTION_INFO_SUFFIX_BYTE: u8 = 5;
 const EPOCH_EXECUTED_BLOCK_SET_SUFFIX_BYTE: u8 = 6;
 const EPOCH_SKIPPED_BLOCK_SET_SUFFIX_BYTE: u8 = 7;
 const BLOCK_REWARD_RESULT_SUFFIX_BYTE: u8 = 8;
+const EPOCH_STATE_DIFF_SUFFIX_BYTE: u8 = 9;
 const BLOCK_TERMINAL_KEY: &[u8] = b"block_terminals";
 const GC_PROGRESS_KEY: &[u8] = b"gc_progress";
+const EXECUTION_PLUGIN_CHECKPOINT_KEY_PREFIX: &[u8] =
+    b"execution_plugin_checkpoint_";
 
 #[derive(Clone, Copy, Hash, Ord, PartialOrd, Eq, PartialEq, EnumIter)]
 enum DBTable {
@@ -50,6 +56,7 @@ enum DBTable {
     BlockTraces,
     HashByBlockNumber,
     RewardByPosEpoch,
+    StorageKeyPreimage,
 }
 
 fn rocks_db_col(table: DBTable) -> u32 {
@@ -62,6 +69,7 @@ fn rocks_db_col(table: DBTable) -> u32 {
         DBTable::BlockTraces => COL_BLOCK_TRACES,
         DBTable::HashByBlockNumber => COL_HASH_BY_BLOCK_NUMBER,
         DBTable::RewardByPosEpoch => COL_REWARD_BY_POS_EPOCH,
+        DBTable::StorageKeyPreimage => COL_STORAGE_KEY_PREIMAGE,
     }
 }
 
@@ -75,6 +83,7 @@ fn sqlite_db_table(table: DBTable) -> String {
         DBTable::BlockTraces => "block_traces",
         DBTable::HashByBlockNumber => "hash_by_block_number",
         DBTable::RewardByPosEpoch => "reward_by_pos_epoch",
+        DBTable::StorageKeyPreimage => "storage_key_preimage",
     }
     .into()
 }
@@ -444,6 +453,26 @@ impl DBManager {
         );
     }
 
+    pub fn insert_epoch_state_diff_to_db(
+        &self, hash: &H256, diff: &EpochStateDiff,
+    ) {
+        self.insert_encodable_val(
+            DBTable::Blocks,
+            &epoch_state_diff_key(hash),
+            diff,
+        );
+    }
+
+    pub fn epoch_state_diff_from_db(
+        &self, hash: &H256,
+    ) -> Option<EpochStateDiff> {
+        self.load_decodable_val(DBTable::Blocks, &epoch_state_diff_key(hash))
+    }
+
+    pub fn remove_epoch_state_diff_from_db(&self, hash: &H256) {
+        self.remove_from_db(DBTable::Blocks, &epoch_state_diff_key(hash))
+    }
+
     pub fn insert_instance_id_to_db(&self, instance_id: u64) {
         self.insert_encodable_val(DBTable::Misc, b"instance", &instance_id);
     }
@@ -487,6 +516,25 @@ impl DBManager {
         self.load_decodable_val(DBTable::Misc, GC_PROGRESS_KEY)
     }
 
+    pub fn insert_execution_plugin_checkpoint_to_db(
+        &self, plugin_name: &str, checkpoint: &ExecutionPluginCheckpoint,
+    ) {
+        self.insert_encodable_val(
+            DBTable::Misc,
+            &execution_plugin_checkpoint_key(plugin_name),
+            checkpoint,
+        );
+    }
+
+    pub fn execution_plugin_checkpoint_from_db(
+        &self, plugin_name: &str,
+    ) -> Option<ExecutionPluginCheckpoint> {
+        self.load_decodable_val(
+            DBTable::Misc,
+            &execution_plugin_checkpoint_key(plugin_name),
+        )
+    }
+
     pub fn insert_pos_reward(
         &self, pos_epoch: u64, pos_reward: &PosRewardInfo,
     ) {
@@ -506,6 +554,18 @@ impl DBManager {
         )
     }
 
+    pub fn insert_storage_key_preimage_to_db(
+        &self, key: &[u8], preimage: &StorageKeyPreimage,
+    ) {
+        self.insert_encodable_val(DBTable::StorageKeyPreimage, key, preimage);
+    }
+
+    pub fn storage_key_preimage_from_db(
+        &self, key: &[u8],
+    ) -> Option<StorageKeyPreimage> {
+        self.load_decodable_val(DBTable::StorageKeyPreimage, key)
+    }
+
     /// The functions below are private utils used by the DBManager to access
     /// database
     fn insert_to_db(&self, table: DBTable, db_key: &[u8], value: Vec<u8>) {
@@ -576,6 +636,12 @@ fn append_suffix(h: &H256, suffix: u8) -> Vec<u8> {
     key
 }
 
+fn execution_plugin_checkpoint_key(plugin_name: &str) -> Vec<u8> {
+    let mut key = EXECUTION_PLUGIN_CHECKPOINT_KEY_PREFIX.to_vec();
+    key.extend_from_slice(plugin_name.as_bytes());
+    key
+}
+
 fn local_block_info_key(block_hash: &H256) -> Vec<u8> {
     append_suffix(block_hash, LOCAL_BLOCK_INFO_SUFFIX_BYTE)
 }
@@ -620,6 +686,10 @@ fn epoch_consensus_epoch_execution_commitment_key(hash: &H256) -> Vec<u8> {
     append_suffix(hash, EPOCH_CONSENSUS_EXECUTION_INFO_SUFFIX_BYTE)
 }
 
+fn epoch_state_diff_key(hash: &H256) -> Vec<u8> {
+    append_suffix(hash, EPOCH_STATE_DIFF_SUFFIX_BYTE)
+}
+
 impl MallocSizeOf for DBManager {
     fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
         // Here we only handle the case that all columns are stored within the
@@ -20,8 +20,8 @@ use parking_lot::{Mutex, RwLock, RwLockReadGuard, RwLockUpgradableReadGuard};
 use primitives::{
     block::CompactBlock,
     receipt::{BlockReceipts, TransactionOutcome},
-    Block, BlockHeader, EpochId, Receipt, SignedTransaction, TransactionIndex,
-    TransactionWithSignature, NULL_EPOCH,
+    Block, BlockHeader, EpochId, EpochStateDiff, Receipt, SignedTransaction,
+    StorageKeyPreimage, TransactionIndex, TransactionWithSignature, NULL_EPOCH,
 };
 use rlp::DecoderError;
 use std::{
@@ -126,6 +126,9 @@ pub struct BlockDataManager {
     invalid_block_set: RwLock<InvalidBlockSet>,
     cur_consensus_era_genesis_hash: RwLock<H256>,
     cur_consensus_era_stable_hash: RwLock<H256>,
+    /// The era genesis and its state root hash of the trusted checkpoint
+    /// that the node starts synchronization from.
+    trusted_checkpoint_state_root: RwLock<Option<(H256, H256)>>,
     instance_id: Mutex<u64>,
 
     config: DataManagerConfiguration,
@@ -223,6 +226,7 @@ impl BlockDataManager {
             ),
             cur_consensus_era_genesis_hash: RwLock::new(true_genesis.hash()),
             cur_consensus_era_stable_hash: RwLock::new(true_genesis.hash()),
+            trusted_checkpoint_state_root: RwLock::new(None),
             tx_data_manager,
             db_manager,
             pow,
@@ -1199,6 +1203,55 @@ impl BlockDataManager {
         self.db_manager.pos_reward_by_pos_epoch(pos_epoch)
     }
 
+    /// Persist the preimages of storage keys written during execution. The
+    /// preimage of a key never changes, so it is fine to overwrite the ones
+    /// recorded before.
+    pub fn insert_storage_key_preimages(
+        &self, preimages: &Vec<(Vec<u8>, StorageKeyPreimage)>,
+    ) {
+        for (key, preimage) in preimages {
+            self.db_manager
+                .insert_storage_key_preimage_to_db(key, preimage);
+        }
+    }
+
+    /// Return the (address, slot) which is encoded into the storage key
+    /// `key`, if it has been recorded.
+    pub fn storage_key_preimage(
+        &self, key: &[u8],
+    ) -> Option<StorageKeyPreimage> {
+        self.db_manager.storage_key_preimage_from_db(key)
+    }
+
+    /// Persist the state changes made by executing the epoch with pivot block
+    /// `epoch_hash`. A re-execution of the epoch overwrites the previous diff.
+    pub fn insert_epoch_state_diff(
+        &self, epoch_hash: &H256, diff: &EpochStateDiff,
+    ) {
+        self.db_manager
+            .insert_epoch_state_diff_to_db(epoch_hash, diff);
+    }
+
+    pub fn epoch_state_diff(
+        &self, epoch_hash: &H256,
+    ) -> Option<EpochStateDiff> {
+        self.db_manager.epoch_state_diff_from_db(epoch_hash)
+    }
+
+    pub fn insert_execution_plugin_checkpoint(
+        &self, plugin_name: &str, checkpoint: &ExecutionPluginCheckpoint,
+    ) {
+        self.db_manager
+            .insert_execution_plugin_checkpoint_to_db(plugin_name, checkpoint);
+    }
+
+    pub fn execution_plugin_checkpoint(
+        &self, plugin_name: &str,
+    ) -> Option<ExecutionPluginCheckpoint> {
+        self.db_manager
+            .execution_plugin_checkpoint_from_db(plugin_name)
+    }
+
     pub fn remove_epoch_execution_commitment(&self, block_hash: &H256) {
         self.epoch_execution_commitments.write().remove(block_hash);
     }
@@ -1538,6 +1591,26 @@ impl BlockDataManager {
         *stable_hash = next_era_hash.clone();
     }
 
+    /// Set the state root hash of the era genesis of the trusted checkpoint,
+    /// which should only be called with a verified checkpoint.
+    pub(crate) fn set_trusted_checkpoint_state_root(
+        &self, era_genesis: H256, state_root_hash: H256,
+    ) {
+        *self.trusted_checkpoint_state_root.write() =
+            Some((era_genesis, state_root_hash));
+    }
+
+    /// Return the state root hash of `epoch` if it is the era genesis of the
+    /// trusted checkpoint.
+    pub fn trusted_checkpoint_state_root(&self, epoch: &H256) -> Option<H256> {
+        match *self.trusted_checkpoint_state_root.read() {
+            Some((era_genesis, state_root_hash)) if era_genesis == *epoch => {
+                Some(state_root_hash)
+            }
+            _ => None,
+        }
+    }
+
     pub fn get_cur_consensus_era_genesis_hash(&self) -> H256 {
         self.cur_consensus_era_genesis_hash.read().clone()
     }
@@ -1762,7 +1835,10 @@ impl BlockDataManager {
         self.gc_epoch_with_defer(
             base_epoch,
             self.config.additional_maintained_trace_epoch_count,
-            |h| self.db_manager.remove_block_trace_from_db(h),
+            |h| {
+                self.db_manager.remove_block_trace_from_db(h);
+                self.db_manager.remove_epoch_state_diff_from_db(h);
+            },
         );
     }
 
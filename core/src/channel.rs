@@ -2,7 +2,7 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
-use crate::UniqueId;
+use crate::{sync::SyncPhaseChange, UniqueId};
 use cfx_types::H256;
 use parking_lot::RwLock;
 use std::{collections::BTreeMap, sync::Arc, time::Duration};
@@ -113,6 +113,7 @@ pub struct Notifications {
     pub new_block_hashes: Arc<Channel<H256>>,
     pub epochs_ordered: Arc<Channel<(u64, Vec<H256>)>>,
     pub blame_verification_results: Arc<Channel<(u64, Option<u64>)>>, /* <height, witness> */
+    pub sync_phase_changes: Arc<Channel<SyncPhaseChange>>,
 }
 
 impl Notifications {
@@ -123,6 +124,7 @@ impl Notifications {
             blame_verification_results: Arc::new(Channel::new(
                 "blame-verification-results",
             )),
+            sync_phase_changes: Arc::new(Channel::new("sync-phase-changes")),
         })
     }
 }
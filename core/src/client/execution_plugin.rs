@@ -0,0 +1,636 @@
+// Copyright 2021 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! In-process plugins notified of the execution results of each epoch on the
+//! pivot chain, e.g. an indexer running inside the node.
+//!
+//! Each plugin runs in its own thread. The last epoch notified to a plugin is
+//! persisted as its checkpoint, so the plugin resumes from the next epoch
+//! after restart. When the pivot chain is reorganized, including to a shorter
+//! one, the plugin is notified to revert to the fork point before the epochs
+//! of the new pivot chain.
+
+use crate::{
+    block_data_manager::{BlockDataManager, ExecutionPluginCheckpoint},
+    observer::trace::BlockExecTraces,
+    ConsensusGraph,
+};
+use cfx_types::H256;
+use parking_lot::Mutex;
+use primitives::{Block, BlockReceipts, EpochStateDiff};
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// The interval to check the newly executed epochs.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// The execution results of an epoch on the pivot chain.
+#[derive(Clone, Debug)]
+pub struct ExecutedEpoch {
+    pub epoch_number: u64,
+    pub pivot_hash: H256,
+    /// The blocks in the execution order, ending with the pivot block.
+    pub blocks: Vec<Arc<Block>>,
+    /// The receipts of `blocks`.
+    pub receipts: Vec<Arc<BlockReceipts>>,
+    /// The traces of `blocks`, or `None` if `executive_trace` is disabled.
+    pub traces: Option<Vec<BlockExecTraces>>,
+    /// `None` if the state diff of the epoch is not recorded.
+    pub state_diff: Option<EpochStateDiff>,
+}
+
+pub trait ExecutionPlugin: Send + Sync {
+    /// The unique name of the plugin, which is the key of its checkpoint.
+    fn name(&self) -> &str;
+
+    /// The first epoch to notify if the plugin has no checkpoint. By default,
+    /// the plugin starts from the latest executed epoch.
+    fn start_epoch(&self) -> Option<u64> { None }
+
+    /// Fires for each executed epoch in order. If it returns an error, the
+    /// checkpoint is not advanced and the epoch will be notified again.
+    fn on_epoch_executed(&self, epoch: &ExecutedEpoch) -> Result<(), String>;
+
+    /// Fires when the epochs after `revert_to` are no longer on the pivot
+    /// chain, where `pivot_hash` is the pivot block of epoch `revert_to`.
+    fn on_epochs_reverted(
+        &self, revert_to: u64, pivot_hash: &H256,
+    ) -> Result<(), String>;
+}
+
+/// Runs the registered execution plugins until it's dropped, which waits for
+/// the plugin threads to stop.
+pub struct ExecutionPluginManager {
+    chain: Arc<dyn ExecutedChain>,
+    plugin_names: Mutex<HashSet<String>>,
+    workers: Mutex<Vec<JoinHandle<()>>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl ExecutionPluginManager {
+    pub fn new(consensus: Arc<ConsensusGraph>) -> Self {
+        Self::with_chain(Arc::new(ConsensusChain { consensus }))
+    }
+
+    fn with_chain(chain: Arc<dyn ExecutedChain>) -> Self {
+        ExecutionPluginManager {
+            chain,
+            plugin_names: Default::default(),
+            workers: Default::default(),
+            stopped: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Start notifying `plugin` in a new thread.
+    pub fn register(
+        &self, plugin: Arc<dyn ExecutionPlugin>,
+    ) -> Result<(), String> {
+        let name = plugin.name().to_string();
+        if !self.plugin_names.lock().insert(name.clone()) {
+            return Err(format!("execution plugin {} exists", name));
+        }
+        let worker = ExecutionPluginWorker {
+            plugin,
+            chain: self.chain.clone(),
+            stopped: self.stopped.clone(),
+        };
+        let handle = thread::Builder::new()
+            .name(format!("execution_plugin_{}", name))
+            .spawn(move || worker.run())
+            .map_err(|e| format!("failed to start plugin {}: {}", name, e))?;
+        self.workers.lock().push(handle);
+        info!("Execution plugin {} registered", name);
+        Ok(())
+    }
+}
+
+impl Drop for ExecutionPluginManager {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        for handle in self.workers.lock().drain(..) {
+            if handle.join().is_err() {
+                warn!("Execution plugin thread panicked");
+            }
+        }
+    }
+}
+
+/// The executed pivot chain notified to the plugins.
+trait ExecutedChain: Send + Sync {
+    fn best_executed_epoch_number(&self) -> u64;
+
+    /// The pivot block of `epoch_number`, or `None` if it is beyond the
+    /// pivot chain.
+    fn pivot_hash(&self, epoch_number: u64) -> Option<H256>;
+
+    fn parent_hash(&self, hash: &H256) -> Result<H256, String>;
+
+    /// The blocks of `epoch_number` in the execution order.
+    fn epoch_hashes(&self, epoch_number: u64) -> Result<Vec<H256>, String>;
+
+    fn epoch_executed(&self, pivot_hash: &H256) -> bool;
+
+    fn executed_epoch(
+        &self, epoch_number: u64, pivot_hash: H256, epoch_hashes: Vec<H256>,
+    ) -> Result<ExecutedEpoch, String>;
+
+    fn checkpoint(
+        &self, plugin_name: &str,
+    ) -> Option<ExecutionPluginCheckpoint>;
+
+    fn save_checkpoint(
+        &self, plugin_name: &str, checkpoint: &ExecutionPluginCheckpoint,
+    );
+}
+
+struct ConsensusChain {
+    consensus: Arc<ConsensusGraph>,
+}
+
+impl ConsensusChain {
+    fn data_man(&self) -> &BlockDataManager { &self.consensus.data_man }
+}
+
+impl ExecutedChain for ConsensusChain {
+    fn best_executed_epoch_number(&self) -> u64 {
+        self.consensus.best_executed_state_epoch_number()
+    }
+
+    fn pivot_hash(&self, epoch_number: u64) -> Option<H256> {
+        self.consensus
+            .inner
+            .read_recursive()
+            .get_pivot_hash_from_epoch_number(epoch_number)
+            .ok()
+    }
+
+    fn parent_hash(&self, hash: &H256) -> Result<H256, String> {
+        Ok(*self
+            .data_man()
+            .block_header_by_hash(hash)
+            .ok_or_else(|| format!("block {:?} missing", hash))?
+            .parent_hash())
+    }
+
+    fn epoch_hashes(&self, epoch_number: u64) -> Result<Vec<H256>, String> {
+        self.consensus
+            .inner
+            .read_recursive()
+            .block_hashes_by_epoch(epoch_number)
+    }
+
+    fn epoch_executed(&self, pivot_hash: &H256) -> bool {
+        self.data_man().epoch_executed(pivot_hash)
+    }
+
+    fn executed_epoch(
+        &self, epoch_number: u64, pivot_hash: H256, epoch_hashes: Vec<H256>,
+    ) -> Result<ExecutedEpoch, String> {
+        let data_man = self.data_man();
+        let mut blocks = Vec::with_capacity(epoch_hashes.len());
+        let mut receipts = Vec::with_capacity(epoch_hashes.len());
+        for hash in &epoch_hashes {
+            blocks.push(
+                data_man
+                    .block_by_hash(hash, false /* update_cache */)
+                    .ok_or_else(|| format!("block {:?} missing", hash))?,
+            );
+            receipts.push(
+                data_man
+                    .block_execution_result_by_hash_with_epoch(
+                        hash,
+                        &pivot_hash,
+                        false, /* update_pivot_assumption */
+                        false, /* update_cache */
+                    )
+                    .ok_or_else(|| format!("receipts of {:?} missing", hash))?
+                    .block_receipts,
+            );
+        }
+        let traces = epoch_hashes
+            .iter()
+            .map(|hash| {
+                data_man.block_traces_by_hash_with_epoch(
+                    hash,
+                    &pivot_hash,
+                    false, /* update_pivot_assumption */
+                    false, /* update_cache */
+                )
+            })
+            .collect();
+        Ok(ExecutedEpoch {
+            epoch_number,
+            pivot_hash,
+            blocks,
+            receipts,
+            traces,
+            state_diff: data_man.epoch_state_diff(&pivot_hash),
+        })
+    }
+
+    fn checkpoint(
+        &self, plugin_name: &str,
+    ) -> Option<ExecutionPluginCheckpoint> {
+        self.data_man().execution_plugin_checkpoint(plugin_name)
+    }
+
+    fn save_checkpoint(
+        &self, plugin_name: &str, checkpoint: &ExecutionPluginCheckpoint,
+    ) {
+        self.data_man()
+            .insert_execution_plugin_checkpoint(plugin_name, checkpoint);
+    }
+}
+
+struct ExecutionPluginWorker {
+    plugin: Arc<dyn ExecutionPlugin>,
+    chain: Arc<dyn ExecutedChain>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl ExecutionPluginWorker {
+    fn run(self) {
+        let mut checkpoint = self.chain.checkpoint(self.plugin.name());
+        let mut initialized = checkpoint.is_some();
+        while !self.stopped.load(Ordering::SeqCst) {
+            let result = if initialized {
+                self.notify_executed_epochs(&mut checkpoint)
+            } else {
+                self.initial_checkpoint().map(|initial| {
+                    checkpoint = initial;
+                    initialized = true;
+                })
+            };
+            if let Err(e) = result {
+                warn!("Execution plugin {}: {}", self.plugin.name(), e);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        debug!("Execution plugin {} stopped", self.plugin.name());
+    }
+
+    /// Return the checkpoint before the start epoch of a new plugin, which
+    /// is `None` if the plugin starts from the genesis.
+    fn initial_checkpoint(
+        &self,
+    ) -> Result<Option<ExecutionPluginCheckpoint>, String> {
+        let start_epoch = self
+            .plugin
+            .start_epoch()
+            .unwrap_or_else(|| self.chain.best_executed_epoch_number());
+        if start_epoch == 0 {
+            return Ok(None);
+        }
+        let pivot_hash =
+            self.chain.pivot_hash(start_epoch - 1).ok_or_else(|| {
+                format!("epoch {} is not on the pivot chain", start_epoch - 1)
+            })?;
+        Ok(Some(ExecutionPluginCheckpoint {
+            epoch_number: start_epoch - 1,
+            pivot_hash,
+        }))
+    }
+
+    /// Notify the plugin of the epochs executed after `checkpoint`, and
+    /// update `checkpoint` accordingly.
+    fn notify_executed_epochs(
+        &self, checkpoint: &mut Option<ExecutionPluginCheckpoint>,
+    ) -> Result<(), String> {
+        while !self.stopped.load(Ordering::SeqCst) {
+            // Check the checkpoint first, so a reorganization to a shorter
+            // pivot chain is reverted without waiting for new epochs.
+            if let Some(last) = checkpoint.clone() {
+                if self.chain.pivot_hash(last.epoch_number)
+                    != Some(last.pivot_hash)
+                {
+                    let fork = self.fork_point(&last)?;
+                    debug!(
+                        "Execution plugin {} reverts to epoch {}",
+                        self.plugin.name(),
+                        fork.epoch_number
+                    );
+                    self.plugin.on_epochs_reverted(
+                        fork.epoch_number,
+                        &fork.pivot_hash,
+                    )?;
+                    self.save_checkpoint(checkpoint, fork);
+                    continue;
+                }
+            }
+
+            let epoch_number =
+                checkpoint.as_ref().map_or(0, |c| c.epoch_number + 1);
+            if epoch_number > self.chain.best_executed_epoch_number() {
+                break;
+            }
+            let epoch_hashes = self.chain.epoch_hashes(epoch_number)?;
+            let pivot_hash =
+                *epoch_hashes.last().expect("epoch should not be empty");
+            // The pivot chain is reorganized after the check above, which
+            // will be reverted in the next poll.
+            if let Some(last) = checkpoint.as_ref() {
+                if self.chain.parent_hash(&pivot_hash)? != last.pivot_hash {
+                    break;
+                }
+            }
+
+            // The pivot chain may be reorganized after reading the epoch, so
+            // wait for its execution under this pivot block.
+            if !self.chain.epoch_executed(&pivot_hash) {
+                break;
+            }
+            let epoch = self.chain.executed_epoch(
+                epoch_number,
+                pivot_hash,
+                epoch_hashes,
+            )?;
+            self.plugin.on_epoch_executed(&epoch)?;
+            self.save_checkpoint(
+                checkpoint,
+                ExecutionPluginCheckpoint {
+                    epoch_number,
+                    pivot_hash,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Return the latest pivot block which is an ancestor of the pivot block
+    /// in `checkpoint` and still on the pivot chain.
+    fn fork_point(
+        &self, checkpoint: &ExecutionPluginCheckpoint,
+    ) -> Result<ExecutionPluginCheckpoint, String> {
+        let mut epoch_number = checkpoint.epoch_number;
+        let mut pivot_hash = checkpoint.pivot_hash;
+        while self.chain.pivot_hash(epoch_number) != Some(pivot_hash) {
+            if epoch_number == 0 {
+                return Err("genesis is not on the pivot chain".into());
+            }
+            pivot_hash = self.chain.parent_hash(&pivot_hash)?;
+            epoch_number -= 1;
+        }
+        Ok(ExecutionPluginCheckpoint {
+            epoch_number,
+            pivot_hash,
+        })
+    }
+
+    fn save_checkpoint(
+        &self, checkpoint: &mut Option<ExecutionPluginCheckpoint>,
+        new_checkpoint: ExecutionPluginCheckpoint,
+    )
+    {
+        self.chain
+            .save_checkpoint(self.plugin.name(), &new_checkpoint);
+        *checkpoint = Some(new_checkpoint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::HashMap, time::Instant};
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Event {
+        Executed(u64, H256),
+        Reverted(u64, H256),
+    }
+
+    #[derive(Default)]
+    struct FakePlugin {
+        events: Mutex<Vec<Event>>,
+        /// Fail the next notification of the epoch.
+        fail_epoch: Mutex<Option<u64>>,
+    }
+
+    impl ExecutionPlugin for FakePlugin {
+        fn name(&self) -> &str { "fake" }
+
+        fn start_epoch(&self) -> Option<u64> { Some(0) }
+
+        fn on_epoch_executed(
+            &self, epoch: &ExecutedEpoch,
+        ) -> Result<(), String> {
+            let mut fail_epoch = self.fail_epoch.lock();
+            if *fail_epoch == Some(epoch.epoch_number) {
+                *fail_epoch = None;
+                return Err("failed".into());
+            }
+            self.events
+                .lock()
+                .push(Event::Executed(epoch.epoch_number, epoch.pivot_hash));
+            Ok(())
+        }
+
+        fn on_epochs_reverted(
+            &self, revert_to: u64, pivot_hash: &H256,
+        ) -> Result<(), String> {
+            self.events
+                .lock()
+                .push(Event::Reverted(revert_to, *pivot_hash));
+            Ok(())
+        }
+    }
+
+    /// A pivot chain where each epoch only has the pivot block.
+    struct FakeChain {
+        pivot_chain: Mutex<Vec<H256>>,
+        parents: Mutex<HashMap<H256, H256>>,
+        best_executed: Mutex<u64>,
+        checkpoints: Mutex<HashMap<String, ExecutionPluginCheckpoint>>,
+    }
+
+    impl FakeChain {
+        fn new() -> Self {
+            FakeChain {
+                pivot_chain: Mutex::new(vec![H256::random()]),
+                parents: Default::default(),
+                best_executed: Mutex::new(0),
+                checkpoints: Default::default(),
+            }
+        }
+
+        /// Append `n` executed epochs to the pivot chain after `epoch`.
+        fn fork(&self, epoch: u64, n: usize) {
+            let mut pivot_chain = self.pivot_chain.lock();
+            pivot_chain.truncate(epoch as usize + 1);
+            for _ in 0..n {
+                let hash = H256::random();
+                self.parents
+                    .lock()
+                    .insert(hash, *pivot_chain.last().unwrap());
+                pivot_chain.push(hash);
+            }
+            *self.best_executed.lock() = pivot_chain.len() as u64 - 1;
+        }
+
+        fn extend(&self, n: usize) {
+            let tip = self.pivot_chain.lock().len() as u64 - 1;
+            self.fork(tip, n);
+        }
+
+        fn hash(&self, epoch: u64) -> H256 {
+            self.pivot_chain.lock()[epoch as usize]
+        }
+    }
+
+    impl ExecutedChain for FakeChain {
+        fn best_executed_epoch_number(&self) -> u64 {
+            *self.best_executed.lock()
+        }
+
+        fn pivot_hash(&self, epoch_number: u64) -> Option<H256> {
+            self.pivot_chain.lock().get(epoch_number as usize).cloned()
+        }
+
+        fn parent_hash(&self, hash: &H256) -> Result<H256, String> {
+            self.parents
+                .lock()
+                .get(hash)
+                .cloned()
+                .ok_or_else(|| format!("block {:?} missing", hash))
+        }
+
+        fn epoch_hashes(&self, epoch_number: u64) -> Result<Vec<H256>, String> {
+            self.pivot_hash(epoch_number)
+                .map(|hash| vec![hash])
+                .ok_or_else(|| format!("epoch {} missing", epoch_number))
+        }
+
+        fn epoch_executed(&self, _pivot_hash: &H256) -> bool { true }
+
+        fn executed_epoch(
+            &self, epoch_number: u64, pivot_hash: H256,
+            _epoch_hashes: Vec<H256>,
+        ) -> Result<ExecutedEpoch, String>
+        {
+            Ok(ExecutedEpoch {
+                epoch_number,
+                pivot_hash,
+                blocks: vec![],
+                receipts: vec![],
+                traces: None,
+                state_diff: None,
+            })
+        }
+
+        fn checkpoint(
+            &self, plugin_name: &str,
+        ) -> Option<ExecutionPluginCheckpoint> {
+            self.checkpoints.lock().get(plugin_name).cloned()
+        }
+
+        fn save_checkpoint(
+            &self, plugin_name: &str, checkpoint: &ExecutionPluginCheckpoint,
+        ) {
+            self.checkpoints
+                .lock()
+                .insert(plugin_name.into(), checkpoint.clone());
+        }
+    }
+
+    fn worker(
+        plugin: &Arc<FakePlugin>, chain: &Arc<FakeChain>,
+    ) -> ExecutionPluginWorker {
+        ExecutionPluginWorker {
+            plugin: plugin.clone(),
+            chain: chain.clone(),
+            stopped: Default::default(),
+        }
+    }
+
+    fn executed(
+        chain: &FakeChain, epochs: impl Iterator<Item = u64>,
+    ) -> Vec<Event> {
+        epochs
+            .map(|epoch| Event::Executed(epoch, chain.hash(epoch)))
+            .collect()
+    }
+
+    #[test]
+    fn test_notify_in_order() {
+        let plugin = Arc::new(FakePlugin::default());
+        let chain = Arc::new(FakeChain::new());
+        let worker = worker(&plugin, &chain);
+        chain.extend(3);
+
+        let mut checkpoint = None;
+        worker.notify_executed_epochs(&mut checkpoint).unwrap();
+        assert_eq!(*plugin.events.lock(), executed(&chain, 0..4));
+
+        // The epoch is notified again after a failure.
+        chain.extend(2);
+        *plugin.fail_epoch.lock() = Some(5);
+        assert!(worker.notify_executed_epochs(&mut checkpoint).is_err());
+        assert_eq!(checkpoint.as_ref().unwrap().epoch_number, 4);
+        worker.notify_executed_epochs(&mut checkpoint).unwrap();
+        assert_eq!(*plugin.events.lock(), executed(&chain, 0..6));
+        assert_eq!(chain.checkpoint("fake"), checkpoint);
+    }
+
+    #[test]
+    fn test_revert() {
+        let plugin = Arc::new(FakePlugin::default());
+        let chain = Arc::new(FakeChain::new());
+        let worker = worker(&plugin, &chain);
+        chain.extend(5);
+        let mut checkpoint = None;
+        worker.notify_executed_epochs(&mut checkpoint).unwrap();
+        plugin.events.lock().clear();
+
+        // A longer pivot chain.
+        chain.fork(3, 4);
+        worker.notify_executed_epochs(&mut checkpoint).unwrap();
+        let mut expected = vec![Event::Reverted(3, chain.hash(3))];
+        expected.extend(executed(&chain, 4..8));
+        assert_eq!(*plugin.events.lock(), expected);
+        plugin.events.lock().clear();
+
+        // A shorter pivot chain without new epochs to notify.
+        chain.fork(2, 0);
+        worker.notify_executed_epochs(&mut checkpoint).unwrap();
+        assert_eq!(
+            *plugin.events.lock(),
+            vec![Event::Reverted(2, chain.hash(2))]
+        );
+        assert_eq!(checkpoint.unwrap().pivot_hash, chain.hash(2));
+    }
+
+    #[test]
+    fn test_resume_from_checkpoint() {
+        let plugin = Arc::new(FakePlugin::default());
+        let chain = Arc::new(FakeChain::new());
+        chain.extend(5);
+        chain.save_checkpoint(
+            "fake",
+            &ExecutionPluginCheckpoint {
+                epoch_number: 3,
+                pivot_hash: chain.hash(3),
+            },
+        );
+
+        let manager = ExecutionPluginManager::with_chain(chain.clone());
+        manager.register(plugin.clone()).unwrap();
+        assert!(manager.register(plugin.clone()).is_err());
+        let expected = executed(&chain, 4..6);
+        let start = Instant::now();
+        while *plugin.events.lock() != expected {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            thread::sleep(POLL_INTERVAL);
+        }
+
+        // The worker thread is joined.
+        drop(manager);
+        assert_eq!(Arc::strong_count(&plugin), 1);
+    }
+}
@@ -17,3 +17,8 @@
 //! Blockchain database client.
 
 mod chain_notify;
+mod execution_plugin;
+
+pub use self::execution_plugin::{
+    ExecutedEpoch, ExecutionPlugin, ExecutionPluginManager,
+};
@@ -114,6 +114,19 @@ impl ConfirmationMeter {
         }
     }
 
+    /// Return the latest epoch whose confirmation risk, as well as the risks of
+    /// all epochs before it, is not higher than `risk_threshold`.
+    pub fn get_epoch_num_with_risk_below(&self, risk_threshold: f64) -> u64 {
+        let inner = self.inner.read();
+        let finality = &inner.finality_manager;
+        let count = finality
+            .risks_less_than
+            .iter()
+            .take_while(|risk| **risk <= risk_threshold)
+            .count() as u64;
+        (finality.lowest_epoch_num + count).saturating_sub(1)
+    }
+
     /// Query the confirmation hash of a specific block.
     pub fn confirmation_risk_by_hash(
         &self, g_inner: &ConsensusGraphInner, hash: H256,
@@ -376,3 +389,60 @@ impl ConfirmationMeter {
         adaptive_risk > CONFIRMATION_METER_MAXIMUM_ADAPTIVE_RISK
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meter_with_risks(
+        lowest_epoch_num: u64, risks: &[f64],
+    ) -> ConfirmationMeter {
+        let meter = ConfirmationMeter::new();
+        {
+            let mut inner = meter.inner.write();
+            inner.finality_manager.lowest_epoch_num = lowest_epoch_num;
+            inner.finality_manager.risks_less_than =
+                risks.iter().cloned().collect();
+        }
+        meter
+    }
+
+    #[test]
+    fn test_epoch_num_with_risk_below() {
+        let meter = meter_with_risks(10, &[1e-8, 1e-6, 1e-4, 1e-2]);
+        assert_eq!(meter.get_epoch_num_with_risk_below(1e-8), 10);
+        assert_eq!(meter.get_epoch_num_with_risk_below(1e-5), 11);
+        assert_eq!(meter.get_epoch_num_with_risk_below(1e-4), 12);
+        assert_eq!(meter.get_epoch_num_with_risk_below(1.0), 13);
+        // No maintained epoch is safe enough, fall back to the latest
+        // confirmed epoch.
+        assert_eq!(meter.get_epoch_num_with_risk_below(1e-9), 9);
+        assert_eq!(
+            meter.get_epoch_num_with_risk_below(1e-9),
+            meter.get_confirmed_epoch_num()
+        );
+    }
+
+    #[test]
+    fn test_epoch_num_with_risk_below_stops_at_first_risky_epoch() {
+        // A later epoch with a lower risk does not make the risky epoch
+        // before it safe.
+        let meter = meter_with_risks(10, &[1e-8, 1e-2, 1e-8]);
+        assert_eq!(meter.get_epoch_num_with_risk_below(1e-6), 10);
+    }
+
+    #[test]
+    fn test_epoch_num_with_risk_below_without_risks() {
+        let meter = meter_with_risks(0, &[]);
+        assert_eq!(meter.get_epoch_num_with_risk_below(1.0), 0);
+
+        let meter = meter_with_risks(5, &[]);
+        assert_eq!(meter.get_epoch_num_with_risk_below(1.0), 4);
+
+        // The risks are shifted out when a new checkpoint is made.
+        let meter = meter_with_risks(10, &[1e-8, 1e-6, 1e-4]);
+        meter.reset_for_checkpoint(0, 12);
+        assert_eq!(meter.get_epoch_num_with_risk_below(1e-5), 11);
+        assert_eq!(meter.get_epoch_num_with_risk_below(1e-4), 12);
+    }
+}
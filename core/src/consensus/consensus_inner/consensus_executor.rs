@@ -37,8 +37,8 @@ use metrics::{register_meter_with_group, Meter, MeterTimer};
 use primitives::{
     compute_block_number,
     receipt::{BlockReceipts, Receipt, TransactionOutcome},
-    Action, Block, BlockHeaderBuilder, BlockNumber, EpochId, SignedTransaction,
-    TransactionIndex, MERKLE_NULL_NODE,
+    Action, Block, BlockHeaderBuilder, BlockNumber, EpochId, EpochStateDiff,
+    SignedTransaction, TransactionIndex, MERKLE_NULL_NODE,
 };
 
 use crate::{
@@ -57,7 +57,7 @@ use crate::{
             build_bloom_and_recover_phantom, decode_register_info,
         },
         revert_reason_decode, EstimateRequest, ExecutionError,
-        ExecutionOutcome, Executive, TransactOptions,
+        ExecutionOutcome, Executive, ParallelExecutor, TransactOptions,
     },
     machine::Machine,
     observer::trace::{ExecTrace, TransactionExecTraces},
@@ -67,7 +67,7 @@ use crate::{
         prefetcher::{
             prefetch_accounts, ExecutionStatePrefetcher, PrefetchTaskHandle,
         },
-        State,
+        DevStateOverride, State,
     },
     verification::{
         compute_receipts_root, VerificationConfig, VerifyTxLocalMode,
@@ -824,6 +824,15 @@ pub struct ConsensusExecutionHandler {
     machine: Arc<Machine>,
     pos_verifier: Arc<PosVerifier>,
     execution_state_prefetcher: Option<Arc<ExecutionStatePrefetcher>>,
+    /// Executes the transactions in a block in parallel if
+    /// `parallel_execution_threads` is configured.
+    parallel_executor: Option<ParallelExecutor>,
+    /// The state overrides queued by the dev mode RPCs, which are applied
+    /// before the transactions of the next computed epoch.
+    pending_dev_state_overrides: Mutex<Vec<DevStateOverride>>,
+    /// The state overrides applied to each epoch, kept to recompute the
+    /// states of the epoch.
+    applied_dev_state_overrides: Mutex<HashMap<EpochId, Vec<DevStateOverride>>>,
 }
 
 impl ConsensusExecutionHandler {
@@ -834,6 +843,17 @@ impl ConsensusExecutionHandler {
         pos_verifier: Arc<PosVerifier>,
     ) -> Self
     {
+        let parallel_executor = if config.parallel_execution_threads > 0 {
+            Some(
+                ParallelExecutor::new(config.parallel_execution_threads)
+                    .expect(
+                        // Do not accept error at starting up.
+                        &concat!(file!(), ":", line!(), ":", column!()),
+                    ),
+            )
+        } else {
+            None
+        };
         ConsensusExecutionHandler {
             tx_pool,
             data_man,
@@ -856,9 +876,55 @@ impl ConsensusExecutionHandler {
             } else {
                 None
             },
+            parallel_executor,
+            pending_dev_state_overrides: Default::default(),
+            applied_dev_state_overrides: Default::default(),
         }
     }
 
+    /// Queue a state override to be applied before the transactions of the
+    /// next computed epoch. This is only used by the dev mode RPCs.
+    pub fn queue_dev_state_override(&self, state_override: DevStateOverride) {
+        self.pending_dev_state_overrides.lock().push(state_override);
+    }
+
+    /// Apply the state overrides of epoch `epoch_id`. If `take_pending` is
+    /// `true`, the pending overrides are assigned to the epoch first.
+    fn apply_dev_state_overrides(
+        &self, epoch_id: &EpochId, state: &mut State, start_block_number: u64,
+        take_pending: bool,
+    ) -> DbResult<()>
+    {
+        let state_overrides = {
+            let mut applied = self.applied_dev_state_overrides.lock();
+            if take_pending && !applied.contains_key(epoch_id) {
+                let pending = std::mem::take(
+                    &mut *self.pending_dev_state_overrides.lock(),
+                );
+                if !pending.is_empty() {
+                    applied.insert(*epoch_id, pending);
+                }
+            }
+            match applied.get(epoch_id) {
+                Some(state_overrides) => state_overrides.clone(),
+                None => return Ok(()),
+            }
+        };
+        let spec = self.machine.spec(start_block_number);
+        for state_override in &state_overrides {
+            debug!(
+                "Apply dev state override {:?} in epoch {:?}",
+                state_override, epoch_id
+            );
+            state.apply_dev_state_override(
+                state_override,
+                spec.account_start_nonce,
+                spec.contract_start_nonce,
+            )?;
+        }
+        Ok(())
+    }
+
     /// Always return `true` for now
     fn handle_execution_work(&self, task: ExecutionTask) -> bool {
         debug!("Receive execution task: {:?}", task);
@@ -1040,7 +1106,7 @@ impl ConsensusExecutionHandler {
             epoch_blocks.len(),
         );
 
-        let mut state = State::new(StateDb::new(
+        let mut state_db = StateDb::new(
             self.data_man
                 .storage_manager
                 .get_state_for_next_epoch(StateIndex::new_for_next_epoch(
@@ -1059,8 +1125,22 @@ impl ConsensusExecutionHandler {
                 .expect("No db error")
                 // Unwrapping is safe because the state exists.
                 .expect("State exists"),
-        ))
-        .expect("Failed to initialize state");
+        );
+        if self.config.record_storage_key_preimages {
+            state_db.enable_storage_key_preimage_recording();
+        }
+        if self.config.persist_epoch_state_diff {
+            state_db.enable_state_change_recording();
+        }
+        let mut state =
+            State::new(state_db).expect("Failed to initialize state");
+        self.apply_dev_state_overrides(
+            epoch_hash,
+            &mut state,
+            start_block_number,
+            true, /* take_pending */
+        )
+        .expect("Can not handle db error in consensus, crashing.");
 
         let epoch_receipts = self
             .process_epoch_transactions(
@@ -1165,6 +1245,20 @@ impl ConsensusExecutionHandler {
                 .expect(&concat!(file!(), ":", line!(), ":", column!()));
         };
 
+        if self.config.record_storage_key_preimages {
+            self.data_man.insert_storage_key_preimages(
+                &state.take_storage_key_preimages(),
+            );
+        }
+        if self.config.persist_epoch_state_diff {
+            self.data_man.insert_epoch_state_diff(
+                &pivot_block.hash(),
+                &EpochStateDiff {
+                    changes: state.take_state_changes(),
+                },
+            );
+        }
+
         self.data_man.insert_epoch_execution_commitment(
             pivot_block.hash(),
             state_root.clone(),
@@ -1295,6 +1389,22 @@ impl ConsensusExecutionHandler {
             last_block_hash = block.hash();
             let mut block_traces: Vec<TransactionExecTraces> =
                 Default::default();
+            let mut parallel_outcomes = match &self.parallel_executor {
+                Some(executor) => Some(
+                    executor
+                        .execute_transactions(
+                            state,
+                            || self.new_state_db_for_epoch(pivot_block),
+                            &env,
+                            self.machine.as_ref(),
+                            &spec,
+                            &block.transactions,
+                            self.config.executive_trace,
+                        )?
+                        .into_iter(),
+                ),
+                None => None,
+            };
             for (idx, transaction) in block.transactions.iter().enumerate() {
                 let tx_outcome_status;
                 let mut transaction_logs = Vec::new();
@@ -1306,9 +1416,18 @@ impl ConsensusExecutionHandler {
                 } else {
                     TransactOptions::exec_with_no_tracing()
                 };
-                let r =
-                    Executive::new(state, &env, self.machine.as_ref(), &spec)
-                        .transact(transaction, options)?;
+                let r = match &mut parallel_outcomes {
+                    Some(outcomes) => {
+                        outcomes.next().expect("one outcome per transaction")
+                    }
+                    None => Executive::new(
+                        state,
+                        &env,
+                        self.machine.as_ref(),
+                        &spec,
+                    )
+                    .transact(transaction, options)?,
+                };
 
                 let gas_fee;
                 let mut gas_sponsor_paid = false;
@@ -1551,6 +1670,30 @@ impl ConsensusExecutionHandler {
         Ok(epoch_receipts)
     }
 
+    /// Create a new state db to execute the epoch of `pivot_block`, which is
+    /// used to fork the state for the parallel execution.
+    fn new_state_db_for_epoch(&self, pivot_block: &Block) -> DbResult<StateDb> {
+        let parent_hash = pivot_block.block_header.parent_hash();
+        let storage = self
+            .data_man
+            .storage_manager
+            .get_state_for_next_epoch(StateIndex::new_for_next_epoch(
+                parent_hash,
+                &self
+                    .data_man
+                    .get_epoch_execution_commitment(parent_hash)
+                    // Unwrapping is safe because the state is being executed.
+                    .unwrap()
+                    .state_root_with_aux_info,
+                pivot_block.block_header.height() - 1,
+                self.data_man.get_snapshot_epoch_count(),
+            ))?
+            .ok_or_else(|| {
+                DbErrorKind::Msg(format!("state of {:?} missing", parent_hash))
+            })?;
+        Ok(StateDb::new(storage))
+    }
+
     fn compute_block_base_reward(
         &self, past_block_count: u64, pivot_height: u64,
     ) -> U512 {
@@ -1902,6 +2045,12 @@ impl ConsensusExecutionHandler {
                 // Unwrapping is safe because the state exists.
                 .unwrap(),
         ))?;
+        self.apply_dev_state_overrides(
+            pivot_hash,
+            &mut state,
+            start_block_number,
+            false, /* take_pending */
+        )?;
         self.process_epoch_transactions(
             *pivot_hash,
             &mut state,
@@ -2026,4 +2175,9 @@ impl ConsensusExecutionHandler {
 
 pub struct ConsensusExecutionConfiguration {
     pub executive_trace: bool,
+    pub record_storage_key_preimages: bool,
+    pub persist_epoch_state_diff: bool,
+    /// The number of threads to execute the transactions in a block in
+    /// parallel, or 0 to execute them sequentially.
+    pub parallel_execution_threads: usize,
 }
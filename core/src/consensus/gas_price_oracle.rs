@@ -0,0 +1,202 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Gas price suggestions based on the transactions in the recent blocks.
+
+use cfx_parameters::rpc::{
+    EVM_GAS_PRICE_BLOCK_SAMPLE_SIZE, EVM_GAS_PRICE_TRANSACTION_SAMPLE_SIZE,
+    GAS_PRICE_BLOCK_SAMPLE_SIZE, GAS_PRICE_DEFAULT_VALUE,
+    GAS_PRICE_TRANSACTION_SAMPLE_SIZE,
+};
+use cfx_types::{Space, U256};
+use std::cmp::max;
+
+#[derive(Clone, Debug)]
+pub struct GasPriceOracleConfig {
+    /// The maximal number of recent blocks sampled for the native space.
+    pub block_sample_size: usize,
+    /// The maximal number of transactions sampled for the native space.
+    pub transaction_sample_size: usize,
+    /// The maximal number of recent blocks sampled for the Ethereum space.
+    pub evm_block_sample_size: usize,
+    /// The maximal number of transactions sampled for the Ethereum space.
+    pub evm_transaction_sample_size: usize,
+    /// The percentiles of the sampled gas prices suggested as the slow,
+    /// standard and fast gas prices.
+    pub slow_percentile: f64,
+    pub standard_percentile: f64,
+    pub fast_percentile: f64,
+}
+
+impl Default for GasPriceOracleConfig {
+    fn default() -> Self {
+        GasPriceOracleConfig {
+            block_sample_size: GAS_PRICE_BLOCK_SAMPLE_SIZE,
+            transaction_sample_size: GAS_PRICE_TRANSACTION_SAMPLE_SIZE,
+            evm_block_sample_size: EVM_GAS_PRICE_BLOCK_SAMPLE_SIZE,
+            evm_transaction_sample_size: EVM_GAS_PRICE_TRANSACTION_SAMPLE_SIZE,
+            slow_percentile: 12.5,
+            standard_percentile: 50.0,
+            fast_percentile: 90.0,
+        }
+    }
+}
+
+impl GasPriceOracleConfig {
+    /// The maximal numbers of the blocks and the transactions sampled for
+    /// `space`.
+    pub fn sample_size(&self, space: Space) -> (usize, usize) {
+        match space {
+            Space::Native => {
+                (self.block_sample_size, self.transaction_sample_size)
+            }
+            Space::Ethereum => {
+                (self.evm_block_sample_size, self.evm_transaction_sample_size)
+            }
+        }
+    }
+}
+
+/// The gas prices and the gas limits sampled from the recent blocks.
+#[derive(Default)]
+pub struct GasPriceSample {
+    prices: Vec<U256>,
+    total_block_gas_limit: u64,
+    total_tx_gas_limit: u64,
+}
+
+impl GasPriceSample {
+    pub fn transaction_count(&self) -> usize { self.prices.len() }
+
+    pub fn add_block(&mut self, gas_limit: u64) {
+        self.total_block_gas_limit += gas_limit;
+    }
+
+    pub fn add_transaction(&mut self, gas_price: U256, gas_limit: u64) {
+        self.prices.push(gas_price);
+        self.total_tx_gas_limit += gas_limit;
+    }
+
+    /// Suggest the gas prices from the sample, where no suggested price is
+    /// lower than `min_gas_price`.
+    pub fn suggest(
+        mut self, config: &GasPriceOracleConfig, min_gas_price: U256,
+    ) -> GasPriceSuggestion {
+        let default_price =
+            max(U256::from(GAS_PRICE_DEFAULT_VALUE), min_gas_price);
+        if self.prices.is_empty() || self.total_tx_gas_limit == 0 {
+            return GasPriceSuggestion {
+                gas_price: default_price,
+                slow: default_price,
+                standard: default_price,
+                fast: default_price,
+                gas_used_ratio: 0.0,
+            };
+        }
+
+        self.prices.sort();
+        let percentile = |percentile: f64| {
+            let index =
+                (self.prices.len() as f64 * percentile / 100.0) as usize;
+            max(self.prices[index.min(self.prices.len() - 1)], min_gas_price)
+        };
+        let slow = percentile(config.slow_percentile);
+        let standard = percentile(config.standard_percentile);
+        let fast = percentile(config.fast_percentile);
+
+        let average_gas_limit_multiple =
+            self.total_block_gas_limit / self.total_tx_gas_limit;
+        let gas_price = if average_gas_limit_multiple > 5 {
+            // used less than 20%
+            default_price
+        } else if average_gas_limit_multiple >= 2 {
+            // used less than 50%
+            slow
+        } else {
+            // used more than 50%
+            standard
+        };
+        GasPriceSuggestion {
+            gas_price,
+            slow,
+            standard,
+            fast,
+            gas_used_ratio: self.total_tx_gas_limit as f64
+                / self.total_block_gas_limit.max(1) as f64,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GasPriceSuggestion {
+    /// The gas price returned by `cfx_gasPrice` and `eth_gasPrice`, which is
+    /// chosen from the tiers below by how full the recent blocks are.
+    pub gas_price: U256,
+    pub slow: U256,
+    pub standard: U256,
+    pub fast: U256,
+    /// The gas limit of the sampled transactions divided by the gas limit of
+    /// the sampled blocks.
+    pub gas_used_ratio: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(prices: &[u64], block_gas_limit: u64) -> GasPriceSample {
+        let mut sample = GasPriceSample::default();
+        sample.add_block(block_gas_limit);
+        for price in prices {
+            sample.add_transaction((*price).into(), 21000);
+        }
+        sample
+    }
+
+    #[test]
+    fn test_empty_sample() {
+        let config = GasPriceOracleConfig::default();
+        let suggestion = GasPriceSample::default().suggest(&config, 1.into());
+        assert_eq!(suggestion.gas_price, GAS_PRICE_DEFAULT_VALUE.into());
+        assert_eq!(suggestion.fast, GAS_PRICE_DEFAULT_VALUE.into());
+        assert_eq!(suggestion.gas_used_ratio, 0.0);
+
+        let min_gas_price = U256::from(GAS_PRICE_DEFAULT_VALUE * 2);
+        let suggestion =
+            GasPriceSample::default().suggest(&config, min_gas_price);
+        assert_eq!(suggestion.slow, min_gas_price);
+    }
+
+    #[test]
+    fn test_percentile_tiers() {
+        let config = GasPriceOracleConfig::default();
+        let prices: Vec<u64> = (1..=100).rev().collect();
+
+        // More than 50% of the block gas limit is used.
+        let suggestion =
+            sample(&prices, 21000 * 150).suggest(&config, 1.into());
+        assert_eq!(suggestion.slow, 13.into());
+        assert_eq!(suggestion.standard, 51.into());
+        assert_eq!(suggestion.fast, 91.into());
+        assert_eq!(suggestion.gas_price, suggestion.standard);
+        assert!(suggestion.gas_used_ratio > 0.5);
+
+        // Less than 50% of the block gas limit is used.
+        let suggestion =
+            sample(&prices, 21000 * 300).suggest(&config, 1.into());
+        assert_eq!(suggestion.gas_price, suggestion.slow);
+
+        // Less than 20% of the block gas limit is used.
+        let suggestion =
+            sample(&prices, 21000 * 1000).suggest(&config, 1.into());
+        assert_eq!(suggestion.gas_price, GAS_PRICE_DEFAULT_VALUE.into());
+
+        // The suggested prices are not lower than the minimum gas price.
+        let suggestion =
+            sample(&prices, 21000 * 150).suggest(&config, 60.into());
+        assert_eq!(suggestion.slow, 60.into());
+        assert_eq!(suggestion.standard, 60.into());
+        assert_eq!(suggestion.fast, 91.into());
+    }
+}
@@ -6,6 +6,7 @@ mod anticone_cache;
 pub mod consensus_inner;
 pub mod consensus_trait;
 pub mod debug_recompute;
+pub mod gas_price_oracle;
 mod pastset_cache;
 pub mod pos_handler;
 
@@ -26,6 +27,9 @@ use crate::{
         consensus_inner::{
             consensus_executor::ConsensusExecutionConfiguration, StateBlameInfo,
         },
+        gas_price_oracle::{
+            GasPriceOracleConfig, GasPriceSample, GasPriceSuggestion,
+        },
         pos_handler::PosVerifier,
     },
     executive::{
@@ -41,22 +45,14 @@ use crate::{
     },
     pow::{PowComputer, ProofOfWorkConfig},
     rpc_errors::{invalid_params_check, Result as RpcResult},
-    state::State,
+    state::{DevStateOverride, State},
     statistics::SharedStatistics,
     transaction_pool::SharedTransactionPool,
     verification::VerificationConfig,
     NodeType, Notifications,
 };
 use cfx_internal_common::ChainIdParams;
-use cfx_parameters::{
-    consensus::*,
-    consensus_internal::REWARD_EPOCH_COUNT,
-    rpc::{
-        EVM_GAS_PRICE_BLOCK_SAMPLE_SIZE, EVM_GAS_PRICE_TRANSACTION_SAMPLE_SIZE,
-        GAS_PRICE_BLOCK_SAMPLE_SIZE, GAS_PRICE_DEFAULT_VALUE,
-        GAS_PRICE_TRANSACTION_SAMPLE_SIZE,
-    },
-};
+use cfx_parameters::{consensus::*, consensus_internal::REWARD_EPOCH_COUNT};
 use cfx_state::state_trait::StateOpsTrait;
 use cfx_statedb::StateDb;
 use cfx_storage::{
@@ -160,6 +156,11 @@ pub struct ConsensusConfig {
     /// The number of extra epochs that we want to keep
     /// states/receipts/transactions.
     pub sync_state_epoch_gap: Option<u64>,
+    /// The sample window and the percentiles used to suggest gas prices.
+    pub gas_price_oracle: GasPriceOracleConfig,
+    /// The maximal confirmation risk of the epochs regarded as safe, i.e.
+    /// the epochs referred to by the `latest_safe` epoch number.
+    pub safe_epoch_confirmation_risk: f64,
 }
 
 #[derive(Debug)]
@@ -502,6 +503,7 @@ impl ConsensusGraph {
             EpochNumber::LatestFinalized => {
                 self.latest_finalized_epoch_number()
             }
+            EpochNumber::LatestSafe => self.latest_safe_epoch_number(),
             EpochNumber::LatestState => self.best_executed_state_epoch_number(),
             EpochNumber::Number(num) => {
                 let epoch_num = num;
@@ -513,36 +515,32 @@ impl ConsensusGraph {
         })
     }
 
-    /// Get the average gas price of the last GAS_PRICE_TRANSACTION_SAMPLE_SIZE
-    /// blocks
+    /// Get the gas price suggested by the gas price oracle.
     pub fn gas_price(&self, space: Space) -> Option<U256> {
+        Some(self.gas_price_oracle(space).gas_price)
+    }
+
+    /// Suggest the gas prices in `space` from the transactions in the recent
+    /// blocks. The suggested prices are not lower than the minimum gas price
+    /// accepted by the transaction pool.
+    pub fn gas_price_oracle(&self, space: Space) -> GasPriceSuggestion {
         let inner = self.inner.read();
         let mut last_epoch_number = inner.best_epoch_number();
-        let (
-            number_of_tx_to_sample,
-            mut number_of_blocks_to_sample,
-            block_gas_ratio,
-        ) = match space {
-            Space::Native => (
-                GAS_PRICE_TRANSACTION_SAMPLE_SIZE,
-                GAS_PRICE_BLOCK_SAMPLE_SIZE,
-                1,
-            ),
-            Space::Ethereum => (
-                EVM_GAS_PRICE_TRANSACTION_SAMPLE_SIZE,
-                EVM_GAS_PRICE_BLOCK_SAMPLE_SIZE,
-                self.txpool.machine().params().evm_transaction_gas_ratio,
-            ),
+        let (mut number_of_blocks_to_sample, number_of_tx_to_sample) =
+            self.config.gas_price_oracle.sample_size(space);
+        let block_gas_ratio = match space {
+            Space::Native => 1,
+            Space::Ethereum => {
+                self.txpool.machine().params().evm_transaction_gas_ratio
+            }
         };
-        let mut prices = Vec::new();
-        let mut total_block_gas_limit: u64 = 0;
-        let mut total_tx_gas_limit: u64 = 0;
+        let mut sample = GasPriceSample::default();
 
         loop {
             if number_of_blocks_to_sample == 0 || last_epoch_number == 0 {
                 break;
             }
-            if prices.len() == number_of_tx_to_sample {
+            if sample.transaction_count() == number_of_tx_to_sample {
                 break;
             }
             let mut hashes = inner
@@ -567,8 +565,9 @@ impl ConsensusGraph {
                     // not need to check every transaction.
                     continue;
                 }
-                total_block_gas_limit +=
-                    block.block_header.gas_limit().as_u64() * block_gas_ratio;
+                sample.add_block(
+                    block.block_header.gas_limit().as_u64() * block_gas_ratio,
+                );
                 for tx in block.transactions.iter() {
                     if space == Space::Ethereum && tx.space() != Space::Ethereum
                     {
@@ -576,41 +575,30 @@ impl ConsensusGraph {
                         // transactions.
                         continue;
                     }
-                    // add the tx.gas() to total_tx_gas_limit even it is packed
+                    // add the tx.gas() to the sample even it is packed
                     // multiple times because these tx all
                     // will occupy block's gas space
-                    total_tx_gas_limit += tx.transaction.gas().as_u64();
-                    prices.push(tx.gas_price().clone());
-                    if prices.len() == number_of_tx_to_sample {
+                    sample.add_transaction(
+                        tx.gas_price().clone(),
+                        tx.transaction.gas().as_u64(),
+                    );
+                    if sample.transaction_count() == number_of_tx_to_sample {
                         break;
                     }
                 }
                 number_of_blocks_to_sample -= 1;
                 if number_of_blocks_to_sample == 0
-                    || prices.len() == number_of_tx_to_sample
+                    || sample.transaction_count() == number_of_tx_to_sample
                 {
                     break;
                 }
             }
         }
 
-        prices.sort();
-        if prices.is_empty() || total_tx_gas_limit == 0 {
-            Some(U256::from(GAS_PRICE_DEFAULT_VALUE))
-        } else {
-            let average_gas_limit_multiple =
-                total_block_gas_limit / total_tx_gas_limit;
-            if average_gas_limit_multiple > 5 {
-                // used less than 20%
-                Some(U256::from(GAS_PRICE_DEFAULT_VALUE))
-            } else if average_gas_limit_multiple >= 2 {
-                // used less than 50%
-                Some(prices[prices.len() / 8])
-            } else {
-                // used more than 50%
-                Some(prices[prices.len() / 2])
-            }
-        }
+        sample.suggest(
+            &self.config.gas_price_oracle,
+            self.txpool.min_tx_price().into(),
+        )
     }
 
     fn validate_stated_epoch(
@@ -1423,6 +1411,14 @@ impl ConsensusGraph {
             .call_virtual(tx, &epoch_id, epoch_size, request)
     }
 
+    /// Queue a state override of the dev mode RPCs, which is applied before
+    /// the transactions of the next executed epoch.
+    pub fn queue_dev_state_override(&self, state_override: DevStateOverride) {
+        self.executor
+            .handler
+            .queue_dev_state_override(state_override)
+    }
+
     /// Get the number of processed blocks (i.e., the number of calls to
     /// on_new_block()
     pub fn get_processed_block_count(&self) -> usize {
@@ -2108,6 +2104,12 @@ impl ConsensusGraphTrait for ConsensusGraph {
             .1
     }
 
+    fn latest_safe_epoch_number(&self) -> u64 {
+        self.confirmation_meter.get_epoch_num_with_risk_below(
+            self.config.safe_epoch_confirmation_risk,
+        )
+    }
+
     fn best_chain_id(&self) -> AllChainID {
         self.best_info.read_recursive().best_chain_id()
     }
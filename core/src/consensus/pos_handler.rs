@@ -10,7 +10,10 @@ use diem_types::{
     epoch_state::EpochState,
     reward_distribution_event::RewardDistributionEvent,
     term_state::{DisputeEvent, UnlockEvent},
-    validator_config::{ConsensusPrivateKey, ConsensusVRFPrivateKey},
+    validator_config::{
+        ConsensusPrivateKey, ConsensusPublicKey, ConsensusVRFPrivateKey,
+        ConsensusVRFPublicKey,
+    },
 };
 use keccak_hash::keccak;
 use primitives::pos::{NodeId, PosBlockId};
@@ -38,7 +41,7 @@ use crate::{
 };
 use cached_pos_ledger_db::CachedPosLedgerDB;
 use consensus_types::block::Block;
-use diem_config::config::SafetyRulesTestConfig;
+use diem_config::config::{SafetyRulesService, SafetyRulesTestConfig};
 use diem_types::{
     account_address::from_consensus_public_key,
     block_info::{PivotBlockDecision, Round},
@@ -49,7 +52,8 @@ use diem_types::{
 use network::NetworkService;
 use parking_lot::Mutex;
 use pos_ledger_db::PosLedgerDB;
-use std::{fs, io::Read, path::PathBuf};
+use safety_rules::SafetyRulesManager;
+use std::{collections::HashMap, fs, io::Read, net::SocketAddr, path::PathBuf};
 
 pub type PosVerifier = PosHandler;
 
@@ -104,6 +108,23 @@ pub struct PosBlock {
      * voters: Vec<NodeId>, */
 }
 
+impl PosBlock {
+    pub fn new(
+        hash: PosBlockId, epoch: u64, round: u64, pivot_decision: H256,
+        version: u64, view: u64,
+    ) -> Self
+    {
+        PosBlock {
+            hash,
+            epoch,
+            round,
+            pivot_decision,
+            version,
+            view,
+        }
+    }
+}
+
 pub struct PosHandler {
     pos: OnceCell<Box<dyn PosInterface>>,
     network: Mutex<Option<Arc<NetworkService>>>,
@@ -187,30 +208,43 @@ impl PosHandler {
         let (test_command_sender, test_command_receiver) =
             channel::new_test(1024);
 
+        let (bls_public_key, vrf_public_key) = self.conf.pos_keys.public_keys();
+        let (consensus_key, vrf_key) = match &self.conf.pos_keys {
+            PosKeys::Local { bls_key, vrf_key } => {
+                (Some(bls_key.clone()), Some(vrf_key.clone()))
+            }
+            PosKeys::Remote { .. } => {
+                // The keys are only known by the remote signer.
+                if !matches!(
+                    pos_config.consensus.safety_rules.service,
+                    SafetyRulesService::Process(_)
+                ) {
+                    bail!("Remote PoS keys require the process safety rules");
+                }
+                (None, None)
+            }
+        };
         pos_config.consensus.safety_rules.test = Some(SafetyRulesTestConfig {
-            author: from_consensus_public_key(
-                &self.conf.bls_key.public_key(),
-                &self.conf.vrf_key.public_key(),
-            ),
-            consensus_key: Some(self.conf.bls_key.clone()),
-            execution_key: Some(self.conf.bls_key.clone()),
+            author: from_consensus_public_key(&bls_public_key, &vrf_public_key),
+            consensus_key: consensus_key.clone(),
+            execution_key: consensus_key,
             waypoint: Some(pos_config.base.waypoint.waypoint()),
         });
-        pos_config.consensus.safety_rules.vrf_private_key =
-            Some(self.conf.vrf_key.clone());
-        pos_config.consensus.safety_rules.export_consensus_key = true;
+        pos_config.consensus.safety_rules.export_consensus_key =
+            vrf_key.is_some();
+        pos_config.consensus.safety_rules.vrf_private_key = vrf_key;
         pos_config.consensus.safety_rules.vrf_proposal_threshold =
             self.conf.vrf_proposal_threshold;
         pos_config.consensus.chain_id = ChainId::new(network.network_id());
+        if let Some(address) = self.conf.backup_service_address {
+            pos_config.storage.backup_service_address = Some(address);
+        }
 
         let pos_drop_handle = start_pos_consensus(
             &pos_config,
             network,
             self.conf.protocol_conf.clone(),
-            Some((
-                self.conf.bls_key.public_key(),
-                self.conf.vrf_key.public_key(),
-            )),
+            Some((bls_public_key, vrf_public_key)),
             pos_genesis,
             self.consensus_network_receiver
                 .lock()
@@ -238,6 +272,17 @@ impl PosHandler {
         Ok(())
     }
 
+    /// Use `pos` instead of starting the PoS consensus, e.g. to replay the
+    /// PoS references recorded from a synced node in benchmarks.
+    pub fn initialize_with(
+        &self, pos: Box<dyn PosInterface>,
+    ) -> Result<(), String> {
+        if self.pos.set(pos).is_err() {
+            bail!("PoS initialized twice!");
+        }
+        Ok(())
+    }
+
     pub fn config(&self) -> &PosConfiguration { &self.conf }
 
     fn pos(&self) -> &Box<dyn PosInterface> { self.pos.get().unwrap() }
@@ -624,14 +669,139 @@ impl PosInterface for PosConnection {
     fn cached_db(&self) -> &Arc<CachedPosLedgerDB> { &self.pos_cache_db }
 }
 
+/// The PoS blocks recorded from a synced node, to replay the PoS references
+/// of its PoW blocks without the PoS consensus. Only the committed blocks are
+/// available, so it cannot be used with the execution.
+#[derive(Clone, Default)]
+pub struct RecordedPos {
+    blocks: Arc<Mutex<HashMap<PosBlockId, PosBlock>>>,
+}
+
+impl RecordedPos {
+    pub fn record(&self, block: PosBlock) {
+        self.blocks.lock().insert(block.hash, block);
+    }
+}
+
+impl PosInterface for RecordedPos {
+    fn initialize(&self) -> Result<(), String> { Ok(()) }
+
+    fn get_committed_block(&self, h: &PosBlockId) -> Option<PosBlock> {
+        self.blocks.lock().get(h).map(|b| PosBlock {
+            hash: b.hash,
+            epoch: b.epoch,
+            round: b.round,
+            pivot_decision: b.pivot_decision,
+            version: b.version,
+            view: b.view,
+        })
+    }
+
+    fn latest_block(&self) -> PosBlockId { unimplemented!("not recorded") }
+
+    fn get_events(
+        &self, _from: &PosBlockId, _to: &PosBlockId,
+    ) -> Vec<ContractEvent> {
+        unimplemented!("not recorded")
+    }
+
+    fn get_epoch_ending_blocks(
+        &self, _start_epoch: u64, _end_epoch: u64,
+    ) -> Vec<PosBlockId> {
+        unimplemented!("not recorded")
+    }
+
+    fn get_reward_event(&self, _epoch: u64) -> Option<RewardDistributionEvent> {
+        unimplemented!("not recorded")
+    }
+
+    fn get_epoch_state(&self, _block_id: &PosBlockId) -> EpochState {
+        unimplemented!("not recorded")
+    }
+
+    fn pos_ledger_db(&self) -> &Arc<PosLedgerDB> {
+        unimplemented!("not recorded")
+    }
+
+    fn consensus_db(&self) -> &Arc<ConsensusDB> {
+        unimplemented!("not recorded")
+    }
+
+    fn cached_db(&self) -> &Arc<CachedPosLedgerDB> {
+        unimplemented!("not recorded")
+    }
+}
+
+/// The keys of the PoS validator run by this node.
+pub enum PosKeys {
+    /// The private keys are loaded by the node.
+    Local {
+        bls_key: ConfigKey<ConsensusPrivateKey>,
+        vrf_key: ConfigKey<ConsensusVRFPrivateKey>,
+    },
+    /// The private keys are held by the safety rules process, and the node
+    /// only knows the public keys.
+    Remote {
+        bls_public_key: ConsensusPublicKey,
+        vrf_public_key: ConsensusVRFPublicKey,
+    },
+}
+
+impl PosKeys {
+    pub fn public_keys(&self) -> (ConsensusPublicKey, ConsensusVRFPublicKey) {
+        match self {
+            PosKeys::Local { bls_key, vrf_key } => {
+                (bls_key.public_key(), vrf_key.public_key())
+            }
+            PosKeys::Remote {
+                bls_public_key,
+                vrf_public_key,
+            } => (bls_public_key.clone(), vrf_public_key.clone()),
+        }
+    }
+
+    /// Query the public keys from the safety rules process if the PoS config
+    /// at `pos_config_path` uses one. Return `None` if the safety rules run
+    /// within the node.
+    pub fn from_remote_signer(
+        pos_config_path: &Option<String>,
+    ) -> Result<Option<Self>, String> {
+        let path = match pos_config_path {
+            Some(path) => PathBuf::from(path),
+            None => return Ok(None),
+        };
+        let pos_config = NodeConfig::load(path)
+            .map_err(|e| format!("Failed to load node config: e={:?}", e))?;
+        let safety_rules_config = &pos_config.consensus.safety_rules;
+        if !matches!(
+            safety_rules_config.service,
+            SafetyRulesService::Process(_)
+        ) {
+            return Ok(None);
+        }
+        let (bls_public_key, vrf_public_key) =
+            SafetyRulesManager::new(safety_rules_config)
+                .client()
+                .public_keys()
+                .map_err(|e| {
+                    format!("Failed to get keys from remote signer: {:?}", e)
+                })?;
+        Ok(Some(PosKeys::Remote {
+            bls_public_key,
+            vrf_public_key,
+        }))
+    }
+}
+
 pub struct PosConfiguration {
-    pub bls_key: ConfigKey<ConsensusPrivateKey>,
-    pub vrf_key: ConfigKey<ConsensusVRFPrivateKey>,
+    pub pos_keys: PosKeys,
     pub diem_conf_path: Option<String>,
     pub protocol_conf: ProtocolConfiguration,
     pub pos_initial_nodes_path: String,
     pub vrf_proposal_threshold: U256,
     pub pos_state_config: PosStateConfig,
+    /// Overrides the backup service address in the PoS config if set.
+    pub backup_service_address: Option<SocketAddr>,
 }
 
 fn diem_hash_to_h256(h: &HashValue) -> PosBlockId { H256::from(h.as_ref()) }
@@ -43,8 +43,10 @@ pub const COL_BLOCK_TRACES: u32 = 5;
 pub const COL_HASH_BY_BLOCK_NUMBER: u32 = 6;
 /// Column for PoS interest reward info.
 pub const COL_REWARD_BY_POS_EPOCH: u32 = 7;
+/// Column for storage key preimages
+pub const COL_STORAGE_KEY_PREIMAGE: u32 = 8;
 /// Number of columns in DB
-pub const NUM_COLUMNS: u32 = 8;
+pub const NUM_COLUMNS: u32 = 9;
 
 /// Modes for updating caches.
 #[derive(Clone, Copy)]
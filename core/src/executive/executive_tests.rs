@@ -5,7 +5,9 @@
 use super::{executive::*, Executed, ExecutionError};
 use crate::{
     evm::FinalizationResult,
-    executive::{CollateralCheckResultToVmResult, ExecutionOutcome},
+    executive::{
+        CollateralCheckResultToVmResult, ExecutionOutcome, ParallelExecutor,
+    },
     machine::Machine,
     state::{State, Substate},
     test_helpers::get_state_for_genesis_write,
@@ -2157,3 +2159,115 @@ fn test_storage_commission_privilege() {
         *COLLATERAL_DRIPS_PER_STORAGE_KEY * U256::from(2)
     );
 }
+
+#[test]
+fn test_parallel_execution_matches_sequential() {
+    let storage_manager = new_state_manager_for_unit_test();
+    let mut state = get_state_for_genesis_write(&storage_manager);
+    let machine = make_byzantium_machine(0);
+    let mut env = Env::default();
+    env.gas_limit = U256::MAX;
+    let spec = machine.spec(env.number);
+
+    let senders: Vec<_> = (0..6).map(|_| Random.generate().unwrap()).collect();
+    for sender in &senders {
+        state
+            .add_balance(
+                &sender.address().with_native_space(),
+                &U256::from(1_000_000_000_000_000_000_000u128),
+                CleanupMode::NoEmpty,
+                spec.account_start_nonce,
+            )
+            .unwrap();
+    }
+    let base_epoch_id = EpochId::from_uint(&U256::from(1));
+    state.commit(base_epoch_id, None).unwrap();
+
+    let mut receiver = Random.generate().unwrap().address();
+    receiver.set_user_account_type_bits();
+    // The contract stores the balance of the caller.
+    let code: Vec<u8> = "3331600055".from_hex().unwrap();
+    let transactions: Vec<_> = vec![
+        (0, 0, Action::Call(senders[1].address()), vec![]),
+        // Conflicts with the previous transaction of the same sender.
+        (0, 1, Action::Create, code.clone()),
+        // Conflicts with the transfer to the sender.
+        (1, 0, Action::Call(senders[2].address()), vec![]),
+        (2, 0, Action::Create, code.clone()),
+        (3, 0, Action::Create, code.clone()),
+        (4, 0, Action::Call(receiver), vec![]),
+        (5, 0, Action::Call(receiver), vec![]),
+        // The nonce is too large.
+        (5, 5, Action::Create, code.clone()),
+    ]
+    .into_iter()
+    .map(|(sender, nonce, action, data)| {
+        Arc::new(
+            Transaction::from(NativeTransaction {
+                nonce: U256::from(nonce),
+                gas_price: U256::one(),
+                gas: U256::from(1_000_000),
+                value: U256::from(1_000),
+                action,
+                storage_limit: 1_000,
+                epoch_height: 0,
+                chain_id: 1,
+                data,
+            })
+            .sign(senders[sender].secret()),
+        )
+    })
+    .collect();
+
+    let state_manager = (*storage_manager).clone();
+    let new_state_db = || -> cfx_statedb::Result<StateDb> {
+        Ok(StateDb::new(
+            state_manager
+                .get_state_for_next_epoch(
+                    StateIndex::new_for_test_only_delta_mpt(&base_epoch_id),
+                )
+                .unwrap()
+                .unwrap(),
+        ))
+    };
+
+    let mut sequential_state = State::new(new_state_db().unwrap()).unwrap();
+    let sequential_outcomes: Vec<_> = transactions
+        .iter()
+        .map(|tx| {
+            Executive::new(&mut sequential_state, &env, &machine, &spec)
+                .transact(tx, TransactOptions::exec_with_tracing())
+                .unwrap()
+        })
+        .collect();
+
+    let mut parallel_state = State::new(new_state_db().unwrap()).unwrap();
+    let parallel_outcomes = ParallelExecutor::new(4)
+        .unwrap()
+        .execute_transactions(
+            &mut parallel_state,
+            new_state_db,
+            &env,
+            &machine,
+            &spec,
+            &transactions,
+            true, /* executive_trace */
+        )
+        .unwrap();
+
+    assert_eq!(
+        format!("{:?}", parallel_outcomes),
+        format!("{:?}", sequential_outcomes)
+    );
+    assert_eq!(
+        parallel_state.total_storage_tokens(),
+        sequential_state.total_storage_tokens()
+    );
+    let sequential_root = sequential_state
+        .commit(EpochId::from_uint(&U256::from(2)), None)
+        .unwrap();
+    let parallel_root = parallel_state
+        .commit(EpochId::from_uint(&U256::from(3)), None)
+        .unwrap();
+    assert_eq!(parallel_root.state_root, sequential_root.state_root);
+}
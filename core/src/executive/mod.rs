@@ -8,6 +8,7 @@ mod executive;
 #[cfg(test)]
 mod executive_tests;
 pub mod internal_contract;
+mod parallel_executor;
 mod vm_exec;
 
 trait CollateralCheckResultToVmResult {
@@ -36,6 +37,7 @@ pub use self::{
         TransactOptions,
     },
     internal_contract::{InternalContractMap, InternalContractTrait},
+    parallel_executor::ParallelExecutor,
 };
 use crate::vm::Error as vmError;
 use cfx_state::CollateralCheckResult;
@@ -0,0 +1,198 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Optimistic parallel execution of the transactions in a block.
+//!
+//! The transactions are first executed speculatively in parallel, each on
+//! the state before the block. Then they are committed in order: the changes
+//! of a transaction are applied if none of the accounts it accessed has been
+//! written by the transactions committed before it, otherwise it is executed
+//! again on the latest state. So the outcomes and the resulting state are
+//! the same as executing the transactions one by one.
+
+use super::{ExecutionOutcome, Executive, TransactOptions};
+use crate::{
+    machine::Machine,
+    state::{SpeculativeChanges, State},
+    vm::{Env, Spec},
+};
+use cfx_statedb::{Result as DbResult, StateDb};
+use primitives::SignedTransaction;
+use rayon::prelude::*;
+use std::{collections::HashSet, sync::Arc};
+
+pub struct ParallelExecutor {
+    thread_pool: rayon::ThreadPool,
+    num_threads: usize,
+}
+
+impl ParallelExecutor {
+    pub fn new(num_threads: usize) -> Result<Self, String> {
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .thread_name(|index| format!("parallel_execution_{}", index))
+            .build()
+            .map_err(|e| format!("failed to start thread pool: {}", e))?;
+        Ok(ParallelExecutor {
+            thread_pool,
+            num_threads,
+        })
+    }
+
+    /// Execute `transactions` on `state` and return their outcomes, where
+    /// `new_state_db` creates a new state db for the same epoch as `state`.
+    pub fn execute_transactions<F>(
+        &self, state: &mut State, new_state_db: F, env: &Env,
+        machine: &Machine, spec: &Spec,
+        transactions: &[Arc<SignedTransaction>], executive_trace: bool,
+    ) -> DbResult<Vec<ExecutionOutcome>>
+    where
+        F: Fn() -> DbResult<StateDb> + Sync,
+    {
+        let speculated = if transactions.len() > 1 && state.can_speculate() {
+            self.speculate(
+                state,
+                &new_state_db,
+                env,
+                machine,
+                spec,
+                transactions,
+                executive_trace,
+            )
+        } else {
+            transactions.iter().map(|_| None).collect()
+        };
+
+        let mut written = HashSet::new();
+        let mut world_statistics_changed = false;
+        let mut re_executed = 0;
+        let mut outcomes = Vec::with_capacity(transactions.len());
+        for (transaction, speculated) in transactions.iter().zip(speculated) {
+            if let Some((outcome, changes)) = speculated {
+                let conflict = !state.can_speculate()
+                    || (changes.world_statistics_read()
+                        && world_statistics_changed)
+                    || changes
+                        .accessed_accounts()
+                        .iter()
+                        .any(|address| written.contains(address));
+                if !conflict {
+                    let written_accounts: Vec<_> =
+                        changes.written_accounts().cloned().collect();
+                    let changed = changes.world_statistics_changed();
+                    if state.apply_speculative_changes(changes) {
+                        written.extend(written_accounts);
+                        world_statistics_changed |= changed;
+                        outcomes.push(outcome);
+                        continue;
+                    }
+                }
+            }
+
+            let (outcome, written_accounts, changed) =
+                state.collect_writes(|state| {
+                    transact(
+                        state,
+                        env,
+                        machine,
+                        spec,
+                        transaction,
+                        executive_trace,
+                    )
+                })?;
+            written.extend(written_accounts);
+            world_statistics_changed |= changed;
+            re_executed += 1;
+            outcomes.push(outcome);
+        }
+        debug!(
+            "parallel execution: {} of {} transactions re-executed",
+            re_executed,
+            transactions.len()
+        );
+        Ok(outcomes)
+    }
+
+    /// Execute `transactions` speculatively on the forks of `state`. The
+    /// result of a transaction is `None` if its speculative execution fails.
+    fn speculate<F>(
+        &self, state: &State, new_state_db: &F, env: &Env, machine: &Machine,
+        spec: &Spec, transactions: &[Arc<SignedTransaction>],
+        executive_trace: bool,
+    ) -> Vec<Option<(ExecutionOutcome, SpeculativeChanges)>>
+    where
+        F: Fn() -> DbResult<StateDb> + Sync,
+    {
+        let num_workers = self.num_threads.min(transactions.len());
+        let worker_results: Vec<Vec<_>> = self.thread_pool.install(|| {
+            (0..num_workers)
+                .into_par_iter()
+                .map(|worker| {
+                    let mut results = Vec::new();
+                    let mut fork: Option<State> = None;
+                    for index in
+                        (worker..transactions.len()).step_by(num_workers)
+                    {
+                        if fork.is_none() {
+                            match new_state_db() {
+                                Ok(db) => {
+                                    fork = Some(state.new_speculative(db))
+                                }
+                                Err(e) => {
+                                    warn!("Failed to fork state: {:?}", e);
+                                    break;
+                                }
+                            }
+                        }
+                        let fork_state =
+                            fork.as_mut().expect("fork state exists");
+                        match fork_state.speculate(|state| {
+                            transact(
+                                state,
+                                env,
+                                machine,
+                                spec,
+                                &transactions[index],
+                                executive_trace,
+                            )
+                        }) {
+                            Ok(result) => results.push((index, result)),
+                            Err(e) => debug!(
+                                "Speculative execution of tx {:?} failed: {:?}",
+                                transactions[index].hash(),
+                                e
+                            ),
+                        }
+                        // The db of the fork is modified, so a new fork is
+                        // needed for the next transaction.
+                        if !fork_state.can_speculate() {
+                            fork = None;
+                        }
+                    }
+                    results
+                })
+                .collect()
+        });
+
+        let mut speculated: Vec<_> =
+            transactions.iter().map(|_| None).collect();
+        for (index, result) in worker_results.into_iter().flatten() {
+            speculated[index] = Some(result);
+        }
+        speculated
+    }
+}
+
+fn transact(
+    state: &mut State, env: &Env, machine: &Machine, spec: &Spec,
+    transaction: &SignedTransaction, executive_trace: bool,
+) -> DbResult<ExecutionOutcome>
+{
+    let options = if executive_trace {
+        TransactOptions::exec_with_tracing()
+    } else {
+        TransactOptions::exec_with_no_tracing()
+    };
+    Executive::new(state, env, machine, spec).transact(transaction, options)
+}
@@ -10,7 +10,9 @@ use crate::{
 use cfx_internal_common::ChainIdParamsOneChainInner;
 use cfx_types::{H160, H256};
 use error_chain::ChainedError;
-use network::{node_table::NodeId, NetworkContext, UpdateNodeOperation};
+use network::{
+    node_table::NodeId, NetworkContext, PeerBehavior, UpdateNodeOperation,
+};
 use parking_lot::Mutex;
 use primitives::{account::AccountError, filter::FilterError, StateRoot};
 use rlp::DecoderError;
@@ -183,6 +185,7 @@ pub fn handle(
     let mut disconnect = true;
     let reason = format!("{}", e.0);
     let mut op = None;
+    let mut behavior = None;
 
     // NOTE: do not use wildcard; this way, the compiler
     // will help covering all the cases.
@@ -228,12 +231,23 @@ pub fn handle(
         | ErrorKind::UnexpectedPeerType{..} => op = Some(UpdateNodeOperation::Failure),
 
         ErrorKind::UnexpectedResponse{..} => {
-            op = Some(UpdateNodeOperation::Demotion)
+            op = Some(UpdateNodeOperation::Demotion);
+            behavior = Some(PeerBehavior::InvalidMessage);
+        }
+
+        ErrorKind::AlreadyThrottled(_) => {
+            op = Some(UpdateNodeOperation::Remove);
+            behavior = Some(PeerBehavior::Spam);
+        }
+
+        ErrorKind::InvalidMessageFormat
+        | ErrorKind::Decoder(_) => {
+            op = Some(UpdateNodeOperation::Remove);
+            behavior = Some(PeerBehavior::InvalidMessage);
         }
 
         ErrorKind::InvalidBloom{..}
         | ErrorKind::InvalidLedgerProofSize{..}
-        | ErrorKind::InvalidMessageFormat
         | ErrorKind::InvalidPreviousStateRoot{..}
         | ErrorKind::InvalidReceipts{..}
         | ErrorKind::InvalidStateProof{..}
@@ -243,9 +257,10 @@ pub fn handle(
         | ErrorKind::InvalidTxRoot{..}
         | ErrorKind::InvalidTxSignature{..}
         | ErrorKind::InvalidWitnessRoot{..}
-        | ErrorKind::AlreadyThrottled(_)
-        | ErrorKind::Decoder(_)
-        | ErrorKind::AccountError(_) => op = Some(UpdateNodeOperation::Remove),
+        | ErrorKind::AccountError(_) => {
+            op = Some(UpdateNodeOperation::Remove);
+            behavior = Some(PeerBehavior::InvalidProof);
+        }
 
         ErrorKind::Throttled(_, resp) => {
             disconnect = false;
@@ -283,7 +298,8 @@ pub fn handle(
             | network::ErrorKind::Throttling(_) => disconnect = false,
 
             network::ErrorKind::BadProtocol | network::ErrorKind::Decoder(_) => {
-                op = Some(UpdateNodeOperation::Remove)
+                op = Some(UpdateNodeOperation::Remove);
+                behavior = Some(PeerBehavior::InvalidMessage);
             }
 
             network::ErrorKind::SocketIo(_)
@@ -300,6 +316,10 @@ pub fn handle(
         }
     };
 
+    if let Some(behavior) = behavior {
+        io.report_peer(peer, behavior);
+    }
+
     if disconnect {
         io.disconnect_peer(peer, op, reason.as_str());
     }
@@ -1078,4 +1078,6 @@ impl NetworkProtocolHandler for Handler {
     fn on_work_dispatch(&self, _io: &dyn NetworkContext, _work_type: u8) {
         unreachable!("Light node handler does not have on_work_dispatch.")
     }
+
+    fn message_ids(&self) -> Vec<u16> { msgid::ALL.to_vec() }
 }
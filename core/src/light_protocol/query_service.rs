@@ -872,6 +872,9 @@ impl QueryService {
             EpochNumber::LatestFinalized => {
                 Ok(self.consensus.latest_finalized_epoch_number())
             }
+            EpochNumber::LatestSafe => {
+                Ok(self.consensus.latest_safe_epoch_number())
+            }
             EpochNumber::Number(n) if n <= latest_verifiable => Ok(n),
             EpochNumber::Number(n) => Err(FilterError::UnableToVerify {
                 epoch: n,
@@ -26,6 +26,9 @@ macro_rules! build_msgid {
         pub mod msgid {
             use super::MsgId;
             $(pub const $name: MsgId = $value;)*
+
+            /// All the message ids of the protocol.
+            pub const ALL: &[MsgId] = &[$($name),*];
         }
     }
 }
@@ -149,7 +152,7 @@ pub fn decode_msg(mut msg: &[u8]) -> Option<(MsgId, Rlp)> {
         return None;
     }
 
-    let msg_id = parse_msg_id_leb128_2_bytes_at_most(&mut msg);
+    let msg_id = parse_msg_id_leb128_2_bytes_at_most(&mut msg)?;
     if msg.is_empty() {
         return None;
     }
@@ -39,6 +39,9 @@ pub struct SafetyRulesConfig {
 
     pub vrf_private_key: Option<ConfigKey<ConsensusVRFPrivateKey>>,
     pub vrf_proposal_threshold: U256,
+
+    /// The keys of a standalone SafetyRules process, see `RemoteSignerConfig`.
+    pub signer: Option<RemoteSignerConfig>,
 }
 
 impl Default for SafetyRulesConfig {
@@ -55,6 +58,7 @@ impl Default for SafetyRulesConfig {
             enable_cached_safety_data: true,
             vrf_private_key: None,
             vrf_proposal_threshold: U256::MAX,
+            signer: None,
         }
     }
 }
@@ -87,6 +91,9 @@ pub enum SafetyRulesService {
 #[serde(deny_unknown_fields)]
 pub struct RemoteService {
     pub server_address: NetworkAddress,
+    /// A file holding a secret shared by the node and the SafetyRules
+    /// process. Requests without a matching tag are rejected.
+    pub auth_token_path: PathBuf,
 }
 
 impl RemoteService {
@@ -99,6 +106,19 @@ impl RemoteService {
     }
 }
 
+/// The keys of a SafetyRules process started with the `safety-rules` binary.
+/// The process loads them on its own, so the consensus key and the VRF key
+/// never reside in the node process connecting to it.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RemoteSignerConfig {
+    /// The encrypted PoS key file generated by the node (`pos_key`). The
+    /// password is read from `CFX_POS_KEY_ENCRYPTION_PASSWORD`.
+    pub pos_key_path: PathBuf,
+    /// The waypoint used to initialize an empty secure storage.
+    pub waypoint: Waypoint,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SafetyRulesTestConfig {
     pub author: PeerId,
@@ -40,7 +40,9 @@ impl Default for RocksdbConfig {
 #[serde(default, deny_unknown_fields)]
 pub struct StorageConfig {
     pub address: SocketAddr,
-    pub backup_service_address: SocketAddr,
+    /// The address of the backup service serving the PoS ledger to
+    /// `db-backup`. `None` disables the backup service.
+    pub backup_service_address: Option<SocketAddr>,
     pub dir: PathBuf,
     pub grpc_max_receive_len: Option<i32>,
     /// None disables pruning. The windows is in number of versions, consider
@@ -58,10 +60,7 @@ impl Default for StorageConfig {
     fn default() -> StorageConfig {
         StorageConfig {
             address: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 6666),
-            backup_service_address: SocketAddr::new(
-                IpAddr::V4(Ipv4Addr::LOCALHOST),
-                6186,
-            ),
+            backup_service_address: None,
             dir: PathBuf::from("db"),
             grpc_max_receive_len: Some(100_000_000),
             // The prune window must at least out live a RPC request because its
@@ -97,7 +96,9 @@ impl StorageConfig {
 
     pub fn randomize_ports(&mut self) {
         self.address.set_port(utils::get_available_port());
-        self.backup_service_address
-            .set_port(utils::get_available_port());
+        if let Some(backup_service_address) = &mut self.backup_service_address
+        {
+            backup_service_address.set_port(utils::get_available_port());
+        }
     }
 }
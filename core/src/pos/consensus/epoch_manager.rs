@@ -56,6 +56,7 @@ use diem_types::{
     epoch_state::EpochState,
     on_chain_config::{OnChainConfigPayload, ValidatorSet},
     transaction::{SignedTransaction, TransactionPayload},
+    validator_config::ConsensusPrivateKey,
 };
 use futures::{
     channel::{mpsc, oneshot},
@@ -238,14 +239,7 @@ impl EpochManager {
             }
             ConsensusProposerType::VrfProposer => Box::new(VrfProposer::new(
                 self.author,
-                self.config
-                    .safety_rules
-                    .vrf_private_key
-                    .as_ref()
-                    .expect(
-                        "VRF private key mush be set for VRF leader election",
-                    )
-                    .private_key(),
+                self.safety_rules_manager.client(),
                 self.config.safety_rules.vrf_proposal_threshold,
                 epoch_state.clone(),
             )),
@@ -410,27 +404,18 @@ impl EpochManager {
         }
 
         diem_info!(epoch = epoch, "Create ProposalGenerator");
-        // TODO(lpl): Decide key management.
         // txn manager is required both by proposal generator (to pull the
         // proposers) and by event processor (to update their status).
-        let proposal_generator = match epoch_state
-            .verifier
-            .get_public_key(&self.author)
-        {
-            Some(public_key) => {
-                // TODO(lpl): Handle no vrf.
-                let vrf_key =
-                    self.config.safety_rules.vrf_private_key.as_ref().unwrap();
-                let private_key = self
-                    .config
-                    .safety_rules
-                    .test
-                    .as_ref()
-                    .expect("test config set")
-                    .consensus_key
-                    .as_ref()
-                    .expect("private key set in pos")
-                    .private_key();
+        // The private keys are held by the safety rules, which may run in
+        // another process, so the generator signs through its client.
+        let proposal_generator = match (
+            epoch_state.verifier.get_public_key(&self.author),
+            epoch_state
+                .verifier
+                .get_vrf_public_key(&self.author)
+                .flatten(),
+        ) {
+            (Some(public_key), Some(vrf_public_key)) => {
                 Some(ProposalGenerator::new(
                     self.author,
                     block_store.clone(),
@@ -438,13 +423,12 @@ impl EpochManager {
                     self.time_service.clone(),
                     self.config.max_block_size,
                     self.pow_handler.clone(),
-                    private_key,
+                    self.safety_rules_manager.client(),
                     public_key,
-                    vrf_key.private_key(),
-                    vrf_key.public_key(),
+                    vrf_public_key,
                 ))
             }
-            None => None,
+            _ => None,
         };
 
         diem_info!(epoch = epoch, "Create RoundState");
@@ -481,21 +465,7 @@ impl EpochManager {
         );
         // Only check if we should send election after entering an new epoch.
         if self.election_control.load(AtomicOrdering::Relaxed) {
-            if let Err(e) = processor
-                .broadcast_election(
-                    self.author,
-                    self.config
-                        .safety_rules
-                        .test
-                        .as_ref()
-                        .expect("test config set")
-                        .consensus_key
-                        .as_ref()
-                        .expect("private key set in pos"),
-                    self.config.safety_rules.vrf_private_key.as_ref().unwrap(),
-                )
-                .await
-            {
+            if let Err(e) = processor.broadcast_election(self.author).await {
                 diem_error!("error in broadcasting election tx: {:?}", e);
             }
         } else {
@@ -924,16 +894,7 @@ impl EpochManager {
         &mut self, block_id: HashValue,
     ) -> anyhow::Result<()> {
         diem_debug!("force_vote_proposal: {:?}", block_id);
-        let bls_key = self
-            .config
-            .safety_rules
-            .test
-            .as_ref()
-            .expect("test config set")
-            .consensus_key
-            .as_ref()
-            .expect("private key set in pos")
-            .private_key();
+        let bls_key = self.local_consensus_key()?;
         let author = self.author;
         match self.processor_mut() {
             RoundProcessor::Normal(p) => {
@@ -948,16 +909,7 @@ impl EpochManager {
         payload: Vec<TransactionPayload>,
     ) -> anyhow::Result<()>
     {
-        let bls_key = self
-            .config
-            .safety_rules
-            .test
-            .as_ref()
-            .expect("test config set")
-            .consensus_key
-            .as_ref()
-            .expect("private key set in pos")
-            .private_key();
+        let bls_key = self.local_consensus_key()?;
         match self.processor_mut() {
             RoundProcessor::Normal(p) => {
                 p.force_propose(round, parent_block_id, payload, &bls_key)
@@ -967,6 +919,18 @@ impl EpochManager {
         }
     }
 
+    /// The consensus private key used by the test commands. It is only known
+    /// if the safety rules are not run as a remote signer.
+    fn local_consensus_key(&self) -> anyhow::Result<ConsensusPrivateKey> {
+        self.config
+            .safety_rules
+            .test
+            .as_ref()
+            .and_then(|test| test.consensus_key.as_ref())
+            .map(|key| key.private_key())
+            .ok_or_else(|| anyhow!("consensus private key is not available"))
+    }
+
     async fn force_sign_pivot_decision(
         &mut self, pivot_decision: PivotBlockDecision,
     ) -> anyhow::Result<()> {
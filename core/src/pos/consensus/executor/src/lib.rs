@@ -42,8 +42,7 @@ use diem_types::{
     proof::accumulator::InMemoryAccumulator,
     reward_distribution_event::{RewardDistributionEvent, VoteCount},
     term_state::{
-        ElectionEvent, PosState, RegisterEvent, RetireEvent,
-        UpdateVotingPowerEvent,
+        ElectionEvent, RegisterEvent, RetireEvent, UpdateVotingPowerEvent,
     },
     transaction::{
         Transaction, TransactionInfo, TransactionListWithProof,
@@ -694,8 +693,7 @@ where V: VMExecutor
             cache.synced_trees().version(),
             cache.synced_trees().state_root(),
             cache.synced_trees().state_tree(),
-            // TODO(lpl): State sync not used yet.
-            PosState::new_empty(),
+            cache.synced_trees().pos_state().clone(),
         );
 
         fail_point!("executor::vm_execute_chunk", |_| {
@@ -949,27 +947,51 @@ impl<V: VMExecutor> TransactionReplayer for Executor<V> {
             first_version,
         );
         while !txns.is_empty() {
-            let num_txns = txns.len();
-
-            let (output, txns_to_commit, _, txns_to_retry, txn_infos_to_retry) =
-                self.replay_transactions_impl(first_version, txns, txn_infos)?;
-            assert!(txns_to_retry.len() < num_txns);
+            // Replay one block at a time, so the PoS state is updated after
+            // every block as it is during the execution.
+            let block_size = txns
+                .iter()
+                .skip(1)
+                .position(|txn| matches!(txn, Transaction::BlockMetadata(_)))
+                .map_or(txns.len(), |pos| pos + 1);
+            let next_txns = txns.split_off(block_size);
+            let next_txn_infos = txn_infos.split_off(block_size);
+
+            while !txns.is_empty() {
+                let num_txns = txns.len();
+
+                let (
+                    output,
+                    txns_to_commit,
+                    _,
+                    txns_to_retry,
+                    txn_infos_to_retry,
+                ) = self.replay_transactions_impl(
+                    first_version,
+                    txns,
+                    txn_infos,
+                )?;
+                assert!(txns_to_retry.len() < num_txns);
+
+                self.db_with_cache.db.writer.save_transactions(
+                    &txns_to_commit,
+                    first_version,
+                    None,
+                    None,
+                    vec![],
+                    vec![],
+                )?;
 
-            self.db_with_cache.db.writer.save_transactions(
-                &txns_to_commit,
-                first_version,
-                None,
-                None,
-                vec![],
-                vec![],
-            )?;
+                self.db_with_cache
+                    .update_synced_trees(output.executed_trees().clone());
 
-            self.db_with_cache
-                .update_synced_trees(output.executed_trees().clone());
+                txns = txns_to_retry;
+                txn_infos = txn_infos_to_retry;
+                first_version += txns_to_commit.len() as u64;
+            }
 
-            txns = txns_to_retry;
-            txn_infos = txn_infos_to_retry;
-            first_version += txns_to_commit.len() as u64;
+            txns = next_txns;
+            txn_infos = next_txn_infos;
         }
         Ok(())
     }
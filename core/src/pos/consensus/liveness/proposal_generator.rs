@@ -19,13 +19,11 @@ use diem_infallible::Mutex;
 use diem_logger::{debug as diem_debug, error as diem_error};
 use diem_types::{
     transaction::{RawTransaction, SignedTransaction, TransactionPayload},
-    validator_config::{
-        ConsensusPrivateKey, ConsensusPublicKey, ConsensusVRFPrivateKey,
-        ConsensusVRFPublicKey,
-    },
+    validator_config::{ConsensusPublicKey, ConsensusVRFPublicKey},
     validator_verifier::ValidatorVerifier,
 };
 use pow_types::PowInterface;
+use safety_rules::TSafetyRules;
 
 use crate::pos::consensus::{
     block_storage::BlockReader, state_replication::TxnManager,
@@ -62,10 +60,10 @@ pub struct ProposalGenerator {
     last_round_generated: Mutex<Round>,
     // Handle the interaction with PoW consensus.
     pow_handler: Arc<dyn PowInterface>,
-    // TODO(lpl): Where to put them?
-    pub private_key: ConsensusPrivateKey,
+    // Signs the transactions sent by this validator. The private key is held
+    // by safety rules, which may run in another process.
+    signer: Mutex<Box<dyn TSafetyRules + Send + Sync>>,
     pub public_key: ConsensusPublicKey,
-    pub vrf_private_key: ConsensusVRFPrivateKey,
     pub vrf_public_key: ConsensusVRFPublicKey,
 }
 
@@ -74,9 +72,8 @@ impl ProposalGenerator {
         author: Author, block_store: Arc<dyn BlockReader + Send + Sync>,
         txn_manager: Arc<dyn TxnManager>, time_service: Arc<dyn TimeService>,
         max_block_size: u64, pow_handler: Arc<dyn PowInterface>,
-        private_key: ConsensusPrivateKey, public_key: ConsensusPublicKey,
-        vrf_private_key: ConsensusVRFPrivateKey,
-        vrf_public_key: ConsensusVRFPublicKey,
+        signer: Box<dyn TSafetyRules + Send + Sync>,
+        public_key: ConsensusPublicKey, vrf_public_key: ConsensusVRFPublicKey,
     ) -> Self
     {
         Self {
@@ -87,15 +84,21 @@ impl ProposalGenerator {
             max_block_size,
             last_round_generated: Mutex::new(0),
             pow_handler,
-            private_key,
+            signer: Mutex::new(signer),
             public_key,
-            vrf_private_key,
             vrf_public_key,
         }
     }
 
     pub fn author(&self) -> Author { self.author }
 
+    /// Sign a transaction sent by this validator with its consensus key.
+    pub fn sign_transaction(
+        &self, raw_tx: RawTransaction,
+    ) -> anyhow::Result<SignedTransaction> {
+        Ok(self.signer.lock().sign_transaction(raw_tx)?)
+    }
+
     /// Creates a NIL block proposal extending the highest certified block from
     /// the block store.
     pub fn generate_nil_block(&self, round: Round) -> anyhow::Result<Block> {
@@ -242,9 +245,8 @@ impl ProposalGenerator {
                             self.author,
                         ) {
                             Ok(raw_tx) => {
-                                let signed_tx = raw_tx
-                                    .sign(&self.private_key)?
-                                    .into_inner();
+                                let signed_tx =
+                                    self.sign_transaction(raw_tx)?;
                                 payload.push(signed_tx);
                             }
                             // TODO(lpl): This is not supposed to happen, so
@@ -309,7 +311,7 @@ impl ProposalGenerator {
                     u64::MAX,
                     Default::default(),
                 );
-                raw_tx.sign(&self.private_key).map(|tx| tx.into_inner())
+                self.sign_transaction(raw_tx)
             })
             .collect::<anyhow::Result<Vec<SignedTransaction>>>()?;
 
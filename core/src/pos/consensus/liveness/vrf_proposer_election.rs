@@ -10,18 +10,20 @@ use consensus_types::common::{Author, Round};
 
 use cfx_types::U256;
 use consensus_types::{block::Block, block_data::BlockData};
-use diem_crypto::{vrf_number_with_nonce, HashValue, VRFPrivateKey, VRFProof};
-use diem_logger::debug as diem_debug;
+use diem_crypto::{vrf_number_with_nonce, HashValue, VRFProof};
+use diem_logger::{debug as diem_debug, error as diem_error};
 use diem_types::{
-    epoch_state::EpochState,
-    validator_config::{ConsensusVRFPrivateKey, ConsensusVRFProof},
+    epoch_state::EpochState, validator_config::ConsensusVRFProof,
 };
 use parking_lot::Mutex;
+use safety_rules::TSafetyRules;
 
 /// The round proposer maps a round to author
 pub struct VrfProposer {
     author: Author,
-    vrf_private_key: ConsensusVRFPrivateKey,
+    // Computes the VRF proofs of this node. The VRF private key is held by
+    // safety rules, which may run in another process.
+    vrf_prover: Mutex<Box<dyn TSafetyRules + Send + Sync>>,
 
     proposal_threshold: HashValue,
 
@@ -35,7 +37,7 @@ pub struct VrfProposer {
 
 impl VrfProposer {
     pub fn new(
-        author: Author, vrf_private_key: ConsensusVRFPrivateKey,
+        author: Author, vrf_prover: Box<dyn TSafetyRules + Send + Sync>,
         proposal_threshold_u256: U256, epoch_state: EpochState,
     ) -> Self
     {
@@ -43,7 +45,7 @@ impl VrfProposer {
         proposal_threshold_u256.to_big_endian(&mut proposal_threshold);
         Self {
             author,
-            vrf_private_key,
+            vrf_prover: Mutex::new(vrf_prover),
             proposal_threshold: HashValue::new(proposal_threshold),
             // current_round and current_seed will not be used before
             // `next_round` is called.
@@ -89,12 +91,17 @@ impl ProposerElection for VrfProposer {
         let mut round_seed = self.current_seed.lock().clone();
         let leader_round = (round + 1) / 3;
         round_seed.extend_from_slice(&leader_round.to_be_bytes());
-        let vrf_output = self
-            .vrf_private_key
-            .compute(round_seed.as_slice())
-            .expect("vrf compute fail")
-            .to_hash()
-            .expect("to hash error");
+        let vrf_output = match self
+            .vrf_prover
+            .lock()
+            .compute_vrf_proof(round_seed.as_slice())
+        {
+            Ok(vrf_proof) => vrf_proof.to_hash().expect("to hash error"),
+            Err(e) => {
+                diem_error!("is_valid_proposer: vrf compute fail: {:?}", e);
+                return false;
+            }
+        };
         for nonce in 0..=voting_power {
             let vrf_number = vrf_number_with_nonce(&vrf_output, nonce);
             if vrf_number <= self.proposal_threshold {
@@ -205,8 +212,9 @@ impl ProposerElection for VrfProposer {
             .get_voting_power(&block_data.author()?)?;
 
         let vrf_proof = self
-            .vrf_private_key
-            .compute(
+            .vrf_prover
+            .lock()
+            .compute_vrf_proof(
                 block_data
                     .vrf_round_seed(self.current_seed.lock().as_slice())
                     .as_slice(),
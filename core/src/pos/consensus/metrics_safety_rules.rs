@@ -12,7 +12,12 @@ use consensus_types::{
 };
 use diem_metrics::monitor;
 use diem_types::{
-    epoch_change::EpochChangeProof, validator_config::ConsensusSignature,
+    epoch_change::EpochChangeProof,
+    transaction::{RawTransaction, SignedTransaction},
+    validator_config::{
+        ConsensusPublicKey, ConsensusSignature, ConsensusVRFProof,
+        ConsensusVRFPublicKey,
+    },
 };
 use safety_rules::{ConsensusState, Error, TSafetyRules};
 use std::sync::Arc;
@@ -100,6 +105,24 @@ impl TSafetyRules for MetricsSafetyRules {
         result
     }
 
+    fn sign_transaction(
+        &mut self, raw_tx: RawTransaction,
+    ) -> Result<SignedTransaction, Error> {
+        monitor!("safety_rules", self.inner.sign_transaction(raw_tx))
+    }
+
+    fn compute_vrf_proof(
+        &mut self, seed: &[u8],
+    ) -> Result<ConsensusVRFProof, Error> {
+        monitor!("safety_rules", self.inner.compute_vrf_proof(seed))
+    }
+
+    fn public_keys(
+        &mut self,
+    ) -> Result<(ConsensusPublicKey, ConsensusVRFPublicKey), Error> {
+        monitor!("safety_rules", self.inner.public_keys())
+    }
+
     fn start_voting(&mut self, initialize: bool) -> Result<(), Error> {
         monitor!("safety_rules", self.inner.start_voting(initialize))
     }
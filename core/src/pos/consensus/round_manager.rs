@@ -26,8 +26,7 @@ use consensus_types::{
     vote::Vote,
     vote_msg::VoteMsg,
 };
-use diem_config::keys::ConfigKey;
-use diem_crypto::{hash::CryptoHash, HashValue, SigningKey, VRFPrivateKey};
+use diem_crypto::{hash::CryptoHash, HashValue, SigningKey};
 use diem_infallible::checked;
 use diem_logger::prelude::*;
 use diem_types::{
@@ -40,7 +39,7 @@ use diem_types::{
         ConflictSignature, DisputePayload, ElectionPayload, RawTransaction,
         SignedTransaction, TransactionPayload,
     },
-    validator_config::{ConsensusPrivateKey, ConsensusVRFPrivateKey},
+    validator_config::ConsensusPrivateKey,
     validator_verifier::ValidatorVerifier,
 };
 #[cfg(test)]
@@ -380,8 +379,7 @@ impl RoundManager {
             },
             self.chain_id,
         );
-        let signed_tx =
-            raw_tx.sign(&proposal_generator.private_key)?.into_inner();
+        let signed_tx = proposal_generator.sign_transaction(raw_tx)?;
         let (tx, rx) = oneshot::channel();
         self.tx_sender.send((signed_tx, tx)).await?;
         // TODO(lpl): Check if we want to wait here.
@@ -392,10 +390,7 @@ impl RoundManager {
 
     pub async fn broadcast_election(
         &mut self, author: AccountAddress,
-        private_key: &ConfigKey<ConsensusPrivateKey>,
-        vrf_private_key: &ConfigKey<ConsensusVRFPrivateKey>,
-    ) -> anyhow::Result<()>
-    {
+    ) -> anyhow::Result<()> {
         if !self.is_voting {
             // This node does not participate in any signing or voting.
             return Ok(());
@@ -410,22 +405,22 @@ impl RoundManager {
         let pos_state = self.storage.pos_ledger_db().get_latest_pos_state();
         if let Some(target_term) = pos_state.next_elect_term(&author) {
             let epoch_vrf_seed = pos_state.target_term_seed(target_term);
+            let (public_key, vrf_public_key) =
+                self.safety_rules.public_keys()?;
             let election_payload = ElectionPayload {
-                public_key: private_key.public_key(),
-                vrf_public_key: vrf_private_key.public_key(),
+                public_key,
+                vrf_public_key,
                 target_term,
-                vrf_proof: vrf_private_key
-                    .private_key()
-                    .compute(epoch_vrf_seed.as_slice())
-                    .unwrap(),
+                vrf_proof: self
+                    .safety_rules
+                    .compute_vrf_proof(epoch_vrf_seed.as_slice())?,
             };
             let raw_tx = RawTransaction::new_election(
                 author,
                 election_payload,
                 self.chain_id,
             );
-            let signed_tx =
-                raw_tx.sign(&private_key.private_key())?.into_inner();
+            let signed_tx = self.safety_rules.sign_transaction(raw_tx)?;
             let (tx, rx) = oneshot::channel();
             self.tx_sender.send((signed_tx, tx)).await?;
             // TODO(lpl): Check if we want to wait here.
@@ -1100,9 +1095,8 @@ impl RoundManager {
                             proposal_generator.author(),
                             dispute_payload,
                         );
-                        let signed_tx = raw_tx
-                            .sign(&proposal_generator.private_key)?
-                            .into_inner();
+                        let signed_tx =
+                            proposal_generator.sign_transaction(raw_tx)?;
                         // TODO(lpl): Track disputed nodes to avoid sending
                         // multiple dispute, and retry if needed?
                         let (tx, rx) = oneshot::channel();
@@ -1356,8 +1350,7 @@ impl RoundManager {
             pivot_decision,
             self.chain_id,
         );
-        let signed_tx =
-            raw_tx.sign(&proposal_generator.private_key)?.into_inner();
+        let signed_tx = proposal_generator.sign_transaction(raw_tx)?;
         let (tx, rx) = oneshot::channel();
         self.tx_sender.send((signed_tx, tx)).await?;
         // TODO(lpl): Check if we want to wait here.
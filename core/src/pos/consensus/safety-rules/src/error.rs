@@ -31,6 +31,8 @@ pub enum Error {
     InvalidProposal(String),
     #[error("Invalid QC: {0}")]
     InvalidQuorumCertificate(String),
+    #[error("Invalid transaction: {0}")]
+    InvalidTransaction(String),
     #[error("{0} is not set, SafetyRules is not initialized")]
     NotInitialized(String),
     #[error("Data not found in secure storage: {0}")]
@@ -39,6 +41,8 @@ pub enum Error {
     SecureStorageUnexpectedError(String),
     #[error("Serialization error: {0}")]
     SerializationError(String),
+    #[error("Request is not authenticated by the SafetyRules auth token")]
+    Unauthenticated,
     #[error("Validator key not found: {0}")]
     ValidatorKeyNotFound(String),
     #[error(
@@ -19,14 +19,15 @@ mod remote_service;
 mod safety_rules;
 mod safety_rules_manager;
 mod serializer;
+pub mod slashing_protection;
 mod t_safety_rules;
 mod thread;
 
 pub use crate::{
     consensus_state::ConsensusState, error::Error,
     persistent_safety_storage::PersistentSafetyStorage, process::Process,
-    safety_rules::SafetyRules, safety_rules_manager::SafetyRulesManager,
-    t_safety_rules::TSafetyRules,
+    remote_service::AuthToken, safety_rules::SafetyRules,
+    safety_rules_manager::SafetyRulesManager, t_safety_rules::TSafetyRules,
 };
 
 #[cfg(any(test, feature = "fuzzing"))]
@@ -12,7 +12,12 @@ use consensus_types::{
 };
 use diem_infallible::RwLock;
 use diem_types::{
-    epoch_change::EpochChangeProof, validator_config::ConsensusSignature,
+    epoch_change::EpochChangeProof,
+    transaction::{RawTransaction, SignedTransaction},
+    validator_config::{
+        ConsensusPublicKey, ConsensusSignature, ConsensusVRFProof,
+        ConsensusVRFPublicKey,
+    },
 };
 use std::sync::Arc;
 
@@ -53,6 +58,24 @@ impl TSafetyRules for LocalClient {
         self.internal.write().sign_timeout(timeout)
     }
 
+    fn sign_transaction(
+        &mut self, raw_tx: RawTransaction,
+    ) -> Result<SignedTransaction, Error> {
+        self.internal.write().sign_transaction(raw_tx)
+    }
+
+    fn compute_vrf_proof(
+        &mut self, seed: &[u8],
+    ) -> Result<ConsensusVRFProof, Error> {
+        self.internal.write().compute_vrf_proof(seed)
+    }
+
+    fn public_keys(
+        &mut self,
+    ) -> Result<(ConsensusPublicKey, ConsensusVRFPublicKey), Error> {
+        self.internal.write().public_keys()
+    }
+
     fn start_voting(&mut self, initialize: bool) -> Result<(), Error> {
         self.internal.write().start_voting(initialize)
     }
@@ -44,6 +44,7 @@ impl<'a> SafetyLogSchema<'a> {
 #[derive(Clone, Copy, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum LogEntry {
+    ComputeVrfProof,
     ConsensusState,
     ConstructAndSignVote,
     Epoch,
@@ -51,8 +52,10 @@ pub enum LogEntry {
     KeyReconciliation,
     LastVotedRound,
     PreferredRound,
+    PublicKeys,
     SignProposal,
     SignTimeout,
+    SignTransaction,
     State,
     Waypoint,
 }
@@ -60,6 +63,7 @@ pub enum LogEntry {
 impl LogEntry {
     pub fn as_str(&self) -> &'static str {
         match self {
+            LogEntry::ComputeVrfProof => "compute_vrf_proof",
             LogEntry::ConsensusState => "consensus_state",
             LogEntry::ConstructAndSignVote => "construct_and_sign_vote",
             LogEntry::Epoch => "epoch",
@@ -67,8 +71,10 @@ impl LogEntry {
             LogEntry::LastVotedRound => "last_voted_round",
             LogEntry::KeyReconciliation => "key_reconciliation",
             LogEntry::PreferredRound => "preferred_round",
+            LogEntry::PublicKeys => "public_keys",
             LogEntry::SignProposal => "sign_proposal",
             LogEntry::SignTimeout => "sign_timeout",
+            LogEntry::SignTransaction => "sign_transaction",
             LogEntry::State => "state",
             LogEntry::Waypoint => "waypoint",
         }
@@ -5,7 +5,33 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
-//! Usage: ./safety-rules node.config
+//! Usage: ./safety-rules safety_rules.yaml
+//!
+//! Runs SafetyRules as a standalone signer for a PoS validator, so that the
+//! consensus key and the VRF key never reside in the node process. The node
+//! connects to it when `consensus.safety_rules.service` of its PoS config is
+//! set to the same `process` service, e.g.
+//!
+//! ```yaml
+//! service:
+//!     type: process
+//!     server_address: "/ip4/127.0.0.1/tcp/5555"
+//!     auth_token_path: "/path/to/safety_rules_token"
+//! backend:
+//!     type: on_disk_storage
+//!     path: "/path/to/signer/secure_storage.json"
+//! signer:
+//!     pos_key_path: "/path/to/pos_key"
+//!     waypoint: "<waypoint of the PoS config>"
+//! ```
+//!
+//! The keys are loaded from `pos_key_path` with the password in
+//! `CFX_POS_KEY_ENCRYPTION_PASSWORD`, and the safety data is kept in the
+//! `backend` storage of the signer. The service only listens on a loopback
+//! address, and rejects the requests not tagged with the secret in the
+//! required `auth_token_path` and the replayed ones. The signer must be started before
+//! the node, which queries it for the public keys of the validator on startup
+//! and fails to start if the signer does not respond after a few retries.
 
 #![forbid(unsafe_code)]
 
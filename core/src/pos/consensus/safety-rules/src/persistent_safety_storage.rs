@@ -8,6 +8,7 @@
 use crate::{
     counters,
     logging::{self, LogEntry, LogEvent},
+    slashing_protection::{self, SignedProposal},
     Error,
 };
 use consensus_types::{common::Author, safety_data::SafetyData};
@@ -15,11 +16,13 @@ use diem_crypto::{
     hash::CryptoHash, PrivateKey, SigningKey, ValidCryptoMaterial,
 };
 use diem_global_constants::{
-    CONSENSUS_KEY, EXECUTION_KEY, OWNER_ACCOUNT, SAFETY_DATA, WAYPOINT,
+    CONSENSUS_KEY, EXECUTION_KEY, OWNER_ACCOUNT, SAFETY_DATA, SIGNED_PROPOSALS,
+    WAYPOINT,
 };
 use diem_logger::prelude::*;
 use diem_secure_storage::{CryptoStorage, KVStorage, OnDiskStorage, Storage};
 use diem_types::{
+    transaction::{RawTransaction, SignedTransaction},
     validator_config::{
         ConsensusPrivateKey, ConsensusPublicKey, ConsensusSignature,
     },
@@ -173,6 +176,20 @@ impl PersistentSafetyStorage {
         }
     }
 
+    pub fn consensus_public_key(&self) -> ConsensusPublicKey {
+        self.private_key.public_key()
+    }
+
+    pub fn sign_transaction(
+        &self, raw_tx: RawTransaction,
+    ) -> Result<SignedTransaction, Error> {
+        let _timer = counters::start_timer("sign", CONSENSUS_KEY);
+        raw_tx
+            .sign(&self.private_key)
+            .map(|tx| tx.into_inner())
+            .map_err(|e| Error::InternalError(e.to_string()))
+    }
+
     pub fn sign<T: Serialize + CryptoHash>(
         &self, key_name: String, key_version: ConsensusPublicKey, message: &T,
     ) -> Result<ConsensusSignature, Error> {
@@ -222,6 +239,18 @@ impl PersistentSafetyStorage {
         }
     }
 
+    /// Record a proposal signed by this validator so that it can be exported
+    /// by the slashing-protection interchange.
+    pub fn record_signed_proposal(
+        &mut self, proposal: SignedProposal,
+    ) -> Result<(), Error> {
+        let _timer = counters::start_timer("set", SIGNED_PROPOSALS);
+        slashing_protection::insert_signed_proposal(
+            &mut self.internal_store,
+            &proposal,
+        )
+    }
+
     pub fn waypoint(&self) -> Result<Waypoint, Error> {
         let _timer = counters::start_timer("get", WAYPOINT);
         Ok(self.internal_store.get(WAYPOINT).map(|v| v.value)?)
@@ -7,19 +7,35 @@
 
 use crate::{
     persistent_safety_storage::PersistentSafetyStorage,
-    remote_service::{self, RemoteService},
-    safety_rules_manager,
+    remote_service::{self, AuthToken, RemoteService},
+    safety_rules_manager, Error,
 };
-use diem_config::config::{SafetyRulesConfig, SafetyRulesService};
+use diem_config::{
+    config::{SafetyRulesConfig, SafetyRulesService, SafetyRulesTestConfig},
+    keys::ConfigKey,
+};
+use diem_crypto::{key_file::load_pri_key, PrivateKey};
+use diem_infallible::Mutex;
+use diem_secure_net::NetworkClient;
+use diem_types::{
+    account_address::{from_consensus_public_key, AccountAddress},
+    validator_config::{ConsensusPrivateKey, ConsensusVRFPrivateKey},
+};
+use std::{net::SocketAddr, sync::Arc};
 
-use std::net::SocketAddr;
+/// The environment variable holding the password of the PoS key file.
+pub const POS_KEY_PASSWORD_ENV: &str = "CFX_POS_KEY_ENCRYPTION_PASSWORD";
 
 pub struct Process {
     data: Option<ProcessData>,
 }
 
 impl Process {
-    pub fn new(config: SafetyRulesConfig) -> Self {
+    pub fn new(mut config: SafetyRulesConfig) -> Self {
+        if config.signer.is_some() {
+            load_signer_keys(&mut config)
+                .unwrap_or_else(|e| panic!("Unable to load keys: {}", e));
+        }
         let storage = safety_rules_manager::storage(&config);
 
         let verify_vote_proposal_signature =
@@ -30,6 +46,16 @@ impl Process {
             _ => panic!("Unexpected SafetyRules service: {:?}", config.service),
         };
         let server_addr = service.server_address();
+        // The service signs anything for whoever connects to it, so it only
+        // accepts local connections.
+        if !server_addr.ip().is_loopback() {
+            panic!(
+                "SafetyRules service must listen on a loopback address: {}",
+                server_addr
+            );
+        }
+        let auth_token = AuthToken::load(&service.auth_token_path)
+            .unwrap_or_else(|e| panic!("{}", e));
 
         Self {
             data: Some(ProcessData {
@@ -38,6 +64,16 @@ impl Process {
                 verify_vote_proposal_signature,
                 export_consensus_key,
                 network_timeout: config.network_timeout_ms,
+                vrf_private_key: config
+                    .vrf_private_key
+                    .as_ref()
+                    .map(|key| key.private_key()),
+                author: config
+                    .test
+                    .as_ref()
+                    .map(|c| c.author)
+                    .unwrap_or_default(),
+                auth_token,
             }),
         }
     }
@@ -50,12 +86,45 @@ impl Process {
             data.verify_vote_proposal_signature,
             data.export_consensus_key,
             data.network_timeout,
-            // TODO(lpl): Support this?
-            None,
+            data.vrf_private_key,
+            data.author,
+            data.auth_token,
         );
     }
 }
 
+/// Load the keys of `config.signer` from the PoS key file, so that the storage
+/// is initialized with them as with the keys set by the node for the other
+/// services.
+fn load_signer_keys(config: &mut SafetyRulesConfig) -> Result<(), Error> {
+    let signer = config.signer.as_ref().expect("checked by caller");
+    let passwd = std::env::var(POS_KEY_PASSWORD_ENV).map_err(|_| {
+        Error::InternalError(format!("{} is not set", POS_KEY_PASSWORD_ENV))
+    })?;
+    let (private_key, vrf_private_key): (
+        ConsensusPrivateKey,
+        ConsensusVRFPrivateKey,
+    ) = load_pri_key(&signer.pos_key_path, passwd.as_bytes()).map_err(|e| {
+        Error::InternalError(format!(
+            "Unable to load {:?}: {}",
+            signer.pos_key_path, e
+        ))
+    })?;
+    let author = from_consensus_public_key(
+        &private_key.public_key(),
+        &vrf_private_key.public_key(),
+    );
+    let consensus_key = ConfigKey::new(private_key);
+    config.test = Some(SafetyRulesTestConfig {
+        author,
+        consensus_key: Some(consensus_key.clone()),
+        execution_key: Some(consensus_key),
+        waypoint: Some(signer.waypoint),
+    });
+    config.vrf_private_key = Some(ConfigKey::new(vrf_private_key));
+    Ok(())
+}
+
 struct ProcessData {
     server_addr: SocketAddr,
     storage: PersistentSafetyStorage,
@@ -63,24 +132,37 @@ struct ProcessData {
     export_consensus_key: bool,
     // Timeout in Seconds for network operations
     network_timeout: u64,
+    vrf_private_key: Option<ConsensusVRFPrivateKey>,
+    author: AccountAddress,
+    auth_token: AuthToken,
 }
 
 pub struct ProcessService {
-    server_addr: SocketAddr,
-    network_timeout_ms: u64,
+    network_client: Arc<Mutex<NetworkClient>>,
+    auth_token: AuthToken,
 }
 
 impl ProcessService {
-    pub fn new(server_addr: SocketAddr, network_timeout: u64) -> Self {
-        Self {
+    pub fn new(
+        server_addr: SocketAddr, network_timeout: u64, auth_token: AuthToken,
+    ) -> Self
+    {
+        let network_client = Arc::new(Mutex::new(NetworkClient::new(
+            "safety-rules",
             server_addr,
-            network_timeout_ms: network_timeout,
+            network_timeout,
+        )));
+        Self {
+            network_client,
+            auth_token,
         }
     }
 }
 
 impl RemoteService for ProcessService {
-    fn server_address(&self) -> SocketAddr { self.server_addr }
+    fn network_client(&self) -> Arc<Mutex<NetworkClient>> {
+        self.network_client.clone()
+    }
 
-    fn network_timeout_ms(&self) -> u64 { self.network_timeout_ms }
+    fn auth_token(&self) -> AuthToken { self.auth_token.clone() }
 }
@@ -13,32 +13,170 @@ use crate::{
     },
     Error, SafetyRules, TSafetyRules,
 };
+use diem_crypto::HashValue;
+use diem_infallible::Mutex;
 use diem_logger::warn as diem_warn;
 use diem_secure_net::{NetworkClient, NetworkServer};
-use diem_types::validator_config::ConsensusVRFPrivateKey;
-use std::net::SocketAddr;
+use diem_types::{
+    account_address::AccountAddress, validator_config::ConsensusVRFPrivateKey,
+};
+use std::{
+    convert::TryInto,
+    fs,
+    net::SocketAddr,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The number of attempts to send a request before giving up, so a node
+/// whose signer is unreachable fails instead of hanging.
+const REQUEST_ATTEMPTS: u32 = 8;
+/// The delay before the first retry, doubled after each failed attempt.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(100);
+/// Requests whose nonce is older than this are rejected even if the service
+/// has not seen a later nonce, e.g. after the service restarts.
+const NONCE_MAX_AGE: Duration = Duration::from_secs(60);
 
 pub trait RemoteService {
     fn client(&self) -> SerializerClient {
-        let network_client = NetworkClient::new(
-            "safety-rules",
-            self.server_address(),
-            self.network_timeout_ms(),
-        );
-        let service = Box::new(RemoteClient::new(network_client));
+        let service = Box::new(RemoteClient::new(
+            self.network_client(),
+            self.auth_token(),
+        ));
         SerializerClient::new_client(service)
     }
 
-    fn server_address(&self) -> SocketAddr;
+    /// The connection to the service. The service handles one connection at a
+    /// time, so it is shared by all the clients.
+    fn network_client(&self) -> Arc<Mutex<NetworkClient>>;
 
-    /// Network Timeout in milliseconds.
-    fn network_timeout_ms(&self) -> u64;
+    /// The secret tagging the requests. The service and the client each hold
+    /// their own `AuthToken` with the secret, as it tracks the last nonce.
+    fn auth_token(&self) -> AuthToken;
+}
+
+/// A secret shared by consensus and the SafetyRules service. Each request is
+/// prefixed with a keyed hash of a nonce and its payload, so only the holders
+/// of the secret can get anything signed by the service. The nonce is the
+/// time in microseconds and strictly increases, so a captured request cannot
+/// be replayed.
+#[derive(Clone)]
+pub struct AuthToken {
+    secret: Vec<u8>,
+    /// The last nonce sent by the client or accepted by the service.
+    last_nonce: Arc<AtomicU64>,
+}
+
+impl AuthToken {
+    pub fn new(secret: Vec<u8>) -> Self {
+        Self {
+            secret,
+            last_nonce: Default::default(),
+        }
+    }
+
+    /// Load the secret from a file, ignoring the surrounding whitespaces.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            Error::InternalError(format!(
+                "Unable to read auth token {:?}: {}",
+                path, e
+            ))
+        })?;
+        let secret = content.trim();
+        if secret.is_empty() {
+            return Err(Error::InternalError(format!(
+                "Auth token {:?} is empty",
+                path
+            )));
+        }
+        Ok(Self::new(secret.as_bytes().to_vec()))
+    }
+
+    fn tag(&self, nonce: u64, payload: &[u8]) -> HashValue {
+        let mut keyed = self.secret.clone();
+        keyed.extend_from_slice(&nonce.to_be_bytes());
+        keyed.extend_from_slice(payload);
+        HashValue::sha3_256_of(&keyed)
+    }
+
+    fn seal(&self, payload: &[u8]) -> Vec<u8> {
+        self.seal_at(payload, now_micros())
+    }
+
+    fn seal_at(&self, payload: &[u8], now: u64) -> Vec<u8> {
+        let last_nonce = self
+            .last_nonce
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+                Some(now.max(last + 1))
+            })
+            .expect("always updated");
+        let nonce = now.max(last_nonce + 1);
+        let mut message = self.tag(nonce, payload).to_vec();
+        message.extend_from_slice(&nonce.to_be_bytes());
+        message.extend_from_slice(payload);
+        message
+    }
+
+    fn open<'a>(&self, message: &'a [u8]) -> Result<&'a [u8], Error> {
+        self.open_at(message, now_micros())
+    }
+
+    fn open_at<'a>(
+        &self, message: &'a [u8], now: u64,
+    ) -> Result<&'a [u8], Error> {
+        const NONCE_LENGTH: usize = std::mem::size_of::<u64>();
+        if message.len() < HashValue::LENGTH + NONCE_LENGTH {
+            return Err(Error::Unauthenticated);
+        }
+        let (tag, rest) = message.split_at(HashValue::LENGTH);
+        let (nonce, payload) = rest.split_at(NONCE_LENGTH);
+        let nonce = u64::from_be_bytes(nonce.try_into().unwrap());
+        // Compare in constant time to not leak the expected tag.
+        let diff = self
+            .tag(nonce, payload)
+            .as_ref()
+            .iter()
+            .zip(tag)
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+        if diff != 0 {
+            return Err(Error::Unauthenticated);
+        }
+        let max_age = NONCE_MAX_AGE.as_micros() as u64;
+        if nonce.saturating_add(max_age) < now {
+            return Err(Error::Unauthenticated);
+        }
+        // Only accept a nonce which is later than all the accepted ones.
+        self.last_nonce
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+                if nonce > last {
+                    Some(nonce)
+                } else {
+                    None
+                }
+            })
+            .map_err(|_| Error::Unauthenticated)?;
+        Ok(payload)
+    }
+}
+
+fn now_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is after the unix epoch")
+        .as_micros() as u64
 }
 
 pub fn execute(
     storage: PersistentSafetyStorage, listen_addr: SocketAddr,
     verify_vote_proposal_signature: bool, export_consensus_key: bool,
     network_timeout_ms: u64, vrf_private_key: Option<ConsensusVRFPrivateKey>,
+    author: AccountAddress, auth_token: AuthToken,
 )
 {
     let mut safety_rules = SafetyRules::new(
@@ -46,7 +184,7 @@ pub fn execute(
         verify_vote_proposal_signature,
         export_consensus_key,
         vrf_private_key,
-        Default::default(),
+        author,
     );
     if let Err(e) = safety_rules.consensus_state() {
         diem_warn!("Unable to print consensus state: {}", e);
@@ -57,9 +195,11 @@ pub fn execute(
         NetworkServer::new("safety-rules", listen_addr, network_timeout_ms);
 
     loop {
-        if let Err(e) =
-            process_one_message(&mut network_server, &mut serializer_service)
-        {
+        if let Err(e) = process_one_message(
+            &mut network_server,
+            &mut serializer_service,
+            &auth_token,
+        ) {
             diem_warn!("Failed to process message: {}", e);
         }
     }
@@ -67,41 +207,183 @@ pub fn execute(
 
 fn process_one_message(
     network_server: &mut NetworkServer,
-    serializer_service: &mut SerializerService,
+    serializer_service: &mut SerializerService, auth_token: &AuthToken,
 ) -> Result<(), Error>
 {
     let request = network_server.read()?;
-    let response = serializer_service.handle_message(request)?;
+    let response = match auth_token.open(&request) {
+        Ok(payload) => serializer_service.handle_message(payload.to_vec())?,
+        // Every request returns a `Result<_, Error>`, so the client can
+        // decode the error whatever the request is.
+        Err(e) => {
+            diem_warn!("Rejected unauthenticated request");
+            bcs::to_bytes(&Result::<(), Error>::Err(e))?
+        }
+    };
     network_server.write(&response)?;
     Ok(())
 }
 
 struct RemoteClient {
-    network_client: NetworkClient,
+    network_client: Arc<Mutex<NetworkClient>>,
+    auth_token: AuthToken,
 }
 
 impl RemoteClient {
-    pub fn new(network_client: NetworkClient) -> Self {
-        Self { network_client }
+    pub fn new(
+        network_client: Arc<Mutex<NetworkClient>>, auth_token: AuthToken,
+    ) -> Self
+    {
+        Self {
+            network_client,
+            auth_token,
+        }
     }
 
     fn process_one_message(&mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
-        self.network_client.write(&input)?;
-        self.network_client.read().map_err(|e| e.into())
+        // Hold the lock until the response is read, so the responses of the
+        // clients sharing the connection are not mixed up. The request is
+        // sealed under the lock to send the nonces in the increasing order.
+        let mut network_client = self.network_client.lock();
+        network_client.write(&self.auth_token.seal(input))?;
+        network_client.read().map_err(|e| e.into())
     }
 }
 
 impl TSerializerClient for RemoteClient {
     fn request(&mut self, input: SafetyRulesInput) -> Result<Vec<u8>, Error> {
         let input_message = bcs::to_bytes(&input)?;
+        let mut retry_delay = INITIAL_RETRY_DELAY;
+        let mut attempt = 1;
         loop {
             match self.process_one_message(&input_message) {
-                Err(err) => diem_warn!(
-                    "Failed to communicate with SafetyRules service: {}",
-                    err
-                ),
                 Ok(value) => return Ok(value),
+                Err(err) if attempt < REQUEST_ATTEMPTS => {
+                    diem_warn!(
+                        "Failed to communicate with SafetyRules service: {}, \
+                         retry in {:?}",
+                        err,
+                        retry_delay
+                    );
+                }
+                Err(err) => return Err(err),
             }
+            thread::sleep(retry_delay);
+            retry_delay *= 2;
+            attempt += 1;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{execute, AuthToken, RemoteClient, NONCE_MAX_AGE};
+    use crate::{
+        serializer::{SafetyRulesInput, SerializerClient},
+        test_utils, ConsensusState, Error, TSafetyRules,
+    };
+    use diem_config::utils;
+    use diem_infallible::Mutex;
+    use diem_secure_net::NetworkClient;
+    use diem_types::validator_signer::ValidatorSigner;
+    use std::{
+        net::{IpAddr, Ipv4Addr, SocketAddr},
+        sync::Arc,
+        thread,
+    };
+
+    #[test]
+    fn test_auth_token() {
+        let token = AuthToken::new(b"secret".to_vec());
+        let message = token.seal(b"payload");
+        assert_eq!(token.open(&message).unwrap(), b"payload");
+
+        let other = AuthToken::new(b"other secret".to_vec());
+        assert_eq!(other.open(&message), Err(Error::Unauthenticated));
+
+        let mut tampered = token.seal(b"payload");
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(token.open(&tampered), Err(Error::Unauthenticated));
+        assert_eq!(token.open(b"short"), Err(Error::Unauthenticated));
+    }
+
+    #[test]
+    fn test_auth_token_rejects_replay() {
+        let client = AuthToken::new(b"secret".to_vec());
+        let service = AuthToken::new(b"secret".to_vec());
+
+        // The nonces increase even if the clock does not move.
+        let first = client.seal_at(b"first", 1000);
+        let second = client.seal_at(b"second", 1000);
+        assert_eq!(service.open_at(&first, 1000).unwrap(), b"first");
+        assert_eq!(service.open_at(&second, 1000).unwrap(), b"second");
+
+        // A request can only be accepted once, and not after a later one.
+        assert_eq!(service.open_at(&second, 1000), Err(Error::Unauthenticated));
+        assert_eq!(service.open_at(&first, 1000), Err(Error::Unauthenticated));
+
+        // A stale request is rejected by a service which has not seen it.
+        let restarted = AuthToken::new(b"secret".to_vec());
+        let stale = NONCE_MAX_AGE.as_micros() as u64 + 1002;
+        assert_eq!(
+            restarted.open_at(&first, stale),
+            Err(Error::Unauthenticated)
+        );
+        let fresh = client.seal_at(b"fresh", stale);
+        assert_eq!(restarted.open_at(&fresh, stale).unwrap(), b"fresh");
+    }
+
+    #[test]
+    fn test_service_rejects_unauthenticated_client() {
+        let signer = ValidatorSigner::from_int(0);
+        let storage = test_utils::test_storage(&signer);
+        let server_addr = SocketAddr::new(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            utils::get_available_port(),
+        );
+        let author = signer.author();
+        thread::spawn(move || {
+            execute(
+                storage,
+                server_addr,
+                false,
+                false,
+                5_000,
+                None,
+                author,
+                AuthToken::new(b"secret".to_vec()),
+            )
+        });
+        let network_client = Arc::new(Mutex::new(NetworkClient::new(
+            "safety-rules",
+            server_addr,
+            5_000,
+        )));
+
+        // A request without any tag.
+        let request = bcs::to_bytes(&SafetyRulesInput::ConsensusState).unwrap();
+        let response = {
+            let mut network_client = network_client.lock();
+            network_client.write(&request).unwrap();
+            network_client.read().unwrap()
+        };
+        let response: Result<ConsensusState, Error> =
+            bcs::from_bytes(&response).unwrap();
+        assert_eq!(response, Err(Error::Unauthenticated));
+
+        // A request tagged with another secret.
+        let mut client =
+            SerializerClient::new_client(Box::new(RemoteClient::new(
+                network_client.clone(),
+                AuthToken::new(b"other secret".to_vec()),
+            )));
+        assert_eq!(client.consensus_state(), Err(Error::Unauthenticated));
+
+        let mut client =
+            SerializerClient::new_client(Box::new(RemoteClient::new(
+                network_client,
+                AuthToken::new(b"secret".to_vec()),
+            )));
+        client.consensus_state().unwrap();
+    }
+}
@@ -12,6 +12,7 @@ use crate::{
     error::Error,
     logging::{LogEntry, LogEvent, SafetyLogSchema},
     persistent_safety_storage::PersistentSafetyStorage,
+    slashing_protection::SignedProposal,
     t_safety_rules::TSafetyRules,
 };
 use consensus_types::{
@@ -28,7 +29,7 @@ use consensus_types::{
 use diem_crypto::{
     hash::{CryptoHash, HashValue},
     traits::Signature,
-    PrivateKey,
+    PrivateKey, VRFPrivateKey,
 };
 use diem_logger::prelude::*;
 use diem_types::{
@@ -37,8 +38,13 @@ use diem_types::{
     epoch_change::EpochChangeProof,
     epoch_state::EpochState,
     ledger_info::LedgerInfo,
+    transaction::{
+        ConflictSignature, DisputePayload, RawTransaction, SignedTransaction,
+        TransactionPayload,
+    },
     validator_config::{
         ConsensusPublicKey, ConsensusSignature, ConsensusVRFPrivateKey,
+        ConsensusVRFProof, ConsensusVRFPublicKey,
     },
     waypoint::Waypoint,
 };
@@ -487,9 +493,12 @@ impl SafetyRules {
         }
 
         let signature = self.sign(&block_data)?;
-        Ok(Block::new_proposal_from_block_data_and_signature(
+        let block = Block::new_proposal_from_block_data_and_signature(
             block_data, signature, None,
-        ))
+        );
+        self.persistent_storage
+            .record_signed_proposal(SignedProposal::new(&block))?;
+        Ok(block)
     }
 
     fn guarded_sign_timeout(
@@ -524,6 +533,144 @@ impl SafetyRules {
         Ok(signature)
     }
 
+    fn vrf_private_key(&self) -> Result<&ConsensusVRFPrivateKey, Error> {
+        self.vrf_private_key
+            .as_ref()
+            .ok_or_else(|| Error::NotInitialized("vrf_private_key".into()))
+    }
+
+    fn guarded_sign_transaction(
+        &mut self, raw_tx: RawTransaction,
+    ) -> Result<SignedTransaction, Error> {
+        let author = self.persistent_storage.author()?;
+        if raw_tx.sender() != author {
+            return Err(Error::InvalidTransaction(format!(
+                "sender {} is not the validator {}",
+                raw_tx.sender(),
+                author
+            )));
+        }
+        // Only the PoS transactions sent by validators are signed, so the key
+        // cannot be used for anything else even if the node is compromised.
+        match raw_tx.payload() {
+            // Signing conflicting pivot decisions is allowed, and a decision
+            // only takes effect with the signatures of the committee.
+            TransactionPayload::PivotDecision(_) => {}
+            TransactionPayload::Election(election) => {
+                let (public_key, vrf_public_key) =
+                    self.guarded_public_keys()?;
+                if election.public_key != public_key
+                    || election.vrf_public_key != vrf_public_key
+                {
+                    return Err(Error::InvalidTransaction(
+                        "election of another node".into(),
+                    ));
+                }
+            }
+            // The staking events of the PoW chain are packed by the proposer,
+            // so they are only signed by the validators of the current epoch.
+            // Every validator executes them only if they match the staking
+            // events between the pivot decisions of the block and its parent.
+            TransactionPayload::Register(_)
+            | TransactionPayload::UpdateVotingPower(_)
+            | TransactionPayload::Retire(_) => {
+                self.signer()?;
+            }
+            TransactionPayload::Dispute(dispute) => {
+                self.verify_dispute(dispute, author)?;
+            }
+            _ => {
+                return Err(Error::InvalidTransaction(
+                    "unsupported transaction payload".into(),
+                ))
+            }
+        }
+        self.persistent_storage.sign_transaction(raw_tx)
+    }
+
+    /// Check that `dispute` proves that another validator of the current
+    /// epoch has signed two proposals or two votes in the same round, so the
+    /// key cannot be used to get an honest validator slashed.
+    fn verify_dispute(
+        &self, dispute: &DisputePayload, author: Author,
+    ) -> Result<(), Error> {
+        let epoch_state = self.epoch_state()?;
+        let verifier = &epoch_state.verifier;
+        if dispute.address == author {
+            return Err(Error::InvalidTransaction(
+                "dispute of the validator itself".into(),
+            ));
+        }
+        if verifier.get_public_key(&dispute.address).as_ref()
+            != Some(&dispute.bls_pub_key)
+            || verifier.get_vrf_public_key(&dispute.address)
+                != Some(Some(dispute.vrf_pub_key.clone()))
+        {
+            return Err(Error::InvalidTransaction(format!(
+                "{} is not a validator of epoch {}",
+                dispute.address, epoch_state.epoch
+            )));
+        }
+
+        let invalid = |e: String| {
+            Error::InvalidTransaction(format!("invalid dispute: {}", e))
+        };
+        // The (epoch, round, block id) of the two signed messages.
+        let (first, second) = match &dispute.conflicting_votes {
+            ConflictSignature::Proposal((first, second)) => {
+                let decode = |bytes: &[u8]| -> Result<_, Error> {
+                    let block: Block = bcs::from_bytes(bytes)
+                        .map_err(|e| invalid(e.to_string()))?;
+                    if block.author() != Some(dispute.address) {
+                        return Err(invalid(
+                            "proposal of another author".into(),
+                        ));
+                    }
+                    block
+                        .validate_signature(verifier)
+                        .map_err(|e| invalid(e.to_string()))?;
+                    Ok((block.epoch(), block.round(), block.id()))
+                };
+                (decode(first)?, decode(second)?)
+            }
+            ConflictSignature::Vote((first, second)) => {
+                let decode = |bytes: &[u8]| -> Result<_, Error> {
+                    let vote: Vote = bcs::from_bytes(bytes)
+                        .map_err(|e| invalid(e.to_string()))?;
+                    if vote.author() != dispute.address {
+                        return Err(invalid("vote of another author".into()));
+                    }
+                    vote.verify(verifier)
+                        .map_err(|e| invalid(e.to_string()))?;
+                    let proposed = vote.vote_data().proposed();
+                    Ok((proposed.epoch(), proposed.round(), proposed.id()))
+                };
+                (decode(first)?, decode(second)?)
+            }
+        };
+        if first.0 != second.0 || first.1 != second.1 || first.2 == second.2 {
+            return Err(invalid("the signed messages do not conflict".into()));
+        }
+        Ok(())
+    }
+
+    fn guarded_compute_vrf_proof(
+        &mut self, seed: &[u8],
+    ) -> Result<ConsensusVRFProof, Error> {
+        self.vrf_private_key()?
+            .compute(seed)
+            .map_err(|e| Error::InternalError(e.to_string()))
+    }
+
+    fn guarded_public_keys(
+        &mut self,
+    ) -> Result<(ConsensusPublicKey, ConsensusVRFPublicKey), Error> {
+        Ok((
+            self.persistent_storage.consensus_public_key(),
+            self.vrf_private_key()?.public_key(),
+        ))
+    }
+
     pub fn start_voting(&mut self, initialize: bool) -> Result<(), Error> {
         if initialize {
             // If the node starts voting with its local safety data,
@@ -573,6 +720,27 @@ impl TSafetyRules for SafetyRules {
         let cb = || self.guarded_sign_timeout(timeout);
         run_and_log(cb, |log| log.round(timeout.round()), LogEntry::SignTimeout)
     }
+
+    fn sign_transaction(
+        &mut self, raw_tx: RawTransaction,
+    ) -> Result<SignedTransaction, Error> {
+        let cb = || self.guarded_sign_transaction(raw_tx);
+        run_and_log(cb, |log| log, LogEntry::SignTransaction)
+    }
+
+    fn compute_vrf_proof(
+        &mut self, seed: &[u8],
+    ) -> Result<ConsensusVRFProof, Error> {
+        let cb = || self.guarded_compute_vrf_proof(seed);
+        run_and_log(cb, |log| log, LogEntry::ComputeVrfProof)
+    }
+
+    fn public_keys(
+        &mut self,
+    ) -> Result<(ConsensusPublicKey, ConsensusVRFPublicKey), Error> {
+        let cb = || self.guarded_public_keys();
+        run_and_log(cb, |log| log, LogEntry::PublicKeys)
+    }
 }
 
 fn run_and_log<F, L, R>(
@@ -9,7 +9,7 @@ use crate::{
     local_client::LocalClient,
     persistent_safety_storage::PersistentSafetyStorage,
     process::ProcessService,
-    remote_service::RemoteService,
+    remote_service::{AuthToken, RemoteService},
     serializer::{SerializerClient, SerializerService},
     thread::ThreadService,
     SafetyRules, TSafetyRules,
@@ -71,9 +71,12 @@ pub struct SafetyRulesManager {
 impl SafetyRulesManager {
     pub fn new(config: &SafetyRulesConfig) -> Self {
         if let SafetyRulesService::Process(conf) = &config.service {
+            let auth_token = AuthToken::load(&conf.auth_token_path)
+                .unwrap_or_else(|e| panic!("{}", e));
             return Self::new_process(
                 conf.server_address(),
                 config.network_timeout_ms,
+                auth_token,
             );
         }
 
@@ -81,19 +84,22 @@ impl SafetyRulesManager {
         let verify_vote_proposal_signature =
             config.verify_vote_proposal_signature;
         let export_consensus_key = config.export_consensus_key;
+        let vrf_private_key =
+            config.vrf_private_key.as_ref().map(|key| key.private_key());
         let author = config.test.as_ref().map(|c| c.author).unwrap_or_default();
         match config.service {
             SafetyRulesService::Local => Self::new_local(
                 storage,
                 verify_vote_proposal_signature,
                 export_consensus_key,
-                config.vrf_private_key.as_ref().map(|key| key.private_key()),
+                vrf_private_key,
                 author,
             ),
             SafetyRulesService::Serializer => Self::new_serializer(
                 storage,
                 verify_vote_proposal_signature,
                 export_consensus_key,
+                vrf_private_key,
                 author,
             ),
             SafetyRulesService::Thread => Self::new_thread(
@@ -101,6 +107,8 @@ impl SafetyRulesManager {
                 verify_vote_proposal_signature,
                 export_consensus_key,
                 config.network_timeout_ms,
+                vrf_private_key,
+                author,
             ),
             _ => {
                 panic!("Unimplemented SafetyRulesService: {:?}", config.service)
@@ -129,8 +137,12 @@ impl SafetyRulesManager {
         }
     }
 
-    pub fn new_process(server_addr: SocketAddr, timeout_ms: u64) -> Self {
-        let process_service = ProcessService::new(server_addr, timeout_ms);
+    pub fn new_process(
+        server_addr: SocketAddr, timeout_ms: u64, auth_token: AuthToken,
+    ) -> Self
+    {
+        let process_service =
+            ProcessService::new(server_addr, timeout_ms, auth_token);
         Self {
             internal_safety_rules: SafetyRulesWrapper::Process(process_service),
         }
@@ -138,15 +150,16 @@ impl SafetyRulesManager {
 
     pub fn new_serializer(
         storage: PersistentSafetyStorage, verify_vote_proposal_signature: bool,
-        export_consensus_key: bool, author: AccountAddress,
+        export_consensus_key: bool,
+        vrf_private_key: Option<ConsensusVRFPrivateKey>,
+        author: AccountAddress,
     ) -> Self
     {
         let safety_rules = SafetyRules::new(
             storage,
             verify_vote_proposal_signature,
             export_consensus_key,
-            // TODO(lpl): Support this?
-            None,
+            vrf_private_key,
             author,
         );
         let serializer_service = SerializerService::new(safety_rules);
@@ -160,6 +173,8 @@ impl SafetyRulesManager {
     pub fn new_thread(
         storage: PersistentSafetyStorage, verify_vote_proposal_signature: bool,
         export_consensus_key: bool, timeout_ms: u64,
+        vrf_private_key: Option<ConsensusVRFPrivateKey>,
+        author: AccountAddress,
     ) -> Self
     {
         let thread = ThreadService::new(
@@ -167,6 +182,8 @@ impl SafetyRulesManager {
             verify_vote_proposal_signature,
             export_consensus_key,
             timeout_ms,
+            vrf_private_key,
+            author,
         );
         Self {
             internal_safety_rules: SafetyRulesWrapper::Thread(thread),
@@ -15,7 +15,12 @@ use consensus_types::{
 };
 use diem_infallible::RwLock;
 use diem_types::{
-    epoch_change::EpochChangeProof, validator_config::ConsensusSignature,
+    epoch_change::EpochChangeProof,
+    transaction::{RawTransaction, SignedTransaction},
+    validator_config::{
+        ConsensusPublicKey, ConsensusSignature, ConsensusVRFProof,
+        ConsensusVRFPublicKey,
+    },
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -27,6 +32,9 @@ pub enum SafetyRulesInput {
     ConstructAndSignVote(Box<MaybeSignedVoteProposal>),
     SignProposal(Box<BlockData>),
     SignTimeout(Box<Timeout>),
+    SignTransaction(Box<RawTransaction>),
+    ComputeVrfProof(Vec<u8>),
+    PublicKeys,
 }
 
 pub struct SerializerService {
@@ -59,6 +67,15 @@ impl SerializerService {
             SafetyRulesInput::SignTimeout(timeout) => {
                 bcs::to_bytes(&self.internal.sign_timeout(&timeout))
             }
+            SafetyRulesInput::SignTransaction(raw_tx) => {
+                bcs::to_bytes(&self.internal.sign_transaction(*raw_tx))
+            }
+            SafetyRulesInput::ComputeVrfProof(seed) => {
+                bcs::to_bytes(&self.internal.compute_vrf_proof(&seed))
+            }
+            SafetyRulesInput::PublicKeys => {
+                bcs::to_bytes(&self.internal.public_keys())
+            }
         };
 
         Ok(output?)
@@ -133,6 +150,39 @@ impl TSafetyRules for SerializerClient {
         ))?;
         bcs::from_bytes(&response)?
     }
+
+    fn sign_transaction(
+        &mut self, raw_tx: RawTransaction,
+    ) -> Result<SignedTransaction, Error> {
+        let _timer = counters::start_timer(
+            "external",
+            LogEntry::SignTransaction.as_str(),
+        );
+        let response =
+            self.request(SafetyRulesInput::SignTransaction(Box::new(raw_tx)))?;
+        bcs::from_bytes(&response)?
+    }
+
+    fn compute_vrf_proof(
+        &mut self, seed: &[u8],
+    ) -> Result<ConsensusVRFProof, Error> {
+        let _timer = counters::start_timer(
+            "external",
+            LogEntry::ComputeVrfProof.as_str(),
+        );
+        let response =
+            self.request(SafetyRulesInput::ComputeVrfProof(seed.to_vec()))?;
+        bcs::from_bytes(&response)?
+    }
+
+    fn public_keys(
+        &mut self,
+    ) -> Result<(ConsensusPublicKey, ConsensusVRFPublicKey), Error> {
+        let _timer =
+            counters::start_timer("external", LogEntry::PublicKeys.as_str());
+        let response = self.request(SafetyRulesInput::PublicKeys)?;
+        bcs::from_bytes(&response)?
+    }
 }
 
 pub trait TSerializerClient: Send + Sync {
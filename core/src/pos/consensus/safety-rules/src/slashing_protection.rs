@@ -0,0 +1,341 @@
+// Copyright 2022 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Slashing-protection interchange between validator hosts.
+//!
+//! When a validator is migrated to another host, the safety data of the old
+//! host must be carried over, otherwise the new host may sign a proposal or a
+//! vote in a round that has already been signed and get slashed. The data is
+//! exported as a JSON file with the following layout:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "author": "<hex encoded validator address>",
+//!   "waypoint": "<version>:<hex encoded ledger info hash>",
+//!   "epoch": 12,
+//!   "last_voted_round": 3456,
+//!   "preferred_round": 3454,
+//!   "signed_proposals": [
+//!     { "epoch": 12, "round": 3450, "block_id": "<hex encoded block id>" }
+//!   ]
+//! }
+//! ```
+//!
+//! Importing the file never lowers the watermarks of the target storage. After
+//! the import, the validator refuses to sign any proposal or vote at or below
+//! the imported `last_voted_round` (or the highest round of the imported
+//! signed proposals) in the imported epoch. Timeouts are refused below that
+//! round or at or below the imported `preferred_round`; a timeout in the
+//! watermark round itself is still signed, as it cannot equivocate with the
+//! vote of that round.
+//!
+//! Both operations must be run while the node owning the storage is stopped.
+
+use crate::Error;
+use consensus_types::{
+    block::Block,
+    common::{Author, Round},
+    safety_data::SafetyData,
+};
+use diem_crypto::HashValue;
+use diem_global_constants::{
+    OWNER_ACCOUNT, SAFETY_DATA, SIGNED_PROPOSALS, WAYPOINT,
+};
+use diem_secure_storage::{KVStorage, Storage};
+use diem_types::waypoint::Waypoint;
+use serde::{Deserialize, Serialize};
+use std::cmp::max;
+
+/// The version of the interchange format produced by this module.
+pub const INTERCHANGE_FORMAT_VERSION: u64 = 1;
+
+/// The number of signed proposals kept in the secure storage. Each proposal is
+/// stored in the slot of its round modulo this number, so recording one only
+/// updates a single key.
+pub const MAX_SIGNED_PROPOSAL_HISTORY: usize = 256;
+
+/// A proposal signed by the validator.
+#[derive(
+    Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize,
+)]
+pub struct SignedProposal {
+    pub epoch: u64,
+    pub round: Round,
+    pub block_id: HashValue,
+}
+
+impl SignedProposal {
+    pub fn new(block: &Block) -> Self {
+        Self {
+            epoch: block.epoch(),
+            round: block.round(),
+            block_id: block.id(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SlashingProtectionInterchange {
+    pub version: u64,
+    pub author: Author,
+    pub waypoint: Waypoint,
+    pub epoch: u64,
+    pub last_voted_round: Round,
+    pub preferred_round: Round,
+    pub signed_proposals: Vec<SignedProposal>,
+}
+
+fn signed_proposal_key(slot: u64) -> String {
+    format!("{}_{}", SIGNED_PROPOSALS, slot)
+}
+
+fn signed_proposal(
+    storage: &Storage, key: &str,
+) -> Result<Option<SignedProposal>, Error> {
+    match storage.get(key) {
+        Ok(response) => Ok(Some(response.value)),
+        Err(diem_secure_storage::Error::KeyNotSet(_)) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Read the signed proposal history from `storage`, sorted by epoch and round.
+/// A storage without history is treated as one that has not signed any
+/// proposal.
+pub fn signed_proposals(
+    storage: &Storage,
+) -> Result<Vec<SignedProposal>, Error> {
+    let mut history = vec![];
+    for slot in 0..MAX_SIGNED_PROPOSAL_HISTORY as u64 {
+        if let Some(proposal) =
+            signed_proposal(storage, &signed_proposal_key(slot))?
+        {
+            history.push(proposal);
+        }
+    }
+    history.sort();
+    Ok(history)
+}
+
+/// Record `proposal` in the slot of its round, unless the slot already holds
+/// a later proposal.
+pub fn insert_signed_proposal(
+    storage: &mut Storage, proposal: &SignedProposal,
+) -> Result<(), Error> {
+    let key = signed_proposal_key(
+        proposal.round % MAX_SIGNED_PROPOSAL_HISTORY as u64,
+    );
+    match signed_proposal(storage, &key)? {
+        Some(existing) if existing >= *proposal => Ok(()),
+        _ => Ok(storage.set(&key, proposal.clone())?),
+    }
+}
+
+/// Merge `proposals` into the signed proposal history in `storage`.
+pub fn insert_signed_proposals(
+    storage: &mut Storage, proposals: &[SignedProposal],
+) -> Result<(), Error> {
+    for proposal in proposals {
+        insert_signed_proposal(storage, proposal)?;
+    }
+    Ok(())
+}
+
+/// Export the slashing-protection data of an initialized secure storage.
+pub fn export_slashing_protection(
+    storage: &Storage,
+) -> Result<SlashingProtectionInterchange, Error> {
+    let safety_data: SafetyData = storage.get(SAFETY_DATA)?.value;
+    Ok(SlashingProtectionInterchange {
+        version: INTERCHANGE_FORMAT_VERSION,
+        author: storage.get(OWNER_ACCOUNT)?.value,
+        waypoint: storage.get(WAYPOINT)?.value,
+        epoch: safety_data.epoch,
+        last_voted_round: safety_data.last_voted_round,
+        preferred_round: safety_data.preferred_round,
+        signed_proposals: signed_proposals(storage)?,
+    })
+}
+
+/// Import `interchange` into `storage`, which is either empty or owned by the
+/// same validator. Returns the resulting safety data.
+pub fn import_slashing_protection(
+    storage: &mut Storage, interchange: &SlashingProtectionInterchange,
+) -> Result<SafetyData, Error> {
+    if interchange.version != INTERCHANGE_FORMAT_VERSION {
+        return Err(Error::InternalError(format!(
+            "unsupported interchange format version {}, expected {}",
+            interchange.version, INTERCHANGE_FORMAT_VERSION
+        )));
+    }
+
+    // Signed proposals are covered by the watermark of their epoch.
+    let last_signed_round = interchange
+        .signed_proposals
+        .iter()
+        .filter(|p| p.epoch == interchange.epoch)
+        .map(|p| p.round)
+        .max()
+        .unwrap_or(0);
+    let imported = SafetyData::new(
+        interchange.epoch,
+        max(interchange.last_voted_round, last_signed_round),
+        interchange.preferred_round,
+        None,
+    );
+
+    let existing = match storage.get::<SafetyData>(SAFETY_DATA) {
+        Ok(response) => Some(response.value),
+        Err(diem_secure_storage::Error::KeyNotSet(_)) => None,
+        Err(e) => return Err(e.into()),
+    };
+    let safety_data = match existing {
+        None => {
+            storage.set(OWNER_ACCOUNT, interchange.author)?;
+            storage.set(WAYPOINT, interchange.waypoint)?;
+            imported
+        }
+        Some(existing) => {
+            let author: Author = storage.get(OWNER_ACCOUNT)?.value;
+            if author != interchange.author {
+                return Err(Error::InternalError(format!(
+                    "interchange author {} does not match storage owner {}",
+                    interchange.author, author
+                )));
+            }
+            if existing.epoch > imported.epoch {
+                existing
+            } else if existing.epoch < imported.epoch {
+                storage.set(WAYPOINT, interchange.waypoint)?;
+                imported
+            } else {
+                SafetyData::new(
+                    existing.epoch,
+                    max(existing.last_voted_round, imported.last_voted_round),
+                    max(existing.preferred_round, imported.preferred_round),
+                    existing.last_vote,
+                )
+            }
+        }
+    };
+
+    insert_signed_proposals(storage, &interchange.signed_proposals)?;
+    storage.set(SAFETY_DATA, safety_data.clone())?;
+    Ok(safety_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diem_secure_storage::InMemoryStorage;
+
+    fn interchange(
+        author: Author, epoch: u64, last_voted_round: Round,
+        signed_rounds: &[Round],
+    ) -> SlashingProtectionInterchange
+    {
+        SlashingProtectionInterchange {
+            version: INTERCHANGE_FORMAT_VERSION,
+            author,
+            waypoint: Waypoint::default(),
+            epoch,
+            last_voted_round,
+            preferred_round: 1,
+            signed_proposals: signed_rounds
+                .iter()
+                .map(|round| SignedProposal {
+                    epoch,
+                    round: *round,
+                    block_id: HashValue::random(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let author = Author::random();
+        let mut source = Storage::from(InMemoryStorage::new());
+        import_slashing_protection(
+            &mut source,
+            &interchange(author, 3, 10, &[8, 12]),
+        )
+        .unwrap();
+
+        let exported = export_slashing_protection(&source).unwrap();
+        assert_eq!(exported.author, author);
+        assert_eq!(exported.epoch, 3);
+        // the highest signed proposal raises the watermark
+        assert_eq!(exported.last_voted_round, 12);
+        assert_eq!(exported.signed_proposals.len(), 2);
+
+        let mut target = Storage::from(InMemoryStorage::new());
+        import_slashing_protection(&mut target, &exported).unwrap();
+        assert_eq!(export_slashing_protection(&target).unwrap(), exported);
+    }
+
+    #[test]
+    fn test_import_never_lowers_watermarks() {
+        let author = Author::random();
+        let mut storage = Storage::from(InMemoryStorage::new());
+        import_slashing_protection(
+            &mut storage,
+            &interchange(author, 3, 20, &[]),
+        )
+        .unwrap();
+
+        let safety_data = import_slashing_protection(
+            &mut storage,
+            &interchange(author, 3, 10, &[]),
+        )
+        .unwrap();
+        assert_eq!(safety_data.last_voted_round, 20);
+
+        // data of an older epoch is ignored
+        let safety_data = import_slashing_protection(
+            &mut storage,
+            &interchange(author, 2, 30, &[]),
+        )
+        .unwrap();
+        assert_eq!(safety_data.epoch, 3);
+        assert_eq!(safety_data.last_voted_round, 20);
+    }
+
+    #[test]
+    fn test_signed_proposal_history_is_bounded() {
+        let mut storage = Storage::from(InMemoryStorage::new());
+        let rounds: Vec<Round> =
+            (1..=MAX_SIGNED_PROPOSAL_HISTORY as Round + 2).collect();
+        let mut proposals =
+            interchange(Author::random(), 3, 0, &rounds).signed_proposals;
+        // recording an older proposal keeps the later one in its slot
+        proposals.reverse();
+        insert_signed_proposals(&mut storage, &proposals).unwrap();
+
+        let history = signed_proposals(&storage).unwrap();
+        assert_eq!(history.len(), MAX_SIGNED_PROPOSAL_HISTORY);
+        assert_eq!(history[0].round, 3);
+        assert_eq!(
+            history.last().unwrap().round,
+            MAX_SIGNED_PROPOSAL_HISTORY as Round + 2
+        );
+    }
+
+    #[test]
+    fn test_import_rejects_other_author() {
+        let mut storage = Storage::from(InMemoryStorage::new());
+        import_slashing_protection(
+            &mut storage,
+            &interchange(Author::random(), 3, 20, &[]),
+        )
+        .unwrap();
+
+        assert!(import_slashing_protection(
+            &mut storage,
+            &interchange(Author::random(), 3, 30, &[]),
+        )
+        .is_err());
+    }
+}
@@ -11,7 +11,12 @@ use consensus_types::{
     vote_proposal::MaybeSignedVoteProposal,
 };
 use diem_types::{
-    epoch_change::EpochChangeProof, validator_config::ConsensusSignature,
+    epoch_change::EpochChangeProof,
+    transaction::{RawTransaction, SignedTransaction},
+    validator_config::{
+        ConsensusPublicKey, ConsensusSignature, ConsensusVRFProof,
+        ConsensusVRFPublicKey,
+    },
 };
 
 /// Interface for SafetyRules
@@ -44,6 +49,25 @@ pub trait TSafetyRules {
         &mut self, timeout: &Timeout,
     ) -> Result<ConsensusSignature, Error>;
 
+    /// As the holder of the private key, SafetyRules also signs the PoS
+    /// transactions sent by this validator, like pivot decisions, elections
+    /// and disputes.
+    fn sign_transaction(
+        &mut self, raw_tx: RawTransaction,
+    ) -> Result<SignedTransaction, Error>;
+
+    /// As the holder of the VRF private key, SafetyRules computes the VRF
+    /// proofs used by the proposer election and the committee election.
+    fn compute_vrf_proof(
+        &mut self, seed: &[u8],
+    ) -> Result<ConsensusVRFProof, Error>;
+
+    /// Returns the consensus public key and the VRF public key of this
+    /// validator.
+    fn public_keys(
+        &mut self,
+    ) -> Result<(ConsensusPublicKey, ConsensusVRFPublicKey), Error>;
+
     /// Allow the safety rule to start voting with saved secure data from
     /// another node.
     fn start_voting(&mut self, _initialize: bool) -> Result<(), Error> {
@@ -14,8 +14,14 @@ fn test_reconnect() {
     let storage = test_utils::test_storage(&signer);
     // test value for network timeout, in milliseconds.
     let network_timeout = 5_000;
-    let safety_rules_manager =
-        SafetyRulesManager::new_thread(storage, false, false, network_timeout);
+    let safety_rules_manager = SafetyRulesManager::new_thread(
+        storage,
+        false,
+        false,
+        network_timeout,
+        None,
+        signer.author(),
+    );
 
     // Verify that after a client has disconnected a new client will connect and
     // resume operations
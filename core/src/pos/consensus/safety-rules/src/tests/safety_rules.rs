@@ -5,9 +5,32 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
-use crate::{test_utils, tests::suite, SafetyRules};
-use diem_crypto::{bls::BLSPrivateKey, Uniform};
-use diem_types::validator_signer::ValidatorSigner;
+use crate::{
+    persistent_safety_storage::PersistentSafetyStorage, slashing_protection,
+    test_utils, tests::suite, Error, SafetyRules, TSafetyRules,
+};
+use consensus_types::{timeout::Timeout, vote::Vote, vote_data::VoteData};
+use diem_crypto::{
+    bls::BLSPrivateKey, HashValue, PrivateKey, Uniform, VRFPrivateKey,
+};
+use diem_secure_storage::{InMemoryStorage, Storage};
+use diem_types::{
+    account_address::AccountAddress,
+    block_info::BlockInfo,
+    chain_id::ChainId,
+    epoch_change::EpochChangeProof,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    on_chain_config::ValidatorSet,
+    transaction::{
+        ConflictSignature, DisputePayload, ElectionPayload, RawTransaction,
+        RetirePayload, TransactionPayload,
+    },
+    validator_config::ConsensusVRFPrivateKey,
+    validator_info::ValidatorInfo,
+    validator_signer::ValidatorSigner,
+    waypoint::Waypoint,
+};
+use std::collections::BTreeMap;
 
 #[test]
 fn test() {
@@ -46,3 +69,238 @@ fn safety_rules(
         )
     })
 }
+
+/// SafetyRules of `signer` in the epoch of the validators `signers`, which
+/// have VRF keys unlike those of `test_utils`.
+fn safety_rules_in_epoch(
+    signer: &ValidatorSigner, signers: &[&ValidatorSigner],
+    vrf_private_key: Option<ConsensusVRFPrivateKey>,
+) -> (SafetyRules, EpochChangeProof)
+{
+    let infos = signers
+        .iter()
+        .map(|v| {
+            ValidatorInfo::new_with_test_network_keys(
+                v.author(),
+                v.public_key(),
+                v.vrf_public_key(),
+                1,
+            )
+        })
+        .collect();
+    let li = LedgerInfo::mock_genesis(Some(ValidatorSet::new(infos)));
+    let storage = PersistentSafetyStorage::initialize(
+        Storage::from(InMemoryStorage::new()),
+        signer.author(),
+        signer.private_key().clone(),
+        Waypoint::new_epoch_boundary(&li).unwrap(),
+        true,
+    );
+    let safety_rules = SafetyRules::new(
+        storage,
+        false,
+        false,
+        vrf_private_key,
+        signer.author(),
+    );
+    let proof = EpochChangeProof::new(
+        vec![LedgerInfoWithSignatures::new(li, BTreeMap::new())],
+        false,
+    );
+    (safety_rules, proof)
+}
+
+fn sign_payload(
+    safety_rules: &mut SafetyRules, signer: &ValidatorSigner,
+    payload: TransactionPayload,
+) -> Result<(), Error> {
+    let raw_tx = RawTransaction::new(
+        signer.author(),
+        payload,
+        u64::MAX,
+        ChainId::default(),
+    );
+    let signed_tx = safety_rules.sign_transaction(raw_tx)?;
+    signed_tx.check_signature().unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_sign_dispute() {
+    let signer = ValidatorSigner::from_int(0);
+    let other = ValidatorSigner::random([1; 32]);
+    let (mut safety_rules, proof) =
+        safety_rules_in_epoch(&signer, &[&signer, &other], None);
+
+    // A vote of `other` for a block in round 2.
+    let vote = |id: HashValue| {
+        let vote_data = VoteData::new(
+            BlockInfo::new(1, 2, id, HashValue::zero(), 0, 0, None, None),
+            BlockInfo::random(1),
+        );
+        let ledger_info =
+            LedgerInfo::new(BlockInfo::empty(), HashValue::zero());
+        let vote = Vote::new(vote_data, other.author(), ledger_info, &other);
+        bcs::to_bytes(&vote).unwrap()
+    };
+    let dispute = |address: AccountAddress,
+                   validator: &ValidatorSigner,
+                   votes: (Vec<u8>, Vec<u8>)| {
+        TransactionPayload::Dispute(DisputePayload {
+            address,
+            bls_pub_key: validator.public_key(),
+            vrf_pub_key: validator.vrf_public_key().unwrap(),
+            conflicting_votes: ConflictSignature::Vote(votes),
+        })
+    };
+    let conflicting_votes =
+        (vote(HashValue::random()), vote(HashValue::random()));
+
+    // The validators are unknown before SafetyRules is initialized.
+    assert!(matches!(
+        sign_payload(
+            &mut safety_rules,
+            &signer,
+            dispute(other.author(), &other, conflicting_votes.clone())
+        ),
+        Err(Error::NotInitialized(_))
+    ));
+    safety_rules.initialize(&proof).unwrap();
+
+    sign_payload(
+        &mut safety_rules,
+        &signer,
+        dispute(other.author(), &other, conflicting_votes.clone()),
+    )
+    .unwrap();
+
+    // The same vote twice does not conflict.
+    let same_vote = vote(HashValue::random());
+    assert!(matches!(
+        sign_payload(
+            &mut safety_rules,
+            &signer,
+            dispute(other.author(), &other, (same_vote.clone(), same_vote))
+        ),
+        Err(Error::InvalidTransaction(_))
+    ));
+
+    // Disputes of the validator itself or of the nodes not in the epoch are
+    // rejected.
+    assert!(matches!(
+        sign_payload(
+            &mut safety_rules,
+            &signer,
+            dispute(signer.author(), &signer, conflicting_votes.clone())
+        ),
+        Err(Error::InvalidTransaction(_))
+    ));
+    assert!(matches!(
+        sign_payload(
+            &mut safety_rules,
+            &signer,
+            dispute(AccountAddress::random(), &other, conflicting_votes)
+        ),
+        Err(Error::InvalidTransaction(_))
+    ));
+}
+
+#[test]
+fn test_sign_election_and_staking_events() {
+    let signer = ValidatorSigner::from_int(0);
+    let other = ValidatorSigner::random([1; 32]);
+    let vrf_private_key = ConsensusVRFPrivateKey::generate_for_testing();
+    let (mut safety_rules, proof) = safety_rules_in_epoch(
+        &signer,
+        &[&signer],
+        Some(vrf_private_key.clone()),
+    );
+
+    let election = |public_key, vrf_public_key| {
+        TransactionPayload::Election(ElectionPayload {
+            public_key,
+            vrf_public_key,
+            target_term: 1,
+            vrf_proof: vrf_private_key.compute(b"seed").unwrap(),
+        })
+    };
+    // Elections are sent before the node is a validator.
+    sign_payload(
+        &mut safety_rules,
+        &signer,
+        election(signer.public_key(), vrf_private_key.public_key()),
+    )
+    .unwrap();
+    assert!(matches!(
+        sign_payload(
+            &mut safety_rules,
+            &signer,
+            election(other.public_key(), other.vrf_public_key().unwrap()),
+        ),
+        Err(Error::InvalidTransaction(_))
+    ));
+
+    // The staking events are only packed by the validators.
+    let retire = TransactionPayload::Retire(RetirePayload {
+        node_id: other.author(),
+        votes: 1,
+    });
+    assert!(matches!(
+        sign_payload(&mut safety_rules, &signer, retire.clone()),
+        Err(Error::NotInitialized(_))
+    ));
+    safety_rules.initialize(&proof).unwrap();
+    sign_payload(&mut safety_rules, &signer, retire).unwrap();
+}
+
+#[test]
+fn test_sign_timeout_after_import() {
+    let signer = ValidatorSigner::from_int(0);
+    // Without the cache, the imported safety data is read from the store.
+    let mut storage = PersistentSafetyStorage::initialize(
+        Storage::from(InMemoryStorage::new()),
+        signer.author(),
+        signer.private_key().clone(),
+        test_utils::validator_signers_to_waypoint(&[&signer]),
+        false,
+    );
+    let mut interchange = slashing_protection::export_slashing_protection(
+        storage.internal_store(),
+    )
+    .unwrap();
+    interchange.last_voted_round = 10;
+    interchange.preferred_round = 5;
+    slashing_protection::import_slashing_protection(
+        storage.internal_store(),
+        &interchange,
+    )
+    .unwrap();
+
+    let mut safety_rules =
+        SafetyRules::new(storage, false, false, None, signer.author());
+    let (proof, _) = test_utils::make_genesis(&signer);
+    safety_rules.initialize(&proof).unwrap();
+
+    let epoch = interchange.epoch;
+    assert_eq!(
+        safety_rules
+            .sign_timeout(&Timeout::new(epoch, 5))
+            .unwrap_err(),
+        Error::IncorrectPreferredRound(5, 5)
+    );
+    assert_eq!(
+        safety_rules
+            .sign_timeout(&Timeout::new(epoch, 9))
+            .unwrap_err(),
+        Error::IncorrectLastVotedRound(9, 10)
+    );
+    // A timeout in the imported watermark round cannot equivocate.
+    safety_rules.sign_timeout(&Timeout::new(epoch, 10)).unwrap();
+    safety_rules.sign_timeout(&Timeout::new(epoch, 11)).unwrap();
+    assert_eq!(
+        safety_rules
+            .sign_timeout(&Timeout::new(epoch, 10))
+            .unwrap_err(),
+        Error::IncorrectLastVotedRound(10, 11)
+    );
+}
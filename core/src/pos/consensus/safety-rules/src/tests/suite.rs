@@ -18,8 +18,9 @@ use diem_crypto::{
 use diem_global_constants::CONSENSUS_KEY;
 use diem_secure_storage::CryptoStorage;
 use diem_types::{
-    epoch_state::EpochState, validator_signer::ValidatorSigner,
-    validator_verifier::ValidatorVerifier,
+    account_address::AccountAddress, block_info::PivotBlockDecision,
+    chain_id::ChainId, epoch_state::EpochState, transaction::RawTransaction,
+    validator_signer::ValidatorSigner, validator_verifier::ValidatorVerifier,
 };
 
 type Proof = test_utils::Proof;
@@ -84,6 +85,7 @@ pub fn run_test_suite(safety_rules: &Callback) {
     // test_reconcile_key(safety_rules);
     test_validator_not_in_set(safety_rules);
     test_key_not_in_store(safety_rules);
+    test_sign_transaction(safety_rules);
 }
 
 fn test_bad_execution_output(safety_rules: &Callback) {
@@ -963,3 +965,32 @@ fn test_key_not_in_store(safety_rules: &Callback) {
     let state = safety_rules.consensus_state().unwrap();
     assert_eq!(state.in_validator_set(), false);
 }
+
+fn test_sign_transaction(safety_rules: &Callback) {
+    let (mut safety_rules, signer, _key) = safety_rules();
+    let pivot_decision = PivotBlockDecision {
+        height: 1,
+        block_hash: Default::default(),
+    };
+
+    // Transactions can be signed before SafetyRules is initialized.
+    let raw_tx = RawTransaction::new_pivot_decision(
+        signer.author(),
+        pivot_decision.clone(),
+        ChainId::default(),
+    );
+    let signed_tx = safety_rules.sign_transaction(raw_tx).unwrap();
+    assert_eq!(signed_tx.sender(), signer.author());
+    signed_tx.check_signature().unwrap();
+
+    // Transactions of other senders are rejected.
+    let raw_tx = RawTransaction::new_pivot_decision(
+        AccountAddress::random(),
+        pivot_decision,
+        ChainId::default(),
+    );
+    assert!(matches!(
+        safety_rules.sign_transaction(raw_tx),
+        Err(Error::InvalidTransaction(_))
+    ));
+}
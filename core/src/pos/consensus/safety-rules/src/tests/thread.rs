@@ -35,6 +35,8 @@ fn safety_rules(
             verify_vote_proposal_signature,
             export_consensus_key,
             network_timeout,
+            None,
+            signer.author(),
         );
         let safety_rules = safety_rules_manager.client();
         (
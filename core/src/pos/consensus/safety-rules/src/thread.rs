@@ -15,11 +15,18 @@
 
 use crate::{
     persistent_safety_storage::PersistentSafetyStorage,
-    remote_service::{self, RemoteService},
+    remote_service::{self, AuthToken, RemoteService},
 };
 use diem_config::utils;
+use diem_crypto::HashValue;
+use diem_infallible::Mutex;
+use diem_secure_net::NetworkClient;
+use diem_types::{
+    account_address::AccountAddress, validator_config::ConsensusVRFPrivateKey,
+};
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
     thread::{self, JoinHandle},
 };
 
@@ -28,20 +35,26 @@ use std::{
 /// queries / requests to SafetyRules.
 pub struct ThreadService {
     _child: JoinHandle<()>,
-    server_addr: SocketAddr,
-    network_timeout: u64,
+    network_client: Arc<Mutex<NetworkClient>>,
+    auth_token: AuthToken,
 }
 
 impl ThreadService {
     pub fn new(
         storage: PersistentSafetyStorage, verify_vote_proposal_signature: bool,
         export_consensus_key: bool, timeout: u64,
+        vrf_private_key: Option<ConsensusVRFPrivateKey>,
+        author: AccountAddress,
     ) -> Self
     {
         let listen_port = utils::get_available_port();
         let listen_addr =
             SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), listen_port);
         let server_addr = listen_addr;
+        // The thread listens on a local port like a process, so the requests
+        // are authenticated with a secret only known by this node.
+        let secret = HashValue::random().to_vec();
+        let service_auth_token = AuthToken::new(secret.clone());
 
         let child = thread::spawn(move || {
             remote_service::execute(
@@ -50,21 +63,28 @@ impl ThreadService {
                 verify_vote_proposal_signature,
                 export_consensus_key,
                 timeout,
-                // TODO(lpl): Support this?
-                None,
+                vrf_private_key,
+                author,
+                service_auth_token,
             )
         });
 
         Self {
             _child: child,
-            server_addr,
-            network_timeout: timeout,
+            network_client: Arc::new(Mutex::new(NetworkClient::new(
+                "safety-rules",
+                server_addr,
+                timeout,
+            ))),
+            auth_token: AuthToken::new(secret),
         }
     }
 }
 
 impl RemoteService for ThreadService {
-    fn server_address(&self) -> SocketAddr { self.server_addr }
+    fn network_client(&self) -> Arc<Mutex<NetworkClient>> {
+        self.network_client.clone()
+    }
 
-    fn network_timeout_ms(&self) -> u64 { self.network_timeout }
+    fn auth_token(&self) -> AuthToken { self.auth_token.clone() }
 }
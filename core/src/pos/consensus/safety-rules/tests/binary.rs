@@ -29,8 +29,13 @@ fn test_consensus_state() {
     let server_port = utils::get_available_port();
     let server_address =
         SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port).into();
-    config.service =
-        SafetyRulesService::Process(RemoteService { server_address });
+    let auth_token_path = diem_temppath::TempPath::new();
+    auth_token_path.create_as_file().unwrap();
+    std::fs::write(auth_token_path.path(), "secret").unwrap();
+    config.service = SafetyRulesService::Process(RemoteService {
+        server_address,
+        auth_token_path: auth_token_path.path().to_path_buf(),
+    });
 
     let config_path = diem_temppath::TempPath::new();
     config_path.create_as_file().unwrap();
@@ -29,6 +29,7 @@ use crate::{
     spec::genesis::GenesisPosState,
     sync::ProtocolConfiguration,
 };
+use backup_service::start_backup_service;
 use cached_pos_ledger_db::CachedPosLedgerDB;
 use consensus_types::db::FakeLedgerBlockDB;
 use diem_config::{config::NodeConfig, utils::get_genesis_txn};
@@ -82,6 +83,7 @@ pub struct PosDropHandle {
     _mempool: Runtime,
     _state_sync_bootstrapper: StateSyncBootstrapper,
     _consensus_runtime: Runtime,
+    _backup_service: Option<Runtime>,
 }
 
 pub fn start_pos_consensus(
@@ -250,6 +252,12 @@ pub fn setup_pos_environment(
         instant.elapsed().as_millis()
     );
 
+    let backup_service =
+        node_config.storage.backup_service_address.map(|address| {
+            diem_info!("Starting PoS backup service at {}", address);
+            start_backup_service(address, pos_ledger_db.clone())
+        });
+
     instant = Instant::now();
     let chunk_executor = setup_chunk_executor(db_rw.clone());
     debug!(
@@ -354,6 +362,7 @@ pub fn setup_pos_environment(
         cached_db: db_with_cache,
         consensus_db,
         tx_sender: mp_client_sender,
+        _backup_service: backup_service,
     }
 }
 
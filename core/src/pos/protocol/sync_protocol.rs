@@ -21,7 +21,7 @@ use diem_types::{
 use io::TimerToken;
 use network::{
     node_table::NodeId, service::ProtocolVersion, NetworkContext,
-    NetworkProtocolHandler, NetworkService, UpdateNodeOperation,
+    NetworkProtocolHandler, NetworkService, PeerBehavior, UpdateNodeOperation,
 };
 
 use crate::{
@@ -276,11 +276,15 @@ impl HotStuffSynchronizationProtocol {
         let reason = format!("{}", e.0);
         let error_reason = format!("{:?}", e);
         let mut op = None;
+        let mut behavior = None;
 
         // NOTE, DO NOT USE WILDCARD IN THE FOLLOWING MATCH STATEMENT!
         // COMPILER WILL HELP TO FIND UNHANDLED ERROR CASES.
         match e.0 {
-            ErrorKind::InvalidBlock => op = Some(UpdateNodeOperation::Demotion),
+            ErrorKind::InvalidBlock => {
+                op = Some(UpdateNodeOperation::Demotion);
+                behavior = Some(PeerBehavior::InvalidBlock);
+            }
             ErrorKind::InvalidGetBlockTxn(_) => {
                 op = Some(UpdateNodeOperation::Demotion)
             }
@@ -288,7 +292,8 @@ impl HotStuffSynchronizationProtocol {
                 op = Some(UpdateNodeOperation::Failure)
             }
             ErrorKind::InvalidMessageFormat => {
-                op = Some(UpdateNodeOperation::Remove)
+                op = Some(UpdateNodeOperation::Remove);
+                behavior = Some(PeerBehavior::InvalidMessage);
             }
             ErrorKind::UnknownPeer => {
                 warn = false;
@@ -310,13 +315,16 @@ impl HotStuffSynchronizationProtocol {
                 op = Some(UpdateNodeOperation::Demotion)
             }
             ErrorKind::InvalidSnapshotManifest(_) => {
-                op = Some(UpdateNodeOperation::Demotion)
+                op = Some(UpdateNodeOperation::Demotion);
+                behavior = Some(PeerBehavior::InvalidProof);
             }
             ErrorKind::InvalidSnapshotChunk(_) => {
-                op = Some(UpdateNodeOperation::Demotion)
+                op = Some(UpdateNodeOperation::Demotion);
+                behavior = Some(PeerBehavior::InvalidProof);
             }
             ErrorKind::AlreadyThrottled(_) => {
-                op = Some(UpdateNodeOperation::Remove)
+                op = Some(UpdateNodeOperation::Remove);
+                behavior = Some(PeerBehavior::Spam);
             }
             ErrorKind::EmptySnapshotChunk => disconnect = false,
             ErrorKind::Throttled(_, msg) => {
@@ -327,7 +335,10 @@ impl HotStuffSynchronizationProtocol {
                     disconnect = true;
                 }
             }
-            ErrorKind::Decoder(_) => op = Some(UpdateNodeOperation::Remove),
+            ErrorKind::Decoder(_) => {
+                op = Some(UpdateNodeOperation::Remove);
+                behavior = Some(PeerBehavior::InvalidMessage);
+            }
             ErrorKind::Io(_) => disconnect = false,
             ErrorKind::Network(kind) => match kind {
                 network::ErrorKind::AddressParse => disconnect = false,
@@ -371,7 +382,8 @@ impl HotStuffSynchronizationProtocol {
             ErrorKind::RpcTimeout => {}
             ErrorKind::RpcCancelledByDisconnection => {}
             ErrorKind::UnexpectedMessage(_) => {
-                op = Some(UpdateNodeOperation::Remove)
+                op = Some(UpdateNodeOperation::Remove);
+                behavior = Some(PeerBehavior::InvalidMessage);
             }
             ErrorKind::NotSupported(_) => disconnect = false,
         }
@@ -388,6 +400,10 @@ impl HotStuffSynchronizationProtocol {
             );
         }
 
+        if let Some(behavior) = behavior {
+            io.report_peer(peer, behavior);
+        }
+
         if disconnect {
             io.disconnect_peer(peer, op, reason.as_str());
         }
@@ -665,6 +681,8 @@ impl NetworkProtocolHandler for HotStuffSynchronizationProtocol {
     fn on_work_dispatch(&self, _io: &dyn NetworkContext, _work_type: u8) {
         todo!()
     }
+
+    fn message_ids(&self) -> Vec<u16> { msgid::ALL.to_vec() }
 }
 
 pub trait Handleable {
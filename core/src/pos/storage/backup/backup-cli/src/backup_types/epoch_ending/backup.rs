@@ -141,6 +141,10 @@ impl EpochEndingBackupController {
         format!("{}-.chunk", first_epoch).try_into().unwrap()
     }
 
+    fn pos_data_chunk_name(first_epoch: u64) -> ShellSafeName {
+        format!("{}-.pos_data.chunk", first_epoch).try_into().unwrap()
+    }
+
     fn get_waypoint(record: &[u8], epoch: u64) -> Result<Waypoint> {
         let li: LedgerInfoWithSignatures = bcs::from_bytes(record)?;
         ensure!(
@@ -165,10 +169,36 @@ impl EpochEndingBackupController {
             .await?;
         chunk_file.write_all(&chunk_bytes).await?;
         chunk_file.shutdown().await?;
+
+        let mut pos_data = self
+            .client
+            .get_epoch_pos_data(first_epoch, last_epoch + 1)
+            .await?;
+        let (pos_data_handle, mut pos_data_file) = self
+            .storage
+            .create_for_write(backup_handle, &Self::pos_data_chunk_name(first_epoch))
+            .await?;
+        let mut num_records = 0;
+        while let Some(record_bytes) = pos_data.read_record_bytes().await? {
+            pos_data_file
+                .write_all(&(record_bytes.len() as u32).to_be_bytes())
+                .await?;
+            pos_data_file.write_all(&record_bytes).await?;
+            num_records += 1;
+        }
+        pos_data_file.shutdown().await?;
+        ensure!(
+            num_records == last_epoch - first_epoch + 1,
+            "Number of EpochPosData not expected. expected: {}, actual: {}.",
+            last_epoch - first_epoch + 1,
+            num_records,
+        );
+
         Ok(EpochEndingChunk {
             first_epoch,
             last_epoch,
             ledger_infos: chunk_handle,
+            pos_data: pos_data_handle,
         })
     }
 
@@ -17,6 +17,9 @@ pub struct EpochEndingChunk {
     pub first_epoch: u64,
     pub last_epoch: u64,
     pub ledger_infos: FileHandle,
+    /// The `EpochPosData` of the same epochs, needed to start a node from
+    /// the restored PoS ledger.
+    pub pos_data: FileHandle,
 }
 
 /// Epoch ending backup manifest, representing epoch ending information in the
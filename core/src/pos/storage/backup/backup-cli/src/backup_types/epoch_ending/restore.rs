@@ -26,6 +26,8 @@ use diem_types::{
     waypoint::Waypoint,
 };
 use futures::StreamExt;
+use pos_ledger_db::backup::backup_handler::EpochPosData;
+use serde::de::DeserializeOwned;
 use std::{collections::HashMap, sync::Arc, time::Instant};
 use structopt::StructOpt;
 
@@ -91,6 +93,7 @@ impl EpochEndingRestoreController {
 
         let mut previous_li: Option<&LedgerInfoWithSignatures> = None;
         let mut ledger_infos = Vec::new();
+        let mut pos_data = Vec::new();
 
         let mut past_target = false;
         for chunk in &manifest.chunks {
@@ -98,7 +101,8 @@ impl EpochEndingRestoreController {
                 break;
             }
 
-            let lis = self.read_chunk(&chunk.ledger_infos).await?;
+            let lis: Vec<LedgerInfoWithSignatures> =
+                self.read_chunk(&chunk.ledger_infos).await?;
             ensure!(
                 chunk.first_epoch + lis.len() as u64 == chunk.last_epoch + 1,
                 "Number of items in chunks doesn't match that in manifest. \
@@ -107,8 +111,17 @@ impl EpochEndingRestoreController {
                 chunk.last_epoch,
                 lis.len(),
             );
+            let pos_data_chunk: Vec<EpochPosData> = self.read_chunk(&chunk.pos_data).await?;
+            ensure!(
+                pos_data_chunk.len() == lis.len(),
+                "Number of EpochPosData doesn't match that of LedgerInfos. \
+                first_epoch: {}, LedgerInfos: {}, EpochPosData: {}",
+                chunk.first_epoch,
+                lis.len(),
+                pos_data_chunk.len(),
+            );
 
-            for li in lis {
+            for (li, data) in lis.into_iter().zip(pos_data_chunk) {
                 if li.ledger_info().version() > self.target_version {
                     past_target = true;
                     break;
@@ -149,7 +162,9 @@ impl EpochEndingRestoreController {
                         })?
                         .verify(&li)?;
                 }
+                verify_pos_data(&li, &data)?;
                 ledger_infos.push(li);
+                pos_data.push(data);
                 previous_li = ledger_infos.last();
                 next_epoch += 1;
             }
@@ -158,13 +173,11 @@ impl EpochEndingRestoreController {
         Ok(EpochEndingRestorePreheatData {
             manifest,
             ledger_infos,
+            pos_data,
         })
     }
 
-    async fn read_chunk(
-        &self,
-        file_handle: &FileHandleRef,
-    ) -> Result<Vec<LedgerInfoWithSignatures>> {
+    async fn read_chunk<T: DeserializeOwned>(&self, file_handle: &FileHandleRef) -> Result<Vec<T>> {
         let mut file = self.storage.open_for_read(file_handle).await?;
         let mut chunk = vec![];
 
@@ -176,9 +189,46 @@ impl EpochEndingRestoreController {
     }
 }
 
+/// Checks that the committed blocks in `pos_data` are chained and end with
+/// the block committed by the epoch ending `li`.
+fn verify_pos_data(li: &LedgerInfoWithSignatures, pos_data: &EpochPosData) -> Result<()> {
+    let epoch = li.ledger_info().epoch();
+    ensure!(
+        pos_data.epoch == epoch,
+        "EpochPosData epoch not expected. Expected: {}, actual: {}.",
+        epoch,
+        pos_data.epoch,
+    );
+    let ending_block = pos_data
+        .committed_blocks
+        .last()
+        .ok_or_else(|| anyhow!("No committed blocks in epoch {}.", epoch))?;
+    ensure!(
+        ending_block.hash == li.ledger_info().consensus_block_id(),
+        "Epoch ending block doesn't match LedgerInfo. In EpochPosData: {}, in LedgerInfo: {}",
+        ending_block.hash,
+        li.ledger_info().consensus_block_id(),
+    );
+    for (parent, block) in pos_data
+        .committed_blocks
+        .iter()
+        .zip(pos_data.committed_blocks.iter().skip(1))
+    {
+        ensure!(
+            block.parent_hash == parent.hash && block.epoch == epoch,
+            "Committed block {} is not a child of {} in epoch {}.",
+            block.hash,
+            parent.hash,
+            epoch,
+        );
+    }
+    Ok(())
+}
+
 struct EpochEndingRestorePreheatData {
     manifest: EpochEndingBackup,
     ledger_infos: Vec<LedgerInfoWithSignatures>,
+    pos_data: Vec<EpochPosData>,
 }
 
 pub struct PreheatedEpochEndingRestore {
@@ -251,6 +301,7 @@ impl PreheatedEpochEndingRestore {
         match self.controller.run_mode.as_ref() {
             RestoreRunMode::Restore { restore_handler } => {
                 restore_handler.save_ledger_infos(&preheat_data.ledger_infos)?;
+                restore_handler.save_epoch_pos_data(&preheat_data.pos_data)?;
 
                 EPOCH_ENDING_EPOCH.set(last_li.epoch() as i64);
                 EPOCH_ENDING_VERSION.set(last_li.version() as i64);
@@ -14,7 +14,8 @@ use crate::{
     },
     storage::{local_fs::LocalFs, BackupStorage},
     utils::{
-        backup_service_client::BackupServiceClient, test_utils::tmp_db_with_random_content,
+        backup_service_client::BackupServiceClient,
+        test_utils::{committed_block_of, tmp_db_with_random_content},
         ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, RocksdbOpt, TrustedWaypointOpt,
     },
 };
@@ -24,9 +25,10 @@ use diem_temppath::TempPath;
 use diem_types::{
     ledger_info::LedgerInfoWithSignatures,
     proptest_types::{AccountInfoUniverse, LedgerInfoWithSignaturesGen},
+    term_state::PosState,
     waypoint::Waypoint,
 };
-use pos_ledger_db::DiemDB;
+use pos_ledger_db::{backup::backup_handler::EpochPosData, PosLedgerDB};
 use proptest::{collection::vec, prelude::*, std_facade::BTreeMap};
 use std::{
     convert::TryInto,
@@ -106,7 +108,7 @@ fn end_to_end() {
         .map(|li| li.ledger_info().next_block_epoch())
         .unwrap_or(0);
 
-    let tgt_db = DiemDB::open(
+    let tgt_db = PosLedgerDB::open(
         &tgt_db_dir,
         true, /* read_only */
         None, /* pruner */
@@ -120,6 +122,14 @@ fn end_to_end() {
             .ledger_info_with_sigs,
         expected_ledger_infos,
     );
+    for li in &expected_ledger_infos {
+        let block_id = li.ledger_info().consensus_block_id();
+        assert_eq!(
+            tgt_db.get_committed_block_by_hash(&block_id).unwrap(),
+            committed_block_of(li.ledger_info()),
+        );
+        assert!(tgt_db.get_pos_state(&block_id).is_ok());
+    }
 
     rt.shutdown_timeout(Duration::from_secs(1));
 }
@@ -165,16 +175,38 @@ prop_compose! {
 
 async fn mock_backup_service_get_epoch_ending_lis(lis: Vec<LedgerInfoWithSignatures>) -> u16 {
     let address = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0);
-    let route = warp::path!("epoch_ending_ledger_infos" / usize / usize).map(move |start, end| {
-        let mut response = Vec::<u8>::new();
-        for li in &lis[start..end] {
-            let bytes = bcs::to_bytes(&li).unwrap();
-            let size_bytes = (bytes.len() as u32).to_be_bytes();
-            response.write_all(&size_bytes).unwrap();
-            response.write_all(&bytes).unwrap();
-        }
-        response
-    });
+    let pos_data = lis
+        .iter()
+        .map(|li| EpochPosData {
+            epoch: li.ledger_info().epoch(),
+            committed_blocks: vec![committed_block_of(li.ledger_info())],
+            reward_event: None,
+            pos_state: PosState::new_empty(),
+        })
+        .collect::<Vec<_>>();
+    let lis_route =
+        warp::path!("epoch_ending_ledger_infos" / usize / usize).map(move |start, end| {
+            let mut response = Vec::<u8>::new();
+            for li in &lis[start..end] {
+                let bytes = bcs::to_bytes(&li).unwrap();
+                let size_bytes = (bytes.len() as u32).to_be_bytes();
+                response.write_all(&size_bytes).unwrap();
+                response.write_all(&bytes).unwrap();
+            }
+            response
+        });
+    let pos_data_route =
+        warp::path!("epoch_pos_data" / usize / usize).map(move |start, end| {
+            let mut response = Vec::<u8>::new();
+            for data in &pos_data[start..end] {
+                let bytes = bcs::to_bytes(&data).unwrap();
+                let size_bytes = (bytes.len() as u32).to_be_bytes();
+                response.write_all(&size_bytes).unwrap();
+                response.write_all(&bytes).unwrap();
+            }
+            response
+        });
+    let route = lis_route.or(pos_data_route);
     let (addr, svr) = warp::serve(route).bind_ephemeral(address);
     tokio::spawn(svr);
     addr.port()
@@ -18,7 +18,8 @@ use crate::{
     },
     storage::{local_fs::LocalFs, BackupStorage},
     utils::{
-        backup_service_client::BackupServiceClient, test_utils::start_local_backup_service,
+        backup_service_client::BackupServiceClient,
+        test_utils::{start_local_backup_service, tmp_db_with_random_content},
         ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, GlobalRestoreOptions,
         RocksdbOpt, TrustedWaypointOpt,
     },
@@ -26,8 +27,7 @@ use crate::{
 use diem_config::config::RocksdbConfig;
 use diem_temppath::TempPath;
 use diem_types::transaction::Version;
-use pos_ledger_db::DiemDB;
-use executor_test_helpers::integration_test_impl::test_execution_with_storage_impl;
+use pos_ledger_db::PosLedgerDB;
 use proptest::prelude::*;
 use std::{convert::TryInto, sync::Arc};
 use storage_interface::DbReader;
@@ -35,15 +35,18 @@ use tokio::time::Duration;
 
 #[derive(Debug)]
 struct TestData {
-    db: Arc<DiemDB>,
+    _db_dir: Arc<TempPath>,
+    db: Arc<PosLedgerDB>,
     txn_start_ver: Version,
     state_snapshot_ver: Option<Version>,
     target_ver: Version,
-    latest_ver: Version,
 }
 
 fn test_data_strategy() -> impl Strategy<Value = TestData> {
-    let db = test_execution_with_storage_impl();
+    // The random transactions can't be executed, so they are restored without replaying.
+    // Replaying is covered by the executor tests.
+    let (db_dir, db, _blocks) = tmp_db_with_random_content();
+    let db_dir = Arc::new(db_dir);
     let latest_ver = db.get_latest_version().unwrap();
 
     (0..=latest_ver)
@@ -57,11 +60,11 @@ fn test_data_strategy() -> impl Strategy<Value = TestData> {
         })
         .prop_map(
             move |(txn_start_ver, state_snapshot_ver, target_ver)| TestData {
+                _db_dir: Arc::clone(&db_dir),
                 db: Arc::clone(&db),
                 txn_start_ver,
                 state_snapshot_ver,
                 target_ver,
-                latest_ver,
             },
         )
 }
@@ -140,9 +143,7 @@ fn test_end_to_end_impl(d: TestData) {
         TransactionRestoreController::new(
             TransactionRestoreOpt {
                 manifest_handle: txn_manifest,
-                replay_from_version: Some(
-                    d.state_snapshot_ver.unwrap_or(Version::max_value() - 1) + 1,
-                ),
+                replay_from_version: None, // max
             },
             global_restore_opt,
             store,
@@ -153,7 +154,7 @@ fn test_end_to_end_impl(d: TestData) {
     .unwrap();
 
     // Check
-    let tgt_db = DiemDB::open(
+    let tgt_db = PosLedgerDB::open(
         &tgt_db_dir,
         false, /* read_only */
         None,  /* pruner */
@@ -177,18 +178,14 @@ fn test_end_to_end_impl(d: TestData) {
             )
             .unwrap()
     );
-    if let Some(state_snapshot_ver) = d.state_snapshot_ver {
-        let first_replayed = state_snapshot_ver + 1;
-        let num_replayed = d.target_ver - state_snapshot_ver;
-        // Events recreated:
-        assert_eq!(
-            d.db.get_transactions(first_replayed, num_replayed, d.target_ver, true)
-                .unwrap(),
-            tgt_db
-                .get_transactions(first_replayed, num_replayed, d.target_ver, true)
-                .unwrap()
-        );
-    };
+    assert_eq!(
+        tgt_db
+            .get_latest_transaction_info_option()
+            .unwrap()
+            .unwrap()
+            .0,
+        d.target_ver,
+    );
 
     rt.shutdown_timeout(Duration::from_secs(1));
 }
@@ -17,22 +17,25 @@ use crate::{
     },
 };
 use anyhow::{anyhow, bail, ensure, Result};
+use cached_pos_ledger_db::CachedPosLedgerDB;
+use consensus_types::db::FakeLedgerBlockDB;
 use diem_logger::prelude::*;
 use diem_types::{
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
     proof::{TransactionAccumulatorRangeProof, TransactionListProof},
+    term_state::PosState,
     transaction::{Transaction, TransactionInfo, TransactionListWithProof, Version},
 };
-use diem_vm::DiemVM;
-use executor::Executor;
+use executor::{vm::FakeVM, Executor};
 use executor_types::TransactionReplayer;
 use futures::StreamExt;
+use pow_types::FakePowHandler;
 use std::{
     cmp::{max, min},
     sync::Arc,
 };
-use storage_interface::DbReaderWriter;
+use storage_interface::{DbReader, DbReaderWriter};
 use structopt::StructOpt;
 use tokio::io::BufReader;
 
@@ -44,7 +47,9 @@ pub struct TransactionRestoreOpt {
         long = "replay-transactions-from-version",
         help = "Transactions with this version and above will be replayed so state and events are \
         gonna pop up. Requires state at the version right before this to exist, either by \
-        recovering a state snapshot, or previous transaction replay."
+        recovering a state snapshot, or previous transaction replay. The version right before \
+        this must also be an epoch ending, whose PoS state is restored from the epoch ending \
+        backups, unless replaying from the genesis."
     )]
     pub replay_from_version: Option<Version>,
 }
@@ -233,7 +238,14 @@ impl TransactionRestoreController {
 
                 let mut current_version = first_to_replay;
                 while !chunk.txns.is_empty() {
-                    let this_batch_size = min(BATCH_SIZE, chunk.txns.len());
+                    // A batch doesn't go across epoch endings, where the PoS state is reloaded.
+                    let next_epoch_ending = self.next_epoch_ending(current_version);
+                    let this_batch_size = min(
+                        min(BATCH_SIZE, chunk.txns.len()),
+                        next_epoch_ending.map_or(usize::max_value(), |v| {
+                            (v - current_version + 1) as usize
+                        }),
+                    );
                     self.transaction_replayer(current_version)?.replay_chunk(
                         current_version,
                         chunk.txns.drain(0..this_batch_size).collect::<Vec<_>>(),
@@ -244,6 +256,9 @@ impl TransactionRestoreController {
                     )?;
                     current_version += this_batch_size as u64;
                     TRANSACTION_REPLAY_VERSION.set(current_version as i64 - 1);
+                    if next_epoch_ending == Some(current_version - 1) {
+                        self.state.transaction_replayer = None;
+                    }
                 }
             }
         } else {
@@ -267,12 +282,61 @@ impl TransactionRestoreController {
         Ok(())
     }
 
-    fn transaction_replayer(&mut self, first_version: Version) -> Result<&mut Executor<DiemVM>> {
+    /// The first epoch ending at or after `version`.
+    fn next_epoch_ending(&self, version: Version) -> Option<Version> {
+        self.epoch_history.as_ref().and_then(|epoch_history| {
+            epoch_history
+                .epoch_endings
+                .iter()
+                .map(|li| li.version())
+                .find(|v| *v >= version)
+        })
+    }
+
+    /// The PoS state before `version`. It is only backed up at epoch endings, so the version
+    /// right before `version` must be an epoch ending. The genesis transaction does not depend
+    /// on the PoS state.
+    fn pos_state_before(&self, version: Version) -> Result<PosState> {
+        if version == 0 {
+            return Ok(PosState::new_empty());
+        }
+        let restore_handler = match self.run_mode.as_ref() {
+            RestoreRunMode::Restore { restore_handler } => restore_handler,
+            RestoreRunMode::Verify => bail!("No PoS state in Verify mode."),
+        };
+        let epoch_ending = self
+            .epoch_history
+            .as_ref()
+            .and_then(|epoch_history| {
+                epoch_history
+                    .epoch_endings
+                    .iter()
+                    .find(|li| li.version() == version - 1)
+            })
+            .ok_or_else(|| {
+                anyhow!(
+                    "Can't replay from version {}: version {} is not a restored epoch ending.",
+                    version,
+                    version - 1,
+                )
+            })?;
+        restore_handler
+            .diemdb
+            .get_pos_state(&epoch_ending.consensus_block_id())
+    }
+
+    fn transaction_replayer(&mut self, first_version: Version) -> Result<&mut Executor<FakeVM>> {
         if self.state.transaction_replayer.is_none() {
             if let RestoreRunMode::Restore { restore_handler } = self.run_mode.as_ref() {
-                let replayer = Executor::new_on_unbootstrapped_db(
+                let db_with_cache = Arc::new(CachedPosLedgerDB::new_for_replay(
                     DbReaderWriter::from_arc(Arc::clone(&restore_handler.diemdb)),
                     restore_handler.get_tree_state(first_version)?,
+                    self.pos_state_before(first_version)?,
+                ));
+                let replayer = Executor::new(
+                    db_with_cache,
+                    Arc::new(FakePowHandler {}),
+                    Arc::new(FakeLedgerBlockDB {}),
                 );
                 self.state.transaction_replayer = Some(replayer);
             } else {
@@ -296,7 +360,7 @@ impl TransactionRestoreController {
 #[derive(Default)]
 struct State {
     frozen_subtree_confirmed: bool,
-    transaction_replayer: Option<Executor<DiemVM>>,
+    transaction_replayer: Option<Executor<FakeVM>>,
 }
 
 struct TransactionRestorePreheatData {
@@ -311,11 +375,23 @@ pub struct PreheatedTransactionRestore {
 
 impl PreheatedTransactionRestore {
     pub async fn run(self) -> Result<()> {
+        self.run_with_replayer(None).await?;
+        Ok(())
+    }
+
+    /// Continue the replay with the replayer left by the previous manifest, because the PoS
+    /// state in the middle of an epoch can not be loaded from the DB. Returns the replayer to
+    /// continue with.
+    async fn run_with_replayer(
+        mut self,
+        transaction_replayer: Option<Executor<FakeVM>>,
+    ) -> Result<Option<Executor<FakeVM>>> {
         let name = self.controller.name();
         diem_info!(
             "{} started. Manifest: {}",
             name, self.controller.manifest_handle
         );
+        self.controller.state.transaction_replayer = transaction_replayer;
         let res = self
             .run_impl()
             .await
@@ -337,7 +413,7 @@ impl PreheatedTransactionRestore {
 }
 
 impl PreheatedTransactionRestore {
-    async fn run_impl(mut self) -> Result<()> {
+    async fn run_impl(mut self) -> Result<Option<Executor<FakeVM>>> {
         let preheat_data = self
             .preheat_result
             .map_err(|e| anyhow!("Preheat failed: {}", e))?;
@@ -362,7 +438,7 @@ impl PreheatedTransactionRestore {
             )
         }
 
-        Ok(())
+        Ok(self.controller.state.transaction_replayer)
     }
 }
 
@@ -427,9 +503,12 @@ impl TransactionRestoreBatchController {
             self.global_opt.concurrent_downloads * 3,
             self.global_opt.concurrent_downloads, /* concurrency */
         );
+        let mut transaction_replayer = None;
         while let Some(preheated_txn_restore) = futs_stream.next().await {
             let v = preheated_txn_restore.get_last_version();
-            preheated_txn_restore.run().await?;
+            transaction_replayer = preheated_txn_restore
+                .run_with_replayer(transaction_replayer)
+                .await?;
             diem_debug!(
                 "Accumulative TPS: {:.0}",
                 (v? + 1) as f64 / timer.elapsed().as_secs_f64()
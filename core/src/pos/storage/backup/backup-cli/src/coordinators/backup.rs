@@ -42,7 +42,9 @@ pub struct BackupCoordinatorOpt {
     // We replay transactions on top of a state snapshot at about 2000 tps, having a state snapshot
     // every 2000 * 3600 * 2 = 14.4 Mil versions will guarantee that to achieve any state in
     // history, it won't take us more than two hours in transaction replaying. Defaulting to 10 Mil
-    // here to make it less than two, and easier for eyes.
+    // here to make it less than two, and easier for eyes. Snapshots are taken at the first epoch
+    // ending after every interval, because the replay needs the PoS state, which is only backed
+    // up at epoch endings.
     #[structopt(long, default_value = "10000000")]
     pub state_snapshot_interval: usize,
     // Assuming the network runs at 100 tps, it's 100 * 3600 = 360k transactions per hour, we don't
@@ -236,14 +238,25 @@ impl BackupCoordinator {
             self.state_snapshot_interval,
         );
 
-        if db_state.committed_version < next_snapshot_version {
+        if db_state.committed_version < next_snapshot_version || db_state.epoch == 0 {
             // wait for the next db_state update
             return Ok(last_snapshot_version_in_backup);
         }
+        // `db_state.epoch` hasn't ended yet, so the latest epoch ending is the one before it.
+        let epoch_ending_version = self
+            .client
+            .get_epoch_ending_ledger_info(db_state.epoch - 1)
+            .await?
+            .ledger_info()
+            .version();
+        if epoch_ending_version < next_snapshot_version {
+            // wait for the next epoch ending
+            return Ok(last_snapshot_version_in_backup);
+        }
 
         StateSnapshotBackupController::new(
             StateSnapshotBackupOpt {
-                version: next_snapshot_version,
+                version: epoch_ending_version,
             },
             self.global_opt.clone(),
             Arc::clone(&self.client),
@@ -252,7 +265,7 @@ impl BackupCoordinator {
         .run()
         .await?;
 
-        Ok(Some(next_snapshot_version))
+        Ok(Some(epoch_ending_version))
     }
 
     async fn backup_transactions(
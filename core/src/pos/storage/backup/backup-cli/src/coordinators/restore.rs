@@ -19,10 +19,12 @@ use crate::{
     storage::BackupStorage,
     utils::{unix_timestamp_sec, GlobalRestoreOptions, RestoreRunMode},
 };
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, ensure, Result};
+use diem_crypto::HashValue;
 use diem_logger::prelude::*;
 use diem_types::transaction::Version;
 use std::sync::Arc;
+use storage_interface::DbReader;
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
@@ -34,6 +36,25 @@ pub struct RestoreCoordinatorOpt {
         help = "Replay all transactions, don't try to use a state snapshot."
     )]
     pub replay_all: bool,
+    #[structopt(
+        long,
+        parse(try_from_str = parse_pos_reference),
+        help = "(multiple) The PoS reference of a PoW block, e.g. the `posReference` returned by \
+        `cfx_getBlockByEpochNumber`. After restoring, the referenced PoS block is checked to be \
+        committed in the restored ledger, so pick PoW blocks not newer than the backup. Required \
+        unless --skip-pos-reference-check is set."
+    )]
+    pub trust_pos_reference: Vec<HashValue>,
+    #[structopt(
+        long,
+        help = "Restore without checking the restored ledger against any PoS reference. The \
+        ledger is then only as trustworthy as the backup storage."
+    )]
+    pub skip_pos_reference_check: bool,
+}
+
+fn parse_pos_reference(s: &str) -> Result<HashValue> {
+    Ok(HashValue::from_hex(s.trim_start_matches("0x"))?)
 }
 
 pub struct RestoreCoordinator {
@@ -41,6 +62,8 @@ pub struct RestoreCoordinator {
     global_opt: GlobalRestoreOptions,
     metadata_cache_opt: MetadataCacheOpt,
     replay_all: bool,
+    trusted_pos_references: Vec<HashValue>,
+    skip_pos_reference_check: bool,
 }
 
 impl RestoreCoordinator {
@@ -54,6 +77,8 @@ impl RestoreCoordinator {
             global_opt,
             metadata_cache_opt: opt.metadata_cache_opt,
             replay_all: opt.replay_all,
+            trusted_pos_references: opt.trust_pos_reference,
+            skip_pos_reference_check: opt.skip_pos_reference_check,
         }
     }
 
@@ -78,6 +103,12 @@ impl RestoreCoordinator {
     }
 
     async fn run_impl(self) -> Result<()> {
+        // Fail before restoring anything if the result can't be verified.
+        ensure!(
+            self.skip_pos_reference_check || !self.trusted_pos_references.is_empty(),
+            "No --trust-pos-reference given to verify the restored ledger. Set \
+            --skip-pos-reference-check to restore without verifying.",
+        );
         let metadata_view = metadata::cache::sync_and_load(
             &self.metadata_cache_opt,
             Arc::clone(&self.storage),
@@ -129,6 +160,16 @@ impl RestoreCoordinator {
         );
 
         if let Some(backup) = state_snapshot {
+            // Transactions are replayed with the PoS state restored from the epoch ending
+            // backups, which only exists at epoch endings.
+            ensure!(
+                epoch_history
+                    .epoch_endings
+                    .iter()
+                    .any(|li| li.version() == backup.version),
+                "State snapshot at version {} is not taken at an epoch ending.",
+                backup.version,
+            );
             StateSnapshotRestoreController::new(
                 StateSnapshotRestoreOpt {
                     manifest_handle: backup.manifest,
@@ -148,8 +189,8 @@ impl RestoreCoordinator {
             .map(|b| b.manifest)
             .collect();
         TransactionRestoreBatchController::new(
-            self.global_opt,
-            self.storage,
+            self.global_opt.clone(),
+            Arc::clone(&self.storage),
             txn_manifests,
             Some(replay_transactions_from_version),
             Some(epoch_history),
@@ -157,6 +198,50 @@ impl RestoreCoordinator {
         .run()
         .await?;
 
+        self.verify_pos_references()
+    }
+
+    /// Checks that the blocks referred by the trusted PoW blocks are committed in the restored
+    /// ledger, i.e. the restored ledger is the one the PoW chain follows.
+    fn verify_pos_references(&self) -> Result<()> {
+        if self.skip_pos_reference_check {
+            diem_warn!("The restored ledger is not checked against any PoS reference.");
+            return Ok(());
+        }
+        let restore_handler = match self.global_opt.run_mode.as_ref() {
+            RestoreRunMode::Restore { restore_handler } => restore_handler,
+            RestoreRunMode::Verify => {
+                diem_info!("PoS references are not checked in a dry run.");
+                return Ok(());
+            }
+        };
+        let latest_li = restore_handler.diemdb.get_latest_ledger_info()?;
+        for pos_reference in &self.trusted_pos_references {
+            let block = restore_handler
+                .diemdb
+                .get_committed_block_by_hash(pos_reference)
+                .map_err(|e| {
+                    anyhow!(
+                        "PoS reference {} is not committed in the restored ledger: {}",
+                        pos_reference,
+                        e
+                    )
+                })?;
+            ensure!(
+                block.version <= latest_li.ledger_info().version(),
+                "PoS reference {} at version {} is newer than the restored LedgerInfo at \
+                version {}.",
+                pos_reference,
+                block.version,
+                latest_li.ledger_info().version(),
+            );
+            diem_info!(
+                "PoS reference {} verified, committed at epoch {} round {}.",
+                pos_reference,
+                block.epoch,
+                block.round,
+            );
+        }
         Ok(())
     }
 }
@@ -5,10 +5,10 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
-use crate::utils::error_notes::ErrorNotes;
-use anyhow::Result;
+use crate::utils::{error_notes::ErrorNotes, read_record_bytes::ReadRecordBytes};
+use anyhow::{anyhow, Result};
 use diem_crypto::HashValue;
-use diem_types::transaction::Version;
+use diem_types::{ledger_info::LedgerInfoWithSignatures, transaction::Version};
 use pos_ledger_db::backup::backup_handler::DbState;
 use futures::TryStreamExt;
 use structopt::StructOpt;
@@ -101,6 +101,27 @@ impl BackupServiceClient {
         .await
     }
 
+    pub async fn get_epoch_pos_data(
+        &self,
+        start_epoch: u64,
+        end_epoch: u64,
+    ) -> Result<impl AsyncRead> {
+        self.get(&format!("epoch_pos_data/{}/{}", start_epoch, end_epoch))
+            .await
+    }
+
+    pub async fn get_epoch_ending_ledger_info(
+        &self,
+        epoch: u64,
+    ) -> Result<LedgerInfoWithSignatures> {
+        let mut file = self.get_epoch_ending_ledger_infos(epoch, epoch + 1).await?;
+        let record_bytes = file
+            .read_record_bytes()
+            .await?
+            .ok_or_else(|| anyhow!("Epoch ending LedgerInfo of epoch {} not found.", epoch))?;
+        Ok(bcs::from_bytes(&record_bytes)?)
+    }
+
     pub async fn get_transactions(
         &self,
         start_version: Version,
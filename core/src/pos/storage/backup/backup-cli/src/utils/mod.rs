@@ -20,7 +20,7 @@ use diem_crypto::HashValue;
 use diem_infallible::duration_since_epoch;
 use diem_jellyfish_merkle::{restore::JellyfishMerkleRestore, NodeBatch, TreeWriter};
 use diem_types::{account_state_blob::AccountStateBlob, transaction::Version, waypoint::Waypoint};
-use pos_ledger_db::{backup::restore_handler::RestoreHandler, DiemDB, GetRestoreHandler};
+use pos_ledger_db::{backup::restore_handler::RestoreHandler, PosLedgerDB, GetRestoreHandler};
 use std::{
     collections::HashMap,
     convert::TryFrom,
@@ -159,7 +159,7 @@ impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
         let target_version = opt.target_version.unwrap_or(Version::max_value());
         let concurrent_downloads = opt.concurernt_downloads.get();
         let run_mode = if let Some(db_dir) = &opt.db_dir {
-            let restore_handler = Arc::new(DiemDB::open(
+            let restore_handler = Arc::new(PosLedgerDB::open(
                 db_dir,
                 false, /* read_only */
                 None,  /* pruner */
@@ -9,8 +9,13 @@ use backup_service::start_backup_service;
 use diem_config::utils::get_available_port;
 use diem_proptest_helpers::ValueGenerator;
 use diem_temppath::TempPath;
-use diem_types::{ledger_info::LedgerInfoWithSignatures, transaction::TransactionToCommit};
-use pos_ledger_db::{test_helper::arb_blocks_to_commit, DiemDB};
+use diem_crypto::HashValue;
+use diem_types::{
+    committed_block::CommittedBlock,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    term_state::PosState, transaction::TransactionToCommit,
+};
+use pos_ledger_db::{test_helper::arb_blocks_to_commit, PosLedgerDB};
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
     sync::Arc,
@@ -18,26 +23,31 @@ use std::{
 use storage_interface::DbWriter;
 use tokio::runtime::Runtime;
 
-pub fn tmp_db_empty() -> (TempPath, Arc<DiemDB>) {
+pub fn tmp_db_empty() -> (TempPath, Arc<PosLedgerDB>) {
     let tmpdir = TempPath::new();
-    let db = Arc::new(DiemDB::new_for_test(&tmpdir));
+    let db = Arc::new(PosLedgerDB::new_for_test(&tmpdir));
 
     (tmpdir, db)
 }
 
 pub fn tmp_db_with_random_content() -> (
     TempPath,
-    Arc<DiemDB>,
+    Arc<PosLedgerDB>,
     Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
 ) {
     let (tmpdir, db) = tmp_db_empty();
     let mut cur_ver = 0;
     let blocks = ValueGenerator::new().generate(arb_blocks_to_commit());
     for (txns_to_commit, ledger_info_with_sigs) in &blocks {
+        // Each LedgerInfo commits a single block, so the epoch ending ones have their
+        // `EpochPosData` to back up.
         db.save_transactions(
             txns_to_commit,
             cur_ver, /* first_version */
             Some(ledger_info_with_sigs),
+            Some(PosState::new_empty()),
+            vec![committed_block_of(ledger_info_with_sigs.ledger_info())],
+            vec![],
         )
         .unwrap();
         cur_ver += txns_to_commit.len() as u64;
@@ -46,7 +56,23 @@ pub fn tmp_db_with_random_content() -> (
     (tmpdir, db, blocks)
 }
 
-pub fn start_local_backup_service(db: Arc<DiemDB>) -> (Runtime, u16) {
+/// The only committed block of the epoch of `li`.
+pub fn committed_block_of(li: &LedgerInfo) -> CommittedBlock {
+    CommittedBlock {
+        hash: li.consensus_block_id(),
+        miner: None,
+        parent_hash: HashValue::zero(),
+        epoch: li.epoch(),
+        round: li.round(),
+        pivot_decision: li.pivot_decision().cloned().unwrap_or_default(),
+        version: li.version(),
+        timestamp: li.timestamp_usecs(),
+        view: li.round(),
+        is_skipped: false,
+    }
+}
+
+pub fn start_local_backup_service(db: Arc<PosLedgerDB>) -> (Runtime, u16) {
     let port = get_available_port();
     let rt = start_backup_service(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port), db);
     (rt, port)
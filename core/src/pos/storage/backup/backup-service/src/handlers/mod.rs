@@ -21,6 +21,7 @@ static STATE_RANGE_PROOF: &str = "state_range_proof";
 static STATE_SNAPSHOT: &str = "state_snapshot";
 static STATE_ROOT_PROOF: &str = "state_root_proof";
 static EPOCH_ENDING_LEDGER_INFOS: &str = "epoch_ending_ledger_infos";
+static EPOCH_POS_DATA: &str = "epoch_pos_data";
 static TRANSACTIONS: &str = "transactions";
 static TRANSACTION_RANGE_PROOF: &str = "transaction_range_proof";
 
@@ -83,6 +84,20 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         })
         .recover(handle_rejection);
 
+    // GET epoch_pos_data/<start_epoch>/<end_epoch>/
+    let bh = backup_handler.clone();
+    let epoch_pos_data = warp::path!(u64 / u64)
+        .map(move |start_epoch, end_epoch| {
+            reply_with_async_channel_writer(&bh, EPOCH_POS_DATA, |bh, sender| async move {
+                send_size_prefixed_bcs_bytes(
+                    bh.get_epoch_pos_data_iter(start_epoch, end_epoch),
+                    sender,
+                )
+                .await
+            })
+        })
+        .recover(handle_rejection);
+
     // GET transactions/<start_version>/<num_transactions>
     let bh = backup_handler.clone();
     let transactions = warp::path!(Version / usize)
@@ -118,6 +133,7 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         .or(warp::path(STATE_SNAPSHOT).and(state_snapshot))
         .or(warp::path(STATE_ROOT_PROOF).and(state_root_proof))
         .or(warp::path(EPOCH_ENDING_LEDGER_INFOS).and(epoch_ending_ledger_infos))
+        .or(warp::path(EPOCH_POS_DATA).and(epoch_pos_data))
         .or(warp::path(TRANSACTIONS).and(transactions))
         .or(warp::path(TRANSACTION_RANGE_PROOF).and(transaction_range_proof));
 
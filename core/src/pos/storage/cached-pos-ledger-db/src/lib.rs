@@ -149,6 +149,19 @@ impl CachedPosLedgerDB {
         }
     }
 
+    /// Build on a DB restored from backups to replay the transactions after
+    /// `tree_state`, where the PoS state is `pos_state`.
+    pub fn new_for_replay(
+        db: DbReaderWriter, tree_state: TreeState, pos_state: PosState,
+    ) -> Self {
+        Self {
+            db,
+            cache: Mutex::new(SpeculationCache::new_for_db_bootstrapping(
+                tree_state, pos_state,
+            )),
+        }
+    }
+
     pub fn committed_block_id(&self) -> HashValue {
         return self.cache.lock().committed_block_id();
     }
@@ -20,12 +20,15 @@ use diem_crypto::hash::HashValue;
 use diem_jellyfish_merkle::iterator::JellyfishMerkleIterator;
 use diem_types::{
     account_state_blob::AccountStateBlob,
+    committed_block::CommittedBlock,
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
     proof::{
         SparseMerkleRangeProof, TransactionAccumulatorRangeProof,
         TransactionInfoWithProof,
     },
+    reward_distribution_event::RewardDistributionEvent,
+    term_state::PosState,
     transaction::{Transaction, TransactionInfo, Version},
 };
 use itertools::zip_eq;
@@ -204,6 +207,68 @@ impl BackupHandler {
                 li
             }))
     }
+
+    /// Gets an iterator that yields the `EpochPosData` of the epochs in
+    /// [`start_epoch`, `end_epoch`).
+    pub fn get_epoch_pos_data_iter(
+        &self, start_epoch: u64, end_epoch: u64,
+    ) -> Result<impl Iterator<Item = Result<EpochPosData>> + '_> {
+        Ok(self
+            .ledger_store
+            .get_epoch_ending_ledger_info_iter(start_epoch, end_epoch)?
+            .map(move |li| self.get_epoch_pos_data(&li?)))
+    }
+
+    fn get_epoch_pos_data(
+        &self, ledger_info: &LedgerInfoWithSignatures,
+    ) -> Result<EpochPosData> {
+        let epoch = ledger_info.ledger_info().epoch();
+        let ending_block_id = ledger_info.ledger_info().consensus_block_id();
+
+        // The committed blocks of an epoch are chained by their parents from
+        // the epoch ending block. The parent of the first block is the
+        // genesis block of the epoch, which is not stored as a committed
+        // block.
+        let mut committed_blocks = Vec::new();
+        let mut block_id = ending_block_id;
+        while let Some(block) =
+            self.ledger_store.get_committed_block_option(&block_id)?
+        {
+            if block.epoch != epoch {
+                break;
+            }
+            block_id = block.parent_hash;
+            committed_blocks.push(block);
+        }
+        ensure!(
+            !committed_blocks.is_empty(),
+            "Epoch ending block {} of epoch {} is not committed.",
+            ending_block_id,
+            epoch,
+        );
+        committed_blocks.reverse();
+
+        Ok(EpochPosData {
+            epoch,
+            committed_blocks,
+            reward_event: self.ledger_store.get_reward_event_option(epoch)?,
+            pos_state: self.ledger_store.get_pos_state(&ending_block_id)?,
+        })
+    }
+}
+
+/// The Conflux specific data of an epoch, which is not covered by the
+/// transactions and the account states of the ledger.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EpochPosData {
+    pub epoch: u64,
+    /// The committed blocks of the epoch in the commit order. The last one is
+    /// the epoch ending block.
+    pub committed_blocks: Vec<CommittedBlock>,
+    /// `None` if no reward is distributed at the end of the epoch.
+    pub reward_event: Option<RewardDistributionEvent>,
+    /// The PoS state after the epoch ending block.
+    pub pos_state: PosState,
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
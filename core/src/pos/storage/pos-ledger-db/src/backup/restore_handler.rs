@@ -6,11 +6,19 @@
 // See http://www.gnu.org/licenses/
 
 use crate::{
-    change_set::ChangeSet, event_store::EventStore, ledger_store::LedgerStore,
-    schema::transaction_accumulator::TransactionAccumulatorSchema,
-    state_store::StateStore, transaction_store::TransactionStore, PosLedgerDB,
+    backup::backup_handler::EpochPosData,
+    change_set::ChangeSet,
+    event_store::EventStore,
+    ledger_store::LedgerStore,
+    schema::{
+        reward_event::RewardEventSchema,
+        transaction_accumulator::TransactionAccumulatorSchema,
+    },
+    state_store::StateStore,
+    transaction_store::TransactionStore,
+    PosLedgerDB,
 };
-use anyhow::{ensure, Result};
+use anyhow::{anyhow, ensure, Result};
 use diem_crypto::{hash::SPARSE_MERKLE_PLACEHOLDER_HASH, HashValue};
 use diem_jellyfish_merkle::restore::JellyfishMerkleRestore;
 use diem_types::{
@@ -88,6 +96,31 @@ impl RestoreHandler {
         Ok(())
     }
 
+    /// Save the committed blocks, the reward events and the PoS states of the
+    /// epochs, whose ledger infos are saved with `save_ledger_infos`.
+    pub fn save_epoch_pos_data(&self, pos_data: &[EpochPosData]) -> Result<()> {
+        let mut cs = ChangeSet::new();
+        for data in pos_data {
+            for block in &data.committed_blocks {
+                self.ledger_store.put_committed_block(block, &mut cs)?;
+            }
+            if let Some(reward_event) = &data.reward_event {
+                cs.batch
+                    .put::<RewardEventSchema>(&data.epoch, reward_event)?;
+            }
+            let ending_block =
+                data.committed_blocks.last().ok_or_else(|| {
+                    anyhow!("No committed blocks in epoch {}.", data.epoch)
+                })?;
+            self.ledger_store.put_pos_state(
+                &ending_block.hash,
+                data.pos_state.clone(),
+                &mut cs,
+            )?;
+        }
+        self.db.write_schemas(cs.batch, false)
+    }
+
     pub fn confirm_or_save_frozen_subtrees(
         &self, num_leaves: LeafCount, frozen_subtrees: &[HashValue],
     ) -> Result<()> {
@@ -5,10 +5,23 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
-use crate::{test_helper::arb_blocks_to_commit, PosLedgerDB};
+use crate::{
+    backup::backup_handler::EpochPosData, test_helper::arb_blocks_to_commit,
+    GetRestoreHandler, PosLedgerDB,
+};
 use anyhow::Result;
+use diem_crypto::HashValue;
 use diem_temppath::TempPath;
+use diem_types::{
+    block_info::{BlockInfo, PivotBlockDecision},
+    committed_block::CommittedBlock,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    reward_distribution_event::RewardDistributionEvent,
+    term_state::PosState,
+};
 use proptest::prelude::*;
+use std::{collections::BTreeMap, sync::Arc};
 use storage_interface::DbWriter;
 
 proptest! {
@@ -46,3 +59,79 @@ proptest! {
         prop_assert_eq!(actual, expected);
     }
 }
+
+fn committed_block(
+    epoch: u64, view: u64, parent_hash: HashValue,
+) -> CommittedBlock {
+    CommittedBlock {
+        hash: HashValue::random(),
+        miner: None,
+        parent_hash,
+        epoch,
+        round: view,
+        pivot_decision: PivotBlockDecision {
+            height: view,
+            block_hash: Default::default(),
+        },
+        version: view,
+        timestamp: view,
+        view,
+        is_skipped: false,
+    }
+}
+
+#[test]
+fn test_epoch_pos_data_roundtrip() {
+    // Two epochs with two blocks each. The first block of an epoch is the
+    // child of the epoch genesis block, which is not committed.
+    let mut pos_data = Vec::new();
+    let mut ledger_infos = Vec::new();
+    for epoch in 1..3 {
+        let first = committed_block(epoch, epoch * 2, HashValue::random());
+        let ending = committed_block(epoch, epoch * 2 + 1, first.hash);
+        let block_info = BlockInfo::new(
+            epoch,
+            ending.round,
+            ending.hash,
+            HashValue::zero(),
+            ending.version,
+            ending.timestamp,
+            Some(EpochState::empty()),
+            Some(ending.pivot_decision.clone()),
+        );
+        ledger_infos.push(LedgerInfoWithSignatures::new(
+            LedgerInfo::new(block_info, HashValue::zero()),
+            BTreeMap::new(),
+        ));
+        pos_data.push(EpochPosData {
+            epoch,
+            committed_blocks: vec![first, ending],
+            reward_event: if epoch == 1 {
+                None
+            } else {
+                Some(RewardDistributionEvent::default())
+            },
+            pos_state: PosState::new_empty(),
+        });
+    }
+
+    let tmp_dir = TempPath::new();
+    let db = Arc::new(PosLedgerDB::new_for_test(&tmp_dir));
+    let restore_handler = db.get_restore_handler();
+    restore_handler.save_ledger_infos(&ledger_infos).unwrap();
+    restore_handler.save_epoch_pos_data(&pos_data).unwrap();
+
+    let backup = db
+        .get_backup_handler()
+        .get_epoch_pos_data_iter(1, 3)
+        .unwrap()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(backup.len(), 2);
+    for (actual, expected) in backup.iter().zip(pos_data.iter()) {
+        assert_eq!(actual.epoch, expected.epoch);
+        assert_eq!(actual.committed_blocks, expected.committed_blocks);
+        assert_eq!(actual.reward_event, expected.reward_event);
+        assert!(actual.pos_state == expected.pos_state);
+    }
+}
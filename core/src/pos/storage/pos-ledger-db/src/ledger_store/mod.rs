@@ -509,10 +509,16 @@ impl LedgerStore {
         self.db.write_schemas(cs.batch, false)
     }
 
+    pub fn get_reward_event_option(
+        &self, epoch: u64,
+    ) -> Result<Option<RewardDistributionEvent>> {
+        self.db.get::<RewardEventSchema>(&epoch)
+    }
+
     pub fn get_reward_event(
         &self, epoch: u64,
     ) -> Result<RewardDistributionEvent> {
-        self.db.get::<RewardEventSchema>(&epoch)?.ok_or_else(|| {
+        self.get_reward_event_option(epoch)?.ok_or_else(|| {
             DiemDbError::NotFound(format!("RewardEvent of epoch {}", epoch))
                 .into()
         })
@@ -530,18 +536,22 @@ impl LedgerStore {
         Ok(())
     }
 
+    pub fn get_committed_block_option(
+        &self, block_hash: &HashValue,
+    ) -> Result<Option<CommittedBlock>> {
+        self.db.get::<CommittedBlockSchema>(&block_hash)
+    }
+
     pub fn get_committed_block_by_hash(
         &self, block_hash: &HashValue,
     ) -> Result<CommittedBlock> {
-        self.db
-            .get::<CommittedBlockSchema>(&block_hash)?
-            .ok_or_else(|| {
-                DiemDbError::NotFound(format!(
-                    "committed block of id {}",
-                    block_hash
-                ))
-                .into()
-            })
+        self.get_committed_block_option(block_hash)?.ok_or_else(|| {
+            DiemDbError::NotFound(format!(
+                "committed block of id {}",
+                block_hash
+            ))
+            .into()
+        })
     }
 
     pub fn put_ledger_info_by_voted_block(
@@ -68,6 +68,7 @@ pub struct ProofOfWorkSolution {
 #[derive(Debug, Clone, DeriveMallocSizeOf)]
 pub enum MiningType {
     Stratum,
+    GetWork,
     CPU,
     Disable,
 }
@@ -78,6 +79,7 @@ impl FromStr for MiningType {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mining_type = match s {
             "stratum" => Self::Stratum,
+            "getwork" => Self::GetWork,
             "cpu" => Self::CPU,
             "disable" => Self::Disable,
             _ => return Err("invalid mining type".into()),
@@ -157,6 +159,10 @@ impl ProofOfWorkConfig {
         matches!(self.mining_type, MiningType::Stratum)
     }
 
+    pub fn use_getwork(&self) -> bool {
+        matches!(self.mining_type, MiningType::GetWork)
+    }
+
     pub fn enable_mining(&self) -> bool {
         !matches!(self.mining_type, MiningType::Disable)
     }
@@ -0,0 +1,205 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! State changes requested by the dev mode RPCs, e.g. `dev_setBalance`.
+//!
+//! They are applied outside of transaction execution, so the state
+//! statistics are maintained here: the balance difference is minted or
+//! burnt, and in the native space the storage entries and code set this way
+//! are owned by the account itself, whose collateral is minted before being
+//! charged.
+
+use super::{State, Substate};
+use cfx_bytes::Bytes;
+use cfx_parameters::staking::{
+    code_collateral_units, DRIPS_PER_STORAGE_COLLATERAL_UNIT,
+};
+use cfx_state::{
+    state_trait::{CheckpointTrait, StateOpsTrait},
+    CleanupMode, CollateralCheckResult, StateTrait, SubstateTrait,
+};
+use cfx_statedb::Result as DbResult;
+use cfx_types::{
+    Address, AddressSpaceUtil, AddressWithSpace, Space, H256, U256,
+};
+use primitives::storage::STORAGE_LAYOUT_REGULAR_V0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DevStateOverride {
+    Balance {
+        address: AddressWithSpace,
+        balance: U256,
+    },
+    Nonce {
+        address: AddressWithSpace,
+        nonce: U256,
+    },
+    Code {
+        address: AddressWithSpace,
+        code: Bytes,
+    },
+    Storage {
+        address: AddressWithSpace,
+        key: H256,
+        value: U256,
+    },
+}
+
+impl State {
+    /// Apply `state_override` to the state. It should only be called when
+    /// there is no checkpoint, i.e. before or after the execution of
+    /// transactions.
+    pub fn apply_dev_state_override(
+        &mut self, state_override: &DevStateOverride,
+        account_start_nonce: U256, contract_start_nonce: U256,
+    ) -> DbResult<()>
+    {
+        match state_override {
+            DevStateOverride::Balance { address, balance } => {
+                self.dev_set_balance(address, balance, account_start_nonce)
+            }
+            DevStateOverride::Nonce { address, nonce } => {
+                self.set_nonce(address, nonce)
+            }
+            DevStateOverride::Code { address, code } => self.dev_set_code(
+                address,
+                code,
+                account_start_nonce,
+                contract_start_nonce,
+            ),
+            DevStateOverride::Storage {
+                address,
+                key,
+                value,
+            } => self.dev_set_storage(address, key, value, account_start_nonce),
+        }
+    }
+
+    fn dev_set_balance(
+        &mut self, address: &AddressWithSpace, balance: &U256,
+        account_start_nonce: U256,
+    ) -> DbResult<()>
+    {
+        let current = self.balance(address)?;
+        if *balance > current {
+            let minted = *balance - current;
+            self.add_balance(
+                address,
+                &minted,
+                CleanupMode::NoEmpty,
+                account_start_nonce,
+            )?;
+            self.add_total_issued(minted);
+            if address.space == Space::Ethereum {
+                self.add_total_evm_tokens(minted);
+            }
+        } else if *balance < current {
+            let burnt = current - *balance;
+            self.sub_balance(address, &burnt, &mut CleanupMode::NoEmpty)?;
+            self.subtract_total_issued(burnt);
+            if address.space == Space::Ethereum {
+                self.subtract_total_evm_tokens(burnt);
+            }
+        }
+        Ok(())
+    }
+
+    fn dev_set_code(
+        &mut self, address: &AddressWithSpace, code: &Bytes,
+        account_start_nonce: U256, contract_start_nonce: U256,
+    ) -> DbResult<()>
+    {
+        let mut substate = Substate::default();
+        if address.space == Space::Native {
+            if let (Some(code_size), Some(code_owner)) =
+                (self.code_size(address)?, self.code_owner(address)?)
+            {
+                substate.record_storage_release(
+                    &code_owner,
+                    code_collateral_units(code_size),
+                );
+            }
+            substate.record_storage_occupy(
+                &address.address,
+                code_collateral_units(code.len()),
+            );
+        }
+
+        if !self.exists(address)? {
+            self.new_contract_with_admin(
+                address,
+                &Address::zero(),
+                U256::zero(),
+                contract_start_nonce,
+                Some(STORAGE_LAYOUT_REGULAR_V0),
+            )?;
+        }
+        let code_owner = match address.space {
+            Space::Native => address.address,
+            Space::Ethereum => Address::zero(),
+        };
+        self.init_code(address, code.clone(), code_owner)?;
+        self.dev_settle_collateral(&substate, account_start_nonce)
+    }
+
+    fn dev_set_storage(
+        &mut self, address: &AddressWithSpace, key: &H256, value: &U256,
+        account_start_nonce: U256,
+    ) -> DbResult<()>
+    {
+        let key = key.as_bytes().to_vec();
+        if address.space == Space::Ethereum {
+            // The storage in the Ethereum space has no owner.
+            return self.set_storage(address, key, *value, Address::zero());
+        }
+
+        // The ownership changes are only collected for the accounts in the
+        // last checkpoint.
+        let mut substate = Substate::default();
+        self.checkpoint();
+        let result = self
+            .set_storage(address, key, *value, address.address)
+            .and_then(|_| self.collect_ownership_changed(&mut substate));
+        self.discard_checkpoint();
+        result?;
+        self.dev_settle_collateral(&substate, account_start_nonce)
+    }
+
+    /// Mint the collateral newly occupied in `substate` to the owners, and
+    /// then charge and refund the collateral.
+    fn dev_settle_collateral(
+        &mut self, substate: &Substate, account_start_nonce: U256,
+    ) -> DbResult<()> {
+        for address in substate.keys_for_collateral_changed() {
+            let (inc, _) = substate.get_collateral_change(address);
+            let minted = *DRIPS_PER_STORAGE_COLLATERAL_UNIT * inc;
+            if minted.is_zero() {
+                continue;
+            }
+            let address_with_space = address.with_native_space();
+            if self.is_contract_with_code(&address_with_space)? {
+                self.add_sponsor_balance_for_collateral(address, &minted)?;
+            } else {
+                self.add_balance(
+                    &address_with_space,
+                    &minted,
+                    CleanupMode::NoEmpty,
+                    account_start_nonce,
+                )?;
+            }
+            self.add_total_issued(minted);
+        }
+        let result = self.settle_collateral_for_all(
+            substate,
+            &mut (),
+            account_start_nonce,
+            false, /* dry_run_no_charge */
+        )?;
+        assert!(
+            matches!(result, CollateralCheckResult::Valid),
+            "the collateral is minted before being charged"
+        );
+        Ok(())
+    }
+}
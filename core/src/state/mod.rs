@@ -9,7 +9,8 @@ use std::{
 
 use num::integer::Roots;
 use parking_lot::{
-    MappedRwLockWriteGuard, RwLock, RwLockUpgradableReadGuard, RwLockWriteGuard,
+    MappedRwLockWriteGuard, Mutex, RwLock, RwLockUpgradableReadGuard,
+    RwLockWriteGuard,
 };
 
 use cfx_bytes::Bytes;
@@ -43,8 +44,9 @@ use diem_types::term_state::MAX_TERM_POINTS;
 #[cfg(test)]
 use primitives::storage::STORAGE_LAYOUT_REGULAR_V0;
 use primitives::{
-    Account, DepositList, EpochId, SkipInputCheck, SponsorInfo, StorageKey,
-    StorageKeyWithSpace, StorageLayout, StorageValue, VoteStakeList,
+    Account, DepositList, EpochId, SkipInputCheck, SponsorInfo,
+    StateEntryChange, StorageKey, StorageKeyPreimage, StorageKeyWithSpace,
+    StorageLayout, StorageValue, VoteStakeList,
 };
 
 use crate::{
@@ -60,16 +62,23 @@ use crate::{
     transaction_pool::SharedTransactionPool,
 };
 
-use self::account_entry::{AccountEntry, AccountState};
+use self::{
+    account_entry::{AccountEntry, AccountState},
+    speculative::StateAccesses,
+};
 pub use self::{
     account_entry::{OverlayAccount, COMMISSION_PRIVILEGE_SPECIAL_KEY},
+    dev_state_override::DevStateOverride,
+    speculative::SpeculativeChanges,
     substate::{cleanup_mode, CallStackInfo, Substate},
 };
 
 mod account_entry;
 #[cfg(test)]
 mod account_entry_tests;
+mod dev_state_override;
 pub mod prefetcher;
+mod speculative;
 #[cfg(test)]
 mod state_tests;
 mod substate;
@@ -82,7 +91,7 @@ pub enum RequireCache {
     VoteStakeList,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 struct WorldStatistics {
     // This is the total number of CFX issued.
     total_issued_tokens: U256,
@@ -123,6 +132,12 @@ pub struct StateGeneric {
     // Checkpoint to the changes.
     world_statistics_checkpoints: RwLock<Vec<WorldStatistics>>,
     checkpoints: RwLock<Vec<HashMap<AddressWithSpace, Option<AccountEntry>>>>,
+
+    // The accesses recorded during speculative execution.
+    accesses: Option<Mutex<StateAccesses>>,
+    // Whether the db is modified by transactions before commit, i.e. by
+    // removing the whitelists of a killed contract.
+    db_modified: bool,
 }
 
 impl StateTrait for StateGeneric {
@@ -219,6 +234,7 @@ impl StateTrait for StateGeneric {
                 .commit_ownership_change(&self.db, substate)?;
         }
 
+        self.record_account_access(&address.with_native_space());
         let account_cache_read_guard = self.cache.read();
         let maybe_account = account_cache_read_guard
             .get(&address.with_native_space())
@@ -1038,30 +1054,37 @@ impl StateOpsTrait for StateGeneric {
     }
 
     fn total_issued_tokens(&self) -> U256 {
+        self.record_world_statistics_read();
         self.world_statistics.total_issued_tokens
     }
 
     fn total_espace_tokens(&self) -> U256 {
+        self.record_world_statistics_read();
         self.world_statistics.total_evm_tokens
     }
 
     fn total_staking_tokens(&self) -> U256 {
+        self.record_world_statistics_read();
         self.world_statistics.total_staking_tokens
     }
 
     fn total_storage_tokens(&self) -> U256 {
+        self.record_world_statistics_read();
         self.world_statistics.total_storage_tokens
     }
 
     fn total_pos_staking_tokens(&self) -> U256 {
+        self.record_world_statistics_read();
         self.world_statistics.total_pos_staking_tokens
     }
 
     fn distributable_pos_interest(&self) -> U256 {
+        self.record_world_statistics_read();
         self.world_statistics.distributable_pos_interest
     }
 
     fn last_distribute_block(&self) -> u64 {
+        self.record_world_statistics_read();
         self.world_statistics.last_distribute_block
     }
 
@@ -1079,6 +1102,7 @@ impl StateOpsTrait for StateGeneric {
             }
         }
 
+        self.record_account_access(address);
         Self::update_cache(
             self.cache.get_mut(),
             self.checkpoints.get_mut(),
@@ -1340,6 +1364,8 @@ impl StateGeneric {
             checkpoints: Default::default(),
             world_statistics: world_stat,
             accounts_to_notify: Default::default(),
+            accesses: None,
+            db_modified: false,
         })
     }
 
@@ -1419,6 +1445,20 @@ impl StateGeneric {
         }
     }
 
+    /// Take the preimages of the storage keys committed by this state, see
+    /// `StateDb::enable_storage_key_preimage_recording`.
+    pub fn take_storage_key_preimages(
+        &mut self,
+    ) -> Vec<(Vec<u8>, StorageKeyPreimage)> {
+        self.db.take_storage_key_preimages()
+    }
+
+    /// Take the state entries changed by the commit of this state, see
+    /// `StateDb::enable_state_change_recording`.
+    pub fn take_state_changes(&mut self) -> Vec<StateEntryChange> {
+        self.db.take_state_changes()
+    }
+
     #[cfg(test)]
     pub fn new_contract(
         &mut self, contract: &AddressWithSpace, balance: U256, nonce: U256,
@@ -1693,6 +1733,9 @@ impl StateGeneric {
             .with_native_space(),
             /* debug_record = */ None,
         )?;
+        if !AM::is_read_only() && !key_values.is_empty() {
+            self.db_modified = true;
+        }
         let mut sponsor_whitelist_control_address = self.require_exists(
             &SPONSOR_WHITELIST_CONTROL_CONTRACT_ADDRESS.with_native_space(),
             /* require_code = */ false,
@@ -1927,6 +1970,7 @@ impl StateGeneric {
         &self, address: &AddressWithSpace, require: RequireCache, f: F,
     ) -> DbResult<U>
     where F: Fn(Option<&OverlayAccount>) -> U {
+        self.record_account_access(address);
         // Return immediately when there is no need to have db operation.
         if let Some(maybe_acc) = self.cache.read().get(address) {
             if let Some(account) = &maybe_acc.account {
@@ -2017,6 +2061,7 @@ impl StateGeneric {
         &self, address: &AddressWithSpace, require_code: bool, default: F,
     ) -> DbResult<MappedRwLockWriteGuard<OverlayAccount>>
     where F: FnOnce(&AddressWithSpace) -> DbResult<OverlayAccount> {
+        self.record_account_access(address);
         let mut cache;
         if !self.cache.read().contains_key(address) {
             let account = self
@@ -0,0 +1,258 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Speculative execution on a fork of the state, which is used to execute
+//! the transactions of a block in parallel.
+//!
+//! A transaction executed speculatively sees the state at the fork point.
+//! The accounts and the world statistics it accesses are recorded, so that
+//! its changes can be applied to the original state later if none of the
+//! accessed entries has been changed in between.
+
+use super::{AccountEntry, State, WorldStatistics};
+use cfx_state::state_trait::CheckpointTrait;
+use cfx_statedb::{Result as DbResult, StateDbGeneric as StateDb};
+use cfx_types::{AddressWithSpace, U256};
+use parking_lot::RwLock;
+use std::collections::HashSet;
+
+#[derive(Default)]
+pub(super) struct StateAccesses {
+    accounts: HashSet<AddressWithSpace>,
+    world_statistics_read: bool,
+}
+
+/// The changes of a transaction executed on a speculative state.
+pub struct SpeculativeChanges {
+    accesses: StateAccesses,
+    accounts: Vec<(AddressWithSpace, AccountEntry)>,
+    base_world_statistics: WorldStatistics,
+    world_statistics: WorldStatistics,
+    db_modified: bool,
+}
+
+impl SpeculativeChanges {
+    /// The accounts loaded or written by the transaction.
+    pub fn accessed_accounts(&self) -> &HashSet<AddressWithSpace> {
+        &self.accesses.accounts
+    }
+
+    pub fn written_accounts(&self) -> impl Iterator<Item = &AddressWithSpace> {
+        self.accounts.iter().map(|(address, _)| address)
+    }
+
+    /// Whether the transaction reads the world statistics which can be
+    /// updated by other transactions, e.g. `total_issued_tokens`.
+    pub fn world_statistics_read(&self) -> bool {
+        self.accesses.world_statistics_read
+    }
+
+    pub fn world_statistics_changed(&self) -> bool {
+        self.world_statistics != self.base_world_statistics
+    }
+
+    /// Whether the transaction modifies the db directly. Such changes are
+    /// not recorded, so the transaction must be executed again.
+    pub fn db_modified(&self) -> bool { self.db_modified }
+}
+
+impl State {
+    /// Whether a speculative state can be forked from this state, i.e. there
+    /// is no checkpoint and the db has not been modified before commit.
+    pub fn can_speculate(&self) -> bool {
+        !self.db_modified && self.checkpoints.read().is_empty()
+    }
+
+    /// Fork a speculative state from this state, where `db` must be a new
+    /// state db for the same epoch as this state.
+    pub fn new_speculative(&self, db: StateDb) -> State {
+        assert!(self.can_speculate());
+        let cache = self
+            .cache
+            .read()
+            .iter()
+            .map(|(address, entry)| (*address, entry.clone_dirty()))
+            .collect();
+        State {
+            db,
+            accounts_to_notify: Default::default(),
+            cache: RwLock::new(cache),
+            world_statistics: self.world_statistics,
+            world_statistics_checkpoints: Default::default(),
+            checkpoints: Default::default(),
+            accesses: None,
+            db_modified: false,
+        }
+    }
+
+    /// Run `f` on this speculative state, and return its result together
+    /// with the changes it made. The changes are reverted from this state
+    /// afterwards. If the db is modified by `f`, this state can no longer be
+    /// used.
+    pub fn speculate<F, T>(
+        &mut self, f: F,
+    ) -> DbResult<(T, SpeculativeChanges)>
+    where F: FnOnce(&mut State) -> DbResult<T> {
+        let base_world_statistics = self.world_statistics;
+        self.accesses = Some(Default::default());
+        self.checkpoint();
+        let result = f(self);
+
+        let cache = self.cache.get_mut();
+        let accounts = self
+            .checkpoints
+            .get_mut()
+            .last()
+            .expect("checkpoint exists")
+            .keys()
+            .filter_map(|address| {
+                cache
+                    .get(address)
+                    .map(|entry| (*address, entry.clone_dirty()))
+            })
+            .collect();
+        let world_statistics = self.world_statistics;
+        self.revert_to_checkpoint();
+        let accesses = self
+            .accesses
+            .take()
+            .expect("accesses are recorded")
+            .into_inner();
+
+        Ok((
+            result?,
+            SpeculativeChanges {
+                accesses,
+                accounts,
+                base_world_statistics,
+                world_statistics,
+                db_modified: self.db_modified,
+            },
+        ))
+    }
+
+    /// Apply `changes` made on a speculative state forked from this state.
+    /// The caller must make sure that the entries accessed by `changes` have
+    /// not been changed since the fork. Return `false` without applying
+    /// anything if the changes can not be applied.
+    pub fn apply_speculative_changes(
+        &mut self, changes: SpeculativeChanges,
+    ) -> bool {
+        assert!(self.checkpoints.get_mut().is_empty());
+        if changes.db_modified {
+            return false;
+        }
+        match self.world_statistics.merge_changes(
+            &changes.base_world_statistics,
+            &changes.world_statistics,
+        ) {
+            Some(world_statistics) => self.world_statistics = world_statistics,
+            None => return false,
+        }
+        let cache = self.cache.get_mut();
+        for (address, entry) in changes.accounts {
+            cache.insert(address, entry);
+        }
+        true
+    }
+
+    /// Run `f` on this state, and return its result together with the
+    /// accounts written and whether the world statistics are changed.
+    pub fn collect_writes<F, T>(
+        &mut self, f: F,
+    ) -> DbResult<(T, HashSet<AddressWithSpace>, bool)>
+    where F: FnOnce(&mut State) -> DbResult<T> {
+        let base_world_statistics = self.world_statistics;
+        self.checkpoint();
+        let result = f(self);
+        let written = self
+            .checkpoints
+            .get_mut()
+            .last()
+            .expect("checkpoint exists")
+            .keys()
+            .cloned()
+            .collect();
+        self.discard_checkpoint();
+        Ok((
+            result?,
+            written,
+            self.world_statistics != base_world_statistics,
+        ))
+    }
+
+    pub(super) fn record_account_access(&self, address: &AddressWithSpace) {
+        if let Some(accesses) = &self.accesses {
+            accesses.lock().accounts.insert(*address);
+        }
+    }
+
+    pub(super) fn record_world_statistics_read(&self) {
+        if let Some(accesses) = &self.accesses {
+            accesses.lock().world_statistics_read = true;
+        }
+    }
+}
+
+impl WorldStatistics {
+    /// Apply the changes from `base` to `updated` on `self`. The changes can
+    /// only be applied when the parameters, which are not updated by
+    /// transactions, are unchanged.
+    fn merge_changes(&self, base: &Self, updated: &Self) -> Option<Self> {
+        if updated.interest_rate_per_block != base.interest_rate_per_block
+            || updated.accumulate_interest_rate != base.accumulate_interest_rate
+            || updated.last_distribute_block != base.last_distribute_block
+        {
+            return None;
+        }
+        Some(WorldStatistics {
+            total_issued_tokens: merge_change(
+                self.total_issued_tokens,
+                base.total_issued_tokens,
+                updated.total_issued_tokens,
+            )?,
+            total_staking_tokens: merge_change(
+                self.total_staking_tokens,
+                base.total_staking_tokens,
+                updated.total_staking_tokens,
+            )?,
+            total_storage_tokens: merge_change(
+                self.total_storage_tokens,
+                base.total_storage_tokens,
+                updated.total_storage_tokens,
+            )?,
+            interest_rate_per_block: self.interest_rate_per_block,
+            accumulate_interest_rate: self.accumulate_interest_rate,
+            total_pos_staking_tokens: merge_change(
+                self.total_pos_staking_tokens,
+                base.total_pos_staking_tokens,
+                updated.total_pos_staking_tokens,
+            )?,
+            distributable_pos_interest: merge_change(
+                self.distributable_pos_interest,
+                base.distributable_pos_interest,
+                updated.distributable_pos_interest,
+            )?,
+            last_distribute_block: self.last_distribute_block,
+            total_evm_tokens: merge_change(
+                self.total_evm_tokens,
+                base.total_evm_tokens,
+                updated.total_evm_tokens,
+            )?,
+        })
+    }
+}
+
+/// Apply the change from `base` to `updated` on `current`. A value decreased
+/// to zero may be the result of a saturating subtraction, which is not a
+/// change that can be applied on another value.
+fn merge_change(current: U256, base: U256, updated: U256) -> Option<U256> {
+    if updated >= base {
+        current.checked_add(updated - base)
+    } else if updated.is_zero() {
+        None
+    } else {
+        current.checked_sub(base - updated)
+    }
+}
@@ -2,7 +2,9 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
-use super::{CleanupMode, CollateralCheckResult, State, Substate};
+use super::{
+    CleanupMode, CollateralCheckResult, DevStateOverride, State, Substate,
+};
 use crate::{
     spec::genesis::DEV_GENESIS_KEY_PAIR,
     test_helpers::get_state_for_genesis_write, vm::Spec,
@@ -19,7 +21,8 @@ use cfx_storage::{
     StorageManagerTrait,
 };
 use cfx_types::{
-    address_util::AddressUtil, Address, AddressSpaceUtil, BigEndianHash, U256,
+    address_util::AddressUtil, Address, AddressSpaceUtil, BigEndianHash, H256,
+    U256,
 };
 use keccak_hash::{keccak, KECCAK_EMPTY};
 use primitives::{EpochId, StorageKey, StorageLayout};
@@ -1888,3 +1891,97 @@ fn test_automatic_collateral_contract_account() {
     assert_eq!(state.total_storage_tokens(), U256::from(0));
     assert_eq!(state.secondary_reward(), U256::from(0));
 }
+
+#[test]
+fn test_dev_state_override() {
+    let storage_manager = new_state_manager_for_unit_test();
+    let mut state = get_state_for_genesis_write(&storage_manager);
+    let spec = Spec::new_spec_for_test();
+    let mut contract_account = Address::from_low_u64_be(1);
+    contract_account.set_contract_type_bits();
+    let contract_account_s = contract_account.with_native_space();
+    let total_issued = state.total_issued_tokens();
+    let apply = |state: &mut State, state_override: DevStateOverride| {
+        state
+            .apply_dev_state_override(
+                &state_override,
+                spec.account_start_nonce,
+                spec.contract_start_nonce,
+            )
+            .unwrap()
+    };
+
+    apply(
+        &mut state,
+        DevStateOverride::Balance {
+            address: contract_account_s,
+            balance: U256::from(100),
+        },
+    );
+    assert_eq!(state.balance(&contract_account_s).unwrap(), U256::from(100));
+    assert_eq!(state.total_issued_tokens(), total_issued + U256::from(100));
+
+    apply(
+        &mut state,
+        DevStateOverride::Code {
+            address: contract_account_s,
+            code: vec![0x12, 0x34],
+        },
+    );
+    assert_eq!(
+        *state.code(&contract_account_s).unwrap().unwrap(),
+        vec![0x12, 0x34]
+    );
+    let code_collateral = *DRIPS_PER_STORAGE_COLLATERAL_UNIT
+        * U256::from(code_collateral_units(2));
+    assert_eq!(
+        state.collateral_for_storage(&contract_account).unwrap(),
+        code_collateral
+    );
+
+    // The collateral of a new entry is minted to the contract.
+    let key = H256::from_low_u64_be(1);
+    apply(
+        &mut state,
+        DevStateOverride::Storage {
+            address: contract_account_s,
+            key,
+            value: U256::one(),
+        },
+    );
+    assert_eq!(
+        state
+            .storage_at(&contract_account_s, key.as_bytes())
+            .unwrap(),
+        U256::one()
+    );
+    assert_eq!(
+        state.collateral_for_storage(&contract_account).unwrap(),
+        code_collateral + *COLLATERAL_DRIPS_PER_STORAGE_KEY
+    );
+    assert_eq!(
+        state.total_storage_tokens(),
+        code_collateral + *COLLATERAL_DRIPS_PER_STORAGE_KEY
+    );
+
+    // The collateral of a released entry is refunded.
+    apply(
+        &mut state,
+        DevStateOverride::Storage {
+            address: contract_account_s,
+            key,
+            value: U256::zero(),
+        },
+    );
+    assert_eq!(
+        state.collateral_for_storage(&contract_account).unwrap(),
+        code_collateral
+    );
+    assert_eq!(
+        state
+            .sponsor_balance_for_collateral(&contract_account)
+            .unwrap(),
+        *COLLATERAL_DRIPS_PER_STORAGE_KEY
+    );
+    assert_eq!(state.balance(&contract_account_s).unwrap(), U256::from(100));
+}
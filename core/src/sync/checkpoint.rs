@@ -0,0 +1,616 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Trusted checkpoint to start synchronization from.
+//!
+//! A new full node normally syncs the whole header chain from the true
+//! genesis before it could sync the state. With a trusted checkpoint provided
+//! by the operator, the node starts with the era genesis in the checkpoint
+//! instead, so only the headers after it are synced, followed by the usual
+//! snapshot state sync phases.
+//!
+//! The checkpoint is verified against the PoS ledger: the epoch change proof
+//! is verified from the PoS waypoint in the PoS config, and the ledger info
+//! signed by the resulting committee must commit a pivot block decision on
+//! the last header of the checkpoint, which links to the era genesis through
+//! the parent hashes of the included headers. The state root of the era
+//! genesis is verified with the deferred state root in the header
+//! `DEFERRED_STATE_EPOCH_COUNT` epochs later, and the blocks of the era
+//! genesis epoch with their headers, which must be in the past of the era
+//! genesis.
+//!
+//! The start block number of the epoch is not committed by any header, so it
+//! is checked against the receipts synced for the epoch instead, whose count
+//! is committed by the deferred receipts root.
+
+use crate::block_data_manager::{
+    BlockDataManager, BlockStatus, EpochExecutionContext, LocalBlockInfo,
+};
+use cfx_parameters::consensus::DEFERRED_STATE_EPOCH_COUNT;
+use cfx_types::H256;
+use diem_types::{
+    epoch_change::{EpochChangeProof, Verifier},
+    ledger_info::LedgerInfoWithSignatures,
+    waypoint::Waypoint,
+};
+use primitives::{BlockHeader, BlockReceipts};
+use rustc_hex::{FromHex, ToHex};
+use serde_derive::{Deserialize, Serialize};
+use std::{collections::HashSet, fs, path::Path, sync::Arc};
+
+/// The JSON format of a checkpoint file, where encoded data are in hex.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CheckpointFile {
+    /// RLP encoded pivot chain headers from the era genesis to the block
+    /// committed by the PoS ledger info.
+    headers: Vec<String>,
+    /// RLP encoded headers of the executed blocks in the era genesis epoch in
+    /// execution order, where the last one is the era genesis.
+    epoch_headers: Vec<String>,
+    /// Block number of the first block in the era genesis epoch.
+    start_block_number: u64,
+    /// Hash of the state root after executing the era genesis epoch.
+    state_root: H256,
+    /// BCS encoded `EpochChangeProof` from the PoS genesis.
+    epoch_change_proof: String,
+    /// BCS encoded `LedgerInfoWithSignatures`.
+    ledger_info: String,
+}
+
+pub struct TrustedCheckpoint {
+    pub headers: Vec<BlockHeader>,
+    pub epoch_headers: Vec<BlockHeader>,
+    pub start_block_number: u64,
+    pub state_root: H256,
+    pub epoch_change_proof: EpochChangeProof,
+    pub ledger_info: LedgerInfoWithSignatures,
+}
+
+impl TrustedCheckpoint {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read checkpoint file: {:?}", e))?;
+        let file: CheckpointFile = serde_json::from_str(&content)
+            .map_err(|e| format!("failed to parse checkpoint file: {:?}", e))?;
+
+        let headers = decode_headers(&file.headers)?;
+        let epoch_headers = decode_headers(&file.epoch_headers)?;
+        let epoch_change_proof =
+            bcs::from_bytes(&decode_hex(&file.epoch_change_proof)?)
+                .map_err(|e| format!("invalid epoch change proof: {:?}", e))?;
+        let ledger_info = bcs::from_bytes(&decode_hex(&file.ledger_info)?)
+            .map_err(|e| format!("invalid ledger info: {:?}", e))?;
+
+        Ok(TrustedCheckpoint {
+            headers,
+            epoch_headers,
+            start_block_number: file.start_block_number,
+            state_root: file.state_root,
+            epoch_change_proof,
+            ledger_info,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let file = CheckpointFile {
+            headers: encode_headers(&self.headers),
+            epoch_headers: encode_headers(&self.epoch_headers),
+            start_block_number: self.start_block_number,
+            state_root: self.state_root,
+            epoch_change_proof: encode_hex(
+                &bcs::to_bytes(&self.epoch_change_proof)
+                    .map_err(|e| format!("{:?}", e))?,
+            ),
+            ledger_info: encode_hex(
+                &bcs::to_bytes(&self.ledger_info)
+                    .map_err(|e| format!("{:?}", e))?,
+            ),
+        };
+        let content = serde_json::to_string_pretty(&file)
+            .map_err(|e| format!("{:?}", e))?;
+        fs::write(path, content)
+            .map_err(|e| format!("failed to write checkpoint file: {:?}", e))
+    }
+
+    pub fn era_genesis(&self) -> Option<&BlockHeader> { self.headers.first() }
+
+    /// Hashes of the executed blocks in the era genesis epoch in execution
+    /// order.
+    pub fn epoch_block_hashes(&self) -> Vec<H256> {
+        self.epoch_headers
+            .iter()
+            .map(|header| header.hash())
+            .collect()
+    }
+
+    /// Verify the checkpoint with the PoS waypoint. Only a verified checkpoint
+    /// can be installed.
+    pub fn verify(
+        self, waypoint: &Waypoint,
+    ) -> Result<VerifiedCheckpoint, String> {
+        self.verify_headers()?;
+
+        let epoch_change = self
+            .epoch_change_proof
+            .verify(waypoint)
+            .map_err(|e| format!("invalid epoch change proof: {:?}", e))?;
+        let epoch_state = epoch_change
+            .ledger_info()
+            .next_epoch_state()
+            .ok_or("epoch change proof has no next epoch state")?;
+        epoch_state
+            .verify(&self.ledger_info)
+            .map_err(|e| format!("invalid ledger info: {:?}", e))?;
+
+        let committed = self.headers.last().expect("checked");
+        match self.ledger_info.ledger_info().pivot_decision() {
+            Some(decision)
+                if decision.block_hash == committed.hash()
+                    && decision.height == committed.height() =>
+            {
+                Ok(VerifiedCheckpoint { checkpoint: self })
+            }
+            _ => Err("ledger info does not commit the checkpoint".into()),
+        }
+    }
+
+    fn verify_headers(&self) -> Result<(), String> {
+        let era_genesis = self.era_genesis().ok_or("no header")?;
+        for pair in self.headers.windows(2) {
+            if *pair[1].parent_hash() != pair[0].hash()
+                || pair[1].height() != pair[0].height() + 1
+            {
+                return Err(format!(
+                    "header {:?} is not the child of {:?}",
+                    pair[1].hash(),
+                    pair[0].hash()
+                ));
+            }
+        }
+        self.verify_epoch_headers(era_genesis)?;
+
+        // With blames, the deferred state root is the root of the state roots
+        // of multiple epochs, which are not included in the checkpoint.
+        let deferred = self
+            .headers
+            .get(DEFERRED_STATE_EPOCH_COUNT as usize)
+            .ok_or("the header with the era genesis state root is missing")?;
+        if deferred.blame() != 0 {
+            return Err(format!(
+                "the state root of the era genesis is blamed by {:?}",
+                deferred.hash()
+            ));
+        }
+        if *deferred.deferred_state_root() != self.state_root {
+            return Err(format!(
+                "state root {:?} mismatches the deferred state root {:?}",
+                self.state_root,
+                deferred.deferred_state_root()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check that the epoch headers end with the era genesis and every other
+    /// block is referenced by a block executed after it in the epoch, i.e.
+    /// all the blocks are in the past of the era genesis.
+    fn verify_epoch_headers(
+        &self, era_genesis: &BlockHeader,
+    ) -> Result<(), String> {
+        if self.epoch_headers.last().map(|header| header.hash())
+            != Some(era_genesis.hash())
+        {
+            return Err("the era genesis should be the last block executed \
+                        in its epoch"
+                .into());
+        }
+        let mut referenced = HashSet::new();
+        for header in self.epoch_headers.iter().rev() {
+            let hash = header.hash();
+            if hash != era_genesis.hash() && !referenced.contains(&hash) {
+                return Err(format!(
+                    "block {:?} is not in the epoch of the era genesis",
+                    hash
+                ));
+            }
+            referenced.insert(*header.parent_hash());
+            referenced.extend(header.referee_hashes().iter().cloned());
+        }
+        Ok(())
+    }
+}
+
+/// A trusted checkpoint verified with the PoS ledger.
+pub struct VerifiedCheckpoint {
+    checkpoint: TrustedCheckpoint,
+}
+
+impl VerifiedCheckpoint {
+    /// Make the era genesis of the checkpoint the current era genesis of the
+    /// data manager, which should be called before the consensus graph is
+    /// created. It is a no-op if the database has synced beyond the era
+    /// genesis.
+    pub fn install(
+        &self, data_man: &BlockDataManager, era_epoch_count: u64,
+    ) -> Result<(), String> {
+        let checkpoint = &self.checkpoint;
+        let era_genesis = checkpoint.era_genesis().expect("verified");
+        let hash = era_genesis.hash();
+        let height = era_genesis.height();
+        if height % era_epoch_count != 0 {
+            return Err(format!(
+                "checkpoint height {} is not at an era boundary",
+                height
+            ));
+        }
+
+        let cur_era_genesis = data_man.get_cur_consensus_era_genesis_hash();
+        if cur_era_genesis == hash {
+            info!("Trusted checkpoint {:?} is already installed", hash);
+            data_man
+                .set_trusted_checkpoint_state_root(hash, checkpoint.state_root);
+            return Ok(());
+        }
+        if cur_era_genesis != data_man.true_genesis.hash() {
+            let cur_height =
+                data_man.block_height_by_hash(&cur_era_genesis).unwrap_or(0);
+            if cur_height >= height {
+                info!(
+                    "Ignore trusted checkpoint at height {}, which is before \
+                     the current era genesis at height {}",
+                    height, cur_height
+                );
+                return Ok(());
+            }
+            return Err(format!(
+                "the database has synced to the era at height {}, please \
+                 start with an empty database to use the checkpoint",
+                cur_height
+            ));
+        }
+
+        info!(
+            "Install trusted checkpoint: era_genesis={:?} height={}",
+            hash, height
+        );
+        data_man.insert_block_header(
+            hash,
+            Arc::new(era_genesis.clone()),
+            true, /* persistent */
+        );
+        data_man.insert_executed_epoch_set_hashes_to_db(
+            height,
+            &checkpoint.epoch_block_hashes(),
+        );
+        data_man.insert_epoch_execution_context(
+            hash,
+            EpochExecutionContext {
+                start_block_number: checkpoint.start_block_number,
+            },
+            true, /* persistent */
+        );
+        data_man.insert_local_block_info(
+            &hash,
+            LocalBlockInfo::new(
+                BlockStatus::Valid,
+                0,
+                data_man.get_instance_id(),
+            ),
+        );
+        data_man.set_cur_consensus_era_genesis_hash(&hash, &hash);
+        data_man.set_trusted_checkpoint_state_root(hash, checkpoint.state_root);
+        Ok(())
+    }
+}
+
+/// Check the state and the receipts synced for `snapshot_epoch_id` with the
+/// trusted checkpoint, if it is the era genesis of the checkpoint.
+/// `epoch_receipts` are the verified receipts in the manifest, as tuples of
+/// the block hash, the epoch hash and the receipts.
+pub fn verify_synced_epoch(
+    data_man: &BlockDataManager, snapshot_epoch_id: &H256,
+    state_root_hash: &H256, epoch_receipts: &[(H256, H256, Arc<BlockReceipts>)],
+) -> Result<(), String>
+{
+    let trusted_state_root =
+        match data_man.trusted_checkpoint_state_root(snapshot_epoch_id) {
+            Some(state_root) => state_root,
+            None => return Ok(()),
+        };
+    if *state_root_hash != trusted_state_root {
+        return Err(format!(
+            "state root {:?} mismatches the trusted checkpoint {:?}",
+            state_root_hash, trusted_state_root
+        ));
+    }
+
+    let start_block_number = data_man
+        .get_epoch_execution_context(snapshot_epoch_id)
+        .map(|ctx| ctx.start_block_number);
+    let synced_block_number = epoch_receipts
+        .iter()
+        .find(|(_, epoch_hash, _)| epoch_hash == snapshot_epoch_id)
+        .map(|(_, _, receipts)| receipts.block_number);
+    if synced_block_number != start_block_number {
+        return Err(format!(
+            "start block number {:?} mismatches the trusted checkpoint {:?}",
+            synced_block_number, start_block_number
+        ));
+    }
+    Ok(())
+}
+
+fn decode_headers(headers: &[String]) -> Result<Vec<BlockHeader>, String> {
+    let mut decoded = Vec::new();
+    for header in headers {
+        decoded.push(
+            rlp::decode(&decode_hex(header)?)
+                .map_err(|e| format!("invalid header: {:?}", e))?,
+        );
+    }
+    Ok(decoded)
+}
+
+fn encode_headers(headers: &[BlockHeader]) -> Vec<String> {
+    headers
+        .iter()
+        .map(|header| encode_hex(&rlp::encode(header)))
+        .collect()
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, String> {
+    s.trim_start_matches("0x")
+        .from_hex()
+        .map_err(|e| format!("invalid hex: {:?}", e))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    format!("0x{}", bytes.to_hex::<String>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        block_data_manager::DbType, pow::PowComputer,
+        sync::utils::initialize_data_manager, vm_factory::VmFactory,
+    };
+    use cfx_types::U256;
+    use diem_crypto::HashValue;
+    use diem_types::{block_info::BlockInfo, ledger_info::LedgerInfo};
+    use primitives::BlockHeaderBuilder;
+    use std::collections::BTreeMap;
+    use tempdir::TempDir;
+
+    fn header(parent_hash: H256, height: u64) -> BlockHeader {
+        BlockHeaderBuilder::new()
+            .with_parent_hash(parent_hash)
+            .with_height(height)
+            .build()
+    }
+
+    /// A checkpoint with the era genesis at height 2000, which references
+    /// another block in its epoch.
+    fn checkpoint() -> TrustedCheckpoint {
+        let state_root = H256::random();
+        let referee = header(H256::random(), 1999);
+        let era_genesis = BlockHeaderBuilder::new()
+            .with_parent_hash(H256::random())
+            .with_height(2000)
+            .with_referee_hashes(vec![referee.hash()])
+            .build();
+        let mut headers = vec![era_genesis.clone()];
+        for _ in 0..DEFERRED_STATE_EPOCH_COUNT {
+            let parent = headers.last().unwrap();
+            headers.push(
+                BlockHeaderBuilder::new()
+                    .with_parent_hash(parent.hash())
+                    .with_height(parent.height() + 1)
+                    .with_deferred_state_root(state_root)
+                    .build(),
+            );
+        }
+        TrustedCheckpoint {
+            headers,
+            epoch_headers: vec![referee, era_genesis],
+            start_block_number: 100,
+            state_root,
+            epoch_change_proof: EpochChangeProof::new(vec![], false),
+            ledger_info: LedgerInfoWithSignatures::new(
+                LedgerInfo::new(BlockInfo::empty(), HashValue::zero()),
+                BTreeMap::new(),
+            ),
+        }
+    }
+
+    fn data_manager(dir: &TempDir) -> Arc<BlockDataManager> {
+        let (data_man, _) = initialize_data_manager(
+            dir.path().to_str().unwrap(),
+            DbType::Rocksdb,
+            Arc::new(PowComputer::new(false)),
+            VmFactory::new(1024 * 32),
+        );
+        data_man
+    }
+
+    fn receipts(block_number: u64) -> Arc<BlockReceipts> {
+        Arc::new(BlockReceipts {
+            receipts: vec![],
+            block_number,
+            secondary_reward: U256::zero(),
+            tx_execution_error_messages: vec![],
+        })
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let checkpoint = checkpoint();
+
+        let dir = TempDir::new("checkpoint").unwrap();
+        let path = dir.path().join("checkpoint.json");
+        checkpoint.save(&path).unwrap();
+        let loaded = TrustedCheckpoint::load(&path).unwrap();
+
+        assert_eq!(loaded.headers, checkpoint.headers);
+        assert_eq!(loaded.epoch_headers, checkpoint.epoch_headers);
+        assert_eq!(loaded.start_block_number, 100);
+        assert_eq!(loaded.state_root, checkpoint.state_root);
+        assert_eq!(loaded.epoch_change_proof, checkpoint.epoch_change_proof);
+        assert_eq!(loaded.ledger_info, checkpoint.ledger_info);
+    }
+
+    #[test]
+    fn test_verify_headers() {
+        assert!(checkpoint().verify_headers().is_ok());
+
+        // not linked
+        let mut cp = checkpoint();
+        cp.headers[1] = header(H256::random(), 2001);
+        assert!(cp.verify_headers().is_err());
+
+        // the era genesis is not the last block in its epoch
+        let mut cp = checkpoint();
+        cp.epoch_headers.reverse();
+        assert!(cp.verify_headers().is_err());
+
+        // a block not referenced in the epoch
+        let mut cp = checkpoint();
+        cp.epoch_headers.insert(0, header(H256::random(), 1999));
+        assert!(cp.verify_headers().is_err());
+    }
+
+    #[test]
+    fn test_verify_state_root() {
+        let mut cp = checkpoint();
+        cp.state_root = H256::random();
+        assert!(cp.verify_headers().is_err());
+
+        // without the header deferring the state root
+        let mut cp = checkpoint();
+        cp.headers.pop();
+        assert!(cp.verify_headers().is_err());
+
+        // the state root is blamed
+        let mut cp = checkpoint();
+        let deferred = cp.headers.pop().unwrap();
+        cp.headers.push(
+            BlockHeaderBuilder::new()
+                .with_parent_hash(*deferred.parent_hash())
+                .with_height(deferred.height())
+                .with_deferred_state_root(cp.state_root)
+                .with_blame(1)
+                .build(),
+        );
+        assert!(cp.verify_headers().is_err());
+    }
+
+    #[test]
+    fn test_verify_without_epoch_change_proof() {
+        let waypoint = Waypoint::new_any(&LedgerInfo::new(
+            BlockInfo::empty(),
+            HashValue::zero(),
+        ));
+        assert!(checkpoint().verify(&waypoint).is_err());
+    }
+
+    #[test]
+    fn test_install() {
+        let dir = TempDir::new("checkpoint").unwrap();
+        let data_man = data_manager(&dir);
+        let checkpoint = VerifiedCheckpoint {
+            checkpoint: checkpoint(),
+        };
+        let era_genesis = checkpoint.checkpoint.headers[0].hash();
+
+        // not at an era boundary
+        assert!(checkpoint.install(&data_man, 300).is_err());
+        assert_eq!(data_man.trusted_checkpoint_state_root(&era_genesis), None);
+
+        checkpoint.install(&data_man, 1000).unwrap();
+        assert_eq!(data_man.get_cur_consensus_era_genesis_hash(), era_genesis);
+        assert!(data_man.block_header_by_hash(&era_genesis).is_some());
+        assert_eq!(
+            data_man.executed_epoch_set_hashes_from_db(2000),
+            Some(checkpoint.checkpoint.epoch_block_hashes())
+        );
+        assert_eq!(
+            data_man
+                .get_epoch_execution_context(&era_genesis)
+                .unwrap()
+                .start_block_number,
+            100
+        );
+        assert_eq!(
+            data_man.trusted_checkpoint_state_root(&era_genesis),
+            Some(checkpoint.checkpoint.state_root)
+        );
+
+        // installing again is a no-op
+        checkpoint.install(&data_man, 1000).unwrap();
+
+        // a checkpoint of another era can't be installed on the database
+        let other = VerifiedCheckpoint {
+            checkpoint: TrustedCheckpoint {
+                headers: vec![header(H256::random(), 3000)],
+                ..checkpoint()
+            },
+        };
+        assert!(other.install(&data_man, 1000).is_err());
+    }
+
+    #[test]
+    fn test_verify_synced_epoch() {
+        let dir = TempDir::new("checkpoint").unwrap();
+        let data_man = data_manager(&dir);
+        let checkpoint = VerifiedCheckpoint {
+            checkpoint: checkpoint(),
+        };
+        checkpoint.install(&data_man, 1000).unwrap();
+        let state_root = checkpoint.checkpoint.state_root;
+        let era_genesis = checkpoint.checkpoint.headers[0].hash();
+        let referee = checkpoint.checkpoint.epoch_headers[0].hash();
+        let parent = *checkpoint.checkpoint.headers[0].parent_hash();
+        let epoch_receipts = vec![
+            (referee, era_genesis, receipts(100)),
+            (era_genesis, era_genesis, receipts(101)),
+            (parent, parent, receipts(99)),
+        ];
+
+        assert!(verify_synced_epoch(
+            &data_man,
+            &era_genesis,
+            &state_root,
+            &epoch_receipts
+        )
+        .is_ok());
+        // other epochs are not checked
+        assert!(verify_synced_epoch(
+            &data_man,
+            &parent,
+            &H256::random(),
+            &epoch_receipts
+        )
+        .is_ok());
+
+        assert!(verify_synced_epoch(
+            &data_man,
+            &era_genesis,
+            &H256::random(),
+            &epoch_receipts
+        )
+        .is_err());
+        let wrong_block_number = vec![
+            (referee, era_genesis, receipts(0)),
+            (era_genesis, era_genesis, receipts(1)),
+        ];
+        assert!(verify_synced_epoch(
+            &data_man,
+            &era_genesis,
+            &state_root,
+            &wrong_block_number
+        )
+        .is_err());
+    }
+}
@@ -10,7 +10,7 @@ use crate::{
             Handleable,
         },
         synchronization_state::PeerFilter,
-        Error, ErrorKind,
+        Error, ErrorKind, SyncItemKind,
     },
 };
 use cfx_parameters::{
@@ -71,6 +71,11 @@ impl Handleable for GetBlockHeadersResponse {
             ctx.io,
             &ctx.manager.request_manager,
         )?;
+        ctx.manager.progress_tracker.on_items_received(
+            &ctx.node_id,
+            SyncItemKind::Header,
+            self.headers.len(),
+        );
 
         // keep first time drift validation error to return later
         let now_timestamp = SystemTime::now()
@@ -73,6 +73,11 @@ impl Handleable for GetBlockTxnResponse {
                         }
                     }
                     // FIXME Should check if hash matches
+                    ctx.manager.request_manager.propagation.on_full_block(
+                        resp_hash,
+                        header.timestamp(),
+                        Some(ctx.node_id),
+                    );
                     let block = Block::new(header, trans);
                     debug!(
                         "transaction received by block: ratio={:?}",
@@ -106,7 +111,10 @@ impl Handleable for GetBlockTxnResponse {
                         // added to received pool
                         ctx.manager
                             .request_manager
-                            .append_received_transactions(signed_txns);
+                            .append_received_transactions(
+                                signed_txns,
+                                Some(ctx.node_id),
+                            );
                     }
                     if insert_result.should_relay()
                         && !ctx.manager.catch_up_mode()
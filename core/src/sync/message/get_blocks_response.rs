@@ -10,7 +10,7 @@ use crate::{
             Handleable,
         },
         synchronization_protocol_handler::RecoverPublicTask,
-        Error,
+        Error, SyncItemKind,
     },
 };
 use cfx_types::H256;
@@ -62,6 +62,11 @@ impl Handleable for GetBlocksResponse {
             .iter()
             .cloned()
             .collect();
+        ctx.manager.progress_tracker.on_items_received(
+            &ctx.node_id,
+            SyncItemKind::Block,
+            self.blocks.len(),
+        );
 
         ctx.manager.recover_public_queue.dispatch(
             ctx.io,
@@ -110,6 +115,11 @@ impl Handleable for GetBlocksWithPublicResponse {
             )?;
             req.hashes.iter().cloned().collect()
         };
+        ctx.manager.progress_tracker.on_items_received(
+            &ctx.node_id,
+            SyncItemKind::Block,
+            self.blocks.len(),
+        );
 
         ctx.manager.recover_public_queue.dispatch(
             ctx.io,
@@ -9,8 +9,9 @@ use crate::{
             metrics::{CMPCT_BLOCK_HANDLE_TIMER, CMPCT_BLOCK_RECOVER_TIMER},
             Context, GetCompactBlocks, Handleable,
         },
+        request_manager::propagation::BlockPropagationStage,
         synchronization_protocol_handler::RecoverPublicTask,
-        Error,
+        Error, SyncItemKind,
     },
 };
 use cfx_types::H256;
@@ -63,6 +64,11 @@ impl Handleable for GetCompactBlocksResponse {
             .iter()
             .cloned()
             .collect();
+        ctx.manager.progress_tracker.on_items_received(
+            &ctx.node_id,
+            SyncItemKind::Block,
+            self.compact_blocks.len() + self.blocks.len(),
+        );
 
         for mut cmpct in self.compact_blocks {
             let hash = cmpct.hash();
@@ -71,6 +77,11 @@ impl Handleable for GetCompactBlocksResponse {
                 warn!("Response has not requested compact block {:?}", hash);
                 continue;
             }
+            ctx.manager.request_manager.propagation.on_blocks(
+                BlockPropagationStage::CompactBlock,
+                &[hash],
+                Some(ctx.node_id),
+            );
 
             if ctx.manager.graph.contains_block(&hash) {
                 debug!(
@@ -124,6 +135,11 @@ impl Handleable for GetCompactBlocksResponse {
                     .into_iter()
                     .map(|tx| tx.unwrap())
                     .collect();
+                ctx.manager.request_manager.propagation.on_full_block(
+                    hash,
+                    header.timestamp(),
+                    Some(ctx.node_id),
+                );
                 let block = Block::new(header, trans);
                 debug!("transaction received by block: ratio=0");
                 debug!(
@@ -8,7 +8,7 @@ use crate::{
         request_manager::RequestMessage, Error, SynchronizationProtocolHandler,
     },
 };
-use network::{node_table::NodeId, NetworkContext};
+use network::{node_table::NodeId, NetworkContext, PeerBehavior};
 
 pub struct Context<'a> {
     pub io: &'a dyn NetworkContext,
@@ -20,9 +20,16 @@ impl<'a> Context<'a> {
     pub fn match_request(
         &self, request_id: u64,
     ) -> Result<RequestMessage, Error> {
-        self.manager
+        let request = self
+            .manager
             .request_manager
-            .match_request(&self.node_id, request_id)
+            .match_request(&self.node_id, request_id)?;
+        self.manager.syn.report_peer(
+            self.io,
+            &self.node_id,
+            PeerBehavior::ValidResponse,
+        );
+        Ok(request)
     }
 
     pub fn send_response(&self, response: &dyn Message) -> Result<(), Error> {
@@ -37,6 +37,11 @@ impl Handleable for NewBlock {
             return Ok(());
         }
         let mut block = self.block;
+        ctx.manager.request_manager.propagation.on_full_block(
+            block.hash(),
+            block.block_header.timestamp(),
+            Some(ctx.node_id),
+        );
         ctx.manager.graph.data_man.recover_block(&mut block)?;
 
         debug!(
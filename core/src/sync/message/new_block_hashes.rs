@@ -4,6 +4,7 @@
 
 use crate::sync::{
     message::{Context, Handleable},
+    request_manager::propagation::BlockPropagationStage,
     Error,
 };
 use cfx_types::H256;
@@ -30,6 +31,11 @@ impl Decodable for NewBlockHashes {
 impl Handleable for NewBlockHashes {
     fn handle(self, ctx: &Context) -> Result<(), Error> {
         debug!("on_new_block_hashes, msg={:?}", self);
+        ctx.manager.request_manager.propagation.on_blocks(
+            BlockPropagationStage::NewBlockHashes,
+            &self.block_hashes,
+            Some(ctx.node_id),
+        );
 
         if ctx.manager.catch_up_mode() {
             // If a node is in catch-up mode and we are not in test-mode, we
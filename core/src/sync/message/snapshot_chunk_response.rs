@@ -7,7 +7,7 @@ use crate::{
     sync::{
         message::{msgid, Context, Handleable, SnapshotChunkRequest},
         state::storage::Chunk,
-        Error, SYNC_PROTO_V1, SYNC_PROTO_V3,
+        Error, SyncItemKind, SYNC_PROTO_V1, SYNC_PROTO_V3,
     },
 };
 use network::service::ProtocolVersion;
@@ -49,6 +49,11 @@ impl Handleable for SnapshotChunkResponse {
                 .resend_request_to_another_peer(ctx.io, &message);
             return Err(e);
         }
+        ctx.manager.progress_tracker.on_items_received(
+            &ctx.node_id,
+            SyncItemKind::Chunk,
+            1,
+        );
 
         ctx.manager.state_sync.handle_snapshot_chunk_response(
             ctx,
@@ -109,6 +109,8 @@ impl Handleable for StatusV2 {
                 notified_capabilities: Default::default(),
                 throttling,
                 throttled_msgs: Default::default(),
+                score: ctx.io.get_peer_score(&ctx.node_id),
+                unreported_valid_responses: 0,
             };
 
             peer_state
@@ -238,6 +240,8 @@ impl Handleable for StatusV3 {
                 notified_capabilities: Default::default(),
                 throttling,
                 throttled_msgs: Default::default(),
+                score: ctx.io.get_peer_score(&ctx.node_id),
+                unreported_valid_responses: 0,
             };
 
             peer_state
@@ -105,7 +105,7 @@ impl Handleable for Transactions {
 
             ctx.manager
                 .request_manager
-                .append_received_transactions(signed_trans);
+                .append_received_transactions(signed_trans, Some(ctx.node_id));
             Ok(())
         } else {
             debug!("All {} transactions are not inserted to the transaction pool, because the node is still in the catch up mode", transactions.len());
@@ -612,7 +612,12 @@ impl Handleable for GetTransactionsResponse {
             }
             ctx.manager
                 .request_manager
-                .transactions_received_from_digests(ctx.io, &req, signed_trans);
+                .transactions_received_from_digests(
+                    ctx.io,
+                    &req,
+                    signed_trans,
+                    ctx.node_id,
+                );
 
             if req.tx_hashes_indices.len() > 0 && !self.tx_hashes.is_empty() {
                 ctx.manager
@@ -691,7 +696,11 @@ impl Handleable for GetTransactionsFromTxHashesResponse {
             }
             ctx.manager
                 .request_manager
-                .transactions_received_from_tx_hashes(&req, signed_trans);
+                .transactions_received_from_tx_hashes(
+                    &req,
+                    signed_trans,
+                    ctx.node_id,
+                );
             Ok(())
         } else {
             debug!("All {} transactions are not inserted to the transaction pool, because the node is still in the catch up mode", self.transactions.len());
@@ -1,6 +1,7 @@
 // Copyright 2019 Conflux Foundation. All rights reserved.
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
+pub mod checkpoint;
 mod error;
 pub mod message;
 pub mod request_manager;
@@ -11,6 +12,7 @@ pub mod tests;
 
 mod synchronization_graph;
 mod synchronization_phases;
+mod synchronization_progress;
 mod synchronization_protocol_handler;
 mod synchronization_service;
 mod synchronization_state;
@@ -30,6 +32,10 @@ pub use self::{
         CatchUpSyncBlockPhase, NormalSyncPhase, SyncPhaseType,
         SynchronizationPhaseManager, SynchronizationPhaseTrait,
     },
+    synchronization_progress::{
+        PeerContribution, ProgressCounter, SyncItemKind, SyncPhaseChange,
+        SyncProgress, SyncProgressTracker,
+    },
     synchronization_protocol_handler::{
         LocalMessageTask, ProtocolConfiguration, SyncHandlerWorkType,
         SynchronizationProtocolHandler, CHECK_RPC_REQUEST_TIMER,
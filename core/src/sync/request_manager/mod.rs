@@ -27,9 +27,10 @@ use malloc_size_of_derive::MallocSizeOf as DeriveMallocSizeOf;
 use metrics::{
     register_meter_with_group, Gauge, GaugeUsize, Meter, MeterTimer,
 };
-use network::{node_table::NodeId, NetworkContext};
+use network::{node_table::NodeId, NetworkContext, PeerBehavior};
 use parking_lot::{Mutex, RwLock};
 use primitives::{SignedTransaction, TransactionWithSignature};
+use propagation::PropagationTracker;
 pub use request_handler::{
     AsAny, Request, RequestHandler, RequestMessage, SynchronizationPeerRequest,
 };
@@ -44,6 +45,7 @@ use tx_handler::{
     ReceivedTransactionContainer, SentTransactionContainer,
 };
 
+pub mod propagation;
 mod request_batcher;
 mod request_handler;
 pub mod tx_handler;
@@ -142,6 +144,10 @@ pub struct RequestManager {
 
     #[ignore_malloc_size_of = "channels are not handled in MallocSizeOf"]
     recover_public_queue: Arc<AsyncTaskQueue<RecoverPublicTask>>,
+
+    /// Records when blocks and transactions arrive and from which peer.
+    #[ignore_malloc_size_of = "bounded by propagation_record_capacity"]
+    pub propagation: PropagationTracker,
 }
 
 impl RequestManager {
@@ -179,6 +185,10 @@ impl RequestManager {
             request_handler: Arc::new(RequestHandler::new(protocol_config)),
             syn,
             recover_public_queue,
+            propagation: PropagationTracker::new(
+                protocol_config.propagation_record_capacity,
+                protocol_config.export_propagation_metrics,
+            ),
         }
     }
 
@@ -767,7 +777,7 @@ impl RequestManager {
     pub fn transactions_received_from_digests(
         &self, io: &dyn NetworkContext,
         get_transactions_request: &GetTransactions,
-        signed_transactions: Vec<Arc<SignedTransaction>>,
+        signed_transactions: Vec<Arc<SignedTransaction>>, peer: NodeId,
     )
     {
         let mut short_id_inflight_keys =
@@ -783,7 +793,7 @@ impl RequestManager {
                 &signed_transactions,
             );
 
-        self.append_received_transactions(signed_transactions);
+        self.append_received_transactions(signed_transactions, Some(peer));
         for tx in &get_transactions_request.short_ids {
             if !keeped_short_ids.contains(tx) {
                 short_id_inflight_keys.remove(&Key::Id(*tx));
@@ -829,7 +839,7 @@ impl RequestManager {
 
     pub fn transactions_received_from_tx_hashes(
         &self, get_transactions_request: &GetTransactionsFromTxHashes,
-        signed_transactions: Vec<Arc<SignedTransaction>>,
+        signed_transactions: Vec<Arc<SignedTransaction>>, peer: NodeId,
     )
     {
         let mut tx_hash_inflight_keys = self
@@ -838,7 +848,7 @@ impl RequestManager {
         for tx in &get_transactions_request.tx_hashes {
             tx_hash_inflight_keys.remove(&Key::Hash(*tx));
         }
-        self.append_received_transactions(signed_transactions);
+        self.append_received_transactions(signed_transactions, Some(peer));
     }
 
     pub fn get_sent_transactions(
@@ -864,9 +874,13 @@ impl RequestManager {
             .append_transactions(transactions)
     }
 
+    /// Add transactions to the received pool. `peer` is the peer that sent
+    /// the transactions, or `None` if they are submitted locally.
     pub fn append_received_transactions(
-        &self, transactions: Vec<Arc<SignedTransaction>>,
+        &self, transactions: Vec<Arc<SignedTransaction>>, peer: Option<NodeId>,
     ) {
+        self.propagation
+            .on_transactions(transactions.iter().map(|tx| tx.hash()), peer);
         self.received_transactions
             .write()
             .append_transactions(transactions)
@@ -876,8 +890,10 @@ impl RequestManager {
         debug!("resend_timeout_requests: start");
         let timeout_requests =
             self.request_handler.process_timeout_requests(io);
-        for req in timeout_requests {
+        for (peer, req) in timeout_requests {
             debug!("Timeout requests: {:?}", req);
+            self.syn
+                .report_peer(io, &peer, PeerBehavior::RequestTimeout);
             self.resend_request_to_another_peer(io, &req);
         }
     }
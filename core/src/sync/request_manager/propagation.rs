@@ -0,0 +1,299 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Propagation timing of blocks and transactions.
+//!
+//! For each block, the first arrival of `NewBlockHashes`, of the compact block
+//! in `GetCompactBlocksResponse` and of the full block is recorded with the
+//! peer it came from. For each transaction, the first time it is seen is
+//! recorded. Timestamps are in milliseconds since the UNIX epoch so that the
+//! records of different nodes can be compared.
+//!
+//! Only the latest `capacity` blocks and transactions are kept. If metrics
+//! export is enabled, the delays between the stages are also reported as
+//! histograms of the metrics group `block_propagation`.
+
+use cfx_types::H256;
+use metrics::{register_meter_with_group, Histogram, Meter, Sample};
+use network::node_table::NodeId;
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+lazy_static! {
+    static ref NEW_HASHES_TO_COMPACT_BLOCK_TIME: Arc<dyn Histogram> =
+        Sample::ExpDecay(0.015).register_with_group(
+            "block_propagation",
+            "new_hashes_to_compact_block_ms",
+            1024
+        );
+    static ref NEW_HASHES_TO_FULL_BLOCK_TIME: Arc<dyn Histogram> =
+        Sample::ExpDecay(0.015).register_with_group(
+            "block_propagation",
+            "new_hashes_to_full_block_ms",
+            1024
+        );
+    static ref BLOCK_TIMESTAMP_TO_FULL_BLOCK_TIME: Arc<dyn Histogram> =
+        Sample::ExpDecay(0.015).register_with_group(
+            "block_propagation",
+            "block_timestamp_to_full_block_ms",
+            1024
+        );
+    static ref TX_FIRST_SEEN_METER: Arc<dyn Meter> =
+        register_meter_with_group("block_propagation", "tx_first_seen");
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PropagationEvent {
+    /// Milliseconds since the UNIX epoch.
+    pub timestamp_ms: u64,
+    /// The peer that sent the item, or `None` if it is from the local node.
+    pub peer: Option<NodeId>,
+}
+
+impl PropagationEvent {
+    fn now(peer: Option<NodeId>) -> Self {
+        PropagationEvent {
+            timestamp_ms: now_ms(),
+            peer,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockPropagationStage {
+    NewBlockHashes,
+    CompactBlock,
+    FullBlock,
+}
+
+/// The first arrival of each propagation stage of a block.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockPropagation {
+    pub new_block_hashes: Option<PropagationEvent>,
+    pub compact_block: Option<PropagationEvent>,
+    pub full_block: Option<PropagationEvent>,
+}
+
+impl BlockPropagation {
+    fn stage_mut(
+        &mut self, stage: BlockPropagationStage,
+    ) -> &mut Option<PropagationEvent> {
+        match stage {
+            BlockPropagationStage::NewBlockHashes => &mut self.new_block_hashes,
+            BlockPropagationStage::CompactBlock => &mut self.compact_block,
+            BlockPropagationStage::FullBlock => &mut self.full_block,
+        }
+    }
+}
+
+/// A map that evicts the earliest inserted entries beyond the capacity.
+struct BoundedMap<K, V> {
+    capacity: usize,
+    entries: HashMap<K, V>,
+    order: VecDeque<K>,
+}
+
+impl<K: Clone + Eq + Hash, V: Default> BoundedMap<K, V> {
+    fn new(capacity: usize) -> Self {
+        BoundedMap {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn get(&self, key: &K) -> Option<&V> { self.entries.get(key) }
+
+    fn get_or_insert(&mut self, key: K) -> &mut V {
+        if !self.entries.contains_key(&key) {
+            while self.order.len() >= self.capacity.max(1) {
+                if let Some(evicted) = self.order.pop_front() {
+                    self.entries.remove(&evicted);
+                }
+            }
+            self.order.push_back(key.clone());
+        }
+        self.entries.entry(key).or_default()
+    }
+}
+
+pub struct PropagationTracker {
+    export_metrics: bool,
+    blocks: Mutex<BoundedMap<H256, BlockPropagation>>,
+    transactions: Mutex<BoundedMap<H256, Option<PropagationEvent>>>,
+}
+
+impl PropagationTracker {
+    pub fn new(capacity: usize, export_metrics: bool) -> Self {
+        PropagationTracker {
+            export_metrics,
+            blocks: Mutex::new(BoundedMap::new(capacity)),
+            transactions: Mutex::new(BoundedMap::new(capacity)),
+        }
+    }
+
+    /// Record the arrival of a propagation stage of the blocks. Only the
+    /// first arrival of each stage is kept.
+    pub fn on_blocks(
+        &self, stage: BlockPropagationStage, hashes: &[H256],
+        peer: Option<NodeId>,
+    )
+    {
+        let event = PropagationEvent::now(peer);
+        let mut blocks = self.blocks.lock();
+        for hash in hashes {
+            let propagation = blocks.get_or_insert(*hash);
+            let slot = propagation.stage_mut(stage);
+            if slot.is_some() {
+                continue;
+            }
+            *slot = Some(event);
+            if self.export_metrics {
+                Self::report_delay(propagation, stage, event.timestamp_ms);
+            }
+        }
+    }
+
+    /// Record the arrival of a full block. `block_timestamp` is the
+    /// timestamp in the block header in seconds.
+    pub fn on_full_block(
+        &self, hash: H256, block_timestamp: u64, peer: Option<NodeId>,
+    ) {
+        let first = self
+            .blocks
+            .lock()
+            .get(&hash)
+            .map_or(true, |propagation| propagation.full_block.is_none());
+        self.on_blocks(BlockPropagationStage::FullBlock, &[hash], peer);
+        if first && self.export_metrics {
+            BLOCK_TIMESTAMP_TO_FULL_BLOCK_TIME.update(
+                now_ms().saturating_sub(block_timestamp.saturating_mul(1000)),
+            );
+        }
+    }
+
+    pub fn on_transactions(
+        &self, hashes: impl Iterator<Item = H256>, peer: Option<NodeId>,
+    ) {
+        let event = PropagationEvent::now(peer);
+        let mut transactions = self.transactions.lock();
+        let mut first_seen = 0;
+        for hash in hashes {
+            let slot = transactions.get_or_insert(hash);
+            if slot.is_none() {
+                *slot = Some(event);
+                first_seen += 1;
+            }
+        }
+        if self.export_metrics {
+            TX_FIRST_SEEN_METER.mark(first_seen);
+        }
+    }
+
+    pub fn block_propagation(&self, hash: &H256) -> Option<BlockPropagation> {
+        self.blocks.lock().get(hash).cloned()
+    }
+
+    pub fn transaction_first_seen(
+        &self, hash: &H256,
+    ) -> Option<PropagationEvent> {
+        self.transactions.lock().get(hash).cloned().flatten()
+    }
+
+    fn report_delay(
+        propagation: &BlockPropagation, stage: BlockPropagationStage,
+        timestamp_ms: u64,
+    )
+    {
+        let histogram: &Arc<dyn Histogram> = match stage {
+            BlockPropagationStage::NewBlockHashes => return,
+            BlockPropagationStage::CompactBlock => {
+                &*NEW_HASHES_TO_COMPACT_BLOCK_TIME
+            }
+            BlockPropagationStage::FullBlock => &*NEW_HASHES_TO_FULL_BLOCK_TIME,
+        };
+        if let Some(announced) = propagation.new_block_hashes {
+            histogram
+                .update(timestamp_ms.saturating_sub(announced.timestamp_ms));
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_arrival_is_kept() {
+        let tracker = PropagationTracker::new(10, false);
+        let hash = H256::random();
+        let peer1 = NodeId::random();
+        let peer2 = NodeId::random();
+        tracker.on_blocks(
+            BlockPropagationStage::NewBlockHashes,
+            &[hash],
+            Some(peer1),
+        );
+        tracker.on_blocks(
+            BlockPropagationStage::NewBlockHashes,
+            &[hash],
+            Some(peer2),
+        );
+        tracker.on_full_block(hash, 0, Some(peer2));
+
+        let propagation = tracker.block_propagation(&hash).unwrap();
+        assert_eq!(propagation.new_block_hashes.unwrap().peer, Some(peer1));
+        assert_eq!(propagation.compact_block, None);
+        assert_eq!(propagation.full_block.unwrap().peer, Some(peer2));
+        assert!(
+            propagation.full_block.unwrap().timestamp_ms
+                >= propagation.new_block_hashes.unwrap().timestamp_ms
+        );
+
+        let tx = H256::random();
+        tracker.on_transactions(vec![tx].into_iter(), None);
+        tracker.on_transactions(vec![tx].into_iter(), Some(peer1));
+        assert_eq!(tracker.transaction_first_seen(&tx).unwrap().peer, None);
+        assert_eq!(tracker.transaction_first_seen(&H256::random()), None);
+    }
+
+    #[test]
+    fn test_capacity() {
+        let tracker = PropagationTracker::new(2, false);
+        let hashes: Vec<_> = (0..3).map(|_| H256::random()).collect();
+        tracker.on_blocks(BlockPropagationStage::CompactBlock, &hashes, None);
+        assert_eq!(tracker.block_propagation(&hashes[0]), None);
+        assert!(tracker.block_propagation(&hashes[1]).is_some());
+        assert!(tracker.block_propagation(&hashes[2]).is_some());
+
+        tracker.on_transactions(hashes.iter().cloned(), None);
+        assert_eq!(tracker.transaction_first_seen(&hashes[0]), None);
+        assert!(tracker.transaction_first_seen(&hashes[2]).is_some());
+    }
+
+    #[test]
+    fn test_block_timestamp_overflow() {
+        // The timestamp is given by the block producer.
+        let tracker = PropagationTracker::new(10, true);
+        let hash = H256::random();
+        tracker.on_full_block(hash, u64::MAX, None);
+        assert!(tracker
+            .block_propagation(&hash)
+            .unwrap()
+            .full_block
+            .is_some());
+    }
+}
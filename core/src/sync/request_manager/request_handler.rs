@@ -158,9 +158,11 @@ impl RequestHandler {
         timeout_requests
     }
 
+    /// Process the timeout requests, and return them along with the peers
+    /// that the requests were sent to.
     pub fn process_timeout_requests(
         &self, io: &dyn NetworkContext,
-    ) -> Vec<RequestMessage> {
+    ) -> Vec<(NodeId, RequestMessage)> {
         // Check if in-flight requests timeout
         let mut timeout_requests = Vec::new();
         let mut peers_to_disconnect = HashSet::new();
@@ -182,7 +184,7 @@ impl RequestHandler {
                     }
                 }
                 req.request.notify_timeout();
-                timeout_requests.push(req);
+                timeout_requests.push((sync_req.peer_id, req));
             } else {
                 debug!("Timeout a removed request {:?}", sync_req);
             }
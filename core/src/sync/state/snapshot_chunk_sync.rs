@@ -206,6 +206,18 @@ impl SnapshotChunkSync {
 
     pub fn status(&self) -> Status { self.inner.read().status }
 
+    /// Return the number of restored chunks and the number of all chunks in
+    /// the manifest, if the manifest has been received.
+    pub fn chunk_progress(&self) -> Option<(usize, usize)> {
+        let inner = self.inner.read();
+        match inner.status {
+            Status::DownloadingChunks(_) | Status::Completed => {
+                inner.chunk_manager.as_ref().map(|m| m.progress())
+            }
+            _ => None,
+        }
+    }
+
     pub fn handle_snapshot_manifest_response(
         &self, ctx: &Context, response: SnapshotManifestResponse,
         request: &SnapshotManifestRequest,
@@ -26,6 +26,7 @@ pub struct SnapshotChunkManager {
     pending_chunks: VecDeque<ChunkKey>,
     downloading_chunks: HashMap<ChunkKey, DownloadingChunkStatus>,
     num_downloaded: usize,
+    num_chunks: usize,
     config: SnapshotChunkConfig,
 
     restorer: Restorer,
@@ -66,6 +67,7 @@ impl SnapshotChunkManager {
             snapshot_candidate,
             snapshot_info,
             active_peers,
+            num_chunks: chunks.len(),
             pending_chunks: chunks.into(),
             downloading_chunks: Default::default(),
             num_downloaded: 0,
@@ -182,6 +184,13 @@ impl SnapshotChunkManager {
 
     pub fn is_inactive(&self) -> bool { self.active_peers.is_empty() }
 
+    /// Return the number of chunks accepted and the number of all chunks.
+    pub fn progress(&self) -> (usize, usize) {
+        let remaining =
+            self.pending_chunks.len() + self.downloading_chunks.len();
+        (self.num_chunks - remaining, self.num_chunks)
+    }
+
     pub fn set_active_peers(&mut self, new_active_peers: HashSet<NodeId>) {
         self.active_peers = new_active_peers;
     }
@@ -4,6 +4,7 @@ use crate::{
     block_data_manager::BlockExecutionResult,
     message::NetworkContext,
     sync::{
+        checkpoint::verify_synced_epoch,
         error::{Error, ErrorKind},
         message::{
             msgid, Context, SnapshotManifestRequest, SnapshotManifestResponse,
@@ -163,6 +164,19 @@ impl SnapshotManifestManager {
                     }
                 };
 
+            if let Err(e) = verify_synced_epoch(
+                &ctx.manager.graph.data_man,
+                self.snapshot_candidate.get_snapshot_epoch_id(),
+                &state_root_with_aux_info.aux_info.state_root_hash,
+                &epoch_receipts,
+            ) {
+                warn!("{}, re-sync manifest from other peer", e);
+                self.resync_manifest(ctx);
+                bail!(ErrorKind::InvalidSnapshotManifest(
+                    "manifest mismatches the trusted checkpoint".into(),
+                ));
+            }
+
             // Check proofs for keys.
             if let Err(e) =
                 response.manifest.validate(&snapshot_info.merkle_root)
@@ -41,8 +41,27 @@ pub enum SyncPhaseType {
     Normal = 5,
 }
 
+impl SyncPhaseType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SyncPhaseType::CatchUpRecoverBlockHeaderFromDB => {
+                "CatchUpRecoverBlockHeaderFromDbPhase"
+            }
+            SyncPhaseType::CatchUpSyncBlockHeader => {
+                "CatchUpSyncBlockHeaderPhase"
+            }
+            SyncPhaseType::CatchUpCheckpoint => "CatchUpCheckpointPhase",
+            SyncPhaseType::CatchUpFillBlockBodyPhase => {
+                "CatchUpFillBlockBodyPhase"
+            }
+            SyncPhaseType::CatchUpSyncBlock => "CatchUpSyncBlockPhase",
+            SyncPhaseType::Normal => "NormalSyncPhase",
+        }
+    }
+}
+
 pub trait SynchronizationPhaseTrait: Send + Sync {
-    fn name(&self) -> &'static str;
+    fn name(&self) -> &'static str { self.phase_type().name() }
     fn phase_type(&self) -> SyncPhaseType;
     fn next(
         &self, _io: &dyn NetworkContext,
@@ -192,8 +211,6 @@ impl CatchUpRecoverBlockHeaderFromDbPhase {
 }
 
 impl SynchronizationPhaseTrait for CatchUpRecoverBlockHeaderFromDbPhase {
-    fn name(&self) -> &'static str { "CatchUpRecoverBlockHeaderFromDbPhase" }
-
     fn phase_type(&self) -> SyncPhaseType {
         SyncPhaseType::CatchUpRecoverBlockHeaderFromDB
     }
@@ -242,8 +259,6 @@ impl CatchUpSyncBlockHeaderPhase {
 }
 
 impl SynchronizationPhaseTrait for CatchUpSyncBlockHeaderPhase {
-    fn name(&self) -> &'static str { "CatchUpSyncBlockHeaderPhase" }
-
     fn phase_type(&self) -> SyncPhaseType {
         SyncPhaseType::CatchUpSyncBlockHeader
     }
@@ -311,8 +326,6 @@ impl CatchUpCheckpointPhase {
 }
 
 impl SynchronizationPhaseTrait for CatchUpCheckpointPhase {
-    fn name(&self) -> &'static str { "CatchUpCheckpointPhase" }
-
     fn phase_type(&self) -> SyncPhaseType { SyncPhaseType::CatchUpCheckpoint }
 
     fn next(
@@ -400,8 +413,6 @@ impl CatchUpFillBlockBodyPhase {
 }
 
 impl SynchronizationPhaseTrait for CatchUpFillBlockBodyPhase {
-    fn name(&self) -> &'static str { "CatchUpFillBlockBodyPhase" }
-
     fn phase_type(&self) -> SyncPhaseType {
         SyncPhaseType::CatchUpFillBlockBodyPhase
     }
@@ -488,8 +499,6 @@ impl CatchUpSyncBlockPhase {
 }
 
 impl SynchronizationPhaseTrait for CatchUpSyncBlockPhase {
-    fn name(&self) -> &'static str { "CatchUpSyncBlockPhase" }
-
     fn phase_type(&self) -> SyncPhaseType { SyncPhaseType::CatchUpSyncBlock }
 
     fn next(
@@ -550,8 +559,6 @@ impl NormalSyncPhase {
 }
 
 impl SynchronizationPhaseTrait for NormalSyncPhase {
-    fn name(&self) -> &'static str { "NormalSyncPhase" }
-
     fn phase_type(&self) -> SyncPhaseType { SyncPhaseType::Normal }
 
     fn next(
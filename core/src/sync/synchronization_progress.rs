@@ -0,0 +1,349 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Progress tracking of the catch-up synchronization.
+//!
+//! `SyncProgressTracker` records the phase transitions, the sync data received
+//! from each peer, and samples of the progress counter that drives the current
+//! phase, e.g. the epoch of the header chain in `CatchUpSyncBlockHeader` and
+//! the restored snapshot chunks in `CatchUpCheckpoint`. The throughput and the
+//! estimated time to finish the current phase are computed from the samples
+//! within `THROUGHPUT_WINDOW`.
+
+use crate::{
+    message::MsgId,
+    sync::{message::msgid, SyncPhaseType},
+};
+use network::node_table::NodeId;
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+/// The time window to compute the throughput and the ETA.
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(60);
+
+/// A phase transition of the synchronization.
+#[derive(Clone, Debug)]
+pub struct SyncPhaseChange {
+    pub from: SyncPhaseType,
+    pub to: SyncPhaseType,
+    /// Time spent in the phase `from`.
+    pub elapsed: Duration,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncItemKind {
+    Header,
+    Block,
+    Chunk,
+}
+
+/// Sync data received from a peer.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PeerContribution {
+    pub headers: u64,
+    pub blocks: u64,
+    pub chunks: u64,
+    pub bytes: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProgressCounter {
+    pub current: u64,
+    pub target: u64,
+}
+
+impl ProgressCounter {
+    pub fn remaining(&self) -> u64 { self.target.saturating_sub(self.current) }
+}
+
+pub struct SyncProgress {
+    pub phase: SyncPhaseType,
+    /// Time spent in the current phase.
+    pub phase_elapsed: Duration,
+    /// Finished phases and the time spent in them, in order.
+    pub finished_phases: Vec<(SyncPhaseType, Duration)>,
+    /// The best epoch of the local header chain against the median epoch of
+    /// the peers.
+    pub headers: Option<ProgressCounter>,
+    /// Block bodies filled in `CatchUpFillBlockBody`.
+    pub blocks: Option<ProgressCounter>,
+    /// Snapshot chunks restored against the chunks in the manifest.
+    pub chunks: Option<ProgressCounter>,
+    /// Bytes of sync data received per second.
+    pub bytes_per_second: f64,
+    /// Estimated time to finish the current phase.
+    pub eta: Option<Duration>,
+    /// Sync data received from each connected peer.
+    pub peers: Vec<(NodeId, PeerContribution)>,
+}
+
+impl SyncProgress {
+    /// The counter that the current phase makes progress on.
+    pub fn phase_counter(&self) -> Option<ProgressCounter> {
+        phase_counter(self.phase, self.headers, self.blocks, self.chunks)
+    }
+}
+
+fn phase_counter(
+    phase: SyncPhaseType, headers: Option<ProgressCounter>,
+    blocks: Option<ProgressCounter>, chunks: Option<ProgressCounter>,
+) -> Option<ProgressCounter>
+{
+    match phase {
+        SyncPhaseType::CatchUpSyncBlockHeader
+        | SyncPhaseType::CatchUpSyncBlock => headers,
+        SyncPhaseType::CatchUpCheckpoint => chunks,
+        SyncPhaseType::CatchUpFillBlockBodyPhase => blocks,
+        SyncPhaseType::CatchUpRecoverBlockHeaderFromDB
+        | SyncPhaseType::Normal => None,
+    }
+}
+
+/// Return if the message carries data downloaded for synchronization.
+pub fn is_sync_data(msg_id: MsgId) -> bool {
+    matches!(
+        msg_id,
+        msgid::GET_BLOCK_HASHES_RESPONSE
+            | msgid::GET_BLOCK_HEADERS_RESPONSE
+            | msgid::GET_BLOCKS_RESPONSE
+            | msgid::GET_BLOCKS_WITH_PUBLIC_RESPONSE
+            | msgid::GET_CMPCT_BLOCKS_RESPONSE
+            | msgid::GET_BLOCK_TXN_RESPONSE
+            | msgid::GET_SNAPSHOT_MANIFEST_RESPONSE
+            | msgid::GET_SNAPSHOT_CHUNK_RESPONSE
+    )
+}
+
+struct Inner {
+    phase: SyncPhaseType,
+    phase_start: Instant,
+    finished_phases: Vec<(SyncPhaseType, Duration)>,
+    peers: HashMap<NodeId, PeerContribution>,
+    /// Bytes of sync data received within `THROUGHPUT_WINDOW`.
+    received_bytes: VecDeque<(Instant, usize)>,
+    /// Samples of the counter of the current phase within
+    /// `THROUGHPUT_WINDOW`.
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl Inner {
+    fn expire(&mut self, now: Instant) {
+        while let Some((time, _)) = self.received_bytes.front() {
+            if now.duration_since(*time) <= THROUGHPUT_WINDOW {
+                break;
+            }
+            self.received_bytes.pop_front();
+        }
+        // Keep the latest expired sample so that the rate is computed over
+        // the full window.
+        while self.samples.len() > 1
+            && now.duration_since(self.samples[1].0) > THROUGHPUT_WINDOW
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    fn bytes_per_second(&self, now: Instant) -> f64 {
+        let start = match self.received_bytes.front() {
+            Some((time, _)) => *time,
+            None => return 0.0,
+        };
+        let bytes: usize = self.received_bytes.iter().map(|(_, b)| *b).sum();
+        // Avoid overestimating on the first received message.
+        let elapsed = now.duration_since(start).max(Duration::from_secs(1));
+        bytes as f64 / elapsed.as_secs_f64()
+    }
+
+    fn eta(&self, counter: ProgressCounter) -> Option<Duration> {
+        if counter.remaining() == 0 {
+            return Some(Duration::from_secs(0));
+        }
+        let (first_time, first) = self.samples.front()?;
+        let (last_time, last) = self.samples.back()?;
+        let elapsed = last_time.duration_since(*first_time).as_secs_f64();
+        if *last <= *first || elapsed == 0.0 {
+            return None;
+        }
+        let rate = (*last - *first) as f64 / elapsed;
+        Some(Duration::from_secs_f64(counter.remaining() as f64 / rate))
+    }
+}
+
+pub struct SyncProgressTracker {
+    inner: Mutex<Inner>,
+}
+
+impl SyncProgressTracker {
+    pub fn new(initial_phase: SyncPhaseType) -> Self {
+        SyncProgressTracker {
+            inner: Mutex::new(Inner {
+                phase: initial_phase,
+                phase_start: Instant::now(),
+                finished_phases: Vec::new(),
+                peers: HashMap::new(),
+                received_bytes: VecDeque::new(),
+                samples: VecDeque::new(),
+            }),
+        }
+    }
+
+    pub fn phase_changed(&self, to: SyncPhaseType) -> SyncPhaseChange {
+        let mut inner = self.inner.lock();
+        let from = inner.phase;
+        let elapsed = inner.phase_start.elapsed();
+        inner.finished_phases.push((from, elapsed));
+        inner.phase = to;
+        inner.phase_start = Instant::now();
+        inner.samples.clear();
+        SyncPhaseChange { from, to, elapsed }
+    }
+
+    pub fn on_bytes_received(&self, peer: &NodeId, bytes: usize) {
+        let mut inner = self.inner.lock();
+        let now = Instant::now();
+        inner.peers.entry(*peer).or_default().bytes += bytes as u64;
+        inner.received_bytes.push_back((now, bytes));
+        inner.expire(now);
+    }
+
+    pub fn on_items_received(
+        &self, peer: &NodeId, kind: SyncItemKind, count: usize,
+    ) {
+        let mut inner = self.inner.lock();
+        let contribution = inner.peers.entry(*peer).or_default();
+        let counter = match kind {
+            SyncItemKind::Header => &mut contribution.headers,
+            SyncItemKind::Block => &mut contribution.blocks,
+            SyncItemKind::Chunk => &mut contribution.chunks,
+        };
+        *counter += count as u64;
+    }
+
+    pub fn on_peer_disconnected(&self, peer: &NodeId) {
+        self.inner.lock().peers.remove(peer);
+    }
+
+    /// Record a sample of the counter of the current phase to compute the
+    /// ETA.
+    pub fn sample(&self, counter: ProgressCounter) {
+        let mut inner = self.inner.lock();
+        let now = Instant::now();
+        inner.samples.push_back((now, counter.current));
+        inner.expire(now);
+    }
+
+    pub fn report(
+        &self, headers: Option<ProgressCounter>,
+        blocks: Option<ProgressCounter>, chunks: Option<ProgressCounter>,
+    ) -> SyncProgress
+    {
+        let mut inner = self.inner.lock();
+        let now = Instant::now();
+        inner.expire(now);
+        let eta = phase_counter(inner.phase, headers, blocks, chunks)
+            .and_then(|counter| inner.eta(counter));
+        let mut peers: Vec<_> = inner
+            .peers
+            .iter()
+            .map(|(peer, contribution)| (*peer, contribution.clone()))
+            .collect();
+        peers.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes));
+        SyncProgress {
+            phase: inner.phase,
+            phase_elapsed: now.duration_since(inner.phase_start),
+            finished_phases: inner.finished_phases.clone(),
+            headers,
+            blocks,
+            chunks,
+            bytes_per_second: inner.bytes_per_second(now),
+            eta,
+            peers,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counter(current: u64, target: u64) -> ProgressCounter {
+        ProgressCounter { current, target }
+    }
+
+    #[test]
+    fn test_phase_changed() {
+        let tracker =
+            SyncProgressTracker::new(SyncPhaseType::CatchUpSyncBlockHeader);
+        tracker.sample(counter(10, 100));
+        let change = tracker.phase_changed(SyncPhaseType::CatchUpCheckpoint);
+        assert_eq!(change.from, SyncPhaseType::CatchUpSyncBlockHeader);
+        assert_eq!(change.to, SyncPhaseType::CatchUpCheckpoint);
+
+        let progress = tracker.report(None, None, Some(counter(0, 10)));
+        assert_eq!(progress.phase, SyncPhaseType::CatchUpCheckpoint);
+        assert_eq!(progress.finished_phases.len(), 1);
+        assert_eq!(progress.phase_counter(), Some(counter(0, 10)));
+        // The samples of the previous phase are dropped.
+        assert_eq!(progress.eta, None);
+    }
+
+    #[test]
+    fn test_peer_contribution() {
+        let tracker = SyncProgressTracker::new(SyncPhaseType::CatchUpSyncBlock);
+        let peer1 = NodeId::random();
+        let peer2 = NodeId::random();
+        tracker.on_bytes_received(&peer1, 100);
+        tracker.on_items_received(&peer1, SyncItemKind::Header, 3);
+        tracker.on_bytes_received(&peer2, 1000);
+        tracker.on_items_received(&peer2, SyncItemKind::Block, 2);
+        tracker.on_items_received(&peer2, SyncItemKind::Block, 1);
+
+        let progress = tracker.report(None, None, None);
+        assert_eq!(progress.peers.len(), 2);
+        assert_eq!(progress.peers[0].0, peer2);
+        assert_eq!(
+            progress.peers[0].1,
+            PeerContribution {
+                headers: 0,
+                blocks: 3,
+                chunks: 0,
+                bytes: 1000,
+            }
+        );
+        assert_eq!(progress.peers[1].1.headers, 3);
+        assert!(progress.bytes_per_second > 0.0);
+
+        tracker.on_peer_disconnected(&peer2);
+        let progress = tracker.report(None, None, None);
+        assert_eq!(progress.peers.len(), 1);
+        assert_eq!(progress.peers[0].0, peer1);
+    }
+
+    #[test]
+    fn test_eta() {
+        let now = Instant::now();
+        let mut inner = Inner {
+            phase: SyncPhaseType::CatchUpSyncBlockHeader,
+            phase_start: now,
+            finished_phases: vec![],
+            peers: HashMap::new(),
+            received_bytes: VecDeque::new(),
+            samples: VecDeque::new(),
+        };
+        assert_eq!(inner.eta(counter(10, 100)), None);
+        assert_eq!(inner.eta(counter(100, 100)), Some(Duration::from_secs(0)));
+
+        inner.samples.push_back((now, 10));
+        inner.samples.push_back((now + Duration::from_secs(10), 30));
+        // 2 per second
+        assert_eq!(inner.eta(counter(30, 100)), Some(Duration::from_secs(35)));
+
+        // no progress
+        inner.samples.push_back((now + Duration::from_secs(20), 5));
+        assert_eq!(inner.eta(counter(5, 100)), None);
+    }
+}
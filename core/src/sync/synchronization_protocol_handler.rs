@@ -8,6 +8,7 @@ use super::{
 };
 use crate::{
     block_data_manager::BlockStatus,
+    channel::Channel,
     light_protocol::Provider as LightProvider,
     message::{decode_msg, Message, MsgId},
     sync::{
@@ -19,12 +20,16 @@ use crate::{
         request_manager::{try_get_block_hashes, Request},
         state::SnapshotChunkSync,
         synchronization_phases::{SyncPhaseType, SynchronizationPhaseManager},
+        synchronization_progress::{
+            is_sync_data, ProgressCounter, SyncPhaseChange, SyncProgress,
+            SyncProgressTracker,
+        },
         synchronization_state::PeerFilter,
         StateSyncConfiguration,
         SYNCHRONIZATION_PROTOCOL_OLD_VERSIONS_TO_SUPPORT,
         SYNCHRONIZATION_PROTOCOL_VERSION, SYNC_PROTO_V1, SYNC_PROTO_V2,
     },
-    ConsensusGraph, NodeType,
+    ConsensusGraph, NodeType, Notifications,
 };
 use cfx_internal_common::ChainIdParamsDeprecated;
 use cfx_parameters::{block::MAX_BLOCK_SIZE_IN_BYTES, sync::*};
@@ -37,7 +42,7 @@ use metrics::{register_meter_with_group, Meter, MeterTimer};
 use network::{
     node_table::NodeId, service::ProtocolVersion,
     throttling::THROTTLING_SERVICE, Error as NetworkError, HandlerWorkType,
-    NetworkContext, NetworkProtocolHandler, UpdateNodeOperation,
+    NetworkContext, NetworkProtocolHandler, PeerBehavior, UpdateNodeOperation,
 };
 use parking_lot::{Mutex, RwLock};
 use primitives::{Block, BlockHeader, EpochId, SignedTransaction};
@@ -383,6 +388,13 @@ pub struct SynchronizationProtocolHandler {
 
     // provider for serving light protocol queries
     light_provider: Arc<LightProvider>,
+
+    #[ignore_malloc_size_of = "insignificant"]
+    pub progress_tracker: SyncProgressTracker,
+    /// The number of blocks to fill when `CatchUpFillBlockBodyPhase` starts.
+    blocks_to_fill: Mutex<usize>,
+    #[ignore_malloc_size_of = "channels are not handled in MallocSizeOf"]
+    sync_phase_changes: Arc<Channel<SyncPhaseChange>>,
 }
 
 #[derive(Clone, Default, DeriveMallocSizeOf)]
@@ -424,6 +436,8 @@ pub struct ProtocolConfiguration {
     pub min_phase_change_normal_peer_count: usize,
     pub pos_genesis_pivot_decision: H256,
     pub check_status_genesis: bool,
+    pub propagation_record_capacity: usize,
+    pub export_propagation_metrics: bool,
 
     pub pos_started_as_voter: bool,
 }
@@ -435,6 +449,7 @@ impl SynchronizationProtocolHandler {
         initial_sync_phase: SyncPhaseType,
         sync_graph: SharedSynchronizationGraph,
         light_provider: Arc<LightProvider>, consensus: Arc<ConsensusGraph>,
+        notifications: Arc<Notifications>,
     ) -> Self
     {
         let sync_state = Arc::new(SynchronizationState::new(
@@ -478,6 +493,9 @@ impl SynchronizationProtocolHandler {
             state_sync,
             synced_epoch_id: Default::default(),
             light_provider,
+            progress_tracker: SyncProgressTracker::new(initial_sync_phase),
+            blocks_to_fill: Mutex::new(0),
+            sync_phase_changes: notifications.sync_phase_changes.clone(),
         }
     }
 
@@ -546,7 +564,7 @@ impl SynchronizationProtocolHandler {
         &self, transactions: Vec<Arc<SignedTransaction>>,
     ) {
         self.request_manager
-            .append_received_transactions(transactions);
+            .append_received_transactions(transactions, None);
     }
 
     fn dispatch_message(
@@ -603,13 +621,18 @@ impl SynchronizationProtocolHandler {
         let reason = format!("{}", e.0);
         let error_reason = format!("{:?}", e);
         let mut op = None;
+        let mut behavior = None;
 
         // NOTE, DO NOT USE WILDCARD IN THE FOLLOWING MATCH STATEMENT!
         // COMPILER WILL HELP TO FIND UNHANDLED ERROR CASES.
         match e.0 {
-            ErrorKind::InvalidBlock => op = Some(UpdateNodeOperation::Failure),
+            ErrorKind::InvalidBlock => {
+                op = Some(UpdateNodeOperation::Failure);
+                behavior = Some(PeerBehavior::InvalidBlock);
+            }
             ErrorKind::InvalidGetBlockTxn(_) => {
-                op = Some(UpdateNodeOperation::Demotion)
+                op = Some(UpdateNodeOperation::Demotion);
+                behavior = Some(PeerBehavior::InvalidMessage);
             }
             ErrorKind::InvalidStatus(_) => {
                 op = Some(UpdateNodeOperation::Demotion)
@@ -617,7 +640,8 @@ impl SynchronizationProtocolHandler {
             ErrorKind::InvalidMessageFormat => {
                 // TODO: Shall we blacklist a node when the message format is
                 // wrong? maybe it's a different version of sync protocol?
-                op = Some(UpdateNodeOperation::Remove)
+                op = Some(UpdateNodeOperation::Remove);
+                behavior = Some(PeerBehavior::InvalidMessage);
             }
             ErrorKind::UnknownPeer => {
                 warn = false;
@@ -626,7 +650,8 @@ impl SynchronizationProtocolHandler {
             // TODO handle the unexpected response case (timeout or real invalid
             // message type)
             ErrorKind::UnexpectedResponse => {
-                op = Some(UpdateNodeOperation::Demotion)
+                op = Some(UpdateNodeOperation::Demotion);
+                behavior = Some(PeerBehavior::InvalidMessage);
             }
             ErrorKind::RequestNotFound => {
                 disconnect = false;
@@ -636,19 +661,23 @@ impl SynchronizationProtocolHandler {
                 disconnect = false;
                 warn = false;
             }
-            ErrorKind::TooManyTrans => {}
+            ErrorKind::TooManyTrans => behavior = Some(PeerBehavior::Spam),
             ErrorKind::InvalidTimestamp => {
-                op = Some(UpdateNodeOperation::Demotion)
+                op = Some(UpdateNodeOperation::Demotion);
+                behavior = Some(PeerBehavior::InvalidBlock);
             }
             ErrorKind::InvalidSnapshotManifest(_) => {
-                op = Some(UpdateNodeOperation::Demotion)
+                op = Some(UpdateNodeOperation::Demotion);
+                behavior = Some(PeerBehavior::InvalidProof);
             }
             ErrorKind::InvalidSnapshotChunk(_) => {
-                op = Some(UpdateNodeOperation::Demotion)
+                op = Some(UpdateNodeOperation::Demotion);
+                behavior = Some(PeerBehavior::InvalidProof);
             }
             ErrorKind::EmptySnapshotChunk => disconnect = false,
             ErrorKind::AlreadyThrottled(_) => {
-                op = Some(UpdateNodeOperation::Remove)
+                op = Some(UpdateNodeOperation::Remove);
+                behavior = Some(PeerBehavior::Spam);
             }
             ErrorKind::Throttled(_, msg) => {
                 disconnect = false;
@@ -658,7 +687,10 @@ impl SynchronizationProtocolHandler {
                     disconnect = true;
                 }
             }
-            ErrorKind::Decoder(_) => op = Some(UpdateNodeOperation::Remove),
+            ErrorKind::Decoder(_) => {
+                op = Some(UpdateNodeOperation::Remove);
+                behavior = Some(PeerBehavior::InvalidMessage);
+            }
             ErrorKind::Io(_) => disconnect = false,
             ErrorKind::Network(kind) => match kind {
                 network::ErrorKind::SendUnsupportedMessage { .. } => {
@@ -681,11 +713,13 @@ impl SynchronizationProtocolHandler {
                 network::ErrorKind::AddressResolve(_) => disconnect = false,
                 network::ErrorKind::Auth => disconnect = false,
                 network::ErrorKind::BadProtocol => {
-                    op = Some(UpdateNodeOperation::Remove)
+                    op = Some(UpdateNodeOperation::Remove);
+                    behavior = Some(PeerBehavior::InvalidMessage);
                 }
                 network::ErrorKind::BadAddr => disconnect = false,
                 network::ErrorKind::Decoder(_) => {
-                    op = Some(UpdateNodeOperation::Remove)
+                    op = Some(UpdateNodeOperation::Remove);
+                    behavior = Some(PeerBehavior::InvalidMessage);
                 }
                 network::ErrorKind::Expired => disconnect = false,
                 network::ErrorKind::Disconnect(_) => disconnect = false,
@@ -712,7 +746,8 @@ impl SynchronizationProtocolHandler {
             ErrorKind::RpcCancelledByDisconnection => {}
             ErrorKind::RpcTimeout => {}
             ErrorKind::UnexpectedMessage(_) => {
-                op = Some(UpdateNodeOperation::Remove)
+                op = Some(UpdateNodeOperation::Remove);
+                behavior = Some(PeerBehavior::InvalidMessage);
             }
             ErrorKind::NotSupported(_) => disconnect = false,
         }
@@ -729,6 +764,10 @@ impl SynchronizationProtocolHandler {
             );
         }
 
+        if let Some(behavior) = behavior {
+            self.syn.report_peer(io, peer, behavior);
+        }
+
         if disconnect {
             io.disconnect_peer(peer, op, reason.as_str());
         }
@@ -1063,6 +1102,11 @@ impl SynchronizationProtocolHandler {
                 warn!("Response has not requested block {:?}", hash);
                 continue;
             }
+            self.request_manager.propagation.on_full_block(
+                hash,
+                block.block_header.timestamp(),
+                Some(task.failed_peer),
+            );
             if let Err(e) = self.graph.data_man.recover_block(&mut block) {
                 warn!("Recover block {:?} with error {:?}", hash, e);
                 continue;
@@ -1620,6 +1664,47 @@ impl SynchronizationProtocolHandler {
         self.graph.update_total_weight_delta_heartbeat();
     }
 
+    fn on_phase_changed(&self, phase_type: SyncPhaseType) {
+        if phase_type == SyncPhaseType::CatchUpFillBlockBodyPhase {
+            *self.blocks_to_fill.lock() =
+                self.graph.inner.read().block_to_fill_set.len();
+        }
+        let change = self.progress_tracker.phase_changed(phase_type);
+        info!(
+            "sync phase changed from {:?} to {:?} after {:?}",
+            change.from, change.to, change.elapsed
+        );
+        self.sync_phase_changes.send(change);
+    }
+
+    pub fn sync_progress(&self) -> SyncProgress {
+        let headers = self.syn.median_epoch_from_normal_peers().map(|target| {
+            ProgressCounter {
+                current: self.graph.consensus.best_epoch_number(),
+                target,
+            }
+        });
+        let blocks = match self.phase_manager.get_current_phase().phase_type() {
+            SyncPhaseType::CatchUpFillBlockBodyPhase => {
+                let total = *self.blocks_to_fill.lock();
+                let remaining = self.graph.inner.read().block_to_fill_set.len();
+                Some(ProgressCounter {
+                    current: total.saturating_sub(remaining) as u64,
+                    target: total as u64,
+                })
+            }
+            _ => None,
+        };
+        let chunks =
+            self.state_sync.chunk_progress().map(|(current, target)| {
+                ProgressCounter {
+                    current: current as u64,
+                    target: target as u64,
+                }
+            });
+        self.progress_tracker.report(headers, blocks, chunks)
+    }
+
     pub fn update_sync_phase(&self, io: &dyn NetworkContext) {
         match self.phase_manager_lock.try_lock() {
             Some(_pm_lock) => {
@@ -1635,10 +1720,14 @@ impl SynchronizationProtocolHandler {
                             io,
                             self,
                         );
+                        self.on_phase_changed(next_phase_type);
                     } else {
                         break;
                     }
                 }
+                if let Some(counter) = self.sync_progress().phase_counter() {
+                    self.progress_tracker.sample(counter);
+                }
             }
             None => {
                 debug!("update_sync_phase: phase_manager locked by another IO Worker");
@@ -1863,6 +1952,10 @@ impl NetworkProtocolHandler for SynchronizationProtocolHandler {
 
         debug!("on_message: peer={}, msgid={:?}", peer, msg_id);
 
+        if is_sync_data(msg_id) {
+            self.progress_tracker.on_bytes_received(peer, raw.len());
+        }
+
         self.dispatch_message(io, peer, msg_id.into(), rlp)
             .unwrap_or_else(|e| self.handle_error(io, peer, msg_id.into(), e));
 
@@ -1887,6 +1980,8 @@ impl NetworkProtocolHandler for SynchronizationProtocolHandler {
         }
     }
 
+    fn message_ids(&self) -> Vec<u16> { msgid::ALL.to_vec() }
+
     fn on_peer_connected(
         &self, io: &dyn NetworkContext, node_id: &NodeId,
         peer_protocol_version: ProtocolVersion,
@@ -1918,6 +2013,7 @@ impl NetworkProtocolHandler for SynchronizationProtocolHandler {
         self.syn.handshaking_peers.write().remove(peer);
         self.request_manager.on_peer_disconnected(io, peer);
         self.state_sync.on_peer_disconnected(&peer);
+        self.progress_tracker.on_peer_disconnected(peer);
     }
 
     fn on_timeout(&self, io: &dyn NetworkContext, timer: TimerToken) {
@@ -8,11 +8,15 @@ use super::{
 use crate::{
     light_protocol::Provider as LightProvider,
     sync::{
-        request_manager::RequestManager, synchronization_phases::SyncPhaseType,
+        request_manager::{
+            propagation::{BlockPropagation, PropagationEvent},
+            RequestManager,
+        },
+        synchronization_phases::SyncPhaseType,
         synchronization_protocol_handler::ProtocolConfiguration,
-        StateSyncConfiguration, SynchronizationPhaseTrait,
+        StateSyncConfiguration, SyncProgress, SynchronizationPhaseTrait,
     },
-    ConsensusGraph, NodeType,
+    ConsensusGraph, NodeType, Notifications,
 };
 use cfx_types::H256;
 use malloc_size_of_derive::MallocSizeOf as DeriveMallocSizeOf;
@@ -36,7 +40,7 @@ impl SynchronizationService {
         protocol_config: ProtocolConfiguration,
         state_sync_config: StateSyncConfiguration,
         initial_sync_phase: SyncPhaseType, light_provider: Arc<LightProvider>,
-        consensus: Arc<ConsensusGraph>,
+        consensus: Arc<ConsensusGraph>, notifications: Arc<Notifications>,
     ) -> Self
     {
         let sync_handler = Arc::new(SynchronizationProtocolHandler::new(
@@ -47,6 +51,7 @@ impl SynchronizationService {
             sync_graph.clone(),
             light_provider,
             consensus,
+            notifications,
         ));
 
         assert_eq!(sync_handler.is_consortium(), sync_graph.is_consortium());
@@ -74,6 +79,26 @@ impl SynchronizationService {
         self.protocol_handler.phase_manager.get_current_phase()
     }
 
+    pub fn sync_progress(&self) -> SyncProgress {
+        self.protocol_handler.sync_progress()
+    }
+
+    pub fn block_propagation(&self, hash: &H256) -> Option<BlockPropagation> {
+        self.protocol_handler
+            .request_manager
+            .propagation
+            .block_propagation(hash)
+    }
+
+    pub fn transaction_first_seen(
+        &self, hash: &H256,
+    ) -> Option<PropagationEvent> {
+        self.protocol_handler
+            .request_manager
+            .propagation
+            .transaction_first_seen(hash)
+    }
+
     pub fn append_received_transactions(
         &self, transactions: Vec<Arc<SignedTransaction>>,
     ) {
@@ -14,11 +14,12 @@ use crate::{
 };
 use malloc_size_of_derive::MallocSizeOf as DeriveMallocSizeOf;
 use network::{
-    node_table::NodeId, service::ProtocolVersion, Error as NetworkError,
-    ErrorKind as NetworkErrorKind,
+    node_score::selection_weight, node_table::NodeId, service::ProtocolVersion,
+    Error as NetworkError, ErrorKind as NetworkErrorKind, NetworkContext,
+    PeerBehavior,
 };
 use parking_lot::RwLock;
-use rand::prelude::SliceRandom;
+use rand::{prelude::SliceRandom, Rng};
 use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
@@ -26,6 +27,9 @@ use std::{
 };
 use throttling::token_bucket::{ThrottledManager, TokenBucketManager};
 
+/// The number of valid responses from a peer reported at once.
+const VALID_RESPONSES_PER_REPORT: u32 = 16;
+
 #[derive(DeriveMallocSizeOf)]
 pub struct SynchronizationPeerState {
     pub node_id: NodeId,
@@ -58,6 +62,13 @@ pub struct SynchronizationPeerState {
     // should not send requests to the remote peer. Otherwise, the remote peer
     // may disconnect the TCP connection.
     pub throttled_msgs: ThrottledManager<MsgId>,
+
+    // Score of the remote peer in network layer, which is updated when
+    // reporting peer behaviors. Peers with higher score are preferred when
+    // selecting peers to send requests.
+    pub score: f64,
+    // Valid responses from the remote peer not reported to network layer yet.
+    pub unreported_valid_responses: u32,
 }
 
 impl SynchronizationPeerState {
@@ -167,6 +178,45 @@ impl SynchronizationState {
         }
     }
 
+    /// Report the peer behavior to network layer, and update the cached peer
+    /// score for peer selection. Valid responses are frequent, so they are
+    /// reported in batches of `VALID_RESPONSES_PER_REPORT` to not take the
+    /// lock of the node database for every response.
+    pub fn report_peer(
+        &self, io: &dyn NetworkContext, node_id: &NodeId,
+        behavior: PeerBehavior,
+    )
+    {
+        let times = if behavior == PeerBehavior::ValidResponse {
+            match self.peers.read().get(node_id) {
+                Some(state) => {
+                    let mut state = state.write();
+                    state.unreported_valid_responses += 1;
+                    if state.unreported_valid_responses
+                        < VALID_RESPONSES_PER_REPORT
+                    {
+                        return;
+                    }
+                    std::mem::take(&mut state.unreported_valid_responses)
+                }
+                None => return,
+            }
+        } else {
+            1
+        };
+
+        // The lock of peers should not be held when reporting, because the
+        // peer will be disconnected and removed if banned.
+        let score = match io.report_peer_times(node_id, behavior, times) {
+            Some(score) => score,
+            None => return,
+        };
+
+        if let Some(state) = self.peers.read().get(node_id) {
+            state.write().score = score;
+        }
+    }
+
     /// Updates the heartbeat for the specified peer. It takes no effect if the
     /// peer is in handshaking status or not found.
     pub fn update_heartbeat(&self, node_id: &NodeId) {
@@ -321,6 +371,15 @@ impl<'a> PeerFilter<'a> {
     }
 
     pub fn select_all(self, syn: &SynchronizationState) -> Vec<NodeId> {
+        self.select_all_with_score(syn)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    fn select_all_with_score(
+        self, syn: &SynchronizationState,
+    ) -> Vec<(NodeId, f64)> {
         let mut peers = Vec::new();
 
         let check_state = self.throttle_msg_ids.is_some()
@@ -378,20 +437,38 @@ impl<'a> PeerFilter<'a> {
                 }
             }
 
-            peers.push(*id);
+            peers.push((*id, peer.read().score));
         }
 
         peers
     }
 
+    /// Randomly select a peer, weighted by the peer score.
     pub fn select(self, syn: &SynchronizationState) -> Option<NodeId> {
-        self.select_all(syn).choose(&mut random::new()).cloned()
+        self.select_all_with_score(syn)
+            .choose_weighted(&mut random::new(), |(_, score)| {
+                selection_weight(*score)
+            })
+            .ok()
+            .map(|(id, _)| *id)
     }
 
+    /// Randomly select `n` peers without replacement, weighted by the peer
+    /// score.
     pub fn select_n(self, n: usize, syn: &SynchronizationState) -> Vec<NodeId> {
-        let mut peers = self.select_all(syn);
-        peers.shuffle(&mut random::new());
+        let mut rng = random::new();
+        // Weighted random sampling (Efraimidis-Spirakis): sort peers by
+        // `u ^ (1 / weight)` in descending order and take the first `n`.
+        let mut peers: Vec<(NodeId, f64)> = self
+            .select_all_with_score(syn)
+            .into_iter()
+            .map(|(id, score)| {
+                let weight = selection_weight(score);
+                (id, rng.gen::<f64>().powf(1.0 / weight))
+            })
+            .collect();
+        peers.sort_by(|(_, a), (_, b)| b.partial_cmp(a).expect("not NaN"));
         peers.truncate(n);
-        peers
+        peers.into_iter().map(|(id, _)| id).collect()
     }
 }
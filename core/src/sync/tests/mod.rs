@@ -2,6 +2,8 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
+mod simulation;
+
 use crate::{
     block_data_manager::DbType,
     sync::{
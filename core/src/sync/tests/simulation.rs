@@ -0,0 +1,491 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Scenarios of several full nodes connected through the network simulator.
+//!
+//! Each node runs the real synchronization and PoS (HotStuff) protocol
+//! handlers on its own synchronization and consensus graphs, and all the
+//! messages and timers are driven by the virtual clock of the simulator. The
+//! request timeouts inside the handlers are measured with the wall clock, so
+//! they are configured long enough to never expire in a scenario.
+
+use crate::{
+    block_data_manager::DbType,
+    light_protocol::Provider as LightProvider,
+    message::Message,
+    pos::{
+        consensus::network::{
+            ConsensusMsg, NetworkReceivers as ConsensusNetworkReceivers,
+            NetworkTask as ConsensusNetworkTask,
+        },
+        mempool::network::{
+            NetworkReceivers as MempoolNetworkReceivers,
+            NetworkTask as MempoolNetworkTask,
+        },
+        protocol::{
+            sync_protocol::HotStuffSynchronizationProtocol, HSB_PROTOCOL_ID,
+            HSB_PROTOCOL_VERSION,
+        },
+    },
+    pow::{PowComputer, ProofOfWorkProblem},
+    sync::{
+        utils::{
+            initialize_data_manager,
+            initialize_synchronization_graph_with_data_manager,
+        },
+        ProtocolConfiguration, StateSyncConfiguration, SyncPhaseType,
+        SynchronizationGraph, SynchronizationProtocolHandler,
+        SYNCHRONIZATION_PROTOCOL_VERSION,
+    },
+    vm_factory::VmFactory,
+    ConsensusGraph, NodeType, Notifications,
+};
+use cfx_parameters::consensus::{
+    ADAPTIVE_WEIGHT_DEFAULT_BETA, ERA_DEFAULT_EPOCH_COUNT, GENESIS_GAS_LIMIT,
+    HEAVY_BLOCK_DEFAULT_DIFFICULTY_RATIO,
+    TIMER_CHAIN_BLOCK_DEFAULT_DIFFICULTY_RATIO, TIMER_CHAIN_DEFAULT_BETA,
+};
+use cfx_types::{address_util::AddressUtil, Address, H256, U256};
+use consensus_types::{quorum_cert::QuorumCert, sync_info::SyncInfo};
+use diem_crypto::{HashValue, PrivateKey, Uniform};
+use diem_types::{
+    account_address::{from_consensus_public_key, AccountAddress},
+    ledger_info::LedgerInfo,
+    on_chain_config::ValidatorSet,
+    validator_config::{ConsensusPrivateKey, ConsensusVRFPrivateKey},
+};
+use futures::{FutureExt, StreamExt};
+use keccak_hash::keccak;
+use network::simulator::{LinkConfig, SimNodeIndex, Simulator};
+use primitives::{Block, BlockHeaderBuilder};
+use rand_08::{prelude::StdRng, SeedableRng};
+use std::{
+    sync::{Arc, Weak},
+    time::Duration,
+};
+use tempdir::TempDir;
+
+const SYNC_PROTOCOL_ID: [u8; 3] = *b"cfx";
+/// The initial difficulty of the test PoW configuration, which is not
+/// adjusted for the heights in the scenarios.
+const DIFFICULTY: u64 = 10;
+/// Long enough for the wall clock to never reach a timeout.
+const WALL_CLOCK_TIMEOUT: Duration = Duration::from_secs(600);
+
+struct SimFullNode {
+    sync: Arc<SynchronizationGraph>,
+    consensus: Arc<ConsensusGraph>,
+    sync_handler: Arc<SynchronizationProtocolHandler>,
+    hsb_handler: Arc<HotStuffSynchronizationProtocol>,
+    pos_address: AccountAddress,
+    consensus_receivers: ConsensusNetworkReceivers,
+    _mempool_receivers: MempoolNetworkReceivers,
+    _db_dir: TempDir,
+}
+
+impl SimFullNode {
+    fn best_block_hash(&self) -> H256 { self.consensus.best_block_hash() }
+
+    fn has_blocks(&self, hashes: &[H256]) -> bool {
+        hashes.iter().all(|hash| self.sync.contains_block(hash))
+    }
+
+    /// Wait until the consensus graph, which runs on its own thread, has
+    /// processed the blocks.
+    fn wait_for_consensus(&self, hashes: &[H256]) {
+        for hash in hashes {
+            self.consensus.wait_for_generation(hash);
+        }
+    }
+
+    /// Take the authors of the consensus messages received so far.
+    fn received_consensus_messages(&mut self) -> Vec<AccountAddress> {
+        let mut authors = Vec::new();
+        while let Some(Some((author, _))) = self
+            .consensus_receivers
+            .consensus_messages
+            .next()
+            .now_or_never()
+        {
+            authors.push(author);
+        }
+        authors
+    }
+}
+
+fn protocol_config() -> ProtocolConfiguration {
+    ProtocolConfiguration {
+        send_tx_period: Duration::from_secs(1),
+        check_request_period: Duration::from_secs(1),
+        check_phase_change_period: Duration::from_secs(1),
+        heartbeat_period_interval: Duration::from_secs(1),
+        heartbeat_timeout: WALL_CLOCK_TIMEOUT,
+        block_cache_gc_period: Duration::from_secs(5),
+        expire_block_gc_period: Duration::from_secs(900),
+        sync_expire_block_timeout: Duration::from_secs(7200),
+        headers_request_timeout: WALL_CLOCK_TIMEOUT,
+        blocks_request_timeout: WALL_CLOCK_TIMEOUT,
+        transaction_request_timeout: WALL_CLOCK_TIMEOUT,
+        snapshot_candidate_request_timeout: WALL_CLOCK_TIMEOUT,
+        snapshot_manifest_request_timeout: WALL_CLOCK_TIMEOUT,
+        snapshot_chunk_request_timeout: WALL_CLOCK_TIMEOUT,
+        tx_maintained_for_peer_timeout: WALL_CLOCK_TIMEOUT,
+        max_inflight_request_count: 64,
+        received_tx_index_maintain_timeout: WALL_CLOCK_TIMEOUT,
+        inflight_pending_tx_index_maintain_timeout: WALL_CLOCK_TIMEOUT,
+        max_trans_count_received_in_catch_up: 60000,
+        min_peers_tx_propagation: 8,
+        max_peers_tx_propagation: 128,
+        max_downloading_chunks: 8,
+        test_mode: true,
+        chunk_size_byte: 4_000_000,
+        timeout_observing_period_s: 600,
+        max_allowed_timeout_in_observing_period: 10,
+        max_unprocessed_block_size: 1 << 26,
+        max_chunk_number_in_manifest: 500,
+        allow_phase_change_without_peer: true,
+        propagation_record_capacity: 1000,
+        ..Default::default()
+    }
+}
+
+fn state_sync_config() -> StateSyncConfiguration {
+    StateSyncConfiguration {
+        max_downloading_chunks: 8,
+        candidate_request_timeout: WALL_CLOCK_TIMEOUT,
+        chunk_request_timeout: WALL_CLOCK_TIMEOUT,
+        manifest_request_timeout: WALL_CLOCK_TIMEOUT,
+    }
+}
+
+/// Create `n` full nodes with PoS keys in a simulator. The nodes are not
+/// connected yet.
+fn setup(seed: u64, n: usize) -> (Simulator, Vec<SimFullNode>) {
+    let mut sim = Simulator::new(seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut nodes = Vec::new();
+    for _ in 0..n {
+        let bls_key = ConsensusPrivateKey::generate(&mut rng);
+        let vrf_key = ConsensusVRFPrivateKey::generate(&mut rng);
+        let pos_public_key = (bls_key.public_key(), vrf_key.public_key());
+        let pos_address =
+            from_consensus_public_key(&pos_public_key.0, &pos_public_key.1);
+        let index = sim.add_node(Some(pos_public_key));
+
+        let db_dir = TempDir::new("sim_full_node").unwrap();
+        let vm = VmFactory::new(1024 * 32);
+        // Octopus is too slow to verify the PoW of every received header.
+        let pow = Arc::new(PowComputer::new(false));
+        let (data_man, _) = initialize_data_manager(
+            db_dir.path().to_str().unwrap(),
+            DbType::Rocksdb,
+            pow.clone(),
+            vm.clone(),
+        );
+        let (sync, consensus) =
+            initialize_synchronization_graph_with_data_manager(
+                data_man,
+                ADAPTIVE_WEIGHT_DEFAULT_BETA,
+                HEAVY_BLOCK_DEFAULT_DIFFICULTY_RATIO,
+                TIMER_CHAIN_BLOCK_DEFAULT_DIFFICULTY_RATIO,
+                TIMER_CHAIN_DEFAULT_BETA,
+                ERA_DEFAULT_EPOCH_COUNT,
+                pow,
+                vm,
+            );
+        // Without a network service, blocks are not relayed to light peers.
+        let light_provider = Arc::new(LightProvider::new(
+            consensus.clone(),
+            sync.clone(),
+            Weak::new(),
+            consensus.txpool.clone(),
+            None, /* throttling_config_file */
+            NodeType::Archive,
+        ));
+        let sync_handler = Arc::new(SynchronizationProtocolHandler::new(
+            NodeType::Archive,
+            protocol_config(),
+            state_sync_config(),
+            SyncPhaseType::Normal,
+            sync.clone(),
+            light_provider,
+            consensus.clone(),
+            Notifications::init(),
+        ));
+        sim.register_protocol(
+            index,
+            sync_handler.clone(),
+            SYNC_PROTOCOL_ID,
+            SYNCHRONIZATION_PROTOCOL_VERSION,
+        )
+        .unwrap();
+
+        let (consensus_network_task, consensus_receivers) =
+            ConsensusNetworkTask::new();
+        let (mempool_network_task, mempool_receivers) =
+            MempoolNetworkTask::new();
+        let hsb_handler = Arc::new(HotStuffSynchronizationProtocol::new(
+            keccak(sim.node_id(index)),
+            consensus_network_task,
+            mempool_network_task,
+            protocol_config(),
+        ));
+        sim.register_protocol(
+            index,
+            hsb_handler.clone(),
+            HSB_PROTOCOL_ID,
+            HSB_PROTOCOL_VERSION,
+        )
+        .unwrap();
+
+        nodes.push(SimFullNode {
+            sync,
+            consensus,
+            sync_handler,
+            hsb_handler,
+            pos_address,
+            consensus_receivers,
+            _mempool_receivers: mempool_receivers,
+            _db_dir: db_dir,
+        });
+    }
+    (sim, nodes)
+}
+
+/// Mine a block with a valid proof of work on `node` and broadcast it to the
+/// peers of the node.
+fn mine(
+    sim: &mut Simulator, nodes: &[SimFullNode], node: SimNodeIndex,
+    parent_hash: H256, height: u64,
+) -> H256
+{
+    let sync = &nodes[node].sync;
+    let mut author = Address::from_low_u64_be(node as u64 + 1);
+    author.set_user_account_type_bits();
+    let mut header = BlockHeaderBuilder::new()
+        .with_parent_hash(parent_hash)
+        .with_height(height)
+        .with_gas_limit(GENESIS_GAS_LIMIT.into())
+        .with_difficulty(DIFFICULTY.into())
+        .with_author(author)
+        .with_custom(
+            sync.machine()
+                .params()
+                .custom_prefix(height)
+                .unwrap_or_default(),
+        )
+        .build();
+    let problem = ProofOfWorkProblem::new(
+        height,
+        header.problem_hash(),
+        DIFFICULTY.into(),
+    );
+    for nonce in 0u64.. {
+        let nonce = U256::from(nonce);
+        let pow_hash = sync.pow.compute(&nonce, &problem.block_hash, height);
+        if ProofOfWorkProblem::validate_hash_against_boundary(
+            &pow_hash,
+            &nonce,
+            &problem.boundary,
+        ) {
+            header.set_nonce(nonce);
+            header.pow_hash = Some(pow_hash);
+            break;
+        }
+    }
+    let hash = header.compute_hash();
+
+    let handler = nodes[node].sync_handler.clone();
+    handler.on_mined_block(Block::new(header, vec![]));
+    sim.with_context(node, SYNC_PROTOCOL_ID, |io| {
+        handler.relay_blocks(io, vec![hash])
+    })
+    .unwrap();
+    hash
+}
+
+/// Mine a chain of `length` blocks on `parent_hash`, and return the hashes.
+fn mine_chain(
+    sim: &mut Simulator, nodes: &[SimFullNode], node: SimNodeIndex,
+    mut parent_hash: H256, parent_height: u64, length: u64,
+) -> Vec<H256>
+{
+    let mut hashes = Vec::new();
+    for height in parent_height + 1..=parent_height + length {
+        parent_hash = mine(sim, nodes, node, parent_hash, height);
+        hashes.push(parent_hash);
+    }
+    hashes
+}
+
+/// Send a consensus message from `node` to the committee members connected
+/// to it, like `NetworkSender::send_to_many`.
+fn send_to_committee(
+    sim: &mut Simulator, nodes: &[SimFullNode], node: SimNodeIndex,
+    committee: &[SimNodeIndex],
+)
+{
+    let genesis_qc = QuorumCert::certificate_for_genesis_from_ledger_info(
+        &LedgerInfo::genesis(HashValue::zero(), ValidatorSet::empty()),
+        HashValue::zero(),
+    );
+    let msg = ConsensusMsg::SyncInfo(Box::new(SyncInfo::new(
+        genesis_qc.clone(),
+        genesis_qc,
+        None,
+    )));
+    let handler = nodes[node].hsb_handler.clone();
+    let recipients: Vec<AccountAddress> =
+        committee.iter().map(|m| nodes[*m].pos_address).collect();
+    sim.with_context(node, HSB_PROTOCOL_ID, |io| {
+        for recipient in recipients {
+            let peer_hash =
+                match handler.pos_peer_mapping.read().get(&recipient) {
+                    Some(peer_hash) => *peer_hash,
+                    None => continue,
+                };
+            if let Some(peer) = handler.peers.get(&peer_hash) {
+                let peer_id = peer.read().get_id();
+                msg.send(io, &peer_id).unwrap();
+            }
+        }
+    });
+}
+
+#[test]
+fn test_partition_and_heal() {
+    let (mut sim, nodes) = setup(1, 4);
+    sim.set_default_link(LinkConfig {
+        latency: Duration::from_millis(50),
+        jitter: Duration::from_millis(20),
+        loss_rate: 0.0,
+    });
+    sim.connect_all();
+    sim.run_for(Duration::from_secs(1));
+    let genesis = nodes[0].best_block_hash();
+
+    sim.partition(&[&[0, 1], &[2, 3]]);
+    let left = mine_chain(&mut sim, &nodes, 0, genesis, 0, 3);
+    let right = mine_chain(&mut sim, &nodes, 2, genesis, 0, 5);
+    assert!(sim.run_until(Duration::from_secs(30), |_| {
+        nodes[1].has_blocks(&left) && nodes[3].has_blocks(&right)
+    }));
+    // Nothing crosses the partition.
+    sim.run_for(Duration::from_secs(5));
+    assert!(!nodes[1].sync.contains_block(&right[0]));
+    assert!(!nodes[3].sync.contains_block(&left[0]));
+    assert!(sim.stats().blocked > 0);
+
+    // The heartbeats announce the new terminals after the consensus graphs
+    // process the blocks.
+    for node in &nodes[0..2] {
+        node.wait_for_consensus(&left);
+    }
+    for node in &nodes[2..4] {
+        node.wait_for_consensus(&right);
+    }
+    sim.heal();
+    let all: Vec<H256> = left.iter().chain(right.iter()).cloned().collect();
+    assert!(sim.run_until(Duration::from_secs(60), |_| {
+        nodes.iter().all(|node| node.has_blocks(&all))
+    }));
+    for node in &nodes {
+        node.wait_for_consensus(&all);
+        // The longer chain is heavier.
+        assert_eq!(node.best_block_hash(), *right.last().unwrap());
+    }
+}
+
+#[test]
+fn test_fork_resolution() {
+    let (mut sim, nodes) = setup(2, 3);
+    // 0 - 1 - 2
+    sim.set_default_link(LinkConfig {
+        latency: Duration::from_millis(100),
+        ..Default::default()
+    });
+    sim.connect(0, 1);
+    sim.connect(1, 2);
+    sim.run_for(Duration::from_secs(1));
+    let genesis = nodes[0].best_block_hash();
+
+    // Both ends mine a block at the same time, so the nodes see the fork in
+    // different orders.
+    let a = mine(&mut sim, &nodes, 0, genesis, 1);
+    let b = mine(&mut sim, &nodes, 2, genesis, 1);
+    assert!(sim.run_until(Duration::from_secs(30), |_| {
+        nodes.iter().all(|node| node.has_blocks(&[a, b]))
+    }));
+    for node in &nodes {
+        node.wait_for_consensus(&[a, b]);
+    }
+    let best = nodes[0].best_block_hash();
+    assert!(best == a || best == b);
+    for node in &nodes {
+        assert_eq!(node.best_block_hash(), best);
+    }
+
+    // Extending the other branch makes it heavier, and all the nodes switch
+    // their pivot chain to it.
+    let (loser, miner) = if best == a { (b, 2) } else { (a, 0) };
+    let extension = mine_chain(&mut sim, &nodes, miner, loser, 1, 2);
+    assert!(sim.run_until(Duration::from_secs(30), |_| {
+        nodes.iter().all(|node| node.has_blocks(&extension))
+    }));
+    for node in &nodes {
+        node.wait_for_consensus(&extension);
+        assert_eq!(node.best_block_hash(), *extension.last().unwrap());
+    }
+}
+
+/// Consensus messages reach the recipients passed to `send_to_committee` that
+/// are connected to the sender, as the recipient set changes between sends.
+/// No epoch change is driven through the PoS ledger.
+#[test]
+fn test_consensus_message_recipients() {
+    let (mut sim, mut nodes) = setup(3, 4);
+    // Node 3 joins the network later.
+    for a in 0..3 {
+        for b in a + 1..3 {
+            sim.connect(a, b);
+        }
+    }
+    sim.run_for(Duration::from_secs(1));
+
+    // Recipients {1, 2}.
+    send_to_committee(&mut sim, &nodes, 0, &[1, 2]);
+    sim.run_for(Duration::from_secs(1));
+    let author = nodes[0].pos_address;
+    assert_eq!(nodes[1].received_consensus_messages(), vec![author]);
+    assert_eq!(nodes[2].received_consensus_messages(), vec![author]);
+    assert!(nodes[3].received_consensus_messages().is_empty());
+
+    // Recipients {2, 3}, where node 3 joins and node 1 leaves.
+    sim.connect(3, 0);
+    sim.connect(3, 2);
+    sim.disconnect(1, 0);
+    sim.run_for(Duration::from_secs(1));
+    assert!(!nodes[0]
+        .hsb_handler
+        .pos_peer_mapping
+        .read()
+        .contains_key(&nodes[1].pos_address));
+    send_to_committee(&mut sim, &nodes, 0, &[2, 3]);
+    sim.run_for(Duration::from_secs(1));
+    assert!(nodes[1].received_consensus_messages().is_empty());
+    assert_eq!(nodes[2].received_consensus_messages(), vec![author]);
+    assert_eq!(nodes[3].received_consensus_messages(), vec![author]);
+
+    // A recipient on the other side of a partition is unreachable
+    // until the partition heals.
+    sim.partition(&[&[0, 2], &[3]]);
+    send_to_committee(&mut sim, &nodes, 0, &[2, 3]);
+    sim.run_for(Duration::from_secs(1));
+    assert_eq!(nodes[2].received_consensus_messages(), vec![author]);
+    assert!(nodes[3].received_consensus_messages().is_empty());
+    sim.heal();
+    send_to_committee(&mut sim, &nodes, 0, &[2, 3]);
+    sim.run_for(Duration::from_secs(1));
+    assert_eq!(nodes[3].received_consensus_messages(), vec![author]);
+}
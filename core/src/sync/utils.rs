@@ -29,7 +29,7 @@ use crate::{
     cache_config::CacheConfig,
     consensus::{
         consensus_inner::consensus_executor::ConsensusExecutionConfiguration,
-        pos_handler::{PosConfiguration, PosVerifier},
+        pos_handler::{PosConfiguration, PosKeys, PosVerifier},
         ConsensusConfig, ConsensusInnerConfig,
     },
     db::NUM_COLUMNS,
@@ -178,27 +178,39 @@ pub fn initialize_data_manager(
     (data_man, genesis_block)
 }
 
-pub fn initialize_synchronization_graph_with_data_manager(
-    data_man: Arc<BlockDataManager>, beta: u64, h: u64, tcr: u64, tcb: u64,
-    era_epoch_count: u64, pow: Arc<PowComputer>, vm: VmFactory,
-) -> (Arc<SynchronizationGraph>, Arc<ConsensusGraph>)
-{
-    let machine = Arc::new(new_machine_with_builtin(Default::default(), vm));
+/// Create a `PosVerifier` without the PoS handler, so PoS is never enabled.
+pub fn initialize_pos_verifier() -> Arc<PosVerifier> {
     let mut rng = StdRng::from_seed([0u8; 32]);
-    let pos_verifier = Arc::new(PosVerifier::new(
+    Arc::new(PosVerifier::new(
         None,
         // These configurations will not be used.
         PosConfiguration {
-            bls_key: ConfigKey::new(ConsensusPrivateKey::generate(&mut rng)),
-            vrf_key: ConfigKey::new(ConsensusVRFPrivateKey::generate(&mut rng)),
+            pos_keys: PosKeys::Local {
+                bls_key: ConfigKey::new(ConsensusPrivateKey::generate(
+                    &mut rng,
+                )),
+                vrf_key: ConfigKey::new(ConsensusVRFPrivateKey::generate(
+                    &mut rng,
+                )),
+            },
             diem_conf_path: Default::default(),
             protocol_conf: Default::default(),
             pos_initial_nodes_path: "".to_string(),
             vrf_proposal_threshold: Default::default(),
             pos_state_config: Default::default(),
+            backup_service_address: None,
         },
         u64::MAX,
-    ));
+    ))
+}
+
+pub fn initialize_synchronization_graph_with_data_manager(
+    data_man: Arc<BlockDataManager>, beta: u64, h: u64, tcr: u64, tcb: u64,
+    era_epoch_count: u64, pow: Arc<PowComputer>, vm: VmFactory,
+) -> (Arc<SynchronizationGraph>, Arc<ConsensusGraph>)
+{
+    let machine = Arc::new(new_machine_with_builtin(Default::default(), vm));
+    let pos_verifier = initialize_pos_verifier();
 
     let verification_config = VerificationConfig::new(
         true, /* test_mode */
@@ -259,6 +271,8 @@ pub fn initialize_synchronization_graph_with_data_manager(
             get_logs_filter_max_limit: None,
             sync_state_starting_epoch: None,
             sync_state_epoch_gap: None,
+            gas_price_oracle: Default::default(),
+            safe_epoch_confirmation_risk: 0.000001,
         },
         txpool.clone(),
         statistics.clone(),
@@ -268,6 +282,9 @@ pub fn initialize_synchronization_graph_with_data_manager(
         notifications.clone(),
         ConsensusExecutionConfiguration {
             executive_trace: false,
+            record_storage_key_preimages: false,
+            persist_epoch_state_diff: false,
+            parallel_execution_threads: 0,
         },
         verification_config.clone(),
         NodeType::Archive,
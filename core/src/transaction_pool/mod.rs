@@ -10,11 +10,20 @@ mod test_treap;
 mod account_cache;
 mod garbage_collector;
 mod nonce_pool;
+mod packing_policy;
 mod transaction_pool_inner;
 
 extern crate rand;
 
-pub use self::{impls::TreapMap, transaction_pool_inner::TransactionStatus};
+pub use self::{
+    impls::TreapMap,
+    packing_policy::{
+        new_packing_policy, ContractCapPolicy, FifoPolicy, GasPricePolicy,
+        PackedTransactions, PackingPolicy, SenderCapPolicy,
+        WhitelistPriorityPolicy,
+    },
+    transaction_pool_inner::TransactionStatus,
+};
 use crate::{
     block_data_manager::BlockDataManager, consensus::BestInformation,
     machine::Machine, state::State, verification::VerificationConfig,
@@ -90,6 +99,7 @@ pub struct TxPoolConfig {
     pub tx_weight_exp: u8,
     pub packing_gas_limit_block_count: u64,
     pub target_block_gas_limit: u64,
+    pub packing_policy: Arc<dyn PackingPolicy>,
 }
 
 impl MallocSizeOf for TxPoolConfig {
@@ -110,6 +120,7 @@ impl Default for TxPoolConfig {
             tx_weight_exp: 1,
             packing_gas_limit_block_count: 10,
             target_block_gas_limit: DEFAULT_TARGET_BLOCK_GAS_LIMIT,
+            packing_policy: Arc::new(GasPricePolicy),
         }
     }
 }
@@ -196,6 +207,12 @@ impl TransactionPool {
 
     pub fn machine(&self) -> Arc<Machine> { self.machine.clone() }
 
+    /// The minimal gas price of the transactions accepted by the pool.
+    pub fn min_tx_price(&self) -> u64 { self.config.min_tx_price }
+
+    /// The maximal number of the transactions in the pool.
+    pub fn capacity(&self) -> usize { self.config.capacity }
+
     pub fn get_transaction(
         &self, tx_hash: &H256,
     ) -> Option<Arc<SignedTransaction>> {
@@ -632,6 +649,7 @@ impl TransactionPool {
             best_block_number,
             &self.verification_config,
             &self.machine,
+            &*self.config.packing_policy,
         )
     }
 
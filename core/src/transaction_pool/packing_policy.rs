@@ -0,0 +1,270 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Policies deciding which of the ready transactions in the pool are packed
+//! into a block, and in which order.
+
+use cfx_types::{Address, AddressWithSpace, U256};
+use primitives::{Action, SignedTransaction};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, HashMap, HashSet},
+    sync::Arc,
+};
+
+pub trait PackingPolicy: Send + Sync {
+    /// Whether the policy orders any ready transactions by `packing_order`.
+    /// If it's `false`, all ready transactions are sampled with their gas
+    /// prices as the weights.
+    fn is_ordered(&self) -> bool { false }
+
+    /// The order in which the ready transaction `tx` is packed, the lower the
+    /// earlier. `arrival_seq` is the order in which `tx` is received by the
+    /// pool. The ordered transactions are packed before the ones sampled with
+    /// their gas prices as the weights, which are those with `None`.
+    fn packing_order(
+        &self, _tx: &SignedTransaction, _arrival_seq: u64,
+    ) -> Option<u64> {
+        None
+    }
+
+    /// Whether `tx`, which is selected from the ready transactions and fits in
+    /// the block, is packed after the transactions in `packed`. A rejected
+    /// transaction stays in the pool for the later blocks.
+    fn accept(
+        &self, _tx: &SignedTransaction, _packed: &PackedTransactions,
+    ) -> bool {
+        true
+    }
+}
+
+/// The counts of the transactions packed into the block being assembled.
+#[derive(Default)]
+pub struct PackedTransactions {
+    per_sender: HashMap<AddressWithSpace, usize>,
+    per_callee: HashMap<AddressWithSpace, usize>,
+}
+
+impl PackedTransactions {
+    pub fn count_from(&self, sender: &AddressWithSpace) -> usize {
+        self.per_sender.get(sender).cloned().unwrap_or(0)
+    }
+
+    pub fn count_to(&self, callee: &AddressWithSpace) -> usize {
+        self.per_callee.get(callee).cloned().unwrap_or(0)
+    }
+
+    pub fn insert(&mut self, tx: &SignedTransaction) {
+        *self.per_sender.entry(tx.sender()).or_insert(0) += 1;
+        if let Some(callee) = callee(tx) {
+            *self.per_callee.entry(callee).or_insert(0) += 1;
+        }
+    }
+}
+
+fn callee(tx: &SignedTransaction) -> Option<AddressWithSpace> {
+    match tx.action() {
+        Action::Call(address) => Some(AddressWithSpace {
+            address: *address,
+            space: tx.space(),
+        }),
+        Action::Create => None,
+    }
+}
+
+struct OrderedCandidate {
+    order: u64,
+    arrival_seq: u64,
+    tx: Arc<SignedTransaction>,
+}
+
+impl OrderedCandidate {
+    fn key(&self) -> (Reverse<u64>, &U256, Reverse<u64>) {
+        (
+            Reverse(self.order),
+            self.tx.gas_price(),
+            Reverse(self.arrival_seq),
+        )
+    }
+}
+
+impl PartialEq for OrderedCandidate {
+    fn eq(&self, other: &Self) -> bool { self.key() == other.key() }
+}
+
+impl Eq for OrderedCandidate {}
+
+impl PartialOrd for OrderedCandidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedCandidate {
+    fn cmp(&self, other: &Self) -> Ordering { self.key().cmp(&other.key()) }
+}
+
+/// The ready transactions ordered by a packing policy, with the ties broken by
+/// the higher gas price and then the earlier arrival.
+#[derive(Default)]
+pub struct PackingCandidates {
+    heap: BinaryHeap<OrderedCandidate>,
+}
+
+impl PackingCandidates {
+    /// Add `tx` if it's ordered by `policy`.
+    pub fn push(
+        &mut self, policy: &dyn PackingPolicy, tx: Arc<SignedTransaction>,
+        arrival_seq: u64,
+    )
+    {
+        if let Some(order) = policy.packing_order(&tx, arrival_seq) {
+            self.heap.push(OrderedCandidate {
+                order,
+                arrival_seq,
+                tx,
+            });
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<Arc<SignedTransaction>> {
+        self.heap.pop().map(|candidate| candidate.tx)
+    }
+}
+
+/// Sample the transactions with their gas prices as the weights.
+pub struct GasPricePolicy;
+
+impl PackingPolicy for GasPricePolicy {}
+
+/// Pack the transactions in the order they are received.
+pub struct FifoPolicy;
+
+impl PackingPolicy for FifoPolicy {
+    fn is_ordered(&self) -> bool { true }
+
+    fn packing_order(
+        &self, _tx: &SignedTransaction, arrival_seq: u64,
+    ) -> Option<u64> {
+        Some(arrival_seq)
+    }
+}
+
+/// Pack at most `max_per_sender` transactions from each sender into a block.
+pub struct SenderCapPolicy {
+    pub max_per_sender: usize,
+}
+
+impl PackingPolicy for SenderCapPolicy {
+    fn accept(
+        &self, tx: &SignedTransaction, packed: &PackedTransactions,
+    ) -> bool {
+        packed.count_from(&tx.sender()) < self.max_per_sender
+    }
+}
+
+/// Pack at most `max_per_contract` transactions calling the same address
+/// into a block.
+pub struct ContractCapPolicy {
+    pub max_per_contract: usize,
+}
+
+impl PackingPolicy for ContractCapPolicy {
+    fn accept(
+        &self, tx: &SignedTransaction, packed: &PackedTransactions,
+    ) -> bool {
+        match callee(tx) {
+            Some(callee) => packed.count_to(&callee) < self.max_per_contract,
+            None => true,
+        }
+    }
+}
+
+/// Pack the transactions from the whitelisted senders first, in the order of
+/// their gas prices, and then sample the others.
+pub struct WhitelistPriorityPolicy {
+    pub whitelist: HashSet<Address>,
+}
+
+impl PackingPolicy for WhitelistPriorityPolicy {
+    fn is_ordered(&self) -> bool { true }
+
+    fn packing_order(
+        &self, tx: &SignedTransaction, _arrival_seq: u64,
+    ) -> Option<u64> {
+        if self.whitelist.contains(&tx.sender().address) {
+            Some(0)
+        } else {
+            None
+        }
+    }
+}
+
+/// Create the packing policy configured by `name`.
+pub fn new_packing_policy(
+    name: &str, max_per_sender: usize, max_per_contract: usize,
+    whitelist: HashSet<Address>,
+) -> Result<Arc<dyn PackingPolicy>, String>
+{
+    let policy: Arc<dyn PackingPolicy> = match name {
+        "gas_price" => Arc::new(GasPricePolicy),
+        "fifo" => Arc::new(FifoPolicy),
+        "sender_cap" => Arc::new(SenderCapPolicy { max_per_sender }),
+        "contract_cap" => Arc::new(ContractCapPolicy { max_per_contract }),
+        "whitelist_priority" => Arc::new(WhitelistPriorityPolicy { whitelist }),
+        _ => return Err(format!("invalid packing policy {}", name)),
+    };
+    Ok(policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cfx_types::{AddressSpaceUtil, U256};
+    use keylib::{Generator, KeyPair, Random};
+    use primitives::{NativeTransaction, Transaction};
+
+    fn new_test_tx(
+        sender: &KeyPair, nonce: usize, callee: Address,
+    ) -> Arc<SignedTransaction> {
+        Arc::new(
+            Transaction::from(NativeTransaction {
+                nonce: U256::from(nonce),
+                gas_price: U256::from(1),
+                gas: U256::from(50000),
+                action: Action::Call(callee),
+                value: U256::from(0),
+                storage_limit: 0,
+                epoch_height: 0,
+                chain_id: 1,
+                data: Vec::new(),
+            })
+            .sign(sender.secret()),
+        )
+    }
+
+    #[test]
+    fn test_caps() {
+        let alice = Random.generate().unwrap();
+        let bob = Random.generate().unwrap();
+        let contract = Address::random();
+
+        let mut packed = PackedTransactions::default();
+        packed.insert(&new_test_tx(&alice, 0, contract));
+        packed.insert(&new_test_tx(&alice, 1, Address::random()));
+        assert_eq!(packed.count_from(&alice.address().with_native_space()), 2);
+        assert_eq!(packed.count_to(&contract.with_native_space()), 1);
+
+        let sender_cap = SenderCapPolicy { max_per_sender: 2 };
+        assert!(!sender_cap.accept(&new_test_tx(&alice, 2, contract), &packed));
+        assert!(sender_cap.accept(&new_test_tx(&bob, 0, contract), &packed));
+
+        let contract_cap = ContractCapPolicy {
+            max_per_contract: 1,
+        };
+        assert!(!contract_cap.accept(&new_test_tx(&bob, 0, contract), &packed));
+        assert!(contract_cap
+            .accept(&new_test_tx(&bob, 0, Address::random()), &packed));
+    }
+}
@@ -3,6 +3,7 @@ use super::{
     garbage_collector::GarbageCollector,
     impls::TreapMap,
     nonce_pool::{InsertResult, NoncePool, TxWithReadyInfo},
+    packing_policy::{PackedTransactions, PackingCandidates, PackingPolicy},
 };
 use crate::{
     machine::Machine,
@@ -295,6 +296,14 @@ impl SpacedReadyAccountPool {
         self.packing_pool.sample_peek()
     }
 
+    fn iter(&self) -> impl Iterator<Item = &Arc<SignedTransaction>> {
+        self.packing_pool
+            .treap
+            .iter()
+            .map(|(_, tx)| tx)
+            .chain(self.waiting_pool.iter().map(|(_, tx)| &tx.0))
+    }
+
     fn try_shrink_packing_pool(&mut self) {
         while self.packing_pool.total_gas > self.packing_pool.total_gas_capacity
         {
@@ -543,6 +552,11 @@ impl ReadyAccountPool {
         self.native_pool.sample_peek()
     }
 
+    /// Iterate over all ready transactions in an arbitrary order.
+    fn iter(&self) -> impl Iterator<Item = &Arc<SignedTransaction>> {
+        self.native_pool.iter().chain(self.evm_pool.iter())
+    }
+
     fn peek_evm(&self) -> Option<Arc<SignedTransaction>> {
         self.evm_pool.sample_peek()
     }
@@ -644,6 +658,10 @@ pub struct TransactionPoolInner {
     /// It should contain the same transaction set as `deferred_pool`.
     txs: HashMap<H256, Arc<SignedTransaction>>,
     tx_sponsored_gas_map: HashMap<H256, (U256, u64)>,
+    /// The order in which the transactions in `txs` are received, used by
+    /// the packing policies preferring earlier transactions.
+    tx_arrival_seq: HashMap<H256, u64>,
+    next_arrival_seq: u64,
 }
 
 impl TransactionPoolInner {
@@ -666,6 +684,8 @@ impl TransactionPoolInner {
             garbage_collector: GarbageCollector::default(),
             txs: HashMap::new(),
             tx_sponsored_gas_map: HashMap::new(),
+            tx_arrival_seq: HashMap::new(),
+            next_arrival_seq: 0,
         }
     }
 
@@ -676,6 +696,8 @@ impl TransactionPoolInner {
         self.garbage_collector.clear();
         self.txs.clear();
         self.tx_sponsored_gas_map.clear();
+        self.tx_arrival_seq.clear();
+        self.next_arrival_seq = 0;
         self.total_received_count = 0;
         self.unpacked_transaction_count = 0;
     }
@@ -838,6 +860,7 @@ impl TransactionPoolInner {
             // maintain txs
             self.txs.remove(&to_remove_tx.hash());
             self.tx_sponsored_gas_map.remove(&to_remove_tx.hash());
+            self.tx_arrival_seq.remove(&to_remove_tx.hash());
         }
 
         // Insert back skipped nodes to keep `garbage_collector`
@@ -914,6 +937,7 @@ impl TransactionPoolInner {
                     transaction.hash(),
                     (sponsored_gas, sponsored_storage),
                 );
+                self.record_arrival(transaction.hash());
                 if !packed {
                     self.unpacked_transaction_count += 1;
                 }
@@ -936,6 +960,12 @@ impl TransactionPoolInner {
                     transaction.hash(),
                     (sponsored_gas, sponsored_storage),
                 );
+                // Packing re-inserts the same transaction, which keeps its
+                // arrival order.
+                if replaced_tx.hash() != transaction.hash() {
+                    self.tx_arrival_seq.remove(&replaced_tx.hash());
+                    self.record_arrival(transaction.hash());
+                }
                 if !packed {
                     self.unpacked_transaction_count += 1;
                 }
@@ -945,6 +975,45 @@ impl TransactionPoolInner {
         result
     }
 
+    fn record_arrival(&mut self, tx_hash: H256) {
+        self.tx_arrival_seq.insert(tx_hash, self.next_arrival_seq);
+        self.next_arrival_seq += 1;
+    }
+
+    fn arrival_seq(&self, tx_hash: &H256) -> u64 {
+        self.tx_arrival_seq
+            .get(tx_hash)
+            .cloned()
+            .unwrap_or(u64::MAX)
+    }
+
+    /// Pop the next ready transaction to pack, preferring the candidates
+    /// ordered by the packing policy.
+    fn pop_packing_candidate(
+        &mut self, candidates: &mut PackingCandidates, sample_eth_tx: bool,
+    ) -> Option<Arc<SignedTransaction>> {
+        while let Some(tx) = candidates.pop() {
+            if tx.space() == Space::Ethereum && !sample_eth_tx {
+                continue;
+            }
+            match self.ready_account_pool.get(&tx.sender()) {
+                Some(ready_tx) if ready_tx.hash() == tx.hash() => {
+                    self.ready_account_pool.remove(&tx.sender());
+                    return Some(tx);
+                }
+                // The candidate is no longer the ready transaction of the
+                // sender.
+                _ => continue,
+            }
+        }
+
+        if sample_eth_tx {
+            self.ready_account_pool.pop()
+        } else {
+            self.ready_account_pool.pop_native()
+        }
+    }
+
     pub fn get_account_pending_info(
         &self, address: &AddressWithSpace,
     ) -> Option<(U256, U256, U256, H256)> {
@@ -1144,14 +1213,23 @@ impl TransactionPoolInner {
         &mut self, num_txs: usize, block_gas_limit: U256, evm_gas_limit: U256,
         block_size_limit: usize, best_epoch_height: u64,
         best_block_number: u64, verification_config: &VerificationConfig,
-        machine: &Machine,
+        machine: &Machine, packing_policy: &dyn PackingPolicy,
     ) -> Vec<Arc<SignedTransaction>>
     {
         let mut packed_transactions: Vec<Arc<SignedTransaction>> = Vec::new();
+        let mut packed = PackedTransactions::default();
         if num_txs == 0 {
             return packed_transactions;
         }
 
+        let mut candidates = PackingCandidates::default();
+        if packing_policy.is_ordered() {
+            for tx in self.ready_account_pool.iter() {
+                let arrival_seq = self.arrival_seq(&tx.hash());
+                candidates.push(packing_policy, tx.clone(), arrival_seq);
+            }
+        }
+
         let mut total_tx_gas_limit: U256 = 0.into();
         let mut eth_total_tx_gas_limit: U256 = 0.into();
         let mut total_tx_size: usize = 0;
@@ -1165,11 +1243,9 @@ impl TransactionPoolInner {
         let spec = machine.spec(best_block_number);
         let transitions = &machine.params().transition_heights;
 
-        'out: while let Some(tx) = if sample_eth_tx {
-            self.ready_account_pool.pop()
-        } else {
-            self.ready_account_pool.pop_native()
-        } {
+        'out: while let Some(tx) =
+            self.pop_packing_candidate(&mut candidates, sample_eth_tx)
+        {
             let tx_size = tx.rlp_size();
             if block_gas_limit - total_tx_gas_limit < *tx.gas_limit()
                 || block_size_limit - total_tx_size < tx_size
@@ -1211,12 +1287,18 @@ impl TransactionPoolInner {
                 }
             }
 
+            if !packing_policy.accept(&tx, &packed) {
+                recycle_txs.push(tx.clone());
+                continue 'out;
+            }
+
             total_tx_gas_limit += *tx.gas_limit();
             if tx.space() == Space::Ethereum {
                 eth_total_tx_gas_limit += *tx.gas_limit();
             }
             total_tx_size += tx_size;
 
+            packed.insert(&tx);
             packed_transactions.push(tx.clone());
             self.insert_transaction_without_readiness_check(
                 tx.clone(),
@@ -1229,6 +1311,13 @@ impl TransactionPoolInner {
                     .unwrap_or((U256::from(0), 0)),
             );
             self.recalculate_readiness_with_local_info(&tx.sender());
+            if packing_policy.is_ordered() {
+                if let Some(next_tx) = self.ready_account_pool.get(&tx.sender())
+                {
+                    let arrival_seq = self.arrival_seq(&next_tx.hash());
+                    candidates.push(packing_policy, next_tx, arrival_seq);
+                }
+            }
             if packed_transactions.len() >= num_txs {
                 break 'out;
             }
@@ -1492,8 +1581,24 @@ impl TransactionPoolInner {
 
 #[cfg(test)]
 mod test_transaction_pool_inner {
-    use super::{DeferredPool, InsertResult, TxWithReadyInfo};
-    use crate::transaction_pool::transaction_pool_inner::ReadyAccountPool;
+    use super::{
+        DeferredPool, InsertResult, TransactionPoolInner, TxWithReadyInfo,
+    };
+    use crate::{
+        machine::new_machine_with_builtin,
+        sync::utils::initialize_pos_verifier,
+        transaction_pool::{
+            transaction_pool_inner::ReadyAccountPool, FifoPolicy,
+            GasPricePolicy, PackingPolicy, SenderCapPolicy,
+            WhitelistPriorityPolicy,
+        },
+        verification::VerificationConfig,
+        vm_factory::VmFactory,
+    };
+    use cfx_parameters::{
+        block::{MAX_BLOCK_SIZE_IN_BYTES, REFEREE_DEFAULT_BOUND},
+        consensus::TRANSACTION_DEFAULT_EPOCH_BOUND,
+    };
     use cfx_types::{Address, AddressSpaceUtil, U256};
     use keylib::{Generator, KeyPair, Random};
     use primitives::{
@@ -1826,4 +1931,134 @@ mod test_transaction_pool_inner {
         assert_eq!(ready_pool.native_pool.packing_pool.len(), 0);
         assert_eq!(ready_pool.native_pool.waiting_pool.len(), 0);
     }
+
+    fn insert_ready_tx(
+        pool: &mut TransactionPoolInner, tx: Arc<SignedTransaction>,
+    ) {
+        let nonce_and_balance = (U256::zero(), U256::from(1u64 << 60));
+        let result = pool.insert_transaction_without_readiness_check(
+            tx.clone(),
+            false, /* packed */
+            true,  /* force */
+            Some(nonce_and_balance),
+            (U256::zero(), 0),
+        );
+        assert!(matches!(result, InsertResult::NewAdded));
+        pool.recalculate_readiness_with_local_info(&tx.sender());
+    }
+
+    fn pack(
+        pool: &mut TransactionPoolInner, num_txs: usize,
+        packing_policy: &dyn PackingPolicy,
+    ) -> Vec<Arc<SignedTransaction>>
+    {
+        let machine = Arc::new(new_machine_with_builtin(
+            Default::default(),
+            VmFactory::new(1024 * 32),
+        ));
+        let verification_config = VerificationConfig::new(
+            true, /* test_mode */
+            REFEREE_DEFAULT_BOUND,
+            MAX_BLOCK_SIZE_IN_BYTES,
+            TRANSACTION_DEFAULT_EPOCH_BOUND,
+            machine.clone(),
+            initialize_pos_verifier(),
+        );
+        pool.pack_transactions(
+            num_txs,
+            U256::from(50000 * 100), /* block_gas_limit */
+            U256::zero(),            /* evm_gas_limit */
+            MAX_BLOCK_SIZE_IN_BYTES,
+            0, /* best_epoch_height */
+            0, /* best_block_number */
+            &verification_config,
+            &machine,
+            packing_policy,
+        )
+    }
+
+    #[test]
+    fn test_pack_transactions_fifo() {
+        let mut pool = TransactionPoolInner::new(100, 1, 1, U256::MAX);
+        let alice = Random.generate().unwrap();
+        let bob = Random.generate().unwrap();
+        let eva = Random.generate().unwrap();
+
+        // The later transactions have the higher gas prices.
+        let eva0 = new_test_tx(&eva, 0, 1, 0);
+        let alice0 = new_test_tx(&alice, 0, 10, 0);
+        let alice1 = new_test_tx(&alice, 1, 10, 0);
+        let bob0 = new_test_tx(&bob, 0, 100, 0);
+        for tx in &[&eva0, &alice0, &alice1, &bob0] {
+            insert_ready_tx(&mut pool, (*tx).clone());
+        }
+
+        // The earliest transactions are selected, and `alice1` is selected
+        // once `alice0` is packed.
+        assert_eq!(
+            pack(&mut pool, 2, &FifoPolicy),
+            vec![eva0.clone(), alice0.clone()]
+        );
+        assert_eq!(
+            pack(&mut pool, 4, &FifoPolicy),
+            vec![eva0, alice0, alice1, bob0]
+        );
+    }
+
+    #[test]
+    fn test_pack_transactions_whitelist_priority() {
+        let mut pool = TransactionPoolInner::new(100, 1, 1, U256::MAX);
+        let alice = Random.generate().unwrap();
+        let bob = Random.generate().unwrap();
+        let eva = Random.generate().unwrap();
+
+        let alice0 = new_test_tx(&alice, 0, 1000, 0);
+        let bob0 = new_test_tx(&bob, 0, 1, 0);
+        let bob1 = new_test_tx(&bob, 1, 1, 0);
+        let eva0 = new_test_tx(&eva, 0, 10, 0);
+        for tx in &[&alice0, &bob0, &bob1, &eva0] {
+            insert_ready_tx(&mut pool, (*tx).clone());
+        }
+
+        // The whitelisted senders go first, in the order of the gas prices.
+        let policy = WhitelistPriorityPolicy {
+            whitelist: vec![bob.address(), eva.address()].into_iter().collect(),
+        };
+        assert_eq!(
+            pack(&mut pool, 3, &policy),
+            vec![eva0.clone(), bob0.clone(), bob1.clone()]
+        );
+
+        // The others are sampled after the whitelisted ones.
+        assert_eq!(pack(&mut pool, 4, &policy), vec![eva0, bob0, bob1, alice0]);
+    }
+
+    #[test]
+    fn test_pack_transactions_sender_cap() {
+        let mut pool = TransactionPoolInner::new(100, 1, 1, U256::MAX);
+        let alice = Random.generate().unwrap();
+        let bob = Random.generate().unwrap();
+
+        let alice0 = new_test_tx(&alice, 0, 10, 0);
+        let alice1 = new_test_tx(&alice, 1, 10, 0);
+        let bob0 = new_test_tx(&bob, 0, 10, 0);
+        for tx in &[&alice0, &alice1, &bob0] {
+            insert_ready_tx(&mut pool, (*tx).clone());
+        }
+
+        let mut packed =
+            pack(&mut pool, 3, &SenderCapPolicy { max_per_sender: 1 });
+        packed.sort_by_key(|tx| tx.sender() != alice0.sender());
+        assert_eq!(packed, vec![alice0.clone(), bob0.clone()]);
+
+        // Without a policy, the transactions of a sender are still packed in
+        // the nonce order.
+        let packed = pack(&mut pool, 3, &GasPricePolicy);
+        assert_eq!(packed.len(), 3);
+        let alice_txs: Vec<_> = packed
+            .into_iter()
+            .filter(|tx| tx.sender() == alice0.sender())
+            .collect();
+        assert_eq!(alice_txs, vec![alice0, alice1]);
+    }
 }
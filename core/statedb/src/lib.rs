@@ -52,6 +52,14 @@ mod impls {
 
         /// Checkpoints allow callers to revert un-committed changes.
         checkpoints: Vec<Checkpoint>,
+
+        /// Preimages of the storage slot keys written to the storage, keyed
+        /// by the encoded key bytes. Only collected when enabled.
+        storage_key_preimages: Option<Vec<(Key, StorageKeyPreimage)>>,
+
+        /// The state entries written to the storage together with their
+        /// original values. Only collected when enabled.
+        state_changes: Option<Vec<StateEntryChange>>,
     }
 
     pub trait StateDbCheckpointMethods {
@@ -75,6 +83,44 @@ mod impls {
                 accessed_entries: Default::default(),
                 storage,
                 checkpoints: Default::default(),
+                storage_key_preimages: None,
+                state_changes: None,
+            }
+        }
+
+        /// Collect the preimages of all storage slot keys which are written
+        /// to the storage from now on.
+        pub fn enable_storage_key_preimage_recording(&mut self) {
+            if self.storage_key_preimages.is_none() {
+                self.storage_key_preimages = Some(Vec::new());
+            }
+        }
+
+        /// Take the storage key preimages collected so far. Returns an empty
+        /// list if the recording is not enabled.
+        pub fn take_storage_key_preimages(
+            &mut self,
+        ) -> Vec<(Key, StorageKeyPreimage)> {
+            match &mut self.storage_key_preimages {
+                Some(preimages) => std::mem::take(preimages),
+                None => vec![],
+            }
+        }
+
+        /// Collect the original and the new values of all entries which are
+        /// written to the storage from now on.
+        pub fn enable_state_change_recording(&mut self) {
+            if self.state_changes.is_none() {
+                self.state_changes = Some(Vec::new());
+            }
+        }
+
+        /// Take the state changes collected so far. Returns an empty list if
+        /// the recording is not enabled.
+        pub fn take_state_changes(&mut self) -> Vec<StateEntryChange> {
+            match &mut self.state_changes {
+                Some(changes) => std::mem::take(changes),
+                None => vec![],
             }
         }
 
@@ -379,6 +425,30 @@ mod impls {
                     let storage_key = StorageKeyWithSpace::from_key_bytes::<
                         SkipInputCheck,
                     >(k);
+                    if let Some(preimage) = storage_key.storage_key_preimage() {
+                        if let Some(record) = debug_record.as_deref_mut() {
+                            record
+                                .storage_key_preimages
+                                .push((k.clone(), preimage.clone()));
+                        }
+                        if let Some(preimages) = &mut self.storage_key_preimages
+                        {
+                            preimages.push((k.clone(), preimage));
+                        }
+                    }
+                    if let Some(changes) = &mut self.state_changes {
+                        changes.push(StateEntryChange {
+                            key: k.clone(),
+                            original_value: v
+                                .original_value
+                                .as_ref()
+                                .map(|v| v.to_vec()),
+                            current_value: v
+                                .current_value
+                                .as_ref()
+                                .map(|v| v.to_vec()),
+                        });
+                    }
                     match &v.current_value {
                         Some(v) => {
                             self.storage.set(storage_key, (&**v).into())?;
@@ -607,7 +677,8 @@ mod impls {
     use hashbrown::HashMap;
     use parking_lot::RwLock;
     use primitives::{
-        EpochId, SkipInputCheck, StorageKey, StorageKeyWithSpace, StorageLayout,
+        EpochId, SkipInputCheck, StateEntryChange, StorageKey,
+        StorageKeyPreimage, StorageKeyWithSpace, StorageLayout,
     };
     use std::{
         collections::{btree_map::Entry::Occupied, BTreeMap},
@@ -3,12 +3,18 @@
 // See http://www.gnu.org/licenses/
 
 use super::StateDbGeneric;
-use cfx_internal_common::StateRootWithAuxInfo;
+use cfx_internal_common::{
+    debug::ComputeEpochDebugRecord, StateRootWithAuxInfo,
+};
 use cfx_storage::{
     utils::access_mode, ErrorKind, MptKeyValue, Result, StorageStateTrait,
 };
+use cfx_types::Address;
 use parking_lot::Mutex;
-use primitives::{EpochId, StorageKey, StorageKeyWithSpace, MERKLE_NULL_NODE};
+use primitives::{
+    storage::STORAGE_LAYOUT_REGULAR_V0, EpochId, StateEntryChange, StorageKey,
+    StorageKeyWithSpace, MERKLE_NULL_NODE,
+};
 use std::collections::HashMap;
 
 type StorageValue = Box<[u8]>;
@@ -329,3 +335,96 @@ fn test_checkpoint_evict_memory() {
     // value is removed from state-db
     assert_eq!(state_db.get_from_cache(&key(b"00")), None);
 }
+
+#[test]
+fn test_storage_key_preimage_recording() {
+    let address = Address::from_low_u64_be(1);
+    let mut contents = RawStorage::new();
+    contents.insert(
+        StorageKey::new_storage_root_key(&address)
+            .with_native_space()
+            .to_key_bytes(),
+        STORAGE_LAYOUT_REGULAR_V0.to_bytes().into(),
+    );
+    let storage = MockStorage::with_contents(contents);
+    let mut state_db = StateDbTest::new(Box::new(storage));
+    state_db.enable_storage_key_preimage_recording();
+
+    // account keys have no preimage to record
+    state_db
+        .set_raw(storage_key(b"11"), value(b"v1"), None)
+        .unwrap();
+
+    let slot = [7u8; 32];
+    let slot_key =
+        StorageKey::new_storage_key(&address, &slot).with_native_space();
+    state_db.set_raw(slot_key, value(b"v1"), None).unwrap();
+
+    let mut debug_record = ComputeEpochDebugRecord::default();
+    state_db
+        .commit(MERKLE_NULL_NODE, Some(&mut debug_record))
+        .unwrap();
+
+    let preimages = state_db.take_storage_key_preimages();
+    assert_eq!(preimages.len(), 1);
+    assert_eq!(preimages[0].0, slot_key.to_key_bytes());
+    assert_eq!(preimages[0].1.address, address);
+    assert_eq!(preimages[0].1.slot, slot.to_vec());
+
+    // the preimages are also included in the debug dump
+    assert_eq!(debug_record.storage_key_preimages, preimages);
+
+    // preimages are only returned once
+    assert!(state_db.take_storage_key_preimages().is_empty());
+}
+
+#[test]
+fn test_state_change_recording() {
+    let mut state_db = init_state_db();
+    state_db.enable_state_change_recording();
+
+    // (11, v0) --> (11, v1)
+    state_db
+        .set_raw(storage_key(b"11"), value(b"v1"), None)
+        .unwrap();
+
+    // (22, v0) --> none
+    state_db.delete(storage_key(b"22"), None).unwrap();
+
+    // none --> (33, v1)
+    state_db
+        .set_raw(storage_key(b"33"), value(b"v1"), None)
+        .unwrap();
+
+    // unchanged entries are not recorded
+    state_db
+        .set_raw(storage_key(b"00"), value(b"v0"), None)
+        .unwrap();
+
+    state_db.commit(MERKLE_NULL_NODE, None).unwrap();
+
+    let changes = state_db.take_state_changes();
+    assert_eq!(
+        changes,
+        vec![
+            StateEntryChange {
+                key: key(b"11"),
+                original_value: Some(b"v0".to_vec()),
+                current_value: Some(b"v1".to_vec()),
+            },
+            StateEntryChange {
+                key: key(b"22"),
+                original_value: Some(b"v0".to_vec()),
+                current_value: None,
+            },
+            StateEntryChange {
+                key: key(b"33"),
+                original_value: None,
+                current_value: Some(b"v1".to_vec()),
+            },
+        ]
+    );
+
+    // changes are only returned once
+    assert!(state_db.take_state_changes().is_empty());
+}
@@ -0,0 +1,381 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Bandwidth accounting by protocol and message id.
+//!
+//! Each session accounts the packets sent to and received from the remote
+//! peer in `BandwidthStats`, which is exposed in `SessionDetails`. Besides,
+//! the traffic of all peers is aggregated into meters of the metrics group
+//! `network_bandwidth`, named `{protocol}_{in|out}_{bytes|msgs}` for each
+//! protocol and `{protocol}_{in|out}_{msg_id}_{bytes|msgs}` for each message.
+//!
+//! The meters are registered along with the protocol handlers, for the
+//! message ids declared by the handlers. Packets of the other protocols are
+//! neither metered nor accounted to the sessions, and packets of undeclared
+//! or malformed message ids are metered as
+//! `{protocol}_{in|out}_unknown_{bytes|msgs}` and accounted as `unknown`, so
+//! a peer cannot grow the stats of its session with arbitrary ids.
+//!
+//! Session packets, i.e. Hello and Disconnect, are accounted under the
+//! protocol `session`, and UDP discovery packets under `discovery`, both by
+//! packet id. Packet sizes are the sizes on the wire, i.e. after compression,
+//! and outbound packets are accounted when queued.
+
+use crate::{discovery, session, ProtocolId};
+use lazy_static::lazy_static;
+use metrics::{register_meter_with_group, Meter};
+use parking_lot::RwLock;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+/// Protocol name to account session packets.
+pub const SESSION_PROTOCOL_NAME: &str = "session";
+/// Protocol name to account UDP discovery packets.
+pub const DISCOVERY_PROTOCOL_NAME: &str = "discovery";
+
+const METRICS_GROUP: &str = "network_bandwidth";
+
+lazy_static! {
+    /// Meters of the registered protocols, keyed by the protocol name.
+    static ref METERS: RwLock<HashMap<String, ProtocolMeters>> = {
+        let mut meters = HashMap::new();
+        meters.insert(
+            SESSION_PROTOCOL_NAME.into(),
+            ProtocolMeters::new(SESSION_PROTOCOL_NAME, &session::PACKET_IDS),
+        );
+        meters.insert(
+            DISCOVERY_PROTOCOL_NAME.into(),
+            ProtocolMeters::new(
+                DISCOVERY_PROTOCOL_NAME,
+                &discovery::PACKET_IDS,
+            ),
+        );
+        RwLock::new(meters)
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+impl Direction {
+    fn metric_name(&self) -> &'static str {
+        match self {
+            Direction::Inbound => "in",
+            Direction::Outbound => "out",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficCounter {
+    pub messages: u64,
+    pub bytes: u64,
+}
+
+impl TrafficCounter {
+    fn add(&mut self, bytes: usize) {
+        self.messages += 1;
+        self.bytes += bytes as u64;
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Traffic {
+    pub inbound: TrafficCounter,
+    pub outbound: TrafficCounter,
+}
+
+impl Traffic {
+    fn add(&mut self, direction: Direction, bytes: usize) {
+        match direction {
+            Direction::Inbound => self.inbound.add(bytes),
+            Direction::Outbound => self.outbound.add(bytes),
+        }
+    }
+
+    fn merge(&mut self, other: &Traffic) {
+        self.inbound.messages += other.inbound.messages;
+        self.inbound.bytes += other.inbound.bytes;
+        self.outbound.messages += other.outbound.messages;
+        self.outbound.bytes += other.outbound.bytes;
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolTraffic {
+    pub total: Traffic,
+    /// Traffic by declared message id.
+    pub messages: BTreeMap<u16, Traffic>,
+    /// Traffic of the undeclared or malformed message ids.
+    pub unknown: Traffic,
+}
+
+/// Traffic of a session by protocol name.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BandwidthStats {
+    pub protocols: BTreeMap<String, ProtocolTraffic>,
+}
+
+impl BandwidthStats {
+    /// Account a packet of a session and mark the global meters. It's a
+    /// no-op if the protocol is not registered.
+    pub fn record(
+        &mut self, protocol: &str, msg_id: u16, direction: Direction,
+        bytes: usize,
+    )
+    {
+        let meters = METERS.read();
+        let meters = match meters.get(protocol) {
+            Some(meters) => meters,
+            None => return,
+        };
+        let traffic = self.protocols.entry(protocol.into()).or_default();
+        traffic.total.add(direction, bytes);
+        if meters.is_declared(msg_id) {
+            traffic
+                .messages
+                .entry(msg_id)
+                .or_default()
+                .add(direction, bytes);
+        } else {
+            traffic.unknown.add(direction, bytes);
+        }
+        meters.mark(msg_id, direction, bytes);
+    }
+
+    /// Traffic of all protocols.
+    pub fn total(&self) -> Traffic {
+        let mut total = Traffic::default();
+        for traffic in self.protocols.values() {
+            total.merge(&traffic.total);
+        }
+        total
+    }
+}
+
+/// Name of the protocol for accounting, where `None` stands for the session
+/// packets.
+pub fn protocol_name(protocol: Option<&ProtocolId>) -> String {
+    match protocol {
+        Some(protocol) => String::from_utf8_lossy(&protocol[..]).into(),
+        None => SESSION_PROTOCOL_NAME.into(),
+    }
+}
+
+/// Register the global meters of a protocol and its messages. It's a no-op
+/// if the protocol is already registered.
+pub fn register_protocol_meters(protocol: &str, msg_ids: &[u16]) {
+    if METERS.read().contains_key(protocol) {
+        return;
+    }
+    METERS
+        .write()
+        .entry(protocol.into())
+        .or_insert_with(|| ProtocolMeters::new(protocol, msg_ids));
+}
+
+/// Mark the global meters of a packet without accounting it to a session.
+pub fn mark_meters(
+    protocol: &str, msg_id: u16, direction: Direction, bytes: usize,
+) {
+    if let Some(meters) = METERS.read().get(protocol) {
+        meters.mark(msg_id, direction, bytes);
+    }
+}
+
+struct TrafficMeters {
+    bytes: Arc<dyn Meter>,
+    msgs: Arc<dyn Meter>,
+}
+
+impl TrafficMeters {
+    fn new(name: &str) -> Self {
+        TrafficMeters {
+            bytes: register_meter_with_group(
+                METRICS_GROUP,
+                &format!("{}_bytes", name),
+            ),
+            msgs: register_meter_with_group(
+                METRICS_GROUP,
+                &format!("{}_msgs", name),
+            ),
+        }
+    }
+
+    fn mark(&self, bytes: usize) {
+        self.bytes.mark(bytes);
+        self.msgs.mark(1);
+    }
+}
+
+/// Meters of a protocol in one direction.
+struct DirectionMeters {
+    total: TrafficMeters,
+    messages: HashMap<u16, TrafficMeters>,
+    /// Meters of the undeclared or malformed message ids.
+    unknown: TrafficMeters,
+}
+
+impl DirectionMeters {
+    fn new(protocol: &str, direction: Direction, msg_ids: &[u16]) -> Self {
+        let prefix = format!("{}_{}", protocol, direction.metric_name());
+        DirectionMeters {
+            total: TrafficMeters::new(&prefix),
+            messages: msg_ids
+                .iter()
+                .map(|id| {
+                    (*id, TrafficMeters::new(&format!("{}_{}", prefix, id)))
+                })
+                .collect(),
+            unknown: TrafficMeters::new(&format!("{}_unknown", prefix)),
+        }
+    }
+
+    fn mark(&self, msg_id: u16, bytes: usize) {
+        self.total.mark(bytes);
+        self.messages
+            .get(&msg_id)
+            .unwrap_or(&self.unknown)
+            .mark(bytes);
+    }
+}
+
+struct ProtocolMeters {
+    inbound: DirectionMeters,
+    outbound: DirectionMeters,
+}
+
+impl ProtocolMeters {
+    fn new(protocol: &str, msg_ids: &[u16]) -> Self {
+        ProtocolMeters {
+            inbound: DirectionMeters::new(
+                protocol,
+                Direction::Inbound,
+                msg_ids,
+            ),
+            outbound: DirectionMeters::new(
+                protocol,
+                Direction::Outbound,
+                msg_ids,
+            ),
+        }
+    }
+
+    fn is_declared(&self, msg_id: u16) -> bool {
+        self.inbound.messages.contains_key(&msg_id)
+    }
+
+    fn mark(&self, msg_id: u16, direction: Direction, bytes: usize) {
+        match direction {
+            Direction::Inbound => self.inbound.mark(msg_id, bytes),
+            Direction::Outbound => self.outbound.mark(msg_id, bytes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        register_protocol_meters("record_test", &[1, 2]);
+        let mut stats = BandwidthStats::default();
+        stats.record("record_test", 1, Direction::Inbound, 100);
+        stats.record("record_test", 1, Direction::Inbound, 50);
+        stats.record("record_test", 2, Direction::Outbound, 10);
+        stats.record(SESSION_PROTOCOL_NAME, 0x80, Direction::Outbound, 20);
+
+        let traffic = &stats.protocols["record_test"];
+        assert_eq!(
+            traffic.total.inbound,
+            TrafficCounter {
+                messages: 2,
+                bytes: 150
+            }
+        );
+        assert_eq!(
+            traffic.total.outbound,
+            TrafficCounter {
+                messages: 1,
+                bytes: 10
+            }
+        );
+        assert_eq!(traffic.messages[&1].inbound.bytes, 150);
+        assert_eq!(traffic.messages[&1].outbound, TrafficCounter::default());
+        assert_eq!(traffic.messages[&2].outbound.messages, 1);
+
+        let total = stats.total();
+        assert_eq!(total.inbound.bytes, 150);
+        assert_eq!(
+            total.outbound,
+            TrafficCounter {
+                messages: 2,
+                bytes: 30
+            }
+        );
+    }
+
+    #[test]
+    fn test_record_unknown() {
+        register_protocol_meters("unknown_test", &[1]);
+        let mut stats = BandwidthStats::default();
+        // The undeclared message ids share one entry.
+        for msg_id in 2..100 {
+            stats.record("unknown_test", msg_id, Direction::Inbound, 10);
+        }
+        // The unregistered protocols are ignored.
+        stats.record("unregistered", 1, Direction::Inbound, 10);
+
+        assert_eq!(stats.protocols.len(), 1);
+        let traffic = &stats.protocols["unknown_test"];
+        assert!(traffic.messages.is_empty());
+        assert_eq!(
+            traffic.unknown.inbound,
+            TrafficCounter {
+                messages: 98,
+                bytes: 980
+            }
+        );
+        assert_eq!(traffic.total, traffic.unknown);
+    }
+
+    #[test]
+    fn test_register_protocol_meters() {
+        // Packets of unregistered protocols are not metered.
+        mark_meters("unregistered", 1, Direction::Inbound, 10);
+        assert!(!METERS.read().contains_key("unregistered"));
+
+        register_protocol_meters("test", &[1, 2]);
+        // Registering again keeps the meters.
+        register_protocol_meters("test", &[3]);
+        mark_meters("test", 3, Direction::Outbound, 10);
+        let meters = METERS.read();
+        let mut ids: Vec<_> =
+            meters["test"].inbound.messages.keys().cloned().collect();
+        ids.sort();
+        assert_eq!(ids, vec![1, 2]);
+        assert!(meters[SESSION_PROTOCOL_NAME]
+            .outbound
+            .messages
+            .contains_key(&0x80));
+        assert_eq!(meters[DISCOVERY_PROTOCOL_NAME].inbound.messages.len(), 4);
+    }
+
+    #[test]
+    fn test_protocol_name() {
+        assert_eq!(protocol_name(Some(b"cfx")), "cfx");
+        assert_eq!(protocol_name(None), SESSION_PROTOCOL_NAME);
+    }
+}
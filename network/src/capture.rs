@@ -0,0 +1,527 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Capture of protocol messages, so that the exact message sequence received
+//! by a node could be replayed offline to reproduce sync issues.
+//!
+//! Besides the messages, peer connection events are also captured so that
+//! the protocol handler observes the same set of peers during replay.
+//!
+//! Each message is appended to the capture file as a 4-byte big-endian length
+//! followed by the RLP encoded `CapturedMessage`. Capture files are named
+//! with an increasing index, and rotated when the file size limit is reached.
+//! Only the latest `max_files` capture files are kept.
+
+use crate::{
+    node_table::NodeId,
+    service::ProtocolVersion,
+    session::{decode_pos_public_key, encode_pos_public_key},
+    Error, ErrorKind, ProtocolId,
+};
+use diem_types::validator_config::{ConsensusPublicKey, ConsensusVRFPublicKey};
+use lazy_static::lazy_static;
+use metrics::{register_meter_with_group, Meter};
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+lazy_static! {
+    static ref CAPTURE_DROPPED_METER: Arc<dyn Meter> =
+        register_meter_with_group("network_system_data", "capture_dropped");
+}
+
+const CAPTURE_FILE_PREFIX: &str = "capture-";
+const CAPTURE_FILE_SUFFIX: &str = ".bin";
+
+pub const DEFAULT_CAPTURE_FILE_SIZE: u64 = 64 * 1024 * 1024;
+pub const DEFAULT_CAPTURE_MAX_FILES: usize = 16;
+/// Maximum number of messages waiting to be written.
+const CAPTURE_CHANNEL_SIZE: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureKind {
+    /// Message received from peer.
+    Inbound = 0,
+    /// Message sent to peer.
+    Outbound = 1,
+    /// Peer connected, with RLP encoded `PeerConnected` as data.
+    PeerConnected = 2,
+    /// Peer disconnected, with empty data.
+    PeerDisconnected = 3,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedMessage {
+    pub kind: CaptureKind,
+    pub protocol: ProtocolId,
+    pub node_id: NodeId,
+    /// Milliseconds since UNIX epoch.
+    pub timestamp_ms: u64,
+    pub data: Vec<u8>,
+}
+
+impl Encodable for CapturedMessage {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(5)
+            .append(&(self.kind as u8))
+            .append(&&self.protocol[..])
+            .append(&self.node_id)
+            .append(&self.timestamp_ms)
+            .append(&self.data);
+    }
+}
+
+impl Decodable for CapturedMessage {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        if rlp.item_count()? != 5 {
+            return Err(DecoderError::RlpIncorrectListLen);
+        }
+
+        let kind = match rlp.val_at::<u8>(0)? {
+            0 => CaptureKind::Inbound,
+            1 => CaptureKind::Outbound,
+            2 => CaptureKind::PeerConnected,
+            3 => CaptureKind::PeerDisconnected,
+            _ => return Err(DecoderError::Custom("invalid capture kind")),
+        };
+
+        let protocol_bytes: Vec<u8> = rlp.val_at(1)?;
+        let mut protocol = ProtocolId::default();
+        if protocol_bytes.len() != protocol.len() {
+            return Err(DecoderError::Custom("invalid protocol id"));
+        }
+        protocol.copy_from_slice(&protocol_bytes);
+
+        Ok(CapturedMessage {
+            kind,
+            protocol,
+            node_id: rlp.val_at(2)?,
+            timestamp_ms: rlp.val_at(3)?,
+            data: rlp.val_at(4)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptureConfig {
+    /// Directory to store the capture files.
+    pub dir: String,
+    /// Capture file is rotated once its size exceeds this limit in bytes.
+    pub max_file_size: u64,
+    /// Maximum number of capture files to keep, and the oldest file is
+    /// removed when exceeded.
+    pub max_files: usize,
+}
+
+impl CaptureConfig {
+    pub fn new(dir: String) -> Self {
+        CaptureConfig {
+            dir,
+            max_file_size: DEFAULT_CAPTURE_FILE_SIZE,
+            max_files: DEFAULT_CAPTURE_MAX_FILES,
+        }
+    }
+}
+
+struct CaptureFile {
+    file: BufWriter<File>,
+    index: u64,
+    size: u64,
+}
+
+enum CaptureEvent {
+    Message(CapturedMessage),
+    /// Flush the written messages to file, and notify when done.
+    Flush(SyncSender<()>),
+}
+
+/// Recorder that appends protocol messages to rotating capture files.
+///
+/// Messages are written by a background thread, so that the network threads
+/// are never blocked by the file system. Messages are dropped if the writer
+/// falls behind by `CAPTURE_CHANNEL_SIZE` messages.
+pub struct MessageRecorder {
+    sender: Option<SyncSender<CaptureEvent>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl MessageRecorder {
+    pub fn new(config: CaptureConfig) -> Result<Self, Error> {
+        fs::create_dir_all(&config.dir)?;
+        let (sender, receiver) = mpsc::sync_channel(CAPTURE_CHANNEL_SIZE);
+        let writer = thread::Builder::new()
+            .name("capture writer".into())
+            .spawn(move || {
+                CaptureWriter {
+                    config,
+                    current: None,
+                }
+                .run(receiver)
+            })?;
+        Ok(MessageRecorder {
+            sender: Some(sender),
+            writer: Some(writer),
+        })
+    }
+
+    /// Record a message. Failures are only logged, so that capture never
+    /// affects the network.
+    pub fn record(
+        &self, kind: CaptureKind, protocol: ProtocolId, node_id: &NodeId,
+        data: &[u8],
+    )
+    {
+        let msg = CapturedMessage {
+            kind,
+            protocol,
+            node_id: *node_id,
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            data: data.to_vec(),
+        };
+
+        let sender = self.sender.as_ref().expect("only taken on drop");
+        match sender.try_send(CaptureEvent::Message(msg)) {
+            Ok(_) => {}
+            Err(TrySendError::Full(_)) => CAPTURE_DROPPED_METER.mark(1),
+            Err(TrySendError::Disconnected(_)) => {
+                warn!("Failed to capture message: capture writer stopped")
+            }
+        }
+    }
+
+    /// Wait until the recorded messages are written to file.
+    pub fn flush(&self) {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let flushed = self
+            .sender
+            .as_ref()
+            .expect("only taken on drop")
+            .send(CaptureEvent::Flush(sender))
+            .is_ok()
+            && receiver.recv().is_ok();
+        if !flushed {
+            warn!("Failed to flush captured messages: capture writer stopped");
+        }
+    }
+}
+
+impl Drop for MessageRecorder {
+    fn drop(&mut self) {
+        // The writer thread writes all the pending messages and exits once
+        // the channel is closed.
+        self.sender.take();
+        if let Some(writer) = self.writer.take() {
+            if writer.join().is_err() {
+                warn!("Capture writer panicked");
+            }
+        }
+    }
+}
+
+/// Writer that owns the capture files, and runs in the background thread.
+struct CaptureWriter {
+    config: CaptureConfig,
+    current: Option<CaptureFile>,
+}
+
+impl CaptureWriter {
+    fn run(mut self, receiver: Receiver<CaptureEvent>) {
+        while let Ok(event) = receiver.recv() {
+            self.handle(event);
+            // Flush once the pending messages are written, so that the
+            // messages are written in batches without staying in the buffer.
+            while let Ok(event) = receiver.try_recv() {
+                self.handle(event);
+            }
+            if let Err(e) = self.flush() {
+                warn!("Failed to flush captured messages: {:?}", e);
+            }
+        }
+    }
+
+    fn handle(&mut self, event: CaptureEvent) {
+        match event {
+            CaptureEvent::Message(msg) => {
+                if let Err(e) = self.append(&msg) {
+                    warn!("Failed to capture message: {:?}", e);
+                }
+            }
+            CaptureEvent::Flush(done) => {
+                if let Err(e) = self.flush() {
+                    warn!("Failed to flush captured messages: {:?}", e);
+                }
+                let _ = done.send(());
+            }
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        if let Some(f) = self.current.as_mut() {
+            f.file.flush()?;
+        }
+        Ok(())
+    }
+
+    fn append(&mut self, msg: &CapturedMessage) -> Result<(), Error> {
+        let encoded = rlp::encode(msg);
+        let mut record = Vec::with_capacity(4 + encoded.len());
+        record.extend_from_slice(&(encoded.len() as u32).to_be_bytes());
+        record.extend_from_slice(&encoded);
+
+        let rotate = match self.current.as_ref() {
+            None => true,
+            Some(f) => {
+                f.size > 0
+                    && f.size + record.len() as u64 > self.config.max_file_size
+            }
+        };
+
+        if rotate {
+            let index = match self.current.as_ref() {
+                Some(f) => f.index + 1,
+                // Never overwrite the captures of previous runs.
+                None => capture_files(&self.config.dir)?
+                    .last()
+                    .map_or(0, |(index, _)| index + 1),
+            };
+            self.flush()?;
+            let path = capture_file_path(&self.config.dir, index);
+            let file =
+                OpenOptions::new().create(true).append(true).open(&path)?;
+            debug!("Start new capture file {:?}", path);
+            self.current = Some(CaptureFile {
+                file: BufWriter::new(file),
+                index,
+                size: 0,
+            });
+            self.remove_stale_files()?;
+        }
+
+        let f = self.current.as_mut().expect("capture file opened");
+        f.file.write_all(&record)?;
+        f.size += record.len() as u64;
+
+        Ok(())
+    }
+
+    fn remove_stale_files(&self) -> Result<(), Error> {
+        let files = capture_files(&self.config.dir)?;
+        let stale = files.len().saturating_sub(self.config.max_files);
+        for (_, path) in files.into_iter().take(stale) {
+            debug!("Remove stale capture file {:?}", path);
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Data of `PeerConnected` event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerConnected {
+    pub version: ProtocolVersion,
+    pub pos_public_key: Option<(ConsensusPublicKey, ConsensusVRFPublicKey)>,
+    /// Whether the connection is initiated by the local node.
+    pub originated: bool,
+}
+
+impl Encodable for PeerConnected {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(3).append(&self.version.0);
+        match self.pos_public_key {
+            Some(ref key) => s.append(&encode_pos_public_key(key)),
+            None => s.append_empty_data(),
+        };
+        s.append(&self.originated);
+    }
+}
+
+impl Decodable for PeerConnected {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        let key_bytes: Vec<u8> = rlp.val_at(1)?;
+        let pos_public_key =
+            if key_bytes.is_empty() {
+                None
+            } else {
+                Some(decode_pos_public_key(&key_bytes).map_err(|_| {
+                    DecoderError::Custom("invalid pos public key")
+                })?)
+            };
+        Ok(PeerConnected {
+            version: ProtocolVersion(rlp.val_at(0)?),
+            pos_public_key,
+            originated: rlp.val_at(2)?,
+        })
+    }
+}
+
+fn capture_file_path(dir: &str, index: u64) -> PathBuf {
+    Path::new(dir).join(format!(
+        "{}{:08}{}",
+        CAPTURE_FILE_PREFIX, index, CAPTURE_FILE_SUFFIX
+    ))
+}
+
+/// List the capture files in the specified directory in order of index.
+pub fn capture_files<P: AsRef<Path>>(
+    dir: P,
+) -> Result<Vec<(u64, PathBuf)>, Error> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let index = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(CAPTURE_FILE_PREFIX))
+            .and_then(|name| name.strip_suffix(CAPTURE_FILE_SUFFIX))
+            .and_then(|index| index.parse::<u64>().ok());
+        if let Some(index) = index {
+            files.push((index, path));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Reader to iterate the messages in a capture file.
+pub struct CaptureReader {
+    reader: BufReader<File>,
+}
+
+impl CaptureReader {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Ok(CaptureReader {
+            reader: BufReader::new(File::open(path)?),
+        })
+    }
+
+    fn read_next(&mut self) -> Result<Option<CapturedMessage>, Error> {
+        let mut len_bytes = [0u8; 4];
+        match self.reader.read_exact(&mut len_bytes) {
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        let mut encoded = vec![0u8; u32::from_be_bytes(len_bytes) as usize];
+        self.reader.read_exact(&mut encoded).map_err(|e| {
+            // The last record may be truncated if the node crashed.
+            ErrorKind::Decoder(format!("truncated capture record: {}", e))
+        })?;
+
+        Ok(Some(rlp::decode(&encoded)?))
+    }
+}
+
+impl Iterator for CaptureReader {
+    type Item = Result<CapturedMessage, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> { self.read_next().transpose() }
+}
+
+/// Read all the captured messages in the specified directory in order.
+pub fn read_capture_dir<P: AsRef<Path>>(
+    dir: P,
+) -> Result<Vec<CapturedMessage>, Error> {
+    let mut messages = Vec::new();
+    for (_, path) in capture_files(dir)? {
+        for msg in CaptureReader::open(path)? {
+            messages.push(msg?);
+        }
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    fn recorder(dir: &TempDir, max_file_size: u64) -> MessageRecorder {
+        MessageRecorder::new(CaptureConfig {
+            dir: dir.path().to_str().unwrap().into(),
+            max_file_size,
+            max_files: 3,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_record_and_read() {
+        let dir = TempDir::new("capture").unwrap();
+        let recorder = recorder(&dir, DEFAULT_CAPTURE_FILE_SIZE);
+
+        let node_id = NodeId::random();
+        recorder.record(CaptureKind::Inbound, *b"cfx", &node_id, &[1, 2, 3]);
+        recorder.record(CaptureKind::Outbound, *b"pos", &node_id, &[]);
+        recorder.flush();
+
+        let messages = read_capture_dir(dir.path()).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].kind, CaptureKind::Inbound);
+        assert_eq!(messages[0].protocol, *b"cfx");
+        assert_eq!(messages[0].node_id, node_id);
+        assert_eq!(messages[0].data, vec![1, 2, 3]);
+        assert_eq!(messages[1].kind, CaptureKind::Outbound);
+        assert_eq!(messages[1].protocol, *b"pos");
+        assert!(messages[1].data.is_empty());
+    }
+
+    #[test]
+    fn test_rotate() {
+        let dir = TempDir::new("capture").unwrap();
+        let recorder = recorder(&dir, 200);
+
+        let node_id = NodeId::random();
+        for i in 0..20u8 {
+            recorder.record(CaptureKind::Inbound, *b"cfx", &node_id, &[i; 50]);
+        }
+        recorder.flush();
+
+        let files = capture_files(dir.path()).unwrap();
+        assert_eq!(files.len(), 3);
+
+        // only the latest messages are kept
+        let messages = read_capture_dir(dir.path()).unwrap();
+        assert!(messages.len() < 20);
+        assert_eq!(messages.last().unwrap().data, vec![19; 50]);
+
+        // new recorder never overwrites the existing captures
+        let last_index = files.last().unwrap().0;
+        let recorder = self::recorder(&dir, 200);
+        recorder.record(CaptureKind::Inbound, *b"cfx", &node_id, &[20]);
+        recorder.flush();
+        let files = capture_files(dir.path()).unwrap();
+        assert_eq!(files.last().unwrap().0, last_index + 1);
+    }
+
+    #[test]
+    fn test_truncated_record() {
+        let dir = TempDir::new("capture").unwrap();
+        let recorder = recorder(&dir, DEFAULT_CAPTURE_FILE_SIZE);
+        recorder.record(CaptureKind::Inbound, *b"cfx", &NodeId::random(), &[1]);
+        drop(recorder);
+
+        let (_, path) = capture_files(dir.path()).unwrap().pop().unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[0, 0, 0, 10, 1]).unwrap();
+
+        let mut reader = CaptureReader::open(&path).unwrap();
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_err());
+    }
+}
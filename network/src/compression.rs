@@ -0,0 +1,186 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Per-session payload compression.
+//!
+//! Compression is negotiated with a session packet extension: each peer
+//! attaches a compression extension that lists the supported algorithms to
+//! its Hello packet. A protocol packet is only compressed if the remote peer
+//! advertised the algorithm, in which case the same extension is attached to
+//! the packet with the algorithm used. Peers that do not know about the
+//! extension ignore it in Hello and never receive compressed packets.
+
+use crate::{Error, ErrorKind};
+use lazy_static::lazy_static;
+use metrics::{register_meter_with_group, Meter};
+use std::sync::Arc;
+
+lazy_static! {
+    static ref COMPRESS_RAW_METER: Arc<dyn Meter> =
+        register_meter_with_group("network_system_data", "compress_raw");
+    static ref COMPRESS_COMPRESSED_METER: Arc<dyn Meter> =
+        register_meter_with_group("network_system_data", "compress_compressed");
+    static ref DECOMPRESS_COMPRESSED_METER: Arc<dyn Meter> =
+        register_meter_with_group(
+            "network_system_data",
+            "decompress_compressed"
+        );
+    static ref DECOMPRESS_RAW_METER: Arc<dyn Meter> =
+        register_meter_with_group("network_system_data", "decompress_raw");
+}
+
+/// Session packet extension id for compression.
+pub const EXTENSION_COMPRESSION: u8 = 0x01;
+
+/// Default size in bytes above which protocol payloads are compressed.
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 1024;
+
+/// Compression algorithms that could be negotiated between peers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionAlgorithm {
+    Snappy = 1,
+}
+
+impl CompressionAlgorithm {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(CompressionAlgorithm::Snappy),
+            _ => None,
+        }
+    }
+}
+
+/// Algorithms supported by the local node, in order of preference.
+const SUPPORTED_ALGORITHMS: [CompressionAlgorithm; 1] =
+    [CompressionAlgorithm::Snappy];
+
+/// Extension attached to Hello packet to advertise the supported algorithms.
+pub fn hello_extension() -> Vec<u8> {
+    let mut ext = vec![EXTENSION_COMPRESSION];
+    ext.extend(SUPPORTED_ALGORITHMS.iter().map(|a| *a as u8));
+    ext
+}
+
+/// Pick the preferred algorithm that is also supported by the remote peer,
+/// based on the extensions of its Hello packet.
+pub fn negotiate(extensions: &[Vec<u8>]) -> Option<CompressionAlgorithm> {
+    let peer_algorithms = extensions
+        .iter()
+        .find(|ext| ext.first() == Some(&EXTENSION_COMPRESSION))?;
+
+    SUPPORTED_ALGORITHMS
+        .iter()
+        .find(|a| peer_algorithms[1..].contains(&(**a as u8)))
+        .cloned()
+}
+
+/// Compress the payload and return the extension to attach to the packet.
+///
+/// Returns `None` if the compressed payload is not smaller than the raw one,
+/// and the payload should be sent uncompressed.
+pub fn compress(
+    algorithm: CompressionAlgorithm, data: &[u8],
+) -> Option<(Vec<u8>, Vec<u8>)> {
+    let compressed = match algorithm {
+        CompressionAlgorithm::Snappy => {
+            snap::raw::Encoder::new().compress_vec(data).ok()?
+        }
+    };
+
+    if compressed.len() >= data.len() {
+        return None;
+    }
+
+    COMPRESS_RAW_METER.mark(data.len());
+    COMPRESS_COMPRESSED_METER.mark(compressed.len());
+
+    Some((compressed, vec![EXTENSION_COMPRESSION, algorithm as u8]))
+}
+
+/// Decompress the payload if the packet has compression extension.
+///
+/// The decompressed payload is limited to `max_len` bytes, so that a
+/// malicious peer cannot exhaust the memory with a small packet.
+pub fn decompress(
+    data: &[u8], extensions: &[Vec<u8>], max_len: usize,
+) -> Result<Option<Vec<u8>>, Error> {
+    let ext = match extensions
+        .iter()
+        .find(|ext| ext.first() == Some(&EXTENSION_COMPRESSION))
+    {
+        Some(ext) => ext,
+        None => return Ok(None),
+    };
+
+    if ext.len() != 2 {
+        debug!("invalid compression extension {:?}", ext);
+        bail!(ErrorKind::BadProtocol);
+    }
+
+    let decompressed = match CompressionAlgorithm::from_u8(ext[1]) {
+        Some(CompressionAlgorithm::Snappy) => {
+            let len = snap::raw::decompress_len(data)
+                .map_err(|e| ErrorKind::Decoder(format!("snappy: {}", e)))?;
+            if len > max_len {
+                debug!(
+                    "decompressed payload too large, len = {}, max = {}",
+                    len, max_len
+                );
+                bail!(ErrorKind::OversizedPacket);
+            }
+            snap::raw::Decoder::new()
+                .decompress_vec(data)
+                .map_err(|e| ErrorKind::Decoder(format!("snappy: {}", e)))?
+        }
+        None => {
+            debug!("unsupported compression algorithm {}", ext[1]);
+            bail!(ErrorKind::BadProtocol);
+        }
+    };
+
+    DECOMPRESS_COMPRESSED_METER.mark(data.len());
+    DECOMPRESS_RAW_METER.mark(decompressed.len());
+
+    Ok(Some(decompressed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate(&[]), None);
+        assert_eq!(negotiate(&[vec![EXTENSION_COMPRESSION]]), None);
+        assert_eq!(negotiate(&[vec![EXTENSION_COMPRESSION, 9]]), None);
+        assert_eq!(
+            negotiate(&[vec![7, 1], hello_extension()]),
+            Some(CompressionAlgorithm::Snappy)
+        );
+    }
+
+    #[test]
+    fn test_compress_roundtrip() {
+        let data = vec![7u8; 4096];
+        let (compressed, ext) =
+            compress(CompressionAlgorithm::Snappy, &data).unwrap();
+        assert!(compressed.len() < data.len());
+
+        let decompressed =
+            decompress(&compressed, &[ext.clone()], data.len()).unwrap();
+        assert_eq!(decompressed, Some(data.clone()));
+
+        // exceeds the size limit
+        assert!(decompress(&compressed, &[ext], data.len() - 1).is_err());
+
+        // not compressed
+        assert_eq!(decompress(&data, &[], data.len()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_compress_incompressible() {
+        let data: Vec<u8> = (0..=255u8).collect();
+        assert!(compress(CompressionAlgorithm::Snappy, &data).is_none());
+    }
+}
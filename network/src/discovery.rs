@@ -3,6 +3,7 @@
 // See http://www.gnu.org/licenses/
 
 use crate::{
+    bandwidth::{self, Direction},
     hash::keccak,
     node_database::NodeDatabase,
     node_table::{NodeId, *},
@@ -30,6 +31,12 @@ const PACKET_PING: u8 = 1;
 const PACKET_PONG: u8 = 2;
 const PACKET_FIND_NODE: u8 = 3;
 const PACKET_NEIGHBOURS: u8 = 4;
+pub(crate) const PACKET_IDS: [u16; 4] = [
+    PACKET_PING as u16,
+    PACKET_PONG as u16,
+    PACKET_FIND_NODE as u16,
+    PACKET_NEIGHBOURS as u16,
+];
 
 struct PingRequest {
     // Time when the request was sent
@@ -194,6 +201,12 @@ impl Discovery {
     ) -> Result<H256, Error>
     {
         let packet = assemble_packet(packet_id, payload, &self.secret)?;
+        bandwidth::mark_meters(
+            bandwidth::DISCOVERY_PROTOCOL_NAME,
+            packet_id as u16,
+            Direction::Outbound,
+            packet.len(),
+        );
         let hash = H256::from_slice(&packet[1..=32]);
         self.send_to(uio, packet, address.clone());
         Ok(hash)
@@ -223,6 +236,13 @@ impl Discovery {
         let node_id = recover(&signature.into(), &keccak(signed))?;
 
         let packet_id = signed[0];
+        // Including the UDP protocol byte.
+        bandwidth::mark_meters(
+            bandwidth::DISCOVERY_PROTOCOL_NAME,
+            packet_id as u16,
+            Direction::Inbound,
+            packet.len() + 1,
+        );
         let rlp = Rlp::new(&signed[1..]);
         match packet_id {
             PACKET_PING => {
@@ -37,6 +37,9 @@ pub type ProtocolId = [u8; PROTOCOL_ID_SIZE];
 pub type HandlerWorkType = u8;
 pub type PeerId = usize;
 
+pub mod bandwidth;
+pub mod capture;
+mod compression;
 mod connection;
 mod discovery;
 mod error;
@@ -44,15 +47,21 @@ mod handshake;
 mod ip;
 mod ip_utils;
 mod node_database;
+pub mod node_score;
 pub mod node_table;
+mod peers_file;
+pub mod replay;
 pub mod service;
 mod session;
 mod session_manager;
+#[cfg(any(test, feature = "simulator"))]
+pub mod simulator;
 pub mod throttling;
 
 pub use crate::{
     error::{DisconnectReason, Error, ErrorKind, ThrottlingReason},
     ip::SessionIpLimitConfig,
+    node_score::{NodeScore, PeerBehavior},
     node_table::Node,
     service::NetworkService,
     session::SessionDetails,
@@ -60,7 +69,11 @@ pub use crate::{
 pub use io::TimerToken;
 
 use crate::{
-    node_table::NodeId,
+    capture::CaptureConfig,
+    compression::{CompressionAlgorithm, DEFAULT_COMPRESSION_THRESHOLD},
+    node_score::DEFAULT_BAN_SCORE_THRESHOLD,
+    node_table::{NodeEndpoint, NodeId},
+    peers_file::DEFAULT_PEERS_FILE_CHECK_INTERVAL_MS,
     service::{
         ProtocolVersion, DEFAULT_CONNECTION_LIFETIME_FOR_PROMOTION,
         DEFAULT_DISCOVERY_REFRESH_TIMEOUT, DEFAULT_DISCOVERY_ROUND_TIMEOUT,
@@ -119,6 +132,8 @@ pub struct NetworkConfiguration {
     pub max_handshakes: usize,
     /// List of reserved node addresses.
     pub reserved_nodes: Vec<String>,
+    /// Only accept incoming connections from reserved nodes.
+    pub reserved_peers_only: bool,
     /// IP filter
     pub ip_filter: IpFilter,
     /// Timeout duration for initiating peer connection management
@@ -139,6 +154,18 @@ pub struct NetworkConfiguration {
     /// Maximum number of P2P nodes for subnet B (ip/16).
     pub subnet_quota: usize,
     pub session_ip_limit_config: SessionIpLimitConfig,
+    /// Protocol payloads larger than this size in bytes are compressed if
+    /// the remote peer supports compression. None disables compression.
+    pub compression_threshold: Option<usize>,
+    /// Peers whose score drops below this threshold are disconnected and
+    /// blacklisted. None disables the automatic banning.
+    pub ban_score_threshold: Option<f64>,
+    /// Capture protocol messages to files for offline replay if enabled.
+    pub capture: Option<CaptureConfig>,
+    /// File of static peers that are kept as trusted nodes, which is
+    /// reloaded when modified.
+    pub peers_file: Option<String>,
+    pub peers_file_check_interval: Duration,
 
     pub discovery_config: DiscoveryConfiguration,
 }
@@ -164,6 +191,7 @@ impl NetworkConfiguration {
             max_incoming_peers: 0,
             max_handshakes: 0,
             reserved_nodes: Vec::new(),
+            reserved_peers_only: false,
             ip_filter: IpFilter::default(),
             housekeeping_timeout: DEFAULT_HOUSEKEEPING_TIMEOUT,
             discovery_refresh_timeout: DEFAULT_DISCOVERY_REFRESH_TIMEOUT,
@@ -176,6 +204,13 @@ impl NetworkConfiguration {
             test_mode: false,
             subnet_quota: 32,
             session_ip_limit_config: SessionIpLimitConfig::default(),
+            compression_threshold: Some(DEFAULT_COMPRESSION_THRESHOLD),
+            ban_score_threshold: Some(DEFAULT_BAN_SCORE_THRESHOLD),
+            capture: None,
+            peers_file: None,
+            peers_file_check_interval: Duration::from_millis(
+                DEFAULT_PEERS_FILE_CHECK_INTERVAL_MS,
+            ),
             discovery_config,
         }
     }
@@ -288,6 +323,10 @@ pub trait NetworkProtocolHandler: Sync + Send {
     fn on_work_dispatch(
         &self, _io: &dyn NetworkContext, _work_type: HandlerWorkType,
     );
+
+    /// Ids of the messages of the protocol, which are metered separately in
+    /// the bandwidth meters.
+    fn message_ids(&self) -> Vec<u16> { Vec::new() }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
@@ -322,6 +361,27 @@ pub trait NetworkContext {
 
     fn insert_peer_node_tag(&self, peer: NodeId, key: &str, value: &str);
 
+    /// Report the behavior of peer to update its score, and returns the new
+    /// score if the peer is known. The peer is disconnected and blacklisted
+    /// once its score drops below the ban threshold.
+    fn report_peer(&self, peer: &NodeId, behavior: PeerBehavior)
+        -> Option<f64>;
+
+    /// Report the same behavior of peer `times` times at once, e.g. the
+    /// behaviors batched by the protocol handler to save the locking.
+    fn report_peer_times(
+        &self, peer: &NodeId, behavior: PeerBehavior, times: u32,
+    ) -> Option<f64> {
+        let mut score = None;
+        for _ in 0..times {
+            score = self.report_peer(peer, behavior);
+        }
+        score
+    }
+
+    /// Get the current score of peer, which is 0 for unknown peer.
+    fn get_peer_score(&self, peer: &NodeId) -> f64;
+
     fn is_peer_self(&self, _node_id: &NodeId) -> bool;
 
     fn self_node_id(&self) -> NodeId;
@@ -335,6 +395,8 @@ pub struct SessionMetadata {
     pub originated: bool,
     /// Packet header version of the peer.
     pub peer_header_version: u8,
+    /// Compression algorithm negotiated in Hello packet.
+    pub peer_compression: Option<CompressionAlgorithm>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -388,6 +450,34 @@ pub struct PeerInfo {
     pub protocols: Vec<ProtocolInfo>,
 }
 
+/// Connected peer information for admin RPC.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminPeerInfo {
+    pub node_id: NodeId,
+    pub address: SocketAddr,
+    /// Whether the connection is initiated by the remote peer.
+    pub inbound: bool,
+    pub trusted: bool,
+    pub reserved: bool,
+    pub score: Option<f64>,
+    pub protocols: Vec<ProtocolInfo>,
+}
+
+/// Local node information for admin RPC.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalNodeInfo {
+    pub node_id: NodeId,
+    /// Node url with format "cfxnode://ID@IP:PORT" that could be used by
+    /// other nodes to connect to this node.
+    pub url: String,
+    pub listen_address: SocketAddr,
+    pub public_endpoint: NodeEndpoint,
+    pub protocols: Vec<ProtocolInfo>,
+    pub reserved_peers_only: bool,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct IpFilter {
     pub predefined: AllowIP,
@@ -443,21 +533,56 @@ pub enum AllowIP {
     None,
 }
 
-pub fn parse_msg_id_leb128_2_bytes_at_most(msg: &mut &[u8]) -> u16 {
-    let buf = *msg;
+/// The message id to account packets whose id cannot be parsed, which is out
+/// of the range of the 2 bytes LEB128 encoded ids.
+pub const INVALID_MSG_ID: u16 = u16::MAX;
+
+/// Parse the message id encoded at the end of a message and strip it, or
+/// return `None` if the message is too short to contain it.
+pub fn parse_msg_id_leb128_2_bytes_at_most(msg: &mut &[u8]) -> Option<u16> {
+    let (&byte, rest) = msg.split_last()?;
+    let mut ret = byte as u16 & 0x7f;
+    let rest = if byte & 0x80 != 0 {
+        let (&byte, rest) = rest.split_last()?;
+        ret |= (byte as u16 & 0x7f) << 7;
+        rest
+    } else {
+        rest
+    };
+
+    *msg = rest;
+
+    Some(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_msg_id_leb128_2_bytes_at_most;
 
-    let mut ret = 0;
-    let mut pos = buf.len() - 1;
+    #[test]
+    fn test_parse_msg_id() {
+        let mut msg: &[u8] = &[0xc0, 0x05];
+        assert_eq!(parse_msg_id_leb128_2_bytes_at_most(&mut msg), Some(5));
+        assert_eq!(msg, &[0xc0]);
 
-    let byte = buf[pos] as u16;
-    ret |= byte & 0x7f;
-    if byte & 0x80 != 0 {
-        pos -= 1;
-        let byte = buf[pos] as u16;
-        ret |= (byte & 0x7f) << 7;
+        // 0x105 is encoded as 0x02, 0x85.
+        let mut msg: &[u8] = &[0xc0, 0x02, 0x85];
+        assert_eq!(parse_msg_id_leb128_2_bytes_at_most(&mut msg), Some(0x105));
+        assert_eq!(msg, &[0xc0]);
+
+        let mut msg: &[u8] = &[0x02, 0x85];
+        assert_eq!(parse_msg_id_leb128_2_bytes_at_most(&mut msg), Some(0x105));
+        assert!(msg.is_empty());
     }
 
-    *msg = &buf[..pos];
+    #[test]
+    fn test_parse_malformed_msg_id() {
+        let mut msg: &[u8] = &[];
+        assert_eq!(parse_msg_id_leb128_2_bytes_at_most(&mut msg), None);
 
-    ret
+        // The second byte is missing.
+        let mut msg: &[u8] = &[0x85];
+        assert_eq!(parse_msg_id_leb128_2_bytes_at_most(&mut msg), None);
+        assert_eq!(msg, &[0x85]);
+    }
 }
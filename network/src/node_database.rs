@@ -4,6 +4,7 @@
 
 use crate::{
     ip::{NodeIpLimit, NodeTagIndex, ValidateInsertResult},
+    node_score::PeerBehavior,
     node_table::{Node, NodeContact, NodeEntry, NodeId, NodeTable},
     IpFilter,
 };
@@ -43,6 +44,11 @@ const BLACKLISTED_NODES_FILE: &str = "blacklisted_nodes.json";
 /// - Failed to create TCP connection/session.
 /// - Failed to communicate due to invalid protocol message.
 ///
+/// ## report
+/// Protocol handlers report the behaviors of peers, e.g. invalid blocks,
+/// request timeout or valid responses, which are accumulated in the node
+/// score. Node score decays over time, and persisted along with the node.
+///
 /// # Promote/Demote
 ///
 /// ## Promote
@@ -206,6 +212,7 @@ impl NodeDatabase {
             if let Some(old_node) = self.promote_with_untrusted(&node.id, ip) {
                 node.last_connected = old_node.last_connected;
                 node.stream_token = old_node.stream_token;
+                node.score = old_node.score;
                 self.trusted_node_tag_index.add_node(&node);
                 self.trusted_nodes
                     .add_node(node, false /* preserve_last_contact */);
@@ -261,6 +268,7 @@ impl NodeDatabase {
                 node.last_contact = old_node.last_contact;
                 node.last_connected = old_node.last_connected;
                 node.stream_token = old_node.stream_token;
+                node.score = old_node.score;
                 self.trusted_node_tag_index.add_node(&node);
                 self.trusted_nodes
                     .add_node(node, false /* preserve_last_contact */);
@@ -316,6 +324,54 @@ impl NodeDatabase {
         }
     }
 
+    /// Update the score of specified node with the reported behavior, and
+    /// return the new score if the node exists.
+    pub fn report(
+        &mut self, id: &NodeId, behavior: PeerBehavior,
+    ) -> Option<f64> {
+        self.report_times(id, behavior, 1)
+    }
+
+    /// Report the same behavior of specified node `times` times at once.
+    pub fn report_times(
+        &mut self, id: &NodeId, behavior: PeerBehavior, times: u32,
+    ) -> Option<f64> {
+        let node = match self.trusted_nodes.get_mut(id) {
+            Some(node) => node,
+            None => self.untrusted_nodes.get_mut(id)?,
+        };
+
+        Some(node.score.report_times(behavior, times))
+    }
+
+    /// Get the current score of specified node. Returns `None` if the node
+    /// does not exist.
+    pub fn score(&self, id: &NodeId) -> Option<f64> {
+        self.get(id, false /* trusted_only */)
+            .or_else(|| self.blacklisted_nodes.get(id))
+            .map(|node| node.score.value())
+    }
+
+    /// Reset the score of specified node. If the node is blacklisted, it will
+    /// be removed from the blacklisted node table so that it is allowed to
+    /// connect again. Returns false if the node does not exist.
+    pub fn reset_score(&mut self, id: &NodeId) -> bool {
+        if self.blacklisted_nodes.remove_with_id(id).is_some() {
+            return true;
+        }
+
+        let node = match self.trusted_nodes.get_mut(id) {
+            Some(node) => node,
+            None => match self.untrusted_nodes.get_mut(id) {
+                Some(node) => node,
+                None => return false,
+            },
+        };
+
+        node.score.reset();
+        true
+    }
+
     /// Get node from trusted and/or untrusted node table for the specified id.
     pub fn get(&self, id: &NodeId, trusted_only: bool) -> Option<&Node> {
         self.trusted_nodes.get(id).or_else(|| {
@@ -566,6 +622,34 @@ impl NodeDatabase {
         );
     }
 
+    pub fn remove_tag(&mut self, id: NodeId, key: &str) {
+        let (trusted, node) =
+            if let Some(node) = self.trusted_nodes.get_mut(&id) {
+                (true, node)
+            } else if let Some(node) = self.untrusted_nodes.get_mut(&id) {
+                (false, node)
+            } else {
+                return;
+            };
+
+        let removed = match node.tags.remove(key) {
+            Some(removed) => removed,
+            None => return,
+        };
+
+        // do not update tag index for untrusted node
+        if !trusted || !self.ip_limit.is_enabled() {
+            return;
+        }
+
+        let subnet = self
+            .ip_limit
+            .subnet(&id)
+            .expect("node index should always exist");
+        self.trusted_node_tag_index
+            .remove(&id, subnet, &key.into(), &removed);
+    }
+
     /// Set the specified node to blacklisted.
     pub fn set_blacklisted(&mut self, id: &NodeId) {
         // update the last failure time
@@ -615,7 +699,10 @@ impl NodeDatabase {
 #[cfg(test)]
 mod tests {
     use super::NodeDatabase;
-    use crate::node_table::{NodeEndpoint, NodeEntry, NodeId};
+    use crate::{
+        node_score::PeerBehavior,
+        node_table::{NodeEndpoint, NodeEntry, NodeId},
+    };
     use std::{str::FromStr, time::Duration};
 
     fn new_entry(addr: &str) -> NodeEntry {
@@ -783,6 +870,35 @@ mod tests {
         assert_eq!(db.get(&n.id, false), None);
     }
 
+    #[test]
+    fn test_report_and_reset_score() {
+        let mut db = NodeDatabase::new(None, 2);
+
+        let n = new_entry("127.0.0.1:999");
+        assert_eq!(db.report(&n.id, PeerBehavior::InvalidBlock), None);
+        assert_eq!(db.score(&n.id), None);
+
+        db.insert_with_token(n.clone(), 5);
+        assert!(db.report(&n.id, PeerBehavior::InvalidBlock).unwrap() < 0.0);
+        assert!(db.score(&n.id).unwrap() < 0.0);
+
+        // score preserved when blacklisted
+        db.set_blacklisted(&n.id);
+        assert!(db.score(&n.id).unwrap() < 0.0);
+
+        // reset score of blacklisted node
+        assert!(db.reset_score(&n.id));
+        assert_eq!(db.evaluate_blacklisted(&n.id), false);
+        assert_eq!(db.score(&n.id), None);
+
+        // reset score of untrusted node
+        db.insert_with_token(n.clone(), 6);
+        db.report(&n.id, PeerBehavior::Spam);
+        assert!(db.reset_score(&n.id));
+        assert_eq!(db.score(&n.id), Some(0.0));
+        assert!(!db.reset_score(&NodeId::random()));
+    }
+
     #[test]
     fn test_blacklisted_lifetime() {
         let mut db = NodeDatabase::new(None, 2);
@@ -797,4 +913,28 @@ mod tests {
         assert_eq!(db.evaluate_blacklisted(&n.id), false);
         assert_eq!(db.get(&n.id, false), None);
     }
+
+    #[test]
+    fn test_remove_tag() {
+        let mut db = NodeDatabase::new(None, 2);
+        let key = "node_type".to_string();
+        let value = "archive".to_string();
+
+        let n = new_entry("127.0.0.1:999");
+        db.insert_trusted(n.clone());
+        db.set_tag(n.id, &key, &value);
+        assert!(db
+            .sample_trusted_node_ids_with_tag(1, &key, &value)
+            .contains(&n.id));
+
+        db.remove_tag(n.id, &key);
+        assert!(db.get(&n.id, true).unwrap().tags.is_empty());
+        assert!(db
+            .sample_trusted_node_ids_with_tag(1, &key, &value)
+            .is_empty());
+
+        // removing an absent tag is a no-op
+        db.remove_tag(n.id, &key);
+        db.remove_tag(NodeId::random(), &key);
+    }
 }
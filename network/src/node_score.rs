@@ -0,0 +1,180 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::time::{Duration, SystemTime};
+
+/// Maximum score that a node could accumulate with good behaviors.
+pub const MAX_NODE_SCORE: f64 = 100.0;
+/// Minimum score of a node, so that a banned node could recover in a bounded
+/// time after the ban expires.
+pub const MIN_NODE_SCORE: f64 = -1000.0;
+/// Default score below which a node is disconnected and blacklisted.
+pub const DEFAULT_BAN_SCORE_THRESHOLD: f64 = -500.0;
+/// Score moves halfway back to 0 in this duration.
+const SCORE_HALF_LIFE: Duration = Duration::from_secs(3600);
+
+/// Peer behaviors reported by protocol handlers to update the node score.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerBehavior {
+    /// Responded a request in time.
+    ValidResponse,
+    /// Request to peer timeout.
+    RequestTimeout,
+    /// Sent a malformed, unexpected or unsolicited message.
+    InvalidMessage,
+    /// Sent an invalid block or block header.
+    InvalidBlock,
+    /// Sent data that failed to verify against its proof, e.g. state proof,
+    /// snapshot chunk or PoS ledger info.
+    InvalidProof,
+    /// Sent messages too frequently, e.g. ignored the throttling.
+    Spam,
+}
+
+impl PeerBehavior {
+    pub fn weight(&self) -> f64 {
+        match self {
+            PeerBehavior::ValidResponse => 1.0,
+            PeerBehavior::RequestTimeout => -10.0,
+            PeerBehavior::InvalidMessage => -50.0,
+            PeerBehavior::InvalidBlock => -100.0,
+            PeerBehavior::InvalidProof => -100.0,
+            PeerBehavior::Spam => -200.0,
+        }
+    }
+}
+
+/// Node score that accumulates the weights of reported behaviors, and decays
+/// exponentially towards 0 over time.
+///
+/// It is serialized as the current score value for RPC.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeScore {
+    /// Score value at the time of `updated`.
+    value: f64,
+    updated: SystemTime,
+}
+
+impl Default for NodeScore {
+    fn default() -> Self {
+        NodeScore {
+            value: 0.0,
+            updated: SystemTime::now(),
+        }
+    }
+}
+
+impl NodeScore {
+    pub fn new(value: f64, updated: SystemTime) -> Self {
+        NodeScore { value, updated }
+    }
+
+    /// Score value decayed to now.
+    pub fn value(&self) -> f64 { self.value_at(SystemTime::now()) }
+
+    fn value_at(&self, time: SystemTime) -> f64 {
+        let elapsed = time
+            .duration_since(self.updated)
+            .unwrap_or_else(|_| Duration::from_secs(0));
+        let half_lives = elapsed.as_secs_f64() / SCORE_HALF_LIFE.as_secs_f64();
+        self.value * 0.5f64.powf(half_lives)
+    }
+
+    /// Apply the weight of specified behavior, and return the new score.
+    pub fn report(&mut self, behavior: PeerBehavior) -> f64 {
+        self.report_times(behavior, 1)
+    }
+
+    /// Apply the weight of specified behavior `times` times at once, and
+    /// return the new score.
+    pub fn report_times(&mut self, behavior: PeerBehavior, times: u32) -> f64 {
+        let now = SystemTime::now();
+        self.value = apply_behavior(self.value_at(now), behavior, times);
+        self.updated = now;
+        self.value
+    }
+
+    pub fn reset(&mut self) { *self = NodeScore::default(); }
+}
+
+impl Serialize for NodeScore {
+    fn serialize<S: Serializer>(
+        &self, serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.value())
+    }
+}
+
+impl<'de> Deserialize<'de> for NodeScore {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let value = f64::deserialize(deserializer)?;
+        Ok(NodeScore::new(value, SystemTime::now()))
+    }
+}
+
+/// Apply the weight of specified behavior `times` times to a score without
+/// decay, and clamp the result into [MIN_NODE_SCORE, MAX_NODE_SCORE].
+pub fn apply_behavior(score: f64, behavior: PeerBehavior, times: u32) -> f64 {
+    (score + behavior.weight() * times as f64)
+        .max(MIN_NODE_SCORE)
+        .min(MAX_NODE_SCORE)
+}
+
+/// Weight to randomly select a peer with the specified score, so that peers
+/// with higher score are more likely to be selected while peers with
+/// negative score still have a chance.
+pub fn selection_weight(score: f64) -> f64 { (score + MAX_NODE_SCORE).max(1.0) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let mut score = NodeScore::default();
+        assert_eq!(score.report(PeerBehavior::ValidResponse), 1.0);
+        assert!(score.report(PeerBehavior::InvalidBlock) < -98.0);
+
+        for _ in 0..10 {
+            score.report(PeerBehavior::Spam);
+        }
+        assert_eq!(score.value, MIN_NODE_SCORE);
+
+        for _ in 0..1000 {
+            score.report(PeerBehavior::ValidResponse);
+        }
+        assert!(score.value <= MAX_NODE_SCORE);
+
+        score.reset();
+        assert_eq!(score.value, 0.0);
+    }
+
+    #[test]
+    fn test_report_times() {
+        let mut score = NodeScore::default();
+        assert!(score.report_times(PeerBehavior::ValidResponse, 16) > 15.99);
+        score.report_times(PeerBehavior::Spam, 10);
+        assert_eq!(score.value, MIN_NODE_SCORE);
+    }
+
+    #[test]
+    fn test_decay() {
+        let now = SystemTime::now();
+        let score = NodeScore::new(-400.0, now);
+        assert_eq!(score.value_at(now), -400.0);
+        assert_eq!(score.value_at(now + SCORE_HALF_LIFE), -200.0);
+        assert_eq!(score.value_at(now + 2 * SCORE_HALF_LIFE), -100.0);
+        // clock moves backward
+        assert_eq!(score.value_at(now - SCORE_HALF_LIFE), -400.0);
+    }
+
+    #[test]
+    fn test_selection_weight() {
+        assert!(selection_weight(10.0) > selection_weight(0.0));
+        assert_eq!(selection_weight(MIN_NODE_SCORE), 1.0);
+    }
+}
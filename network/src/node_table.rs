@@ -2,7 +2,9 @@
 // Conflux is free software and distributed under GNU General Public License.
 // See http://www.gnu.org/licenses/
 
-use crate::{ip_utils::*, AllowIP, Error, ErrorKind, IpFilter};
+use crate::{
+    ip_utils::*, node_score::NodeScore, AllowIP, Error, ErrorKind, IpFilter,
+};
 use cfx_types::H512;
 use enum_map::EnumMap;
 use io::*;
@@ -271,6 +273,10 @@ pub struct Node {
     //     - good network nodes first
     // 2. Refuse incoming connection from node with special tags.
     pub tags: HashMap<String, String>,
+    // Reputation score reported by protocol handlers, which is used to
+    // prefer peers with good behaviors and to ban misbehaving peers.
+    #[serde(default)]
+    pub score: NodeScore,
 }
 
 impl Node {
@@ -282,6 +288,7 @@ impl Node {
             last_connected: None,
             stream_token: None,
             tags: Default::default(),
+            score: Default::default(),
         }
     }
 }
@@ -331,6 +338,7 @@ impl FromStr for Node {
             last_connected: None,
             stream_token: None,
             tags: Default::default(),
+            score: Default::default(),
         })
     }
 }
@@ -542,6 +550,9 @@ impl NodeTable {
             return;
         }
 
+        // node score is always preserved for existing node
+        node.score = self.node_reputation_table[_index.0][_index.1].score;
+
         if preserve_last_contact {
             let node_vec = &mut self.node_reputation_table[_index.0];
             node.last_contact = node_vec[_index.1].last_contact;
@@ -923,6 +934,14 @@ mod json {
         pub url: String,
         pub last_contact: Option<NodeContact>,
         pub tags: HashMap<String, String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub score: Option<NodeScore>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct NodeScore {
+        pub value: f64,
+        pub updated: u64,
     }
 
     impl Node {
@@ -932,6 +951,12 @@ mod json {
                     node.last_contact =
                         self.last_contact.map(NodeContact::into_node_contact);
                     node.tags = self.tags;
+                    if let Some(score) = self.score {
+                        let updated = time::UNIX_EPOCH
+                            + Duration::from_secs(score.updated);
+                        node.score =
+                            super::NodeScore::new(score.value, updated);
+                    }
                     Some(node)
                 }
                 _ => None,
@@ -956,10 +981,23 @@ mod json {
                     .map(|d| NodeContact::Demoted(d.as_secs())),
             });
 
+            // persist the decayed score, which is updated at now
+            let score = match node.score.value() {
+                value if value == 0.0 => None,
+                value => SystemTime::now()
+                    .duration_since(time::UNIX_EPOCH)
+                    .ok()
+                    .map(|d| NodeScore {
+                        value,
+                        updated: d.as_secs(),
+                    }),
+            };
+
             Node {
                 url: format!("{}", node),
                 last_contact,
                 tags: node.tags.clone(),
+                score,
             }
         }
     }
@@ -0,0 +1,366 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Static peers loaded from a file that is reloaded on change.
+//!
+//! Like the `static-nodes.json` of Ethereum clients, the peers file is a JSON
+//! array of node URLs, e.g.
+//!
+//! ```json
+//! [
+//!   "cfxnode://ID1@10.0.0.1:32323",
+//!   "cfxnode://ID2@10.0.0.2:32323?node_type=archive&region=us"
+//! ]
+//! ```
+//!
+//! where the optional query string attaches tags to the node, which are
+//! indexed in the trusted node table for sampling, e.g. `node_type=archive`.
+//!
+//! The file is checked periodically, and when its modification time changes,
+//! the listed peers are reconciled with the ones loaded before: new peers are
+//! added as trusted nodes, peers no longer listed are removed and
+//! disconnected, and peers whose endpoint changed are re-added. Peers whose
+//! tags changed only are updated in place, so that they stay connected.
+//!
+//! The loaded peers are saved in the node config directory, and used as the
+//! last loaded peers at startup, so that the peers removed from the file
+//! while the node was down are removed as well.
+
+use crate::node_table::{Node, NodeEndpoint, NodeId};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::SystemTime,
+};
+
+pub const DEFAULT_PEERS_FILE_CHECK_INTERVAL_MS: u64 = 10_000;
+
+const LOADED_PEERS_FILE: &str = "loaded_peers.json";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaticPeer {
+    pub id: NodeId,
+    pub endpoint: NodeEndpoint,
+    pub tags: BTreeMap<String, String>,
+}
+
+impl FromStr for StaticPeer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (url, query) = match s.find('?') {
+            Some(index) => (&s[..index], Some(&s[index + 1..])),
+            None => (s, None),
+        };
+        let node = Node::from_str(url)
+            .map_err(|e| format!("invalid node url {}: {:?}", url, e))?;
+        if node.id == NodeId::default() {
+            return Err(format!("node id is required in {}", url));
+        }
+
+        let mut tags = BTreeMap::new();
+        for pair in query.into_iter().flat_map(|q| q.split('&')) {
+            if pair.is_empty() {
+                continue;
+            }
+            match pair.find('=') {
+                Some(index) if index > 0 => {
+                    tags.insert(
+                        pair[..index].to_string(),
+                        pair[index + 1..].to_string(),
+                    );
+                }
+                _ => return Err(format!("invalid tag {} in {}", pair, s)),
+            }
+        }
+
+        Ok(StaticPeer {
+            id: node.id,
+            endpoint: node.endpoint,
+            tags,
+        })
+    }
+}
+
+impl fmt::Display for StaticPeer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cfxnode://{:x}@{}", self.id, self.endpoint.address)?;
+        for (index, (key, value)) in self.tags.iter().enumerate() {
+            let delimiter = if index == 0 { '?' } else { '&' };
+            write!(f, "{}{}={}", delimiter, key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// A peer listed at the same endpoint with different tags.
+#[derive(Debug)]
+pub struct RetaggedPeer {
+    pub peer: StaticPeer,
+    /// The keys of the tags no longer listed.
+    pub removed_tags: Vec<String>,
+}
+
+/// Changes of the peers file since the last reload. A peer with a changed
+/// endpoint is both removed and added, so the removals should be applied
+/// first.
+#[derive(Debug, Default)]
+pub struct PeersFileChanges {
+    pub added: Vec<StaticPeer>,
+    pub removed: Vec<NodeId>,
+    pub retagged: Vec<RetaggedPeer>,
+}
+
+impl PeersFileChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.retagged.is_empty()
+    }
+}
+
+pub struct PeersFile {
+    path: PathBuf,
+    /// Where the loaded peers are saved, if any.
+    loaded_path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    peers: HashMap<NodeId, StaticPeer>,
+}
+
+impl PeersFile {
+    /// Create a peers file with the peers saved in `config_path` as the last
+    /// loaded ones.
+    pub fn new(path: PathBuf, config_path: Option<&str>) -> Self {
+        let loaded_path =
+            config_path.map(|dir| Path::new(dir).join(LOADED_PEERS_FILE));
+        let peers = match loaded_path {
+            Some(ref loaded_path) if loaded_path.exists() => load(loaded_path)
+                .unwrap_or_else(|e| {
+                    warn!("Failed to load the last loaded peers: {}", e);
+                    Vec::new()
+                }),
+            _ => Vec::new(),
+        };
+
+        PeersFile {
+            path,
+            loaded_path,
+            modified: None,
+            peers: peers.into_iter().map(|peer| (peer.id, peer)).collect(),
+        }
+    }
+
+    /// Reload the peers file if it is modified since the last reload. The
+    /// loaded peers are kept if the file could not be read or parsed.
+    pub fn reload(&mut self) -> Result<PeersFileChanges, String> {
+        let modified = fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|e| format!("failed to read peers file: {:?}", e))?;
+        if self.modified == Some(modified) {
+            return Ok(PeersFileChanges::default());
+        }
+
+        let peers = load(&self.path)?;
+        self.modified = Some(modified);
+        let changes = self.update(peers);
+        if !changes.is_empty() {
+            self.save();
+        }
+        Ok(changes)
+    }
+
+    fn update(&mut self, peers: Vec<StaticPeer>) -> PeersFileChanges {
+        let peers: HashMap<_, _> =
+            peers.into_iter().map(|peer| (peer.id, peer)).collect();
+        let mut changes = PeersFileChanges::default();
+        for (id, old) in &self.peers {
+            match peers.get(id) {
+                Some(new) if new.endpoint != old.endpoint => {
+                    changes.removed.push(*id)
+                }
+                Some(new) if new.tags != old.tags => {
+                    changes.retagged.push(RetaggedPeer {
+                        peer: new.clone(),
+                        removed_tags: old
+                            .tags
+                            .keys()
+                            .filter(|key| !new.tags.contains_key(*key))
+                            .cloned()
+                            .collect(),
+                    })
+                }
+                Some(_) => {}
+                None => changes.removed.push(*id),
+            }
+        }
+        for (id, new) in &peers {
+            match self.peers.get(id) {
+                Some(old) if old.endpoint == new.endpoint => {}
+                _ => changes.added.push(new.clone()),
+            }
+        }
+        self.peers = peers;
+        changes
+    }
+
+    /// Save the loaded peers, so that the peers removed from the peers file
+    /// while the node is down are removed at the next start.
+    fn save(&self) {
+        let path = match self.loaded_path {
+            Some(ref path) => path,
+            None => return,
+        };
+
+        if let Some(dir) = path.parent() {
+            if let Err(e) = fs::create_dir_all(dir) {
+                warn!("Error creating loaded peers directory: {:?}", e);
+                return;
+            }
+        }
+
+        let mut urls: Vec<String> =
+            self.peers.values().map(ToString::to_string).collect();
+        urls.sort();
+        let content = serde_json::to_string_pretty(&urls)
+            .expect("peer urls should be serializable");
+        if let Err(e) = fs::write(path, content) {
+            warn!("Error writing loaded peers file: {:?}", e);
+        }
+    }
+}
+
+fn load(path: &Path) -> Result<Vec<StaticPeer>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read peers file: {:?}", e))?;
+    let urls: Vec<String> = serde_json::from_str(&content)
+        .map_err(|e| format!("failed to parse peers file: {:?}", e))?;
+    urls.iter().map(|url| StaticPeer::from_str(url)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    fn url(id: &NodeId, address: &str, query: &str) -> String {
+        format!("cfxnode://{:x}@{}{}", id, address, query)
+    }
+
+    #[test]
+    fn test_parse() {
+        let id = NodeId::random();
+        let peer = StaticPeer::from_str(&url(
+            &id,
+            "127.0.0.1:32323",
+            "?node_type=archive&region=us",
+        ))
+        .unwrap();
+        assert_eq!(peer.id, id);
+        assert_eq!(peer.endpoint.address.port(), 32323);
+        assert_eq!(peer.tags["node_type"], "archive");
+        assert_eq!(peer.tags["region"], "us");
+
+        assert!(StaticPeer::from_str("127.0.0.1:32323").is_err());
+        assert!(
+            StaticPeer::from_str(&url(&id, "127.0.0.1:32323", "?archive"))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_reload() {
+        let dir = TempDir::new("peers_file").unwrap();
+        let path = dir.path().join("peers.json");
+        let id1 = NodeId::random();
+        let id2 = NodeId::random();
+        let id3 = NodeId::random();
+
+        fs::write(
+            &path,
+            serde_json::to_string(&vec![
+                url(&id1, "127.0.0.1:1", ""),
+                url(&id2, "127.0.0.1:2", ""),
+            ])
+            .unwrap(),
+        )
+        .unwrap();
+        let mut peers_file = PeersFile::new(path.clone(), None);
+        let changes = peers_file.reload().unwrap();
+        assert_eq!(changes.added.len(), 2);
+        assert!(changes.removed.is_empty());
+        // not modified
+        assert!(peers_file.reload().unwrap().is_empty());
+
+        // id1 removed, id2 retagged and id3 added
+        let changes = peers_file.update(vec![
+            StaticPeer::from_str(&url(&id2, "127.0.0.1:2", "?region=eu"))
+                .unwrap(),
+            StaticPeer::from_str(&url(&id3, "127.0.0.1:3", "")).unwrap(),
+        ]);
+        assert_eq!(changes.removed, vec![id1]);
+        assert_eq!(changes.added.len(), 1);
+        assert_eq!(changes.added[0].id, id3);
+        assert_eq!(changes.retagged.len(), 1);
+        assert_eq!(changes.retagged[0].peer.id, id2);
+        assert_eq!(changes.retagged[0].peer.tags["region"], "eu");
+        assert!(changes.retagged[0].removed_tags.is_empty());
+
+        // id2 untagged and id3 moved
+        let changes = peers_file.update(vec![
+            StaticPeer::from_str(&url(&id2, "127.0.0.1:2", "")).unwrap(),
+            StaticPeer::from_str(&url(&id3, "127.0.0.1:4", "")).unwrap(),
+        ]);
+        assert_eq!(changes.removed, vec![id3]);
+        assert_eq!(changes.added.len(), 1);
+        assert_eq!(changes.added[0].id, id3);
+        assert_eq!(changes.retagged.len(), 1);
+        assert_eq!(changes.retagged[0].removed_tags, vec!["region"]);
+
+        // invalid file keeps the loaded peers
+        fs::write(&path, "[\"invalid\"]").unwrap();
+        peers_file.modified = None;
+        assert!(peers_file.reload().is_err());
+        assert_eq!(peers_file.peers.len(), 2);
+    }
+
+    #[test]
+    fn test_loaded_peers() {
+        let dir = TempDir::new("peers_file").unwrap();
+        let path = dir.path().join("peers.json");
+        let config_path = dir.path().join("net_config");
+        let config_path = config_path.to_str();
+        let id1 = NodeId::random();
+        let id2 = NodeId::random();
+
+        let peer1 = url(&id1, "127.0.0.1:1", "?node_type=archive&region=us");
+        let peer2 = url(&id2, "127.0.0.1:2", "");
+        fs::write(&path, serde_json::to_string(&vec![&peer1, &peer2]).unwrap())
+            .unwrap();
+        let mut peers_file = PeersFile::new(path.clone(), config_path);
+        assert_eq!(peers_file.reload().unwrap().added.len(), 2);
+        assert_eq!(
+            StaticPeer::from_str(&peers_file.peers[&id1].to_string()).unwrap(),
+            peers_file.peers[&id1]
+        );
+
+        // unchanged peers are not reloaded after restart
+        let mut peers_file = PeersFile::new(path.clone(), config_path);
+        assert_eq!(peers_file.peers.len(), 2);
+        assert!(peers_file.reload().unwrap().is_empty());
+
+        // id2 removed while the node is down
+        fs::write(&path, serde_json::to_string(&vec![&peer1]).unwrap())
+            .unwrap();
+        let mut peers_file = PeersFile::new(path.clone(), config_path);
+        let changes = peers_file.reload().unwrap();
+        assert_eq!(changes.removed, vec![id2]);
+        assert!(changes.added.is_empty());
+        assert!(changes.retagged.is_empty());
+
+        let peers_file = PeersFile::new(path, config_path);
+        assert_eq!(peers_file.peers.len(), 1);
+    }
+}
@@ -0,0 +1,474 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! Offline replay of captured protocol messages.
+//!
+//! `Replayer` is a fake `NetworkContext` that feeds the captured peer
+//! connection events and inbound messages of a protocol into its handler,
+//! e.g. the synchronization, light or PoS protocol handler, in the captured
+//! order. Dispatched works are processed right after each event, so the
+//! replay is deterministic. Timers are driven by the capture timestamps:
+//! before an event is replayed, the timers due by its timestamp are fired in
+//! order. They could also be triggered with `Replayer::timeout`.
+//!
+//! Messages sent by the handler are collected instead of being sent, so they
+//! could be compared with the captured outbound messages. Note that peers
+//! disconnected by the handler are only recorded, and the handler is notified
+//! when the captured disconnection event is replayed.
+//!
+//! `NetworkService::replay_capture` replays a capture directory into all the
+//! handlers registered by a node, which is enabled by `p2p_replay_dir`.
+
+use crate::{
+    capture::{CaptureKind, CapturedMessage, PeerConnected},
+    node_score::{apply_behavior, PeerBehavior},
+    node_table::NodeId,
+    service::ProtocolVersion,
+    Error, HandlerWorkType, NetworkContext, NetworkProtocolHandler, ProtocolId,
+    UpdateNodeOperation,
+};
+use io::TimerToken;
+use parking_lot::{Mutex, RwLock};
+use priority_send_queue::SendQueuePriority;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+
+struct ReplayPeer {
+    originated: bool,
+    score: f64,
+}
+
+pub struct Replayer {
+    handler: Arc<dyn NetworkProtocolHandler + Sync>,
+    protocol: ProtocolId,
+    self_node_id: NodeId,
+    peers: RwLock<HashMap<NodeId, ReplayPeer>>,
+    works: Mutex<VecDeque<HandlerWorkType>>,
+    timers: Mutex<HashMap<TimerToken, Duration>>,
+    /// Next firing time of the timers in milliseconds since UNIX epoch.
+    deadlines: Mutex<HashMap<TimerToken, u64>>,
+    /// Timestamp of the latest replayed event.
+    clock_ms: Mutex<Option<u64>>,
+    sent: Mutex<Vec<(NodeId, Vec<u8>)>>,
+    disconnected: Mutex<Vec<(NodeId, String)>>,
+}
+
+impl Replayer {
+    pub fn new(
+        handler: Arc<dyn NetworkProtocolHandler + Sync>, protocol: ProtocolId,
+        self_node_id: NodeId,
+    ) -> Self
+    {
+        Replayer {
+            handler,
+            protocol,
+            self_node_id,
+            peers: Default::default(),
+            works: Default::default(),
+            timers: Default::default(),
+            deadlines: Default::default(),
+            clock_ms: Default::default(),
+            sent: Default::default(),
+            disconnected: Default::default(),
+        }
+    }
+
+    /// Initialize the protocol handler, which should be called before
+    /// replaying any message.
+    pub fn initialize(&self) {
+        self.handler.initialize(self);
+        self.process_works();
+    }
+
+    /// Replay a captured event. Events of other protocols and outbound
+    /// messages are ignored, except that they advance the replay clock.
+    pub fn replay(&self, msg: &CapturedMessage) -> Result<(), Error> {
+        self.advance_to(msg.timestamp_ms);
+        if msg.protocol != self.protocol {
+            return Ok(());
+        }
+
+        match msg.kind {
+            CaptureKind::PeerConnected => {
+                let connected: PeerConnected = rlp::decode(&msg.data)?;
+                self.peers.write().insert(
+                    msg.node_id,
+                    ReplayPeer {
+                        originated: connected.originated,
+                        score: 0.0,
+                    },
+                );
+                self.handler.on_peer_connected(
+                    self,
+                    &msg.node_id,
+                    connected.version,
+                    connected.pos_public_key,
+                );
+            }
+            CaptureKind::PeerDisconnected => {
+                self.peers.write().remove(&msg.node_id);
+                self.handler.on_peer_disconnected(self, &msg.node_id);
+            }
+            CaptureKind::Inbound => {
+                if !self.peers.read().contains_key(&msg.node_id) {
+                    debug!(
+                        "Replay message from unknown peer {:?}",
+                        msg.node_id
+                    );
+                }
+                self.handler.on_message(self, &msg.node_id, &msg.data);
+            }
+            CaptureKind::Outbound => {}
+        }
+
+        self.process_works();
+        Ok(())
+    }
+
+    /// Replay all the captured events in order.
+    pub fn replay_all<'a, I>(&self, messages: I) -> Result<usize, Error>
+    where I: IntoIterator<Item = &'a CapturedMessage> {
+        let mut count = 0;
+        for msg in messages {
+            self.replay(msg)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Fire a timer registered by the protocol handler.
+    pub fn timeout(&self, timer: TimerToken) {
+        self.handler.on_timeout(self, timer);
+        self.process_works();
+    }
+
+    /// Add a timer that the protocol handler registered elsewhere, e.g. with
+    /// the live network context when it was initialized. It is first due
+    /// `delay` after the current replay clock.
+    pub fn add_timer(&self, token: TimerToken, delay: Duration) {
+        self.timers.lock().insert(token, delay);
+        self.deadlines.lock().remove(&token);
+    }
+
+    /// Advance the replay clock to `timestamp_ms`, and fire the timers due by
+    /// then in the order of their deadlines. The clock starts at the first
+    /// timestamp, and never goes backwards.
+    pub fn advance_to(&self, timestamp_ms: u64) {
+        if *self.clock_ms.lock().get_or_insert(timestamp_ms) > timestamp_ms {
+            return;
+        }
+        loop {
+            let due = {
+                let now = self.clock_ms.lock().unwrap_or(timestamp_ms);
+                let timers = self.timers.lock();
+                let mut deadlines = self.deadlines.lock();
+                for (token, delay) in timers.iter() {
+                    deadlines
+                        .entry(*token)
+                        .or_insert(now + delay.as_millis() as u64);
+                }
+                deadlines
+                    .iter()
+                    .filter(|(_, deadline)| **deadline <= timestamp_ms)
+                    .map(|(token, deadline)| (*deadline, *token))
+                    .min()
+            };
+            let (deadline, token) = match due {
+                Some(due) => due,
+                None => break,
+            };
+            // A periodic timer is due again `delay` later, and a zero delay
+            // is treated as one millisecond to make progress.
+            let delay = self.timers.lock()[&token].as_millis() as u64;
+            self.deadlines.lock().insert(token, deadline + delay.max(1));
+            *self.clock_ms.lock() = Some(deadline);
+            self.timeout(token);
+        }
+        *self.clock_ms.lock() = Some(timestamp_ms);
+    }
+
+    /// Timers registered by the protocol handler so far.
+    pub fn registered_timers(&self) -> HashMap<TimerToken, Duration> {
+        self.timers.lock().clone()
+    }
+
+    /// Take the messages sent by the protocol handler so far.
+    pub fn take_sent_messages(&self) -> Vec<(NodeId, Vec<u8>)> {
+        std::mem::take(&mut *self.sent.lock())
+    }
+
+    /// Peers disconnected by the protocol handler with reasons.
+    pub fn disconnected_peers(&self) -> Vec<(NodeId, String)> {
+        self.disconnected.lock().clone()
+    }
+
+    fn process_works(&self) {
+        loop {
+            let work_type = self.works.lock().pop_front();
+            match work_type {
+                Some(work_type) => {
+                    self.handler.on_work_dispatch(self, work_type)
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+impl NetworkContext for Replayer {
+    fn get_protocol(&self) -> ProtocolId { self.protocol }
+
+    fn get_peer_connection_origin(&self, node_id: &NodeId) -> Option<bool> {
+        self.peers.read().get(node_id).map(|p| p.originated)
+    }
+
+    fn send(
+        &self, node_id: &NodeId, msg: Vec<u8>,
+        _min_protocol_version: ProtocolVersion,
+        _version_valid_till: ProtocolVersion, _priority: SendQueuePriority,
+    ) -> Result<(), Error>
+    {
+        if *node_id == self.self_node_id {
+            self.handler.send_local_message(self, msg);
+        } else {
+            self.sent.lock().push((*node_id, msg));
+        }
+        Ok(())
+    }
+
+    fn disconnect_peer(
+        &self, node_id: &NodeId, _op: Option<UpdateNodeOperation>,
+        reason: &str,
+    )
+    {
+        self.disconnected.lock().push((*node_id, reason.into()));
+    }
+
+    fn register_timer(
+        &self, token: TimerToken, delay: Duration,
+    ) -> Result<(), Error> {
+        self.add_timer(token, delay);
+        Ok(())
+    }
+
+    fn dispatch_work(&self, work_type: HandlerWorkType) {
+        self.works.lock().push_back(work_type);
+    }
+
+    fn insert_peer_node_tag(&self, _peer: NodeId, _key: &str, _value: &str) {}
+
+    fn report_peer(
+        &self, peer: &NodeId, behavior: PeerBehavior,
+    ) -> Option<f64> {
+        // Scores do not decay during replay to be deterministic.
+        let mut peers = self.peers.write();
+        let peer = peers.get_mut(peer)?;
+        peer.score = apply_behavior(peer.score, behavior, 1);
+        Some(peer.score)
+    }
+
+    fn get_peer_score(&self, peer: &NodeId) -> f64 {
+        self.peers.read().get(peer).map_or(0.0, |p| p.score)
+    }
+
+    fn is_peer_self(&self, node_id: &NodeId) -> bool {
+        *node_id == self.self_node_id
+    }
+
+    fn self_node_id(&self) -> NodeId { self.self_node_id }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diem_types::validator_config::{
+        ConsensusPublicKey, ConsensusVRFPublicKey,
+    };
+
+    /// Handler that echoes messages back to peers via dispatched works.
+    #[derive(Default)]
+    struct EchoHandler {
+        peers: Mutex<Vec<NodeId>>,
+        pending: Mutex<VecDeque<(NodeId, Vec<u8>)>>,
+    }
+
+    impl NetworkProtocolHandler for EchoHandler {
+        fn minimum_supported_version(&self) -> ProtocolVersion {
+            ProtocolVersion(1)
+        }
+
+        fn initialize(&self, io: &dyn NetworkContext) {
+            io.register_timer(7, Duration::from_secs(1)).unwrap();
+        }
+
+        fn on_message(
+            &self, io: &dyn NetworkContext, node_id: &NodeId, data: &[u8],
+        ) {
+            if data.is_empty() {
+                io.report_peer(node_id, PeerBehavior::InvalidMessage);
+                io.disconnect_peer(node_id, None, "empty message");
+                return;
+            }
+            self.pending.lock().push_back((*node_id, data.to_vec()));
+            io.dispatch_work(0);
+        }
+
+        fn on_peer_connected(
+            &self, _io: &dyn NetworkContext, node_id: &NodeId,
+            _peer_protocol_version: ProtocolVersion,
+            _pos_public_key: Option<(
+                ConsensusPublicKey,
+                ConsensusVRFPublicKey,
+            )>,
+        )
+        {
+            self.peers.lock().push(*node_id);
+        }
+
+        fn on_peer_disconnected(
+            &self, _io: &dyn NetworkContext, node_id: &NodeId,
+        ) {
+            self.peers.lock().retain(|id| id != node_id);
+        }
+
+        fn on_timeout(&self, io: &dyn NetworkContext, _timer: TimerToken) {
+            for peer in self.peers.lock().iter() {
+                io.send(
+                    peer,
+                    vec![0xff],
+                    ProtocolVersion(1),
+                    ProtocolVersion(1),
+                    SendQueuePriority::High,
+                )
+                .unwrap();
+            }
+        }
+
+        fn send_local_message(&self, _io: &dyn NetworkContext, _msg: Vec<u8>) {}
+
+        fn on_work_dispatch(
+            &self, io: &dyn NetworkContext, _work_type: HandlerWorkType,
+        ) {
+            while let Some((peer, data)) = self.pending.lock().pop_front() {
+                io.send(
+                    &peer,
+                    data,
+                    ProtocolVersion(1),
+                    ProtocolVersion(1),
+                    SendQueuePriority::High,
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    fn captured(
+        kind: CaptureKind, protocol: ProtocolId, node_id: NodeId,
+        data: Vec<u8>,
+    ) -> CapturedMessage
+    {
+        CapturedMessage {
+            kind,
+            protocol,
+            node_id,
+            timestamp_ms: 0,
+            data,
+        }
+    }
+
+    #[test]
+    fn test_replay() {
+        let handler = Arc::new(EchoHandler::default());
+        let replayer =
+            Replayer::new(handler.clone(), *b"cfx", NodeId::random());
+        replayer.initialize();
+        assert!(replayer.registered_timers().contains_key(&7));
+
+        let peer = NodeId::random();
+        let connected = rlp::encode(&PeerConnected {
+            version: ProtocolVersion(2),
+            pos_public_key: None,
+            originated: true,
+        });
+        let messages = vec![
+            captured(CaptureKind::PeerConnected, *b"cfx", peer, connected),
+            captured(CaptureKind::Inbound, *b"cfx", peer, vec![1, 2]),
+            // ignored
+            captured(CaptureKind::Outbound, *b"cfx", peer, vec![1, 2]),
+            captured(CaptureKind::Inbound, *b"pos", peer, vec![3]),
+            captured(CaptureKind::Inbound, *b"cfx", peer, vec![]),
+        ];
+        assert_eq!(replayer.replay_all(&messages).unwrap(), messages.len());
+
+        assert_eq!(replayer.get_peer_connection_origin(&peer), Some(true));
+        assert_eq!(replayer.take_sent_messages(), vec![(peer, vec![1, 2])]);
+        assert!(replayer.take_sent_messages().is_empty());
+        assert_eq!(
+            replayer.get_peer_score(&peer),
+            PeerBehavior::InvalidMessage.weight()
+        );
+        assert_eq!(
+            replayer.disconnected_peers(),
+            vec![(peer, "empty message".to_string())]
+        );
+
+        replayer.timeout(7);
+        assert_eq!(replayer.take_sent_messages(), vec![(peer, vec![0xff])]);
+
+        replayer
+            .replay(&captured(
+                CaptureKind::PeerDisconnected,
+                *b"cfx",
+                peer,
+                vec![],
+            ))
+            .unwrap();
+        assert!(handler.peers.lock().is_empty());
+        assert_eq!(replayer.get_peer_connection_origin(&peer), None);
+    }
+
+    #[test]
+    fn test_replay_timers() {
+        let handler = Arc::new(EchoHandler::default());
+        let replayer =
+            Replayer::new(handler.clone(), *b"cfx", NodeId::random());
+        replayer.initialize();
+
+        let peer = NodeId::random();
+        let connected = rlp::encode(&PeerConnected {
+            version: ProtocolVersion(2),
+            pos_public_key: None,
+            originated: true,
+        });
+        let at = |timestamp_ms: u64, mut msg: CapturedMessage| {
+            msg.timestamp_ms = timestamp_ms;
+            msg
+        };
+        let messages = vec![
+            at(
+                1000,
+                captured(CaptureKind::PeerConnected, *b"cfx", peer, connected),
+            ),
+            at(1500, captured(CaptureKind::Inbound, *b"cfx", peer, vec![1])),
+            // events of other protocols advance the clock as well
+            at(2500, captured(CaptureKind::Inbound, *b"pos", peer, vec![2])),
+            at(3500, captured(CaptureKind::Inbound, *b"cfx", peer, vec![3])),
+        ];
+        replayer.replay_all(&messages).unwrap();
+
+        // The timer of 1s is due at 2000 and 3000.
+        assert_eq!(
+            replayer.take_sent_messages(),
+            vec![
+                (peer, vec![1]),
+                (peer, vec![0xff]),
+                (peer, vec![0xff]),
+                (peer, vec![3]),
+            ]
+        );
+    }
+}
@@ -11,7 +11,10 @@ use std::{
     net::{Ipv4Addr, SocketAddr, SocketAddrV4},
     path::{Path, PathBuf},
     str::FromStr,
-    sync::{atomic::Ordering as AtomicOrdering, Arc},
+    sync::{
+        atomic::{AtomicBool, Ordering as AtomicOrdering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 
@@ -36,19 +39,27 @@ use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use priority_send_queue::SendQueuePriority;
 
 use crate::{
+    capture::{
+        capture_files, CaptureKind, CaptureReader, MessageRecorder,
+        PeerConnected,
+    },
     discovery::Discovery,
     handshake::BYPASS_CRYPTOGRAPHY,
     io::*,
     ip_utils::{map_external_address, select_public_address},
     node_database::NodeDatabase,
+    node_score::PeerBehavior,
     node_table::*,
     parse_msg_id_leb128_2_bytes_at_most,
+    peers_file::PeersFile,
+    replay::Replayer,
     session::{self, Session, SessionData, SessionDetails},
     session_manager::SessionManager,
-    Error, ErrorKind, HandlerWorkType, IpFilter, NatType, NetworkConfiguration,
-    NetworkContext as NetworkContextTrait, NetworkIoMessage,
-    NetworkProtocolHandler, PeerInfo, ProtocolId, ProtocolInfo,
-    UpdateNodeOperation, NODE_TAG_ARCHIVE, NODE_TAG_NODE_TYPE,
+    AdminPeerInfo, Error, ErrorKind, HandlerWorkType, IpFilter, LocalNodeInfo,
+    NatType, NetworkConfiguration, NetworkContext as NetworkContextTrait,
+    NetworkIoMessage, NetworkProtocolHandler, PeerInfo, ProtocolId,
+    ProtocolInfo, UpdateNodeOperation, INVALID_MSG_ID, NODE_TAG_ARCHIVE,
+    NODE_TAG_NODE_TYPE,
 };
 
 use super::DisconnectReason;
@@ -69,6 +80,7 @@ const DISCOVERY_ROUND: TimerToken = SYS_TIMER + 6;
 const NODE_TABLE: TimerToken = SYS_TIMER + 7;
 const SEND_DELAYED_MESSAGES: TimerToken = SYS_TIMER + 8;
 const CHECK_SESSIONS: TimerToken = SYS_TIMER + 9;
+const PEERS_FILE: TimerToken = SYS_TIMER + 10;
 const HANDLER_TIMER: TimerToken = LAST_SESSION + 256;
 const STOP_NET_POLL: TimerToken = HANDLER_TIMER + 1;
 
@@ -270,6 +282,111 @@ impl NetworkService {
         }
     }
 
+    /// Add a trusted node and persist the node table. The node is removed
+    /// from blacklist if banned.
+    pub fn add_trusted_peer(&self, node: NodeEntry) -> Result<(), Error> {
+        if let Some(ref x) = self.inner {
+            x.add_trusted_node(node);
+            Ok(())
+        } else {
+            Err("Network service not started yet!".into())
+        }
+    }
+
+    /// Remove a trusted node, which is also removed from reserved nodes and
+    /// disconnected if connected. Returns false if the node is not trusted.
+    pub fn remove_trusted_peer(&self, id: &NodeId) -> Result<bool, Error> {
+        if let Some(ref x) = self.inner {
+            x.remove_trusted_node(id)
+        } else {
+            Err("Network service not started yet!".into())
+        }
+    }
+
+    /// Add a reserved node, which is always connected and trusted.
+    pub fn add_reserved_peer(&self, node: NodeEntry) -> Result<(), Error> {
+        if let Some(ref x) = self.inner {
+            let id = node.id;
+            x.add_trusted_node(node);
+            x.reserved_nodes.write().insert(id);
+            Ok(())
+        } else {
+            Err("Network service not started yet!".into())
+        }
+    }
+
+    /// Remove a reserved node, which is still kept as a trusted node.
+    /// Returns false if the node is not reserved.
+    pub fn remove_reserved_peer(&self, id: &NodeId) -> Result<bool, Error> {
+        if let Some(ref x) = self.inner {
+            Ok(x.reserved_nodes.write().remove(id))
+        } else {
+            Err("Network service not started yet!".into())
+        }
+    }
+
+    /// Return the connected peers with trusty and score information.
+    pub fn get_admin_peers(&self) -> Option<Vec<AdminPeerInfo>> {
+        self.inner.as_ref().map(|inner| inner.get_admin_peers())
+    }
+
+    pub fn get_local_node_info(&self) -> Option<LocalNodeInfo> {
+        self.inner.as_ref().map(|inner| inner.get_local_node_info())
+    }
+
+    /// Replay the messages captured in `dir` into the registered protocol
+    /// handlers in the captured order, and return the number of replayed
+    /// events. It should be used when the network is not started, so the
+    /// handlers only observe the captured peers.
+    ///
+    /// The timers registered by the handlers are no longer fired by the IO
+    /// service, but by the replayers at the capture timestamps, so the
+    /// handlers never act on the live network context.
+    pub fn replay_capture(&self, dir: &str) -> Result<usize, Error> {
+        let inner = match self.inner {
+            Some(ref inner) => inner,
+            None => return Err("Network service not started yet!".into()),
+        };
+        inner.replaying.store(true, AtomicOrdering::Relaxed);
+        let replayers: HashMap<ProtocolId, Replayer> = inner
+            .handlers
+            .read()
+            .iter()
+            .map(|(protocol, handler)| {
+                let replayer = Replayer::new(
+                    handler.clone(),
+                    *protocol,
+                    *inner.metadata.id(),
+                );
+                (*protocol, replayer)
+            })
+            .collect();
+
+        // The handlers are already initialized when registered, so only
+        // their timers are moved to the replayers.
+        for timer in inner.timers.read().values() {
+            if let Some(replayer) = replayers.get(&timer.protocol) {
+                replayer.add_timer(timer.token, timer.delay);
+            }
+        }
+
+        let mut count = 0;
+        for (_, path) in capture_files(dir)? {
+            info!("Replay capture file {:?}", path);
+            for msg in CaptureReader::open(path)? {
+                let msg = msg?;
+                // Every event advances the replay clock of all the handlers.
+                for replayer in replayers.values() {
+                    replayer.replay(&msg)?;
+                }
+                if replayers.contains_key(&msg.protocol) {
+                    count += 1;
+                }
+            }
+        }
+        Ok(count)
+    }
+
     /// Get the local address of the client
     pub fn local_addr(&self) -> Option<SocketAddr> {
         self.inner.as_ref().map(|inner_ref| inner_ref.local_addr())
@@ -281,6 +398,10 @@ impl NetworkService {
         protocol: ProtocolId, version: ProtocolVersion,
     ) -> Result<(), Error>
     {
+        bandwidth::register_protocol_meters(
+            &bandwidth::protocol_name(Some(&protocol)),
+            &handler.message_ids(),
+        );
         let (tx, rx) = std::sync::mpsc::sync_channel(0);
         self.io_service.as_ref().unwrap().send_message(
             NetworkIoMessage::AddHandler {
@@ -371,20 +492,35 @@ impl NetworkService {
         &self, node_id: Option<NodeId>,
     ) -> Option<Vec<SessionDetails>> {
         let inner = self.inner.as_ref()?;
-        match node_id {
-            None => Some(
-                inner
-                    .sessions
-                    .all()
-                    .iter()
-                    .map(|s| s.read().details())
-                    .collect(),
-            ),
+        let mut sessions: Vec<SessionDetails> = match node_id {
+            None => inner
+                .sessions
+                .all()
+                .iter()
+                .map(|s| s.read().details())
+                .collect(),
             Some(id) => {
                 let session = inner.sessions.get_by_id(&id)?;
                 let details = session.read().details();
-                Some(vec![details])
+                vec![details]
             }
+        };
+
+        let node_db = inner.node_db.read();
+        for details in sessions.iter_mut() {
+            details.score =
+                details.node_id.as_ref().and_then(|id| node_db.score(id));
+        }
+
+        Some(sessions)
+    }
+
+    /// Reset the score of specified node, and remove it from blacklist if
+    /// banned. Returns false if the node is not found.
+    pub fn reset_node_score(&self, id: &NodeId) -> bool {
+        match self.inner {
+            Some(ref inner) => inner.node_db.write().reset_score(id),
+            None => false,
         }
     }
 
@@ -438,6 +574,7 @@ impl HostMetadata {
 struct ProtocolTimer {
     pub protocol: ProtocolId,
     pub token: TimerToken, // Handler level token
+    pub delay: Duration,
 }
 
 /// The inner implementation of NetworkService. Note that all accesses to the
@@ -462,6 +599,16 @@ pub struct NetworkServiceInner {
 
     /// Delayed message queue and corresponding latency
     delayed_queue: Option<DelayedQueue>,
+
+    /// Recorder to capture protocol messages if enabled.
+    recorder: Option<MessageRecorder>,
+
+    /// Static peers file to reconcile the trusted nodes with if configured.
+    peers_file: Option<Mutex<PeersFile>>,
+
+    /// Set once captured messages are replayed into the protocol handlers,
+    /// after which the protocol timers are no longer fired by the IO service.
+    replaying: AtomicBool,
 }
 
 struct DelayedQueue {
@@ -618,6 +765,14 @@ impl NetworkServiceInner {
 
         let nodes_path = config.config_path.clone();
 
+        let recorder = match config.capture {
+            Some(ref capture_config) => {
+                info!("Capture protocol messages to {}", capture_config.dir);
+                Some(MessageRecorder::new(capture_config.clone())?)
+            }
+            None => None,
+        };
+
         let mut inner = NetworkServiceInner {
             metadata: HostMetadata {
                 network_id: config.id,
@@ -651,6 +806,14 @@ impl NetworkServiceInner {
             dropped_nodes: RwLock::new(HashSet::new()),
             is_consortium: config.is_consortium,
             delayed_queue: None,
+            recorder,
+            peers_file: config.peers_file.as_ref().map(|path| {
+                Mutex::new(PeersFile::new(
+                    path.into(),
+                    config.config_path.as_deref(),
+                ))
+            }),
+            replaying: AtomicBool::new(false),
         };
 
         for n in &config.boot_nodes {
@@ -664,6 +827,8 @@ impl NetworkServiceInner {
             }
         }
 
+        inner.reload_peers_file();
+
         Ok(inner)
     }
 
@@ -700,6 +865,16 @@ impl NetworkServiceInner {
 
     pub fn get_ip_filter(&self) -> &IpFilter { &self.config.ip_filter }
 
+    fn capture(
+        &self, kind: CaptureKind, protocol: ProtocolId, node_id: &NodeId,
+        data: &[u8],
+    )
+    {
+        if let Some(ref recorder) = self.recorder {
+            recorder.record(kind, protocol, node_id, data);
+        }
+    }
+
     fn add_boot_node(&self, id: &str) {
         match Node::from_str(id) {
             Err(e) => {
@@ -714,6 +889,91 @@ impl NetworkServiceInner {
         }
     }
 
+    fn add_trusted_node(&self, node: NodeEntry) {
+        let mut node_db = self.node_db.write();
+        if node_db.evaluate_blacklisted(&node.id) {
+            node_db.reset_score(&node.id);
+        }
+        node_db.insert_trusted(node);
+        node_db.save();
+    }
+
+    /// Reconcile the trusted nodes with the peers file if it is modified
+    /// since the last reload.
+    fn reload_peers_file(&self) {
+        let changes = match self.peers_file {
+            Some(ref peers_file) => match peers_file.lock().reload() {
+                Ok(changes) => changes,
+                Err(e) => {
+                    warn!("Failed to reload peers file: {}", e);
+                    return;
+                }
+            },
+            None => return,
+        };
+        if changes.is_empty() {
+            return;
+        }
+        info!(
+            "Peers file reloaded: added={} removed={} retagged={}",
+            changes.added.len(),
+            changes.removed.len(),
+            changes.retagged.len()
+        );
+
+        for id in changes.removed {
+            if let Err(e) = self.drop_node(id) {
+                debug!("Failed to drop node {:?}: {:?}", id, e);
+            }
+        }
+
+        let mut node_db = self.node_db.write();
+        for peer in changes.added {
+            if node_db.evaluate_blacklisted(&peer.id) {
+                node_db.reset_score(&peer.id);
+            }
+            node_db.insert_trusted(NodeEntry {
+                id: peer.id,
+                endpoint: peer.endpoint,
+            });
+            for (key, value) in &peer.tags {
+                node_db.set_tag(peer.id, key, value);
+            }
+        }
+        // Update the tags in place, so that the peers stay connected.
+        for retagged in changes.retagged {
+            let id = retagged.peer.id;
+            for key in &retagged.removed_tags {
+                node_db.remove_tag(id, key);
+            }
+            for (key, value) in &retagged.peer.tags {
+                node_db.set_tag(id, key, value);
+            }
+        }
+        node_db.save();
+    }
+
+    fn remove_trusted_node(&self, id: &NodeId) -> Result<bool, Error> {
+        self.reserved_nodes.write().remove(id);
+
+        if self
+            .node_db
+            .read()
+            .get(id, true /* trusted_only */)
+            .is_none()
+        {
+            return Ok(false);
+        }
+
+        self.drop_node(*id)?;
+        self.node_db.write().save();
+        Ok(true)
+    }
+
+    pub fn is_reserved_node(&self, id: &NodeId) -> bool {
+        self.reserved_nodes.read().contains(id)
+    }
+
     fn add_reserved_node(&mut self, id: &str) -> Result<(), Error> {
         let n = Node::from_str(id)?;
         self.node_db.write().insert_trusted(NodeEntry {
@@ -753,6 +1013,12 @@ impl NetworkServiceInner {
         }
         io.register_timer(NODE_TABLE, self.config.node_table_timeout)?;
         io.register_timer(CHECK_SESSIONS, DEFAULT_CHECK_SESSIONS_TIMEOUT)?;
+        if self.peers_file.is_some() {
+            io.register_timer(
+                PEERS_FILE,
+                self.config.peers_file_check_interval,
+            )?;
+        }
 
         Ok(())
     }
@@ -978,6 +1244,49 @@ impl NetworkServiceInner {
         }
     }
 
+    pub fn get_admin_peers(&self) -> Vec<AdminPeerInfo> {
+        let mut peers = Vec::with_capacity(self.sessions.count());
+
+        let node_db = self.node_db.read();
+        let reserved_nodes = self.reserved_nodes.read();
+        for session in self.sessions.all() {
+            let sess = session.read();
+            if sess.expired() || !sess.is_ready() {
+                continue;
+            }
+
+            let id = match sess.id() {
+                Some(id) => *id,
+                None => continue,
+            };
+
+            peers.push(AdminPeerInfo {
+                node_id: id,
+                address: sess.address(),
+                inbound: !sess.metadata.originated,
+                trusted: node_db.get(&id, true /* trusted_only */).is_some(),
+                reserved: reserved_nodes.contains(&id),
+                score: node_db.score(&id),
+                protocols: sess.metadata.peer_protocols.clone(),
+            });
+        }
+
+        peers
+    }
+
+    pub fn get_local_node_info(&self) -> LocalNodeInfo {
+        let node_id = self.metadata.id().clone();
+        let public_endpoint = self.metadata.public_endpoint.clone();
+        LocalNodeInfo {
+            node_id,
+            url: Node::new(node_id, public_endpoint.clone()).to_string(),
+            listen_address: self.metadata.local_address,
+            public_endpoint,
+            protocols: self.metadata.protocols.read().clone(),
+            reserved_peers_only: self.config.reserved_peers_only,
+        }
+    }
+
     pub fn get_peer_info(&self) -> Vec<PeerInfo> {
         debug!("get_peer_info: enter");
 
@@ -1001,6 +1310,52 @@ impl NetworkServiceInner {
         peers
     }
 
+    /// Update the score of peer with the reported behavior. Once the score
+    /// drops below the ban threshold, the peer is disconnected and
+    /// blacklisted unless it is a reserved node.
+    fn report_peer(
+        &self, node_id: &NodeId, behavior: PeerBehavior, times: u32,
+        io: &IoContext<NetworkIoMessage>,
+    ) -> Option<f64>
+    {
+        let score =
+            self.node_db.write().report_times(node_id, behavior, times)?;
+        trace!(
+            "report peer, node_id = {:?}, behavior = {:?}, times = {}, score = {}",
+            node_id,
+            behavior,
+            times,
+            score
+        );
+
+        let threshold = match self.config.ban_score_threshold {
+            Some(threshold) => threshold,
+            None => return Some(score),
+        };
+
+        if score < threshold && !self.reserved_nodes.read().contains(node_id)
+        {
+            info!(
+                "Ban peer due to low score, node_id = {:?}, score = {}, last behavior = {:?}",
+                node_id, score, behavior
+            );
+
+            if self.sessions.get_by_id(node_id).is_some() {
+                self.kill_connection(
+                    node_id,
+                    io,
+                    true, /* remote */
+                    Some(UpdateNodeOperation::Remove),
+                    "peer score below ban threshold", /* reason */
+                );
+            } else {
+                self.node_db.write().set_blacklisted(node_id);
+            }
+        }
+
+        Some(score)
+    }
+
     pub fn get_peer_connection_origin(&self, node_id: &NodeId) -> Option<bool> {
         match self.sessions.get_by_id(node_id) {
             Some(session) => {
@@ -1165,6 +1520,16 @@ impl NetworkServiceInner {
                                 protocol.protocol,
                                 self,
                             );
+                            self.capture(
+                                CaptureKind::PeerConnected,
+                                protocol.protocol,
+                                session_node_id.as_ref().unwrap(),
+                                &rlp::encode(&PeerConnected {
+                                    version: protocol.version,
+                                    pos_public_key: pos_public_key_opt.clone(),
+                                    originated: session_metadata.originated,
+                                }),
+                            );
                             network_context
                                 .protocol_handler()
                                 .on_peer_connected(
@@ -1304,6 +1669,7 @@ impl NetworkServiceInner {
 
             for p in to_disconnect {
                 if let Some(h) = self.handlers.read().get(&p).cloned() {
+                    self.capture(CaptureKind::PeerDisconnected, p, &id, &[]);
                     let network_context = NetworkContext::new(io, h, p, self);
                     network_context
                         .protocol_handler()
@@ -1377,6 +1743,7 @@ impl NetworkServiceInner {
 
         for p in to_disconnect {
             if let Some(h) = self.handlers.read().get(&p).cloned() {
+                self.capture(CaptureKind::PeerDisconnected, p, node_id, &[]);
                 let network_context = NetworkContext::new(io, h, p, self);
                 network_context
                     .protocol_handler()
@@ -1632,12 +1999,16 @@ impl IoHandler<NetworkIoMessage> for NetworkServiceInner {
                 self.node_db.write().save();
             }
             CHECK_SESSIONS => self.on_check_sessions(io),
+            PEERS_FILE => self.reload_peers_file(),
             SEND_DELAYED_MESSAGES => {
                 if let Some(ref queue) = self.delayed_queue {
                     queue.send_delayed_messages(self);
                 }
             }
             _ => match self.timers.read().get(&token).cloned() {
+                // The timers of the handlers are driven by the capture
+                // timestamps during replay.
+                Some(_) if self.replaying.load(AtomicOrdering::Relaxed) => {}
                 Some(timer) => {
                     match self.handlers.read().get(&timer.protocol).cloned() {
                         None => warn!(
@@ -1726,6 +2097,7 @@ impl IoHandler<NetworkIoMessage> for NetworkServiceInner {
                     ProtocolTimer {
                         protocol: *protocol,
                         token: *token,
+                        delay: *delay,
                     },
                 );
                 io.register_timer(handler_token, *delay)
@@ -1759,6 +2131,12 @@ impl IoHandler<NetworkIoMessage> for NetworkServiceInner {
                 if let Some(handler) =
                     self.handlers.read().get(protocol).cloned()
                 {
+                    self.capture(
+                        CaptureKind::Inbound,
+                        *protocol,
+                        node_id,
+                        data,
+                    );
                     let network_context =
                         NetworkContext::new(io, handler, *protocol, self);
                     network_context.protocol_handler().on_message(
@@ -1977,7 +2355,8 @@ impl<'a> NetworkContextTrait for NetworkContext<'a> {
         if version_valid_till < self.min_supported_version {
             bail!(ErrorKind::SendUnsupportedMessage {
                 protocol: self.protocol,
-                msg_id: parse_msg_id_leb128_2_bytes_at_most(&mut &*msg),
+                msg_id: parse_msg_id_leb128_2_bytes_at_most(&mut &*msg)
+                    .unwrap_or(INVALID_MSG_ID),
                 peer_protocol_version: None,
                 min_supported_version: Some(self.min_supported_version),
             });
@@ -1991,6 +2370,12 @@ impl<'a> NetworkContextTrait for NetworkContext<'a> {
         let session = self.network_service.sessions.get_by_id(node_id);
         trace!("Sending {} bytes to {}", msg.len(), node_id);
         if let Some(session) = session {
+            self.network_service.capture(
+                CaptureKind::Outbound,
+                self.protocol,
+                node_id,
+                &msg,
+            );
             let latency =
                 self.network_service.delayed_queue.as_ref().and_then(|q| {
                     session
@@ -2074,6 +2459,27 @@ impl<'a> NetworkContextTrait for NetworkContext<'a> {
             .write()
             .set_tag(peer, key, value);
     }
+
+    fn report_peer(
+        &self, peer: &NodeId, behavior: PeerBehavior,
+    ) -> Option<f64> {
+        self.network_service.report_peer(peer, behavior, 1, self.io)
+    }
+
+    fn report_peer_times(
+        &self, peer: &NodeId, behavior: PeerBehavior, times: u32,
+    ) -> Option<f64> {
+        self.network_service
+            .report_peer(peer, behavior, times, self.io)
+    }
+
+    fn get_peer_score(&self, peer: &NodeId) -> f64 {
+        self.network_service
+            .node_db
+            .read()
+            .score(peer)
+            .unwrap_or(0.0)
+    }
 }
 
 fn save_key(path: &Path, key: &Secret) {
@@ -2190,3 +2596,95 @@ impl Decodable for ProtocolVersion {
         Ok(Self(rlp.as_val()?))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DiscoveryConfiguration;
+    use diem_crypto::{PrivateKey, Uniform};
+    use tempdir::TempDir;
+
+    fn new_inner(dir: &Path) -> NetworkServiceInner {
+        let mut config = NetworkConfiguration::new_with_port(
+            1,
+            0,
+            DiscoveryConfiguration::default(),
+        );
+        config.nat_enabled = false;
+        config.config_path =
+            Some(dir.join("net_config").to_str().unwrap().into());
+        config.peers_file =
+            Some(dir.join("peers.json").to_str().unwrap().into());
+        let private_key = ConsensusPrivateKey::generate_for_testing();
+        let vrf_private_key = ConsensusVRFPrivateKey::generate_for_testing();
+        NetworkServiceInner::new(
+            &config,
+            (private_key.public_key(), vrf_private_key.public_key()),
+        )
+        .unwrap()
+    }
+
+    /// Write the peers file, and wait for its modification time to change
+    /// so that it is reloaded.
+    fn write_peers_file(dir: &Path, urls: &[String]) {
+        let path = dir.join("peers.json");
+        let modified = |path: &Path| fs::metadata(path)?.modified();
+        let last_modified = modified(&path).ok();
+        loop {
+            fs::write(&path, serde_json::to_string(urls).unwrap()).unwrap();
+            if modified(&path).ok() != last_modified {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_reload_peers_file() {
+        let dir = TempDir::new("network_service").unwrap();
+        let id1 = NodeId::random();
+        let id2 = NodeId::random();
+        let url = |id: &NodeId, query: &str| {
+            format!("cfxnode://{:x}@127.0.0.1:32323{}", id, query)
+        };
+        let region = ("region".to_string(), "us".to_string());
+
+        write_peers_file(dir.path(), &[url(&id1, "?region=us")]);
+        let inner = new_inner(dir.path());
+        assert!(inner
+            .node_db
+            .read()
+            .sample_trusted_node_ids_with_tag(1, &region.0, &region.1)
+            .contains(&id1));
+        // connected to id1
+        inner.node_db.write().note_success(&id1, Some(5), true);
+
+        // retagged peer is updated in place and stays connected
+        write_peers_file(dir.path(), &[url(&id1, "?node_type=archive")]);
+        inner.reload_peers_file();
+        assert!(inner.dropped_nodes.read().is_empty());
+        {
+            let node_db = inner.node_db.read();
+            let node = node_db.get(&id1, true /* trusted_only */).unwrap();
+            assert_eq!(node.stream_token, Some(5));
+            assert_eq!(node.tags.len(), 1);
+            assert_eq!(node.tags["node_type"], "archive");
+            assert!(node_db
+                .sample_trusted_node_ids_with_tag(1, &region.0, &region.1)
+                .is_empty());
+        }
+
+        // removed peer is disconnected
+        write_peers_file(dir.path(), &[url(&id2, "")]);
+        inner.reload_peers_file();
+        assert!(inner.dropped_nodes.read().contains(&id1));
+        assert!(inner.node_db.read().get(&id1, false).is_none());
+        assert!(inner.node_db.read().get(&id2, true).is_some());
+        drop(inner);
+
+        // peer removed while the node is down is removed at startup
+        write_peers_file(dir.path(), &[]);
+        let inner = new_inner(dir.path());
+        assert!(inner.node_db.read().get(&id2, false).is_none());
+    }
+}
@@ -3,13 +3,18 @@
 // See http://www.gnu.org/licenses/
 
 use crate::{
-    connection::{Connection, ConnectionDetails, SendQueueStatus, WriteStatus},
+    bandwidth::{self, BandwidthStats, Direction},
+    compression,
+    connection::{
+        Connection, ConnectionDetails, SendQueueStatus, WriteStatus,
+        MAX_PAYLOAD_SIZE,
+    },
     handshake::Handshake,
     node_table::{NodeEndpoint, NodeEntry, NodeId},
     parse_msg_id_leb128_2_bytes_at_most,
     service::{NetworkServiceInner, ProtocolVersion},
     DisconnectReason, Error, ErrorKind, ProtocolId, ProtocolInfo,
-    SessionMetadata, UpdateNodeOperation, PROTOCOL_ID_SIZE,
+    SessionMetadata, UpdateNodeOperation, INVALID_MSG_ID, PROTOCOL_ID_SIZE,
 };
 use bytes::Bytes;
 use diem_crypto::{bls::BLS_PUBLIC_KEY_LENGTH, ValidCryptoMaterial};
@@ -55,6 +60,11 @@ pub struct Session {
     last_read: Instant,
     last_write: (Instant, WriteStatus),
     pos_public_key: Option<(ConsensusPublicKey, ConsensusVRFPublicKey)>,
+    /// Protocol payloads larger than this size are compressed if the remote
+    /// peer supports compression.
+    compression_threshold: Option<usize>,
+    /// Traffic of packets sent to and received from the remote peer.
+    bandwidth: BandwidthStats,
 }
 
 /// Session state.
@@ -93,6 +103,9 @@ const PACKET_HELLO: u8 = 0x80;
 const PACKET_DISCONNECT: u8 = 0x01;
 // id for protocol packet
 pub const PACKET_USER: u8 = 0x10;
+/// Ids of the session packets, i.e. except the protocol packet.
+pub(crate) const PACKET_IDS: [u16; 2] =
+    [PACKET_HELLO as u16, PACKET_DISCONNECT as u16];
 /// header_version for protocol packet.
 /// Change the version only when there is a major change to the protocol packet.
 pub const PACKET_HEADER_VERSION: u8 = 0;
@@ -120,6 +133,7 @@ impl Session {
                 peer_protocols: Vec::new(),
                 originated,
                 peer_header_version,
+                peer_compression: None,
             },
             address,
             state: State::Handshake(MovableWrapper::new(handshake)),
@@ -129,6 +143,8 @@ impl Session {
             last_read: Instant::now(),
             last_write: (Instant::now(), WriteStatus::Complete),
             pos_public_key,
+            compression_threshold: host.config.compression_threshold,
+            bandwidth: BandwidthStats::default(),
         })
     }
 
@@ -199,7 +215,8 @@ impl Session {
     }
 
     /// Complete the handshake process:
-    /// 1. For incoming session, check if the remote peer is blacklisted.
+    /// 1. For incoming session, check if the remote peer is blacklisted, or
+    /// not reserved when only reserved peers are allowed.
     /// 2. Change the session state to `State::Session`.
     /// 3. Send Hello packet to remote peer.
     fn complete_handshake<Message>(
@@ -220,6 +237,11 @@ impl Session {
                 return Err(self.send_disconnect(DisconnectReason::Blacklisted));
             }
 
+            // refuse incoming session if only reserved nodes are allowed
+            if host.config.reserved_peers_only && !host.is_reserved_node(&id) {
+                return Err(self.send_disconnect(DisconnectReason::NotReserved));
+            }
+
             self.metadata.id = Some(id);
         }
 
@@ -282,6 +304,7 @@ impl Session {
     fn read_packet(
         &mut self, data: Bytes, host: &NetworkServiceInner,
     ) -> Result<SessionDataWithDisconnectInfo, Error> {
+        let packet_size = data.len();
         let packet = SessionPacket::parse(data)?;
 
         // For protocol packet, the Hello packet should already been received.
@@ -293,6 +316,15 @@ impl Session {
             return Err(ErrorKind::BadProtocol.into());
         }
 
+        if packet.id != PACKET_USER {
+            self.bandwidth.record(
+                bandwidth::SESSION_PROTOCOL_NAME,
+                packet.id as u16,
+                Direction::Inbound,
+                packet_size,
+            );
+        }
+
         match packet.id {
             PACKET_HELLO => {
                 debug!("Read HELLO in session {:?}", self);
@@ -311,6 +343,8 @@ impl Session {
                 // Handle Hello packet to exchange protocols
                 let rlp = Rlp::new(&packet.data);
                 let pos_public_key = self.read_hello(&rlp, host)?;
+                self.metadata.peer_compression =
+                    compression::negotiate(&packet.extensions);
                 Ok(SessionDataWithDisconnectInfo {
                     session_data: SessionData::Ready { pos_public_key },
                     token_to_disconnect,
@@ -325,15 +359,33 @@ impl Session {
                 );
                 Err(ErrorKind::Disconnect(reason).into())
             }
-            PACKET_USER => Ok(SessionDataWithDisconnectInfo {
-                session_data: SessionData::Message {
-                    data: packet.data.to_vec(),
-                    protocol: packet
-                        .protocol
-                        .expect("protocol should available for USER packet"),
-                },
-                token_to_disconnect: None,
-            }),
+            PACKET_USER => {
+                let data = match compression::decompress(
+                    &packet.data,
+                    &packet.extensions,
+                    MAX_PAYLOAD_SIZE,
+                )? {
+                    Some(data) => data,
+                    None => packet.data.to_vec(),
+                };
+                self.bandwidth.record(
+                    &bandwidth::protocol_name(packet.protocol.as_ref()),
+                    parse_msg_id_leb128_2_bytes_at_most(&mut &*data)
+                        .unwrap_or(INVALID_MSG_ID),
+                    Direction::Inbound,
+                    packet_size,
+                );
+
+                Ok(SessionDataWithDisconnectInfo {
+                    session_data: SessionData::Message {
+                        data,
+                        protocol: packet.protocol.expect(
+                            "protocol should available for USER packet",
+                        ),
+                    },
+                    token_to_disconnect: None,
+                })
+            }
             _ => {
                 debug!(
                     "read packet UNKNOWN, packet_id = {:?}, session = {:?}",
@@ -463,27 +515,7 @@ impl Session {
                 // FIXME(lpl): Verify keys.
                 let pos_public_key_bytes: Vec<u8> = rlp.val_at(3)?;
                 trace!("pos_public_key_bytes: {:?}", pos_public_key_bytes);
-                if pos_public_key_bytes.len() < BLS_PUBLIC_KEY_LENGTH {
-                    bail!("pos public key bytes is too short!");
-                }
-                let bls_pub_key = ConsensusPublicKey::try_from(
-                    &pos_public_key_bytes[..BLS_PUBLIC_KEY_LENGTH],
-                )
-                .map_err(|e| {
-                    Error::from_kind(
-                        ErrorKind::Decoder(format!("{:?}", e)).into(),
-                    )
-                })?;
-                let vrf_pub_key = ConsensusVRFPublicKey::try_from(
-                    &pos_public_key_bytes[BLS_PUBLIC_KEY_LENGTH..],
-                )
-                .map_err(|e| {
-                    Error::from_kind(
-                        ErrorKind::Decoder(format!("{:?}", e)).into(),
-                    )
-                })?;
-
-                Ok(Some((bls_pub_key, vrf_pub_key)))
+                Ok(Some(decode_pos_public_key(&pos_public_key_bytes)?))
             }
             length => Err(ErrorKind::Decoder(format!(
                 "Hello has incorrect rlp length: {:?}",
@@ -496,6 +528,10 @@ impl Session {
     /// Assemble a packet with specified protocol id, packet id and data.
     /// Return concrete error if session is expired or the protocol id is
     /// invalid.
+    ///
+    /// Hello packet advertises the supported compression algorithms, and
+    /// protocol packet is compressed if it is large enough and the remote peer
+    /// supports compression.
     fn prepare_packet(
         &self, protocol: Option<ProtocolId>, packet_id: u8, data: Vec<u8>,
     ) -> Result<Vec<u8>, Error> {
@@ -515,14 +551,46 @@ impl Session {
             return Err(ErrorKind::Expired.into());
         }
 
+        let mut extensions = Vec::new();
+        let data = match packet_id {
+            PACKET_HELLO => {
+                extensions.push(compression::hello_extension());
+                data
+            }
+            PACKET_USER => match self.compress(&data) {
+                Some((compressed, extension)) => {
+                    extensions.push(extension);
+                    compressed
+                }
+                None => data,
+            },
+            _ => data,
+        };
+
         Ok(SessionPacket::assemble(
             packet_id,
             self.metadata.peer_header_version,
             protocol,
+            &extensions,
             data,
         ))
     }
 
+    /// Compress the protocol payload with the negotiated algorithm if it
+    /// exceeds the compression threshold.
+    fn compress(&self, data: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+        let algorithm = self.metadata.peer_compression?;
+        let threshold = self.compression_threshold?;
+
+        // Oversized payload is rejected by the connection, and remote peer
+        // refuses to decompress it anyway.
+        if data.len() <= threshold || data.len() > MAX_PAYLOAD_SIZE {
+            return None;
+        }
+
+        compression::compress(algorithm, data)
+    }
+
     #[inline]
     pub fn check_message_protocol_version(
         &self, protocol: Option<ProtocolId>,
@@ -541,7 +609,8 @@ impl Session {
                             protocol,
                             msg_id: parse_msg_id_leb128_2_bytes_at_most(
                                 &mut msg
-                            ),
+                            )
+                            .unwrap_or(INVALID_MSG_ID),
                             peer_protocol_version: Some(peer_protocol.version),
                             min_supported_version: None,
                         });
@@ -565,7 +634,9 @@ impl Session {
             min_proto_version,
             &data,
         )?;
+        let msg_id = Self::msg_id(protocol.as_ref(), packet_id, &data);
         let packet = self.prepare_packet(protocol, packet_id, data)?;
+        self.record_outbound(protocol.as_ref(), msg_id, packet.len());
         self.connection_mut().send(io, packet, priority)
     }
 
@@ -580,10 +651,35 @@ impl Session {
             min_proto_version,
             &data,
         )?;
+        let msg_id = Self::msg_id(protocol.as_ref(), packet_id, &data);
         let packet = self.prepare_packet(protocol, packet_id, data)?;
+        self.record_outbound(protocol.as_ref(), msg_id, packet.len());
         self.connection_mut().write_raw_data(packet)
     }
 
+    /// Id of the message for bandwidth accounting, which is the packet id for
+    /// session packets.
+    fn msg_id(
+        protocol: Option<&ProtocolId>, packet_id: u8, data: &[u8],
+    ) -> u16 {
+        match protocol {
+            Some(_) => parse_msg_id_leb128_2_bytes_at_most(&mut &*data)
+                .unwrap_or(INVALID_MSG_ID),
+            None => packet_id as u16,
+        }
+    }
+
+    fn record_outbound(
+        &mut self, protocol: Option<&ProtocolId>, msg_id: u16, size: usize,
+    ) {
+        self.bandwidth.record(
+            &bandwidth::protocol_name(protocol),
+            msg_id,
+            Direction::Outbound,
+            size,
+        );
+    }
+
     /// Send a Disconnect packet immediately to the remote peer.
     pub fn send_disconnect(&mut self, reason: DisconnectReason) -> Error {
         let packet = rlp::encode(&reason);
@@ -605,12 +701,9 @@ impl Session {
         rlp.append(&host.metadata.network_id);
         rlp.append_list(&*host.metadata.protocols.read());
         host.metadata.public_endpoint.to_rlp_list(&mut rlp);
-        let mut key_bytes =
-            self.pos_public_key.as_ref().unwrap().0.to_bytes().to_vec();
-        key_bytes.append(
-            &mut self.pos_public_key.as_ref().unwrap().1.to_bytes().to_vec(),
-        );
-        rlp.append(&key_bytes);
+        rlp.append(&encode_pos_public_key(
+            self.pos_public_key.as_ref().unwrap(),
+        ));
         self.send_packet(
             io,
             None,
@@ -649,6 +742,8 @@ impl Session {
             last_read: format!("{:?}", self.last_read.elapsed()),
             last_write: format!("{:?}", self.last_write.0.elapsed()),
             last_write_status: format!("{:?}", self.last_write.1),
+            score: None,
+            bandwidth: self.bandwidth.clone(),
         }
     }
 
@@ -684,6 +779,30 @@ impl fmt::Debug for Session {
     }
 }
 
+/// Encode the PoS public keys of node that attached to Hello packet.
+pub fn encode_pos_public_key(
+    pos_public_key: &(ConsensusPublicKey, ConsensusVRFPublicKey),
+) -> Vec<u8> {
+    let mut key_bytes = pos_public_key.0.to_bytes().to_vec();
+    key_bytes.append(&mut pos_public_key.1.to_bytes().to_vec());
+    key_bytes
+}
+
+pub fn decode_pos_public_key(
+    bytes: &[u8],
+) -> Result<(ConsensusPublicKey, ConsensusVRFPublicKey), Error> {
+    if bytes.len() < BLS_PUBLIC_KEY_LENGTH {
+        bail!("pos public key bytes is too short!");
+    }
+    let bls_pub_key =
+        ConsensusPublicKey::try_from(&bytes[..BLS_PUBLIC_KEY_LENGTH])
+            .map_err(|e| ErrorKind::Decoder(format!("{:?}", e)))?;
+    let vrf_pub_key =
+        ConsensusVRFPublicKey::try_from(&bytes[BLS_PUBLIC_KEY_LENGTH..])
+            .map_err(|e| ErrorKind::Decoder(format!("{:?}", e)))?;
+    Ok((bls_pub_key, vrf_pub_key))
+}
+
 /// User friendly session information that used for Debug RPC.
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -696,6 +815,10 @@ pub struct SessionDetails {
     pub last_read: String,
     pub last_write: String,
     pub last_write_status: String,
+    /// Score of the remote node, which is filled by the network service.
+    pub score: Option<f64>,
+    /// Traffic by protocol and message id.
+    pub bandwidth: BandwidthStats,
 }
 
 /// MovableWrapper is a util to move a value out of a struct.
@@ -760,19 +883,30 @@ struct SessionPacket {
 }
 
 impl SessionPacket {
-    // data + Option<protocol> + protocol_flag + packet_id
+    // data + extensions + Option<protocol> + protocol_flag + packet_id
     fn assemble(
         id: u8, header_version: u8, protocol: Option<ProtocolId>,
-        mut data: Vec<u8>,
+        extensions: &[Vec<u8>], mut data: Vec<u8>,
     ) -> Vec<u8>
     {
+        // Extensions are parsed from the end, so the first extension is
+        // appended last.
+        for (i, extension) in extensions.iter().enumerate().rev() {
+            assert!(extension.len() < 0x80, "extension too long");
+            let has_next_extension = (i + 1 < extensions.len()) as u8;
+            data.extend_from_slice(extension);
+            data.push(((extension.len() as u8) << 1) + has_next_extension);
+        }
+        let has_extension = !extensions.is_empty() as u8;
+
         let mut protocol_flag = 0;
         if let Some(protocol) = protocol {
             data.extend_from_slice(&protocol);
             protocol_flag = 1;
         }
 
-        let header_byte = (header_version << 1) + protocol_flag;
+        let header_byte =
+            (has_extension << 4) + (header_version << 1) + protocol_flag;
         data.push(header_byte);
         data.push(id);
 
@@ -855,6 +989,10 @@ impl SessionPacket {
     ) -> Result<(Bytes, Vec<Vec<u8>>), Error> {
         let mut extensions = Vec::new();
         while has_extension {
+            if data.is_empty() {
+                debug!("failed to parse session packet, extension missed.");
+                bail!(ErrorKind::BadProtocol);
+            }
             let extension_byte = data.split_off(data.len() - 1)[0];
             let extension_len = (extension_byte >> 1) as usize;
             has_extension = (extension_byte & 1) != 0;
@@ -888,17 +1026,60 @@ mod tests {
 
     #[test]
     fn test_packet_assemble() {
-        let packet =
-            SessionPacket::assemble(5, PACKET_HEADER_VERSION, None, vec![1, 3]);
+        let packet = SessionPacket::assemble(
+            5,
+            PACKET_HEADER_VERSION,
+            None,
+            &[],
+            vec![1, 3],
+        );
         assert_eq!(packet, vec![1, 3, 0, 5]);
 
         let packet = SessionPacket::assemble(
             6,
             PACKET_HEADER_VERSION,
             Some([8; 3]),
+            &[],
             vec![2, 4],
         );
         assert_eq!(packet, vec![2, 4, 8, 8, 8, 1, 6]);
+
+        let packet = SessionPacket::assemble(
+            PACKET_USER,
+            PACKET_HEADER_VERSION,
+            Some([8; 3]),
+            &[vec![1, 1], vec![7]],
+            vec![2, 4],
+        );
+        assert_eq!(
+            packet,
+            vec![2, 4, 7, 2, 1, 1, 5, 8, 8, 8, 0x11, PACKET_USER]
+        );
+    }
+
+    #[test]
+    fn test_packet_extensions_roundtrip() {
+        let extensions = vec![vec![1, 1], vec![], vec![9, 9, 9]];
+        let packet = SessionPacket::assemble(
+            PACKET_USER,
+            PACKET_HEADER_VERSION,
+            Some([3; 3]),
+            &extensions,
+            vec![1, 2, 3],
+        );
+        assert_eq!(
+            SessionPacket::parse(packet.into()).unwrap(),
+            SessionPacket {
+                id: PACKET_USER,
+                header_version: 0,
+                protocol: Some([3; 3]),
+                data: vec![1, 2, 3].into(),
+                extensions,
+            }
+        );
+
+        // has_extension flag set but extension missed
+        assert!(SessionPacket::parse(vec![0x10, 20].into()).is_err());
     }
 
     #[test]
@@ -0,0 +1,884 @@
+// Copyright 2019 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+//! In-process network simulator for tests.
+//!
+//! `Simulator` hosts several nodes in one process, each with one or more
+//! registered protocol handlers, e.g. the synchronization, light or PoS
+//! protocol handlers, and connects them through an in-memory transport
+//! implementing `NetworkContext`. Messages are delivered according to the
+//! configured link latency, jitter and loss rate, and could be blocked by
+//! network partitions.
+//!
+//! All the events, i.e. message deliveries and timers, are driven by a
+//! virtual clock and processed one at a time in a deterministic order, and
+//! randomness only comes from the seeded random number generator, so a
+//! scenario is reproducible with the same seed. Note that the virtual clock
+//! does not affect the wall clock time used inside protocol handlers.
+//!
+//! The module is only built for tests, and for the tests of other crates
+//! with the `simulator` feature, e.g. the full node scenarios in `cfxcore`.
+
+use crate::{
+    node_score::{apply_behavior, PeerBehavior},
+    node_table::NodeId,
+    parse_msg_id_leb128_2_bytes_at_most,
+    service::ProtocolVersion,
+    Error, ErrorKind, HandlerWorkType, NetworkContext, NetworkProtocolHandler,
+    ProtocolId, UpdateNodeOperation, INVALID_MSG_ID,
+};
+use diem_types::validator_config::{ConsensusPublicKey, ConsensusVRFPublicKey};
+use io::TimerToken;
+use parking_lot::{Mutex, RwLock};
+use priority_send_queue::SendQueuePriority;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::Duration,
+};
+
+/// Index of a node in the simulator.
+pub type SimNodeIndex = usize;
+
+/// Properties of the link between two nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkConfig {
+    /// The minimum delay of a message.
+    pub latency: Duration,
+    /// A random delay within `[0, jitter]` is added to each message.
+    pub jitter: Duration,
+    /// The probability in `[0, 1]` that a message is lost.
+    pub loss_rate: f64,
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        LinkConfig {
+            latency: Duration::from_millis(50),
+            jitter: Duration::from_millis(0),
+            loss_rate: 0.0,
+        }
+    }
+}
+
+/// Statistics of the messages sent between nodes.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SimStats {
+    pub sent: usize,
+    pub delivered: usize,
+    /// Messages lost on links.
+    pub lost: usize,
+    /// Messages blocked by partitions or disconnections.
+    pub blocked: usize,
+}
+
+struct SimPeer {
+    originated: bool,
+    version: ProtocolVersion,
+    score: f64,
+}
+
+/// Actions taken by a protocol handler within a callback, which are applied
+/// after the callback returns.
+enum Action {
+    Send {
+        peer: NodeId,
+        msg: Vec<u8>,
+        min_protocol_version: ProtocolVersion,
+    },
+    Disconnect {
+        peer: NodeId,
+    },
+    Timer {
+        token: TimerToken,
+        delay: Duration,
+    },
+    Work(HandlerWorkType),
+}
+
+struct SimProtocol {
+    handler: Arc<dyn NetworkProtocolHandler + Sync>,
+    version: ProtocolVersion,
+    min_supported_version: ProtocolVersion,
+    peers: RwLock<HashMap<NodeId, SimPeer>>,
+    actions: Mutex<Vec<Action>>,
+}
+
+struct SimNode {
+    id: NodeId,
+    pos_public_key: Option<(ConsensusPublicKey, ConsensusVRFPublicKey)>,
+    protocols: BTreeMap<ProtocolId, SimProtocol>,
+}
+
+enum Event {
+    Deliver {
+        from: SimNodeIndex,
+        to: SimNodeIndex,
+        protocol: ProtocolId,
+        msg: Vec<u8>,
+    },
+    Timer {
+        node: SimNodeIndex,
+        protocol: ProtocolId,
+        token: TimerToken,
+        period: Duration,
+    },
+}
+
+pub struct Simulator {
+    now: Duration,
+    rng: StdRng,
+    nodes: Vec<SimNode>,
+    node_indices: HashMap<NodeId, SimNodeIndex>,
+    /// Pending events ordered by the virtual time and then the insertion
+    /// order.
+    events: BTreeMap<(Duration, u64), Event>,
+    next_event_seq: u64,
+    default_link: LinkConfig,
+    links: HashMap<(SimNodeIndex, SimNodeIndex), LinkConfig>,
+    /// The partition group of each node. Nodes in different groups could not
+    /// communicate with each other.
+    partition: Option<Vec<usize>>,
+    stats: SimStats,
+}
+
+impl Simulator {
+    pub fn new(seed: u64) -> Self {
+        Simulator {
+            now: Duration::from_secs(0),
+            rng: StdRng::seed_from_u64(seed),
+            nodes: Vec::new(),
+            node_indices: HashMap::new(),
+            events: BTreeMap::new(),
+            next_event_seq: 0,
+            default_link: LinkConfig::default(),
+            links: HashMap::new(),
+            partition: None,
+            stats: SimStats::default(),
+        }
+    }
+
+    /// The current virtual time since the simulation started.
+    pub fn now(&self) -> Duration { self.now }
+
+    pub fn stats(&self) -> &SimStats { &self.stats }
+
+    /// Add a node and return its index. Node ids are derived from the index
+    /// to be reproducible.
+    pub fn add_node(
+        &mut self,
+        pos_public_key: Option<(ConsensusPublicKey, ConsensusVRFPublicKey)>,
+    ) -> SimNodeIndex
+    {
+        let index = self.nodes.len();
+        let id = NodeId::from_low_u64_be(index as u64 + 1);
+        self.nodes.push(SimNode {
+            id,
+            pos_public_key,
+            protocols: BTreeMap::new(),
+        });
+        self.node_indices.insert(id, index);
+        index
+    }
+
+    pub fn node_id(&self, node: SimNodeIndex) -> NodeId { self.nodes[node].id }
+
+    pub fn node_index(&self, node_id: &NodeId) -> Option<SimNodeIndex> {
+        self.node_indices.get(node_id).copied()
+    }
+
+    /// Register a protocol handler of a node and initialize it.
+    pub fn register_protocol(
+        &mut self, node: SimNodeIndex,
+        handler: Arc<dyn NetworkProtocolHandler + Sync>, protocol: ProtocolId,
+        version: ProtocolVersion,
+    ) -> Result<(), Error>
+    {
+        if self.nodes[node].protocols.contains_key(&protocol) {
+            bail!(ErrorKind::BadProtocol);
+        }
+        let min_supported_version = handler.minimum_supported_version();
+        self.nodes[node].protocols.insert(
+            protocol,
+            SimProtocol {
+                handler: handler.clone(),
+                version,
+                min_supported_version,
+                peers: Default::default(),
+                actions: Default::default(),
+            },
+        );
+        self.with_context(node, protocol, |io| handler.initialize(io));
+        Ok(())
+    }
+
+    pub fn set_default_link(&mut self, link: LinkConfig) {
+        self.default_link = link;
+    }
+
+    /// Set the properties of the link between two nodes in both directions.
+    pub fn set_link(
+        &mut self, a: SimNodeIndex, b: SimNodeIndex, link: LinkConfig,
+    ) {
+        self.links.insert((a, b), link.clone());
+        self.links.insert((b, a), link);
+    }
+
+    /// Split nodes into groups. Messages between different groups, including
+    /// those in flight, are blocked until `heal`, but the connections are
+    /// kept. Nodes not in any group form a separate group.
+    pub fn partition(&mut self, groups: &[&[SimNodeIndex]]) {
+        let mut partition = vec![groups.len(); self.nodes.len()];
+        for (group, nodes) in groups.iter().enumerate() {
+            for node in nodes.iter() {
+                partition[*node] = group;
+            }
+        }
+        self.partition = Some(partition);
+    }
+
+    pub fn heal(&mut self) { self.partition = None; }
+
+    pub fn is_reachable(&self, a: SimNodeIndex, b: SimNodeIndex) -> bool {
+        match &self.partition {
+            Some(partition) => partition[a] == partition[b],
+            None => true,
+        }
+    }
+
+    /// Connect two nodes on all the protocols supported by both of them, where
+    /// `a` is the node originating the connection.
+    pub fn connect(&mut self, a: SimNodeIndex, b: SimNodeIndex) {
+        if a == b {
+            return;
+        }
+        let protocols: Vec<ProtocolId> = self.nodes[a]
+            .protocols
+            .iter()
+            .filter(|(protocol, state_a)| {
+                self.nodes[b].protocols.get(*protocol).map_or(
+                    false,
+                    |state_b| {
+                        state_a.version >= state_b.min_supported_version
+                            && state_b.version >= state_a.min_supported_version
+                            && !state_a
+                                .peers
+                                .read()
+                                .contains_key(&self.nodes[b].id)
+                    },
+                )
+            })
+            .map(|(protocol, _)| *protocol)
+            .collect();
+
+        for protocol in protocols {
+            self.connect_protocol(a, b, protocol, true);
+            self.connect_protocol(b, a, protocol, false);
+        }
+    }
+
+    /// Connect all pairs of nodes.
+    pub fn connect_all(&mut self) {
+        for a in 0..self.nodes.len() {
+            for b in a + 1..self.nodes.len() {
+                self.connect(a, b);
+            }
+        }
+    }
+
+    /// Disconnect two nodes on all protocols.
+    pub fn disconnect(&mut self, a: SimNodeIndex, b: SimNodeIndex) {
+        let protocols: Vec<ProtocolId> =
+            self.nodes[a].protocols.keys().copied().collect();
+        for protocol in protocols {
+            self.disconnect_protocol(a, b, protocol);
+        }
+    }
+
+    /// Connected peers of a node on a protocol.
+    pub fn peers(
+        &self, node: SimNodeIndex, protocol: ProtocolId,
+    ) -> Vec<NodeId> {
+        let mut peers: Vec<NodeId> = self.nodes[node]
+            .protocols
+            .get(&protocol)
+            .map_or(Vec::new(), |state| {
+                state.peers.read().keys().copied().collect()
+            });
+        peers.sort();
+        peers
+    }
+
+    /// Process the next event and advance the clock to its time. Returns
+    /// false if there is no pending event.
+    pub fn step(&mut self) -> bool {
+        let key = match self.events.keys().next() {
+            Some(key) => *key,
+            None => return false,
+        };
+        let event = self.events.remove(&key).expect("key exists");
+        self.now = key.0;
+
+        match event {
+            Event::Deliver {
+                from,
+                to,
+                protocol,
+                msg,
+            } => {
+                let from_id = self.nodes[from].id;
+                let connected = self.nodes[to]
+                    .protocols
+                    .get(&protocol)
+                    .map_or(false, |state| {
+                        state.peers.read().contains_key(&from_id)
+                    });
+                if !connected || !self.is_reachable(from, to) {
+                    self.stats.blocked += 1;
+                    return true;
+                }
+                self.stats.delivered += 1;
+                let handler = self.handler(to, protocol);
+                self.with_context(to, protocol, |io| {
+                    handler.on_message(io, &from_id, &msg)
+                });
+            }
+            Event::Timer {
+                node,
+                protocol,
+                token,
+                period,
+            } => {
+                self.schedule(
+                    self.now + period,
+                    Event::Timer {
+                        node,
+                        protocol,
+                        token,
+                        period,
+                    },
+                );
+                let handler = self.handler(node, protocol);
+                self.with_context(node, protocol, |io| {
+                    handler.on_timeout(io, token)
+                });
+            }
+        }
+        true
+    }
+
+    /// Process events until the virtual clock reaches `time`.
+    pub fn run_until_time(&mut self, time: Duration) {
+        while let Some((event_time, _)) = self.events.keys().next() {
+            if *event_time > time {
+                break;
+            }
+            self.step();
+        }
+        if self.now < time {
+            self.now = time;
+        }
+    }
+
+    /// Process events for a period of virtual time.
+    pub fn run_for(&mut self, duration: Duration) {
+        self.run_until_time(self.now + duration);
+    }
+
+    /// Process events until `condition` holds or the virtual clock exceeds
+    /// `timeout`. Returns whether the condition holds.
+    pub fn run_until<F>(
+        &mut self, timeout: Duration, mut condition: F,
+    ) -> bool
+    where F: FnMut(&Simulator) -> bool {
+        let deadline = self.now + timeout;
+        loop {
+            if condition(self) {
+                return true;
+            }
+            match self.events.keys().next() {
+                Some((event_time, _)) if *event_time <= deadline => {
+                    self.step();
+                }
+                _ => {
+                    self.now = deadline;
+                    return condition(self);
+                }
+            }
+        }
+    }
+
+    /// Execute `action` in the network context of a node protocol, e.g. to
+    /// broadcast a new block from the node.
+    pub fn with_context<F, R>(
+        &mut self, node: SimNodeIndex, protocol: ProtocolId, action: F,
+    ) -> R
+    where F: FnOnce(&dyn NetworkContext) -> R {
+        let result = {
+            let context = SimContext {
+                node: &self.nodes[node],
+                protocol,
+                state: &self.nodes[node].protocols[&protocol],
+            };
+            action(&context)
+        };
+        self.apply_actions(node, protocol);
+        result
+    }
+
+    fn handler(
+        &self, node: SimNodeIndex, protocol: ProtocolId,
+    ) -> Arc<dyn NetworkProtocolHandler + Sync> {
+        self.nodes[node].protocols[&protocol].handler.clone()
+    }
+
+    fn schedule(&mut self, time: Duration, event: Event) {
+        self.events.insert((time, self.next_event_seq), event);
+        self.next_event_seq += 1;
+    }
+
+    fn connect_protocol(
+        &mut self, node: SimNodeIndex, peer: SimNodeIndex, protocol: ProtocolId,
+        originated: bool,
+    )
+    {
+        let peer_id = self.nodes[peer].id;
+        let peer_version = self.nodes[peer].protocols[&protocol].version;
+        let pos_public_key = self.nodes[peer].pos_public_key.clone();
+        self.nodes[node].protocols[&protocol].peers.write().insert(
+            peer_id,
+            SimPeer {
+                originated,
+                version: peer_version,
+                score: 0.0,
+            },
+        );
+        let handler = self.handler(node, protocol);
+        self.with_context(node, protocol, |io| {
+            handler.on_peer_connected(
+                io,
+                &peer_id,
+                peer_version,
+                pos_public_key,
+            )
+        });
+    }
+
+    fn disconnect_protocol(
+        &mut self, a: SimNodeIndex, b: SimNodeIndex, protocol: ProtocolId,
+    ) {
+        for (node, peer) in [(a, b), (b, a)] {
+            let peer_id = self.nodes[peer].id;
+            let removed = match self.nodes[node].protocols.get(&protocol) {
+                Some(state) => state.peers.write().remove(&peer_id).is_some(),
+                None => false,
+            };
+            if removed {
+                let handler = self.handler(node, protocol);
+                self.with_context(node, protocol, |io| {
+                    handler.on_peer_disconnected(io, &peer_id)
+                });
+            }
+        }
+    }
+
+    /// Apply the actions taken by a node protocol handler. Dispatched works
+    /// are processed immediately at the current virtual time.
+    fn apply_actions(&mut self, node: SimNodeIndex, protocol: ProtocolId) {
+        loop {
+            let actions = std::mem::take(
+                &mut *self.nodes[node].protocols[&protocol].actions.lock(),
+            );
+            if actions.is_empty() {
+                break;
+            }
+            for action in actions {
+                match action {
+                    Action::Send {
+                        peer,
+                        msg,
+                        min_protocol_version,
+                    } => self.send(
+                        node,
+                        peer,
+                        protocol,
+                        msg,
+                        min_protocol_version,
+                    ),
+                    Action::Disconnect { peer } => {
+                        if let Some(peer) = self.node_index(&peer) {
+                            self.disconnect_protocol(node, peer, protocol);
+                        }
+                    }
+                    Action::Timer { token, delay } => self.schedule(
+                        self.now + delay,
+                        Event::Timer {
+                            node,
+                            protocol,
+                            token,
+                            period: delay,
+                        },
+                    ),
+                    Action::Work(work_type) => {
+                        let handler = self.handler(node, protocol);
+                        let context = SimContext {
+                            node: &self.nodes[node],
+                            protocol,
+                            state: &self.nodes[node].protocols[&protocol],
+                        };
+                        handler.on_work_dispatch(&context, work_type);
+                    }
+                }
+            }
+        }
+    }
+
+    fn send(
+        &mut self, from: SimNodeIndex, peer: NodeId, protocol: ProtocolId,
+        msg: Vec<u8>, min_protocol_version: ProtocolVersion,
+    )
+    {
+        let to = match self.node_index(&peer) {
+            Some(to) => to,
+            None => return,
+        };
+        let peer_version = match self.nodes[from].protocols[&protocol]
+            .peers
+            .read()
+            .get(&peer)
+        {
+            Some(peer) => peer.version,
+            // Like `NetworkService`, messages to unknown peers are dropped.
+            None => return,
+        };
+        if peer_version < min_protocol_version {
+            debug!(
+                "Drop message to peer {:?} with protocol version {:?}",
+                peer, peer_version
+            );
+            return;
+        }
+
+        self.stats.sent += 1;
+        let link = self
+            .links
+            .get(&(from, to))
+            .unwrap_or(&self.default_link)
+            .clone();
+        if link.loss_rate > 0.0 && self.rng.gen::<f64>() < link.loss_rate {
+            self.stats.lost += 1;
+            return;
+        }
+        let jitter = if link.jitter > Duration::from_millis(0) {
+            Duration::from_nanos(
+                self.rng.gen_range(0, link.jitter.as_nanos() as u64 + 1),
+            )
+        } else {
+            Duration::from_millis(0)
+        };
+        self.schedule(
+            self.now + link.latency + jitter,
+            Event::Deliver {
+                from,
+                to,
+                protocol,
+                msg,
+            },
+        );
+    }
+}
+
+/// The network context of a node protocol. Handlers could not call back into
+/// the simulator directly, so the actions are recorded and applied later.
+struct SimContext<'a> {
+    node: &'a SimNode,
+    protocol: ProtocolId,
+    state: &'a SimProtocol,
+}
+
+impl<'a> NetworkContext for SimContext<'a> {
+    fn get_protocol(&self) -> ProtocolId { self.protocol }
+
+    fn get_peer_connection_origin(&self, node_id: &NodeId) -> Option<bool> {
+        self.state.peers.read().get(node_id).map(|p| p.originated)
+    }
+
+    fn send(
+        &self, node_id: &NodeId, msg: Vec<u8>,
+        min_protocol_version: ProtocolVersion,
+        version_valid_till: ProtocolVersion, _priority: SendQueuePriority,
+    ) -> Result<(), Error>
+    {
+        if version_valid_till < self.state.min_supported_version {
+            bail!(ErrorKind::SendUnsupportedMessage {
+                protocol: self.protocol,
+                msg_id: parse_msg_id_leb128_2_bytes_at_most(&mut &*msg)
+                    .unwrap_or(INVALID_MSG_ID),
+                peer_protocol_version: None,
+                min_supported_version: Some(self.state.min_supported_version),
+            });
+        }
+
+        if *node_id == self.node.id {
+            self.state.handler.send_local_message(self, msg);
+            return Ok(());
+        }
+
+        self.state.actions.lock().push(Action::Send {
+            peer: *node_id,
+            msg,
+            min_protocol_version,
+        });
+        Ok(())
+    }
+
+    fn disconnect_peer(
+        &self, node_id: &NodeId, _op: Option<UpdateNodeOperation>, reason: &str,
+    ) {
+        debug!(
+            "Node {:?} disconnects peer {:?}: {}",
+            self.node.id, node_id, reason
+        );
+        self.state
+            .actions
+            .lock()
+            .push(Action::Disconnect { peer: *node_id });
+    }
+
+    fn register_timer(
+        &self, token: TimerToken, delay: Duration,
+    ) -> Result<(), Error> {
+        self.state
+            .actions
+            .lock()
+            .push(Action::Timer { token, delay });
+        Ok(())
+    }
+
+    fn dispatch_work(&self, work_type: HandlerWorkType) {
+        self.state.actions.lock().push(Action::Work(work_type));
+    }
+
+    fn insert_peer_node_tag(&self, _peer: NodeId, _key: &str, _value: &str) {}
+
+    fn report_peer(
+        &self, peer: &NodeId, behavior: PeerBehavior,
+    ) -> Option<f64> {
+        // Scores do not decay in the simulator to be deterministic.
+        let mut peers = self.state.peers.write();
+        let peer = peers.get_mut(peer)?;
+        peer.score = apply_behavior(peer.score, behavior, 1);
+        Some(peer.score)
+    }
+
+    fn get_peer_score(&self, peer: &NodeId) -> f64 {
+        self.state.peers.read().get(peer).map_or(0.0, |p| p.score)
+    }
+
+    fn is_peer_self(&self, node_id: &NodeId) -> bool {
+        *node_id == self.node.id
+    }
+
+    fn self_node_id(&self) -> NodeId { self.node.id }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLOOD_PROTOCOL: ProtocolId = *b"fld";
+    const TIMER: TimerToken = 1;
+
+    /// Handler that floods received numbers to all the other peers, and
+    /// periodically broadcasts the largest number it has seen.
+    #[derive(Default)]
+    struct FloodHandler {
+        peers: Mutex<Vec<NodeId>>,
+        best: Mutex<u64>,
+    }
+
+    impl FloodHandler {
+        fn best(&self) -> u64 { *self.best.lock() }
+
+        fn broadcast(&self, io: &dyn NetworkContext, exclude: Option<&NodeId>) {
+            let best = self.best();
+            for peer in self.peers.lock().iter() {
+                if Some(peer) == exclude {
+                    continue;
+                }
+                io.send(
+                    peer,
+                    best.to_be_bytes().to_vec(),
+                    ProtocolVersion(1),
+                    ProtocolVersion(1),
+                    SendQueuePriority::High,
+                )
+                .unwrap();
+            }
+        }
+
+        fn mine(&self, io: &dyn NetworkContext, number: u64) {
+            *self.best.lock() = number;
+            self.broadcast(io, None);
+        }
+    }
+
+    impl NetworkProtocolHandler for FloodHandler {
+        fn minimum_supported_version(&self) -> ProtocolVersion {
+            ProtocolVersion(1)
+        }
+
+        fn initialize(&self, io: &dyn NetworkContext) {
+            io.register_timer(TIMER, Duration::from_secs(1)).unwrap();
+        }
+
+        fn on_message(
+            &self, io: &dyn NetworkContext, node_id: &NodeId, data: &[u8],
+        ) {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(data);
+            let number = u64::from_be_bytes(bytes);
+            if number > self.best() {
+                *self.best.lock() = number;
+                self.broadcast(io, Some(node_id));
+            }
+        }
+
+        fn on_peer_connected(
+            &self, _io: &dyn NetworkContext, node_id: &NodeId,
+            _peer_protocol_version: ProtocolVersion,
+            _pos_public_key: Option<(
+                ConsensusPublicKey,
+                ConsensusVRFPublicKey,
+            )>,
+        )
+        {
+            self.peers.lock().push(*node_id);
+        }
+
+        fn on_peer_disconnected(
+            &self, _io: &dyn NetworkContext, node_id: &NodeId,
+        ) {
+            self.peers.lock().retain(|id| id != node_id);
+        }
+
+        fn on_timeout(&self, io: &dyn NetworkContext, _timer: TimerToken) {
+            self.broadcast(io, None);
+        }
+
+        fn send_local_message(&self, _io: &dyn NetworkContext, _msg: Vec<u8>) {}
+
+        fn on_work_dispatch(
+            &self, _io: &dyn NetworkContext, _work_type: HandlerWorkType,
+        ) {
+        }
+    }
+
+    fn setup(seed: u64, n: usize) -> (Simulator, Vec<Arc<FloodHandler>>) {
+        let mut sim = Simulator::new(seed);
+        let mut handlers = Vec::new();
+        for _ in 0..n {
+            let node = sim.add_node(None);
+            let handler = Arc::new(FloodHandler::default());
+            sim.register_protocol(
+                node,
+                handler.clone(),
+                FLOOD_PROTOCOL,
+                ProtocolVersion(1),
+            )
+            .unwrap();
+            handlers.push(handler);
+        }
+        (sim, handlers)
+    }
+
+    fn mine(sim: &mut Simulator, handler: &FloodHandler, node: usize, n: u64) {
+        sim.with_context(node, FLOOD_PROTOCOL, |io| handler.mine(io, n));
+    }
+
+    #[test]
+    fn test_latency() {
+        let (mut sim, handlers) = setup(0, 3);
+        // 0 - 1 - 2
+        sim.connect(0, 1);
+        sim.connect(1, 2);
+        assert_eq!(sim.peers(1, FLOOD_PROTOCOL).len(), 2);
+        sim.set_link(
+            1,
+            2,
+            LinkConfig {
+                latency: Duration::from_millis(200),
+                ..Default::default()
+            },
+        );
+
+        mine(&mut sim, &handlers[0], 0, 1);
+        sim.run_for(Duration::from_millis(50));
+        assert_eq!(handlers[1].best(), 1);
+        assert_eq!(handlers[2].best(), 0);
+        sim.run_for(Duration::from_millis(199));
+        assert_eq!(handlers[2].best(), 0);
+        sim.run_for(Duration::from_millis(1));
+        assert_eq!(handlers[2].best(), 1);
+        assert_eq!(sim.now(), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_partition() {
+        let (mut sim, handlers) = setup(0, 4);
+        sim.connect_all();
+        sim.partition(&[&[0, 1], &[2, 3]]);
+
+        mine(&mut sim, &handlers[0], 0, 1);
+        mine(&mut sim, &handlers[3], 3, 2);
+        sim.run_for(Duration::from_millis(500));
+        assert_eq!(handlers[1].best(), 1);
+        assert_eq!(handlers[2].best(), 2);
+        assert!(sim.stats().blocked > 0);
+
+        sim.heal();
+        assert!(sim.run_until(Duration::from_secs(5), |_| handlers
+            .iter()
+            .all(|h| h.best() == 2)));
+        assert!(sim.now() <= Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_disconnect() {
+        let (mut sim, handlers) = setup(0, 2);
+        sim.connect(0, 1);
+        sim.disconnect(1, 0);
+        assert!(sim.peers(0, FLOOD_PROTOCOL).is_empty());
+        assert!(handlers[1].peers.lock().is_empty());
+
+        mine(&mut sim, &handlers[0], 0, 1);
+        sim.run_for(Duration::from_secs(3));
+        assert_eq!(handlers[1].best(), 0);
+        assert_eq!(sim.stats().sent, 0);
+    }
+
+    #[test]
+    fn test_deterministic_loss() {
+        let run = |seed| {
+            let (mut sim, handlers) = setup(seed, 5);
+            sim.set_default_link(LinkConfig {
+                latency: Duration::from_millis(20),
+                jitter: Duration::from_millis(100),
+                loss_rate: 0.5,
+            });
+            sim.connect_all();
+            mine(&mut sim, &handlers[0], 0, 1);
+            sim.run_until(Duration::from_secs(60), |_| {
+                handlers.iter().all(|h| h.best() == 1)
+            });
+            (sim.now(), sim.stats().clone())
+        };
+
+        let (time, stats) = run(7);
+        assert!(stats.lost > 0);
+        assert!(time < Duration::from_secs(60));
+        assert_eq!(run(7), (time, stats));
+    }
+}
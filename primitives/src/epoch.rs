@@ -23,6 +23,9 @@ pub enum EpochNumber {
     /// The latest confirmed block (based on the estimation of the confirmation
     /// meter)
     LatestConfirmed,
+    /// The latest block whose confirmation risk is below the configured
+    /// threshold
+    LatestSafe,
     /// Latest block with state.
     LatestState,
     /// Latest mined block.
@@ -24,6 +24,7 @@ pub mod is_default;
 pub mod log_entry;
 pub mod pos;
 pub mod receipt;
+pub mod state_diff;
 pub mod state_root;
 pub mod static_bool;
 pub mod storage;
@@ -42,6 +43,7 @@ pub use crate::{
     epoch::{BlockHashOrEpochNumber, EpochId, EpochNumber, NULL_EPOCH},
     log_entry::LogEntry,
     receipt::{BlockReceipts, Receipt, TransactionOutcome},
+    state_diff::{EpochStateDiff, StateEntryChange},
     state_root::*,
     static_bool::StaticBool,
     storage::{
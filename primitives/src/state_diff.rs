@@ -0,0 +1,46 @@
+// Copyright 2022 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use rlp_derive::{RlpDecodable, RlpEncodable};
+
+/// A state entry written to the storage when committing an epoch. The key is
+/// the encoded `StorageKeyWithSpace`, and a `None` value means that the entry
+/// does not exist (before creation or after deletion).
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct StateEntryChange {
+    pub key: Vec<u8>,
+    pub original_value: Option<Vec<u8>>,
+    pub current_value: Option<Vec<u8>>,
+}
+
+/// All state entries modified by the execution of an epoch, in the order of
+/// their keys.
+#[derive(Clone, Debug, Default, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct EpochStateDiff {
+    pub changes: Vec<StateEntryChange>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EpochStateDiff, StateEntryChange};
+
+    #[test]
+    fn test_epoch_state_diff_rlp() {
+        let diff = EpochStateDiff {
+            changes: vec![
+                StateEntryChange {
+                    key: vec![1, 2, 3],
+                    original_value: None,
+                    current_value: Some(vec![4, 5]),
+                },
+                StateEntryChange {
+                    key: vec![6],
+                    original_value: Some(vec![]),
+                    current_value: None,
+                },
+            ],
+        };
+        assert_eq!(diff, rlp::decode(&rlp::encode(&diff)).unwrap());
+    }
+}
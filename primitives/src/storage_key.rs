@@ -391,6 +391,51 @@ where FromKeyBytesResult<ShouldCheckInput>: ConditionalReturnValue<'a>{
     }
 }
 
+/// The original (address, slot) location of a storage entry. Storage keys are
+/// encoded before being written into the MPT, so the preimage is recorded
+/// during execution to make state diffs and slot dumps human-readable.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    RlpEncodable,
+    RlpDecodable,
+    Serialize,
+    Deserialize,
+)]
+pub struct StorageKeyPreimage {
+    pub space: Space,
+    pub address: Address,
+    pub slot: Vec<u8>,
+}
+
+impl<'a> StorageKeyWithSpace<'a> {
+    /// Returns the preimage of a contract storage slot key. Other kinds of
+    /// keys (account, code, deposit list, etc.) have no slot and return
+    /// `None`.
+    pub fn storage_key_preimage(&self) -> Option<StorageKeyPreimage> {
+        match self.key {
+            StorageKey::StorageKey {
+                address_bytes,
+                storage_key,
+            } => Some(StorageKeyPreimage {
+                space: self.space,
+                address: Address::from_slice(address_bytes),
+                slot: storage_key.to_vec(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl StorageKeyPreimage {
+    pub fn storage_key(&self) -> StorageKeyWithSpace {
+        StorageKey::new_storage_key(&self.address, &self.slot)
+            .with_space(self.space)
+    }
+}
+
 /// The padding is uniquely generated for DeltaMPT at each intermediate epoch,
 /// and it's used to compute padding bytes for address and storage_key. The
 /// padding setup is against an attack where adversary artificially build deep
@@ -737,6 +782,7 @@ use crate::storage_key::delta_mpt_storage_key::ACCOUNT_KEYPART_BYTES;
 use cfx_types::{Address, Space, H256};
 use hash::keccak;
 use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+use rlp_derive::{RlpDecodable, RlpEncodable};
 use serde::{Deserialize, Serialize};
 use std::{
     convert::AsRef,
@@ -747,8 +793,11 @@ use std::{
 
 #[cfg(test)]
 mod tests {
-    use super::{delta_mpt_storage_key::*, DeltaMptKeyPadding, StorageKey};
-    use crate::StorageKeyWithSpace;
+    use super::{
+        delta_mpt_storage_key::*, DeltaMptKeyPadding, StorageKey,
+        StorageKeyPreimage,
+    };
+    use crate::{SkipInputCheck, StorageKeyWithSpace};
     use cfx_types::{Address, H256};
 
     #[test]
@@ -896,4 +945,30 @@ mod tests {
         let key2 = StorageKeyWithSpace::from_delta_mpt_key(&bytes[..]);
         assert_eq!(key, key2);
     }
+
+    #[test]
+    fn test_storage_key_preimage() {
+        let address = "0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6"
+            .parse::<Address>()
+            .unwrap();
+        let storage_key = &[99; 32];
+
+        let key =
+            StorageKey::new_storage_key(&address, storage_key).with_evm_space();
+        let preimage = key.storage_key_preimage().unwrap();
+        assert_eq!(preimage.address, address);
+        assert_eq!(preimage.slot, storage_key.to_vec());
+        assert_eq!(preimage.storage_key(), key);
+
+        let key_bytes = key.to_key_bytes();
+        let decoded =
+            StorageKeyWithSpace::from_key_bytes::<SkipInputCheck>(&key_bytes);
+        assert_eq!(decoded.storage_key_preimage(), Some(preimage.clone()));
+
+        let encoded = rlp::encode(&preimage);
+        assert_eq!(rlp::decode::<StorageKeyPreimage>(&encoded), Ok(preimage));
+
+        let key = StorageKey::new_account_key(&address).with_native_space();
+        assert_eq!(key.storage_key_preimage(), None);
+    }
 }
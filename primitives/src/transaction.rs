@@ -265,6 +265,30 @@ impl NativeTransaction {
             public: None,
         }
     }
+
+    /// Fake sign transactions sent from impersonated accounts in dev mode.
+    // Like phantom transactions, the sender address is used as `r` and `s`
+    // so that transactions with identical fields from different senders will
+    // have different hashes.
+    pub fn fake_sign_impersonated(
+        self, from: AddressWithSpace,
+    ) -> SignedTransaction {
+        SignedTransaction {
+            transaction: TransactionWithSignature {
+                transaction: TransactionWithSignatureSerializePart {
+                    unsigned: Transaction::Native(self),
+                    r: U256::from(from.address.as_ref()),
+                    s: U256::from(from.address.as_ref()),
+                    v: 0,
+                },
+                hash: H256::zero(),
+                rlp_size: None,
+            }
+            .compute_hash(),
+            sender: from.address,
+            public: None,
+        }
+    }
 }
 
 /// Replay protection logic for v part of transaction's signature
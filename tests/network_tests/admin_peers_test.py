@@ -0,0 +1,88 @@
+#!/usr/bin/env python3
+import os
+import sys
+
+sys.path.insert(1, os.path.dirname(sys.path[0]))
+
+from test_framework.test_framework import ConfluxTestFramework
+from test_framework.util import assert_equal, assert_raises_rpc_error, get_peer_addr, wait_until
+
+
+class AdminPeersTest(ConfluxTestFramework):
+    def set_test_params(self):
+        self.num_nodes = 3
+
+    def setup_network(self):
+        self.setup_nodes()
+
+    def run_test(self):
+        node0 = self.nodes[0]
+
+        info = node0.admin_nodeInfo()
+        assert_equal(info["nodeId"], node0.key)
+        assert info["url"].startswith("cfxnode://")
+
+        assert_raises_rpc_error(-32602, "invalid node url", node0.admin_addTrustedPeer, "invalid")
+        assert_raises_rpc_error(-32602, "node id is missing", node0.admin_addTrustedPeer,
+                                "cfxnode://" + get_peer_addr(self.nodes[1]))
+
+        self.test_trusted_peer()
+        self.test_reserved_peer()
+
+    def url(self, index: int) -> str:
+        node = self.nodes[index]
+        return "cfxnode://{}@{}".format(node.key[2:], get_peer_addr(node))
+
+    def admin_peer(self, node_id: str):
+        for peer in self.nodes[0].admin_peers():
+            if peer["nodeId"] == node_id and len(peer["protocols"]) > 0:
+                return peer
+        return None
+
+    def wait_disconnected_or_untrusted(self, node_id: str):
+        # The removed peer may connect again as an untrusted one.
+        def check():
+            peer = self.admin_peer(node_id)
+            return peer is None or not peer["trusted"]
+        wait_until(check)
+
+    def test_trusted_peer(self):
+        node0, key1 = self.nodes[0], self.nodes[1].key
+
+        assert node0.admin_addTrustedPeer(self.url(1))
+        wait_until(lambda: self.admin_peer(key1) is not None)
+        peer = self.admin_peer(key1)
+        assert peer["trusted"]
+        assert not peer["reserved"]
+        assert not peer["inbound"]
+
+        assert node0.admin_removeTrustedPeer(key1)
+        self.wait_disconnected_or_untrusted(key1)
+        # Not trusted any more.
+        assert not node0.admin_removeTrustedPeer(key1)
+
+    def test_reserved_peer(self):
+        node0, key2 = self.nodes[0], self.nodes[2].key
+
+        assert node0.admin_addReservedPeer(self.url(2))
+        wait_until(lambda: self.admin_peer(key2) is not None)
+        peer = self.admin_peer(key2)
+        assert peer["trusted"]
+        assert peer["reserved"]
+
+        # A removed reserved peer is still trusted and connected.
+        assert node0.admin_removeReservedPeer(key2)
+        assert not node0.admin_removeReservedPeer(key2)
+        peer = self.admin_peer(key2)
+        assert peer["trusted"]
+        assert not peer["reserved"]
+
+        # Removing a trusted peer removes it from the reserved peers too.
+        assert node0.admin_addReservedPeer(self.url(2))
+        assert node0.admin_removeTrustedPeer(key2)
+        assert not node0.admin_removeReservedPeer(key2)
+        self.wait_disconnected_or_untrusted(key2)
+
+
+if __name__ == "__main__":
+    AdminPeersTest().main()
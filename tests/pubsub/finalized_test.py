@@ -0,0 +1,114 @@
+#!/usr/bin/env python3
+
+# allow imports from parent directory
+# source: https://stackoverflow.com/a/11158224
+import os, sys
+sys.path.insert(1, os.path.join(sys.path[0], '..'))
+
+import asyncio
+import time
+
+from conflux.rpc import RpcClient
+from conflux.pubsub import PubSubClient
+from conflux.utils import int_to_hex
+from test_framework.test_framework import DefaultConfluxTestFramework
+from test_framework.util import *
+
+CHAIN_LEN = 300
+
+class PubSubFinalizedTest(DefaultConfluxTestFramework):
+    def set_test_params(self):
+        self.num_nodes = 4
+        self.conf_parameters["vrf_proposal_threshold"] = '"{}"'.format(int_to_hex(int(2 ** 256 - 1)))
+        self.conf_parameters["pos_pivot_decision_defer_epoch_count"] = '120'
+        # No auto timeout.
+        self.pos_parameters["round_time_ms"] = 1000000000
+
+    def setup_network(self):
+        super().setup_network()
+        self.rpc = [RpcClient(node) for node in self.nodes]
+        self.pubsub = PubSubClient(self.nodes[0])
+        self.eth_pubsub = PubSubClient(self.nodes[0], True)
+
+    def run_pos_rounds(self, generate_blocks):
+        for _ in range(4):
+            for client in self.rpc:
+                client.pos_proposal_timeout()
+            # Wait for proposal processing
+            time.sleep(0.5)
+            for client in self.rpc:
+                client.pos_new_round_timeout()
+            time.sleep(0.5)
+            if generate_blocks:
+                self.rpc[0].generate_blocks(1)
+                sync_blocks(self.nodes)
+
+    def finalize_pivot_decision(self, chain_len):
+        self.rpc[0].generate_empty_blocks(chain_len)
+        sync_blocks(self.nodes)
+        defer_count = int(self.conf_parameters["pos_pivot_decision_defer_epoch_count"])
+        height = (self.rpc[0].epoch_number() - defer_count) // 60 * 60
+        decision = self.rpc[0].block_by_epoch(int_to_hex(height))["hash"]
+        for client in self.rpc:
+            client.pos_force_sign_pivot_decision(decision, int_to_hex(height))
+        time.sleep(1)
+        self.run_pos_rounds(generate_blocks=True)
+        assert_equal(self.rpc[0].epoch_number("latest_finalized"), height)
+        return height
+
+    async def collect_ranges(self, sub, from_key, to_key):
+        ranges = [(int(m[from_key], 0), int(m[to_key], 0)) async for m in sub.iter()]
+        for prev, cur in zip(ranges, ranges[1:]):
+            assert_equal(cur[0], prev[1] + 1)
+        return ranges
+
+    async def run_async(self):
+        # Initialize pos_consensus_blocks
+        self.run_pos_rounds(generate_blocks=False)
+        wait_until(lambda: self.rpc[0].pos_status() is not None)
+        wait_until(lambda: self.rpc[0].pos_status()["latestCommitted"] is not None)
+
+        sub = await self.pubsub.subscribe("finalizedEpochs")
+        eth_sub = await self.eth_pubsub.subscribe("finalizedBlocks")
+        last_finalized = self.rpc[0].epoch_number("latest_finalized")
+
+        # -------- 1. the first finalized range after subscribing --------
+        height = self.finalize_pivot_decision(CHAIN_LEN + 1)
+
+        ranges = await self.collect_ranges(sub, "fromEpoch", "toEpoch")
+        assert_greater_than(len(ranges), 0)
+        assert_equal(ranges[0][0], last_finalized + 1)
+        assert_equal(ranges[-1][1], height)
+
+        eth_ranges = await self.collect_ranges(eth_sub, "fromBlock", "toBlock")
+        assert_equal(eth_ranges, ranges)
+
+        self.log.info("Pass -- 1")
+
+        # -------- 2. ranges continue from the last published epoch --------
+        height = self.finalize_pivot_decision(CHAIN_LEN)
+
+        next_ranges = await self.collect_ranges(sub, "fromEpoch", "toEpoch")
+        assert_greater_than(len(next_ranges), 0)
+        assert_equal(next_ranges[0][0], ranges[-1][1] + 1)
+        assert_equal(next_ranges[-1][1], height)
+
+        eth_ranges = await self.collect_ranges(eth_sub, "fromBlock", "toBlock")
+        assert_equal(eth_ranges, next_ranges)
+
+        self.log.info("Pass -- 2")
+
+        # -------- 3. only newly finalized epochs are published --------
+        await sub.unsubscribe()
+        await eth_sub.unsubscribe()
+        sub = await self.pubsub.subscribe("finalizedEpochs")
+        self.rpc[0].generate_blocks(10)
+        assert_equal([m async for m in sub.iter()], [])
+
+        self.log.info("Pass -- 3")
+
+    def run_test(self):
+        asyncio.get_event_loop().run_until_complete(self.run_async())
+
+if __name__ == "__main__":
+    PubSubFinalizedTest().main()
@@ -116,6 +116,17 @@ class PubSubTest(ConfluxTestFramework):
         assert(msg["revertTo"] != None)
         assert_equal(int(msg["revertTo"], 16), fork_epoch)
 
+        # the reverted epochs list the logs removed and the transactions to re-execute
+        reverted = msg["revertedEpochs"]
+        removed_logs = [l for e in reverted for l in e["removedLogs"]]
+        assert_equal(len(removed_logs), num_to_reexecute)
+        assert(all(l["removed"] for l in removed_logs))
+
+        reverted_txs = set(h for e in reverted for h in e["transactionHashes"])
+        for r in receipts:
+            if int(r["epochNumber"], 16) > fork_epoch:
+                assert(r["transactionHash"] in reverted_txs)
+
         logs = [l async for l in sub_all.iter()]
         assert_equal(len(logs), num_to_reexecute)
 
@@ -0,0 +1,160 @@
+#!/usr/bin/env python3
+
+# allow imports from parent directory
+# source: https://stackoverflow.com/a/11158224
+import os, sys
+sys.path.insert(1, os.path.join(sys.path[0], '..'))
+
+import asyncio
+import time
+
+from conflux.rpc import RpcClient
+from conflux.pubsub import PubSubClient
+from conflux.utils import int_to_hex
+from test_framework.test_framework import DefaultConfluxTestFramework
+from test_framework.util import *
+
+# confirmation risk 1e-4, scaled the same as `cfx_getConfirmationRiskByHash`
+RISK_THRESHOLD = (2 ** 256 - 1) // 10 ** 4
+MAX_CONFIRMATION_BLOCKS = 200
+
+class PubSubTxConfirmationTest(DefaultConfluxTestFramework):
+    def set_test_params(self):
+        self.num_nodes = 4
+        self.conf_parameters["vrf_proposal_threshold"] = '"{}"'.format(int_to_hex(int(2 ** 256 - 1)))
+        self.conf_parameters["pos_pivot_decision_defer_epoch_count"] = '120'
+        # No auto timeout.
+        self.pos_parameters["round_time_ms"] = 1000000000
+
+    def setup_network(self):
+        super().setup_network()
+        self.rpc = [RpcClient(node) for node in self.nodes]
+        self.pubsub = PubSubClient(self.nodes[0])
+
+    def run_pos_rounds(self, generate_blocks):
+        for _ in range(4):
+            for client in self.rpc:
+                client.pos_proposal_timeout()
+            # Wait for proposal processing
+            time.sleep(0.5)
+            for client in self.rpc:
+                client.pos_new_round_timeout()
+            time.sleep(0.5)
+            if generate_blocks:
+                self.rpc[0].generate_blocks(1)
+                sync_blocks(self.nodes)
+
+    def finalize_pivot_decision(self, chain_len):
+        self.rpc[0].generate_empty_blocks(chain_len)
+        sync_blocks(self.nodes)
+        defer_count = int(self.conf_parameters["pos_pivot_decision_defer_epoch_count"])
+        height = (self.rpc[0].epoch_number() - defer_count) // 60 * 60
+        decision = self.rpc[0].block_by_epoch(int_to_hex(height))["hash"]
+        for client in self.rpc:
+            client.pos_force_sign_pivot_decision(decision, int_to_hex(height))
+        time.sleep(1)
+        self.run_pos_rounds(generate_blocks=True)
+        assert_equal(self.rpc[0].epoch_number("latest_finalized"), height)
+        return height
+
+    def send_in_block(self, parent, nonce):
+        tx = self.rpc[0].new_tx(nonce=nonce)
+        block = self.rpc[0].generate_custom_block(parent_hash=parent, referee=[], txs=[tx])
+        return tx.hash_hex(), block
+
+    def generate_chain(self, parent, len):
+        hashes = [parent]
+        for _ in range(len):
+            hash = self.rpc[0].generate_block_with_parent(hashes[-1])
+            hashes.append(hash)
+        return hashes[1:]
+
+    async def test_risk_threshold(self, nonce):
+        tx_hash, block = self.send_in_block(self.rpc[0].best_block_hash(), nonce)
+        sub = await self.pubsub.subscribe("transactionConfirmation", {
+            "hash": tx_hash,
+            "riskThreshold": hex(RISK_THRESHOLD),
+        })
+
+        # generate blocks in 0.5 sec interval like default until the
+        # confirmation risk is low enough
+        msg = None
+        for _ in range(MAX_CONFIRMATION_BLOCKS):
+            self.rpc[0].generate_empty_blocks(1)
+            time.sleep(0.5)
+            try:
+                msg = await sub.next(timeout=0.1)
+                break
+            except TimeoutError:
+                pass
+
+        assert(msg is not None)
+        assert_equal(msg["transactionHash"], tx_hash)
+        assert_equal(msg["blockHash"], block)
+        assert_equal(msg["epochNumber"], self.rpc[0].block_by_hash(block)["epochNumber"])
+        assert_greater_than_or_equal(RISK_THRESHOLD, int(msg["confirmationRisk"], 0))
+        assert_equal(msg["finalized"], False)
+
+        # the subscription ends after the confirmation
+        self.rpc[0].generate_empty_blocks(5)
+        assert_equal([m async for m in sub.iter()], [])
+
+        self.log.info("Pass -- confirmed by risk threshold")
+
+    async def test_revert_and_finalized(self, nonce):
+        parent = self.rpc[0].best_block_hash()
+        tx_hash, block = self.send_in_block(parent, nonce)
+        sub = await self.pubsub.subscribe("transactionConfirmation", {
+            "hash": tx_hash,
+            "finalized": True,
+        })
+
+        # the transaction is executed on the pivot chain
+        self.generate_chain(block, 10)
+        assert_equal(self.rpc[0].get_transaction_receipt(tx_hash)["blockHash"], block)
+        assert_equal([m async for m in sub.iter()], [])
+
+        # a longer fork without the block drops the transaction
+        self.generate_chain(parent, 20)
+        msg = await sub.next(timeout=5)
+        assert_equal(msg["transactionHash"], tx_hash)
+        assert_equal(msg["revertedBlockHash"], block)
+
+        self.log.info("Pass -- reverted")
+
+        # the block is referenced again and the transaction is still watched
+        self.rpc[0].generate_blocks(10)
+        assert_equal(self.rpc[0].get_transaction_receipt(tx_hash)["blockHash"], block)
+        assert_equal([m async for m in sub.iter()], [])
+
+        height = self.finalize_pivot_decision(300)
+        epoch = self.rpc[0].block_by_hash(block)["epochNumber"]
+        assert_greater_than_or_equal(height, int(epoch, 0))
+
+        msg = await sub.next(timeout=5)
+        assert_equal(msg["transactionHash"], tx_hash)
+        assert_equal(msg["blockHash"], block)
+        assert_equal(msg["epochNumber"], epoch)
+        assert_equal(msg["finalized"], True)
+
+        # the subscription ends after the confirmation
+        self.rpc[0].generate_empty_blocks(5)
+        assert_equal([m async for m in sub.iter()], [])
+
+        self.log.info("Pass -- finalized")
+
+    async def run_async(self):
+        # Initialize pos_consensus_blocks
+        self.run_pos_rounds(generate_blocks=False)
+        wait_until(lambda: self.rpc[0].pos_status() is not None)
+        wait_until(lambda: self.rpc[0].pos_status()["latestCommitted"] is not None)
+
+        nonce = self.rpc[0].get_nonce(self.rpc[0].GENESIS_ADDR)
+        await self.test_risk_threshold(nonce)
+        await self.test_revert_and_finalized(nonce + 1)
+
+    def run_test(self):
+        asyncio.get_event_loop().run_until_complete(self.run_async())
+
+if __name__ == "__main__":
+    PubSubTxConfirmationTest().main()
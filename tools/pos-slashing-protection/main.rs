@@ -0,0 +1,92 @@
+// Copyright 2022 Conflux Foundation. All rights reserved.
+// Conflux is free software and distributed under GNU General Public License.
+// See http://www.gnu.org/licenses/
+
+use std::{env, fs, path::PathBuf, process};
+
+use docopt::Docopt;
+use serde::Deserialize;
+
+use diem_secure_storage::{OnDiskStorage, Storage};
+use safety_rules::slashing_protection::{
+    export_slashing_protection, import_slashing_protection,
+    SlashingProtectionInterchange,
+};
+
+const USAGE: &str = r#"
+Export and import the PoS slashing-protection data of a validator, so that it
+can be migrated to another host without signing any round twice. The node
+using the secure storage must be stopped while running these commands.
+
+Usage:
+    pos-slashing-protection export <storage> <file>
+    pos-slashing-protection import <storage> <file>
+
+Options:
+    -h, --help              Display this message and exit.
+
+Commands:
+    export                  Write the slashing-protection data in the secure
+                            storage file <storage> to the interchange file
+                            <file>.
+    import                  Merge the interchange file <file> into the secure
+                            storage file <storage>. The imported watermarks
+                            never lower the existing ones.
+"#;
+
+#[derive(Debug, Deserialize)]
+struct Args {
+    cmd_export: bool,
+    cmd_import: bool,
+    arg_storage: String,
+    arg_file: String,
+}
+
+fn main() {
+    let args: Args = Docopt::new(USAGE)
+        .and_then(|d| d.argv(env::args()).deserialize())
+        .unwrap_or_else(|e| e.exit());
+
+    match execute(args) {
+        Ok(ok) => println!("{}", ok),
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
+}
+
+fn execute(args: Args) -> Result<String, String> {
+    let storage_path = PathBuf::from(&args.arg_storage);
+    if args.cmd_export {
+        if !storage_path.exists() {
+            return Err(format!("{:?} does not exist", storage_path));
+        }
+        let storage = Storage::from(OnDiskStorage::new(storage_path));
+        let interchange = export_slashing_protection(&storage)
+            .map_err(|e| format!("Unable to export: {}", e))?;
+        let json = serde_json::to_string_pretty(&interchange)
+            .map_err(|e| format!("Unable to serialize: {}", e))?;
+        fs::write(&args.arg_file, json)
+            .map_err(|e| format!("Unable to write {}: {}", args.arg_file, e))?;
+        Ok(format!(
+            "Exported epoch {}, last voted round {}, {} signed proposals",
+            interchange.epoch,
+            interchange.last_voted_round,
+            interchange.signed_proposals.len()
+        ))
+    } else if args.cmd_import {
+        let json = fs::read_to_string(&args.arg_file)
+            .map_err(|e| format!("Unable to read {}: {}", args.arg_file, e))?;
+        let interchange: SlashingProtectionInterchange =
+            serde_json::from_str(&json)
+                .map_err(|e| format!("Invalid interchange file: {}", e))?;
+        let mut storage = Storage::from(OnDiskStorage::new(storage_path));
+        let safety_data =
+            import_slashing_protection(&mut storage, &interchange)
+                .map_err(|e| format!("Unable to import: {}", e))?;
+        Ok(format!("Imported, the storage now has {}", safety_data))
+    } else {
+        unreachable!()
+    }
+}
@@ -126,6 +126,11 @@ impl<K: hash::Hash + Eq + Copy + Debug, V: PartialEq + Eq + Ord + Clone>
         self.data.get(index).map(|node| &node.value)
     }
 
+    /// Iterate over all K-V reference tuples in an arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.data.iter().map(|node| (&node.key, &node.value))
+    }
+
     /// Clear all key-values of the HeapMap.
     pub fn clear(&mut self) {
         self.mapping.clear();